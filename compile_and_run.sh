@@ -1,3 +1,3 @@
 #!/bin/bash
 cat compile_and_run.sh
-/build/parity-bitcoin/target/debug/pbtc --btc --regtest
+PBTC_WALLET_PASSPHRASE=${PBTC_WALLET_PASSPHRASE:-regtest} /build/parity-bitcoin/target/debug/pbtc --btc --regtest
@@ -2,11 +2,17 @@ extern crate crypto as rcrypto;
 extern crate primitives;
 extern crate siphasher;
 
-use primitives::hash::{H160, H256, H32};
+mod muhash;
+
+pub use muhash::MuHash3072;
+use primitives::hash::{H160, H256, H32, H512};
 pub use rcrypto::digest::Digest;
+use rcrypto::hmac::Hmac;
+use rcrypto::mac::Mac;
+use rcrypto::pbkdf2::pbkdf2;
 use rcrypto::ripemd160::Ripemd160;
 use rcrypto::sha1::Sha1;
-use rcrypto::sha2::Sha256;
+use rcrypto::sha2::{Sha256, Sha512};
 use siphasher::sip::SipHasher24;
 use std::hash::Hasher;
 
@@ -171,9 +177,30 @@ pub fn checksum(data: &[u8]) -> H32 {
     result
 }
 
+/// HMAC-SHA512
+#[inline]
+pub fn hmac_sha512(key: &[u8], input: &[u8]) -> H512 {
+    let mut result = H512::default();
+    let mut mac = Hmac::new(Sha512::new(), key);
+    mac.input(input);
+    mac.raw_result(&mut *result);
+    result
+}
+
+/// PBKDF2 with HMAC-SHA512
+#[inline]
+pub fn pbkdf2_hmac_sha512(password: &[u8], salt: &[u8], rounds: u32) -> H512 {
+    let mut result = H512::default();
+    let mut mac = Hmac::new(Sha512::new(), password);
+    pbkdf2(&mut mac, salt, rounds, &mut *result);
+    result
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{checksum, dhash160, dhash256, ripemd160, sha1, sha256, siphash24};
+    use super::{
+        checksum, dhash160, dhash256, hmac_sha512, pbkdf2_hmac_sha512, ripemd160, sha1, sha256, siphash24,
+    };
     use primitives::bytes::Bytes;
 
     #[test]
@@ -227,4 +254,20 @@ mod tests {
     fn test_checksum() {
         assert_eq!(checksum(b"hello"), "9595c9df".into());
     }
+
+    #[test]
+    fn test_hmac_sha512() {
+        // RFC 4231, test case 2
+        let expected = "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737".into();
+        let result = hmac_sha512(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_pbkdf2_hmac_sha512() {
+        let expected = "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04".into();
+        let password = b"abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let result = pbkdf2_hmac_sha512(password, b"mnemonicTREZOR", 2048);
+        assert_eq!(result, expected);
+    }
 }
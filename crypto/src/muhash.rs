@@ -0,0 +1,269 @@
+//! MuHash3072 rolling hash of the set of byte strings.
+//!
+//! Elements are mapped to numbers modulo 3072-bit prime and the set hash is
+//! the product of inserted elements divided by the product of removed ones.
+//! The hash is the same for every order of insertions and removals, so it can
+//! be updated incrementally. Construction is compatible with bitcoind's MuHash3072.
+
+use primitives::hash::H256;
+use rcrypto::chacha20::ChaCha20;
+use rcrypto::symmetriccipher::SynchronousStreamCipher;
+use sha256;
+use std::fmt;
+
+const LIMBS: usize = 48;
+/// Size of serialized number in bytes
+const NUM_SIZE: usize = LIMBS * 8;
+/// 2^3072 - MAX_PRIME_DIFF is the largest 3072-bit prime
+const MAX_PRIME_DIFF: u64 = 1103717;
+
+/// Number modulo 2^3072 - MAX_PRIME_DIFF, stored as little-endian limbs
+#[derive(Clone, Copy)]
+struct Num3072 {
+    limbs: [u64; LIMBS],
+}
+
+impl Num3072 {
+    fn one() -> Self {
+        let mut limbs = [0u64; LIMBS];
+        limbs[0] = 1;
+        Num3072 { limbs: limbs }
+    }
+
+    /// Expand element to the number with ChaCha20 keystream, keyed by element hash
+    fn from_data(data: &[u8]) -> Self {
+        let key = sha256(data);
+        // with zero nonce and counter keystream is the same for 64 and 96-bit nonce variants
+        let mut cipher = ChaCha20::new(&*key, &[0u8; 8]);
+        let zeros = [0u8; NUM_SIZE];
+        let mut keystream = [0u8; NUM_SIZE];
+        cipher.process(&zeros, &mut keystream);
+        Num3072::from_bytes(&keystream)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let mut limbs = [0u64; LIMBS];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+            *limb = chunk.iter().rev().fold(0u64, |limb, byte| (limb << 8) | *byte as u64);
+        }
+        Num3072 { limbs: limbs }
+    }
+
+    fn to_bytes(&self, bytes: &mut Vec<u8>) {
+        for limb in self.limbs.iter() {
+            bytes.extend((0..8).map(|i| (limb >> (8 * i)) as u8));
+        }
+    }
+
+    /// Is number greater or equal to the modulus?
+    fn is_overflow(&self) -> bool {
+        self.limbs[0] > u64::max_value() - MAX_PRIME_DIFF
+            && self.limbs[1..].iter().all(|limb| *limb == u64::max_value())
+    }
+
+    /// Subtract modulus from overflown number (add MAX_PRIME_DIFF modulo 2^3072)
+    fn full_reduce(&mut self) {
+        let mut carry = MAX_PRIME_DIFF as u128;
+        for limb in self.limbs.iter_mut() {
+            let sum = *limb as u128 + carry;
+            *limb = sum as u64;
+            carry = sum >> 64;
+        }
+    }
+
+    fn multiply(&mut self, other: &Num3072) {
+        let mut product = [0u64; 2 * LIMBS];
+        for i in 0..LIMBS {
+            let mut carry = 0u128;
+            for j in 0..LIMBS {
+                let cur = self.limbs[i] as u128 * other.limbs[j] as u128 + product[i + j] as u128 + carry;
+                product[i + j] = cur as u64;
+                carry = cur >> 64;
+            }
+            product[i + LIMBS] = carry as u64;
+        }
+
+        // 2^3072 = MAX_PRIME_DIFF (mod p), so the high half is folded into the low one
+        let mut carry = 0u128;
+        for i in 0..LIMBS {
+            let cur = product[i] as u128 + product[i + LIMBS] as u128 * MAX_PRIME_DIFF as u128 + carry;
+            self.limbs[i] = cur as u64;
+            carry = cur >> 64;
+        }
+        while carry != 0 {
+            let mut add = carry * MAX_PRIME_DIFF as u128;
+            for limb in self.limbs.iter_mut() {
+                let sum = *limb as u128 + add;
+                *limb = sum as u64;
+                add = sum >> 64;
+            }
+            carry = add;
+        }
+
+        if self.is_overflow() {
+            self.full_reduce();
+        }
+    }
+
+    /// a^(p - 2) = a^-1 (mod p)
+    fn inverse(&self) -> Num3072 {
+        // p - 2 = (2^3072 - 1) - (MAX_PRIME_DIFF + 1)
+        let mut exponent = [u64::max_value(); LIMBS];
+        exponent[0] = u64::max_value() - MAX_PRIME_DIFF - 1;
+
+        let mut result = Num3072::one();
+        for limb in exponent.iter().rev() {
+            for bit in (0..64).rev() {
+                let square = result;
+                result.multiply(&square);
+                if (limb >> bit) & 1 == 1 {
+                    result.multiply(self);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Rolling hash of the set of byte strings
+#[derive(Clone, Copy)]
+pub struct MuHash3072 {
+    numerator: Num3072,
+    denominator: Num3072,
+}
+
+impl Default for MuHash3072 {
+    fn default() -> Self {
+        MuHash3072 {
+            numerator: Num3072::one(),
+            denominator: Num3072::one(),
+        }
+    }
+}
+
+impl MuHash3072 {
+    /// Hash of the empty set
+    pub fn new() -> Self {
+        MuHash3072::default()
+    }
+
+    /// Add element to the set
+    pub fn insert(&mut self, data: &[u8]) {
+        self.numerator.multiply(&Num3072::from_data(data));
+    }
+
+    /// Remove element from the set
+    pub fn remove(&mut self, data: &[u8]) {
+        self.denominator.multiply(&Num3072::from_data(data));
+    }
+
+    /// Hash of the set. Requires modular inversion, so the result should be cached by caller
+    pub fn finalize(&self) -> H256 {
+        let mut numerator = self.numerator;
+        let mut denominator = self.denominator;
+        if numerator.is_overflow() {
+            numerator.full_reduce();
+        }
+        if denominator.is_overflow() {
+            denominator.full_reduce();
+        }
+        numerator.multiply(&denominator.inverse());
+
+        let mut bytes = Vec::with_capacity(NUM_SIZE);
+        numerator.to_bytes(&mut bytes);
+        sha256(&bytes)
+    }
+
+    /// Serialized numerator and denominator of the set hash
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(2 * NUM_SIZE);
+        self.numerator.to_bytes(&mut bytes);
+        self.denominator.to_bytes(&mut bytes);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 2 * NUM_SIZE {
+            return None;
+        }
+
+        Some(MuHash3072 {
+            numerator: Num3072::from_bytes(&bytes[..NUM_SIZE]),
+            denominator: Num3072::from_bytes(&bytes[NUM_SIZE..]),
+        })
+    }
+}
+
+impl PartialEq for MuHash3072 {
+    fn eq(&self, other: &Self) -> bool {
+        self.finalize() == other.finalize()
+    }
+}
+
+impl fmt::Debug for MuHash3072 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MuHash3072({:?})", self.finalize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MuHash3072;
+
+    fn element(i: u8) -> [u8; 32] {
+        let mut data = [0u8; 32];
+        data[0] = i;
+        data
+    }
+
+    #[test]
+    fn test_muhash_bitcoind_vector() {
+        let mut muhash = MuHash3072::new();
+        muhash.insert(&element(0));
+        muhash.insert(&element(1));
+        muhash.remove(&element(2));
+        assert_eq!(
+            muhash.finalize().reversed(),
+            "10d312b100cbd32ada024a6646e40d3482fcff103668d2625f10002a607d5863".into()
+        );
+    }
+
+    #[test]
+    fn test_muhash_is_order_independent() {
+        let mut first = MuHash3072::new();
+        first.insert(&element(1));
+        first.insert(&element(2));
+        first.insert(&element(3));
+        first.remove(&element(2));
+
+        let mut second = MuHash3072::new();
+        second.remove(&element(2));
+        second.insert(&element(3));
+        second.insert(&element(2));
+        second.insert(&element(1));
+
+        let mut third = MuHash3072::new();
+        third.insert(&element(3));
+        third.insert(&element(1));
+
+        assert_eq!(first.finalize(), second.finalize());
+        assert_eq!(first.finalize(), third.finalize());
+        assert!(first.finalize() != MuHash3072::new().finalize());
+
+        third.remove(&element(1));
+        third.remove(&element(3));
+        assert_eq!(third.finalize(), MuHash3072::new().finalize());
+    }
+
+    #[test]
+    fn test_muhash_serialization() {
+        let mut muhash = MuHash3072::new();
+        muhash.insert(&element(1));
+        muhash.remove(&element(2));
+
+        let bytes = muhash.to_bytes();
+        assert_eq!(bytes.len(), 768);
+        assert_eq!(MuHash3072::from_bytes(&bytes), Some(muhash));
+        assert_eq!(MuHash3072::from_bytes(&bytes[1..]), None);
+    }
+}
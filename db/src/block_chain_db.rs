@@ -14,26 +14,65 @@ use kv::{
     COL_TRANSACTIONS, COL_TRANSACTIONS_META,
 };
 use parking_lot::RwLock;
-use ser::{deserialize, serialize, List};
-use std::collections::HashMap;
+use primitives::bigint::{Uint, U256};
+use ser::{
+    deserialize, serialize, serialize_with_flags, List, Reader, Serializable,
+    SERIALIZE_TRANSACTION_WITNESS,
+};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
+use std::io;
 use std::path::Path;
 use storage::{
-    BestBlock, BlockChain, BlockHeaderProvider, BlockOrigin, BlockProvider, BlockRef, CanonStore,
-    ConfigStore, Error, ForkChain, Forkable, IndexedBlockProvider, SideChainOrigin, Store,
-    TransactionMeta, TransactionMetaProvider, TransactionOutputProvider, TransactionProvider,
+    script_hash, AddressFunding, AddressIndexKey, AddressIndexProvider, AddressSpending,
+    AddressStats, BestBlock, BlockChain, BlockFilter, BlockFilterProvider, BlockHeaderProvider,
+    BlockOrigin, BlockProvider, BlockRef, CanonStore, ConfigStore, Error, ForkChain, Forkable,
+    IndexedBlockProvider, SanitizationStore, SideChainOrigin, Store, TransactionErrorCode,
+    TransactionErrorProvider, TransactionLocation, TransactionLocationProvider, TransactionMeta,
+    TransactionMetaProvider, TransactionOutputProvider, TransactionProvider, UtxoSnapshotInfo,
+    UtxoSnapshotStore, UtxoSnapshotTransaction, UtxoStats,
 };
 
 const KEY_BEST_BLOCK_NUMBER: &'static str = "best_block_number";
 const KEY_BEST_BLOCK_HASH: &'static str = "best_block_hash";
+const KEY_UTXO_STATS: &'static str = "utxo_stats";
+const KEY_PRUNED_HEIGHT: &'static str = "pruned_height";
+const KEY_ADDRESS_INDEX: &'static str = "address_index";
+/// Present while transactions of UTXO snapshot are written, until the snapshot is verified
+const KEY_SNAPSHOT_LOADING: &'static str = "snapshot_loading";
 
 const MAX_FORK_ROUTE_PRESET: usize = 2048;
+/// Max number of address index entries, read from the database at once
+const ADDRESS_INDEX_PAGE_SIZE: usize = 256;
+
+/// Minimal number of the most recent canonical blocks, which are never pruned.
+/// Nodes advertising NODE_NETWORK_LIMITED are expected to serve at least that many blocks.
+pub const MIN_BLOCKS_TO_KEEP: u32 = 288;
+
+/// Pruning is only attempted when best block number is a multiple of this value,
+/// so that recent blocks aren't read on every canonization
+const PRUNE_INTERVAL: u32 = 32;
+
+/// Number of snapshotted transactions, written to the database at once
+const SNAPSHOT_WRITE_BATCH: usize = 10_000;
+
+/// Which canonical blocks are kept, when pruning is enabled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PruneMode {
+    /// Keep given number of the most recent blocks
+    Depth(u32),
+    /// Keep the most recent blocks, which total size (in bytes) fits given value.
+    /// At least `MIN_BLOCKS_TO_KEEP` blocks are kept regardless of their size.
+    Size(u64),
+}
 
 pub struct BlockChainDatabase<T>
 where
     T: KeyValueDatabase,
 {
     best_block: RwLock<BestBlock>,
+    prune_mode: Option<PruneMode>,
+    address_index: bool,
     db: T,
 }
 
@@ -75,10 +114,14 @@ impl BlockChainDatabase<CacheDatabase<AutoFlushingOverlayDatabase<DiskDatabase>>
 
         cfg.bloom_filters.insert(Some(COL_TRANSACTIONS_META), 32);
 
-        match DiskDatabase::open(cfg, path) {
-            Ok(db) => Ok(Self::open_with_cache(db)),
-            Err(err) => Err(Error::DatabaseError(err)),
+        let db = match DiskDatabase::open(cfg, path) {
+            Ok(db) => Self::open_with_cache(db),
+            Err(err) => return Err(Error::DatabaseError(err)),
+        };
+        if db.is_snapshot_incomplete() {
+            return Err(snapshot_error(INCOMPLETE_SNAPSHOT));
         }
+        Ok(db)
     }
 }
 
@@ -102,8 +145,11 @@ where
     pub fn open_with_cache(db: T) -> Self {
         let db = CacheDatabase::new(AutoFlushingOverlayDatabase::new(db, 50));
         let best_block = Self::read_best_block(&db).unwrap_or_default();
+        let address_index = Self::read_address_index(&db);
         BlockChainDatabase {
             best_block: RwLock::new(best_block),
+            prune_mode: None,
+            address_index: address_index,
             db: db,
         }
     }
@@ -134,14 +180,55 @@ where
         }
     }
 
+    fn read_address_index(db: &T) -> bool {
+        db.get(&Key::Meta(KEY_ADDRESS_INDEX))
+            .expect("db value to be fine")
+            .into_option()
+            .is_some()
+    }
+
     pub fn open(db: T) -> Self {
         let best_block = Self::read_best_block(&db).unwrap_or_default();
+        let address_index = Self::read_address_index(&db);
         BlockChainDatabase {
             best_block: RwLock::new(best_block),
+            prune_mode: None,
+            address_index: address_index,
             db: db,
         }
     }
 
+    /// Enables address index, which is maintained from the genesis block.
+    /// Index could only be enabled for the empty database. Once enabled, it is always maintained.
+    pub fn with_address_index(mut self) -> Result<Self, Error> {
+        if !self.address_index && self.block_hash(0).is_some() {
+            return Err(Error::AddressIndexUnavailable);
+        }
+        self.address_index = true;
+        Ok(self)
+    }
+
+    /// Enables pruning of old blocks.
+    /// Pruned blocks keep their headers and transactions meta, but their bodies are removed
+    /// along with transactions, which are no longer a part of the UTXO set.
+    pub fn with_prune_mode(mut self, prune_mode: PruneMode) -> Self {
+        self.prune_mode = Some(prune_mode);
+        self
+    }
+
+    /// Returns true if loading of UTXO snapshot has been started, but not completed.
+    /// Database then has transactions of unverified snapshot and should be removed
+    pub fn is_snapshot_incomplete(&self) -> bool {
+        self.get(Key::Meta(KEY_SNAPSHOT_LOADING)).is_some()
+    }
+
+    /// Returns number of the most recent pruned block, if any block has been pruned
+    pub fn pruned_height(&self) -> Option<u32> {
+        self.get(Key::Meta(KEY_PRUNED_HEIGHT))
+            .and_then(Value::as_meta)
+            .map(|height| deserialize(&**height).expect("Inconsistent DB. Invalid pruned height."))
+    }
+
     pub fn best_block(&self) -> BestBlock {
         self.best_block.read().clone()
     }
@@ -166,9 +253,12 @@ where
     }
 
     pub fn switch_to_fork(&self, fork: ForkChainDatabase<T>) -> Result<(), Error> {
-        let mut best_block = self.best_block.write();
-        *best_block = fork.blockchain.best_block.read().clone();
-        fork.blockchain.db.flush().map_err(Error::DatabaseError)
+        {
+            let mut best_block = self.best_block.write();
+            *best_block = fork.blockchain.best_block.read().clone();
+            fork.blockchain.db.flush().map_err(Error::DatabaseError)?;
+        }
+        self.prune()
     }
 
     pub fn block_origin(&self, header: &IndexedBlockHeader) -> Result<BlockOrigin, Error> {
@@ -237,7 +327,11 @@ where
             return Err(Error::UnknownParent);
         }
 
+        let chain_work =
+            self.chain_work_inclusive(&parent_hash) + block.header.raw.bits.to_work();
+
         let mut update = DBTransaction::new();
+        update.insert(KeyValue::BlockChainWork(block.hash().clone(), chain_work));
         update.insert(KeyValue::BlockHeader(
             block.hash().clone(),
             block.header.raw,
@@ -261,8 +355,10 @@ where
 
     /// Rollbacks single best block
     fn rollback_best(&self) -> Result<H256, Error> {
-        let decanonized = match self.block(self.best_block.read().hash.clone().into()) {
+        let best_hash = self.best_block.read().hash.clone();
+        let decanonized = match self.block(best_hash.clone().into()) {
             Some(block) => block,
+            None if self.is_pruned(best_hash.into()) => return Err(Error::BlockPruned),
             None => return Ok(H256::default()),
         };
         let decanonized_hash = self.decanonize()?;
@@ -273,7 +369,9 @@ where
         // {CanonChain, SideChain, SideChainBecomingCanonChain}
         let mut update = DBTransaction::new();
         update.delete(Key::BlockHeader(decanonized_hash.clone()));
+        update.delete(Key::BlockChainWork(decanonized_hash.clone()));
         update.delete(Key::BlockTransactions(decanonized_hash.clone()));
+        update.delete(Key::BlockTransactionsValidity(decanonized_hash.clone()));
         for tx in decanonized.transactions.into_iter() {
             update.delete(Key::Transaction(tx.hash()));
         }
@@ -283,18 +381,56 @@ where
         Ok(self.best_block().hash)
     }
 
-    pub fn canonize_with_invalid(&self, hash: &H256, tx_flags: &Vec<bool>) -> Result<(), Error> {
+    /// Marks block as a new best block.
+    /// Block must be already inserted into db, and it's parent must be current best block.
+    /// Updates meta data.
+    /// If block has been canonized with invalid transactions before, the same transactions
+    /// are marked as invalid again. Otherwise all block transactions are considered valid.
+    pub fn canonize(&self, hash: &H256) -> Result<(), Error> {
+        let tx_flags = self.block_transactions_validity(hash);
+        self.canonize_block(hash, tx_flags, &[])?;
+        self.prune()
+    }
+
+    /// Marks block as a new best block.
+    /// Outputs, spent by invalid transactions (tx_errors[i] is some), are not marked as spent
+    /// and outputs of invalid transactions are never returned as unspent.
+    /// Validity flags are stored, so that the block could be canonized again after reorganization.
+    /// Errors are stored in the same database transaction and are kept after block decanonization,
+    /// so that they could be audited later.
+    pub fn canonize_with_invalid(
+        &self,
+        hash: &H256,
+        tx_errors: &[Option<TransactionErrorCode>],
+    ) -> Result<(), Error> {
+        let tx_flags = tx_errors.iter().map(Option::is_none).collect();
+        self.canonize_block(hash, Some(tx_flags), tx_errors)?;
+        self.prune()
+    }
+
+    fn canonize_block(
+        &self,
+        hash: &H256,
+        tx_flags: Option<Vec<bool>>,
+        tx_errors: &[Option<TransactionErrorCode>],
+    ) -> Result<(), Error> {
         let mut best_block = self.best_block.write();
         let block = match self.indexed_block(hash.clone().into()) {
             Some(block) => block,
             None => return Err(Error::CannotCanonize),
         };
 
-
         if best_block.hash != block.header.raw.previous_header_hash {
             return Err(Error::CannotCanonize);
         }
 
+        if let Some(ref tx_flags) = tx_flags {
+            if tx_flags.len() != block.transactions.len() {
+                return Err(Error::CannotCanonize);
+            }
+        }
+        let is_valid = |tx_index: usize| tx_flags.as_ref().map_or(true, |flags| flags[tx_index]);
+
         let new_best_block = BestBlock {
             hash: hash.clone(),
             number: if block.header.raw.previous_header_hash.is_zero() {
@@ -325,127 +461,99 @@ where
             serialize(&new_best_block.number),
         ));
 
+        if let Some(ref tx_flags) = tx_flags {
+            update.insert(KeyValue::BlockTransactionsValidity(
+                hash.clone(),
+                List::from(tx_flags.clone()),
+            ));
+        }
+        for (tx, error) in block.transactions.iter().zip(tx_errors) {
+            if let Some(error) = *error {
+                update.insert(KeyValue::TransactionError(tx.hash.clone(), error));
+            }
+        }
+
+        // statistics are maintained from the genesis block, so they're missing in older databases
+        let mut utxo_stats = if new_best_block.number == 0 {
+            Some(UtxoStats::default())
+        } else {
+            self.utxo_stats()
+        };
+
         let mut modified_meta: HashMap<H256, TransactionMeta> = HashMap::new();
         if let Some(tx) = block.transactions.first() {
-            let meta = TransactionMeta::new_coinbase(new_best_block.number, tx.raw.outputs.len(), tx_flags[0]);
+            let meta = TransactionMeta::new_coinbase(
+                new_best_block.number,
+                tx.raw.outputs.len(),
+                is_valid(0),
+            );
             modified_meta.insert(tx.hash.clone(), meta);
         }
-        let mut i = 0;
-        for tx in block.transactions.iter().skip(1) {
-            i += 1;
-            let tx_flag = tx_flags[i];
-            println!("tx_flag {} {:?}", tx_flag, tx);
 
-            if tx_flag {
+        for (tx_index, tx) in block.transactions.iter().enumerate() {
+            update.insert(KeyValue::TransactionLocation(
+                tx.hash.clone(),
+                TransactionLocation {
+                    block_hash: hash.clone(),
+                    index: tx_index as u32,
+                },
+            ));
+
+            let tx_is_valid = is_valid(tx_index);
+            if tx_index != 0 {
                 modified_meta.insert(
                     tx.hash.clone(),
-                    TransactionMeta::new(new_best_block.number, tx.raw.outputs.len(), true),
+                    TransactionMeta::new(new_best_block.number, tx.raw.outputs.len(), tx_is_valid),
                 );
+            }
 
-                for input in &tx.raw.inputs {
-                    use std::collections::hash_map::Entry;
-
-                    match modified_meta.entry(input.previous_output.hash.clone()) {
-                        Entry::Occupied(mut entry) => {
-                            let meta = entry.get_mut();
-                            meta.denote_used(input.previous_output.index as usize);
-                        }
-                        Entry::Vacant(entry) => {
-                            let mut meta = self
-                                .transaction_meta(&input.previous_output.hash)
-                                .ok_or(Error::CannotCanonize)?;
-                            meta.denote_used(input.previous_output.index as usize);
-                            entry.insert(meta);
-                        }
+            // invalid transactions do not create or spend anything
+            if !tx_is_valid {
+                continue;
+            }
+
+            // genesis block outputs are not a part of UTXO set
+            if new_best_block.number != 0 {
+                if let Some(ref mut utxo_stats) = utxo_stats {
+                    for (output_index, output) in tx.raw.outputs.iter().enumerate() {
+                        let prevout = OutPoint {
+                            hash: tx.hash.clone(),
+                            index: output_index as u32,
+                        };
+                        utxo_stats.add(&prevout, new_best_block.number, tx_index == 0, output);
                     }
                 }
-            } else {
-                modified_meta.insert(
-                    tx.hash.clone(),
-                    TransactionMeta::new(new_best_block.number, tx.raw.outputs.len(), false),
-                );
             }
-        }
-
-        for (hash, meta) in modified_meta.into_iter() {
-            update.insert(KeyValue::TransactionMeta(hash, meta));
-        }
-        println!("hello2");
-        self.db.write(update).map_err(Error::DatabaseError)?;
-        *best_block = new_best_block;
-        Ok(())
-    }
-
-    /// Marks block as a new best block.
-    /// Block must be already inserted into db, and it's parent must be current best block.
-    /// Updates meta data.
-    pub fn canonize(&self, hash: &H256) -> Result<(), Error> {
-        let mut best_block = self.best_block.write();
-        let block = match self.indexed_block(hash.clone().into()) {
-            Some(block) => block,
-            None => return Err(Error::CannotCanonize),
-        };
-
-        if best_block.hash != block.header.raw.previous_header_hash {
-            return Err(Error::CannotCanonize);
-        }
-
-        let new_best_block = BestBlock {
-            hash: hash.clone(),
-            number: if block.header.raw.previous_header_hash.is_zero() {
-                assert_eq!(best_block.number, 0);
-                0
-            } else {
-                best_block.number + 1
-            },
-        };
-
-        trace!(target: "db", "canonize {:?}", new_best_block);
-
-        let mut update = DBTransaction::new();
-        update.insert(KeyValue::BlockHash(
-            new_best_block.number,
-            new_best_block.hash.clone(),
-        ));
-        update.insert(KeyValue::BlockNumber(
-            new_best_block.hash.clone(),
-            new_best_block.number,
-        ));
-        update.insert(KeyValue::Meta(
-            KEY_BEST_BLOCK_HASH,
-            serialize(&new_best_block.hash),
-        ));
-        update.insert(KeyValue::Meta(
-            KEY_BEST_BLOCK_NUMBER,
-            serialize(&new_best_block.number),
-        ));
-
-        let mut modified_meta: HashMap<H256, TransactionMeta> = HashMap::new();
-        if let Some(tx) = block.transactions.first() {
-            let meta = TransactionMeta::new_coinbase(new_best_block.number, tx.raw.outputs.len(), true);
-            modified_meta.insert(tx.hash.clone(), meta);
-        }
 
-        for tx in block.transactions.iter().skip(1) {
-            modified_meta.insert(
-                tx.hash.clone(),
-                TransactionMeta::new(new_best_block.number, tx.raw.outputs.len(), true),
-            );
+            if tx_index == 0 {
+                continue;
+            }
 
             for input in &tx.raw.inputs {
                 use std::collections::hash_map::Entry;
 
-                match modified_meta.entry(input.previous_output.hash.clone()) {
-                    Entry::Occupied(mut entry) => {
-                        let meta = entry.get_mut();
-                        meta.denote_used(input.previous_output.index as usize);
-                    }
+                let meta = match modified_meta.entry(input.previous_output.hash.clone()) {
+                    Entry::Occupied(entry) => entry.into_mut(),
                     Entry::Vacant(entry) => {
-                        let mut meta = self
+                        let meta = self
                             .transaction_meta(&input.previous_output.hash)
                             .ok_or(Error::CannotCanonize)?;
-                        meta.denote_used(input.previous_output.index as usize);
-                        entry.insert(meta);
+                        entry.insert(meta)
+                    }
+                };
+                meta.denote_used(input.previous_output.index as usize);
+
+                if let Some(ref mut utxo_stats) = utxo_stats {
+                    if meta.is_valid() && meta.height() != 0 {
+                        let output = self
+                            .spent_output(&input.previous_output)
+                            .ok_or(Error::CannotCanonize)?;
+                        utxo_stats.remove(
+                            &input.previous_output,
+                            meta.height(),
+                            meta.is_coinbase(),
+                            &output,
+                        );
                     }
                 }
             }
@@ -455,6 +563,21 @@ where
             update.insert(KeyValue::TransactionMeta(hash, meta));
         }
 
+        if let Some(ref utxo_stats) = utxo_stats {
+            update.insert(KeyValue::Meta(KEY_UTXO_STATS, serialize(utxo_stats)));
+        }
+
+        if let Some(filter) = self.build_block_filter(&block, new_best_block.number) {
+            update.insert(KeyValue::BlockFilter(hash.clone(), filter));
+        }
+
+        if self.address_index {
+            if new_best_block.number == 0 {
+                update.insert(KeyValue::Meta(KEY_ADDRESS_INDEX, serialize(&true)));
+            }
+            self.index_addresses(&block, new_best_block.number, &is_valid, &mut update)?;
+        }
+
         self.db.write(update).map_err(Error::DatabaseError)?;
         *best_block = new_best_block;
         Ok(())
@@ -464,6 +587,9 @@ where
         let mut best_block = self.best_block.write();
         let block = match self.indexed_block(best_block.hash.clone().into()) {
             Some(block) => block,
+            None if self.is_pruned(best_block.hash.clone().into()) => {
+                return Err(Error::BlockPruned)
+            }
             None => return Err(Error::CannotCanonize),
         };
         let block_number = best_block.number;
@@ -493,22 +619,68 @@ where
             serialize(&new_best_block.number),
         ));
 
+        let mut utxo_stats = self.utxo_stats();
+        let tx_flags: Vec<bool> = block
+            .transactions
+            .iter()
+            .map(|tx| {
+                self.transaction_meta(&tx.hash)
+                    .map_or(true, |meta| meta.is_valid())
+            })
+            .collect();
+
         let mut modified_meta: HashMap<H256, TransactionMeta> = HashMap::new();
-        for tx in block.transactions.iter().skip(1) {
+        for (tx_index, tx) in block.transactions.iter().enumerate().skip(1) {
+            // outputs, referenced by invalid transactions, have never been marked as spent
+            if !tx_flags[tx_index] {
+                continue;
+            }
+
             for input in &tx.raw.inputs {
                 use std::collections::hash_map::Entry;
 
-                match modified_meta.entry(input.previous_output.hash.clone()) {
-                    Entry::Occupied(mut entry) => {
-                        let meta = entry.get_mut();
-                        meta.denote_unused(input.previous_output.index as usize);
-                    }
+                let meta = match modified_meta.entry(input.previous_output.hash.clone()) {
+                    Entry::Occupied(entry) => entry.into_mut(),
                     Entry::Vacant(entry) => {
-                        let mut meta = self
+                        let meta = self
                             .transaction_meta(&input.previous_output.hash)
                             .ok_or(Error::CannotCanonize)?;
-                        meta.denote_unused(input.previous_output.index as usize);
-                        entry.insert(meta);
+                        entry.insert(meta)
+                    }
+                };
+                meta.denote_unused(input.previous_output.index as usize);
+
+                if let Some(ref mut utxo_stats) = utxo_stats {
+                    if meta.is_valid() && meta.height() != 0 {
+                        let output = self
+                            .spent_output(&input.previous_output)
+                            .ok_or(Error::CannotCanonize)?;
+                        utxo_stats.add(
+                            &input.previous_output,
+                            meta.height(),
+                            meta.is_coinbase(),
+                            &output,
+                        );
+                    }
+                }
+            }
+        }
+
+        // outputs are removed after spent outputs are restored, so that outputs
+        // created and spent in the same block are never removed twice
+        if block_number != 0 {
+            if let Some(ref mut utxo_stats) = utxo_stats {
+                for (tx_index, tx) in block.transactions.iter().enumerate() {
+                    if !tx_flags[tx_index] {
+                        continue;
+                    }
+
+                    for (output_index, output) in tx.raw.outputs.iter().enumerate() {
+                        let prevout = OutPoint {
+                            hash: tx.hash.clone(),
+                            index: output_index as u32,
+                        };
+                        utxo_stats.remove(&prevout, block_number, tx_index == 0, output);
                     }
                 }
             }
@@ -518,7 +690,16 @@ where
             update.insert(KeyValue::TransactionMeta(hash, meta));
         }
 
+        if let Some(ref utxo_stats) = utxo_stats {
+            update.insert(KeyValue::Meta(KEY_UTXO_STATS, serialize(utxo_stats)));
+        }
+
+        if self.address_index {
+            self.unindex_addresses(&block, block_number, &tx_flags, &mut update)?;
+        }
+
         for tx in block.transactions {
+            update.delete(Key::TransactionLocation(tx.hash.clone()));
             update.delete(Key::TransactionMeta(tx.hash));
         }
 
@@ -527,6 +708,267 @@ where
         Ok(block_hash)
     }
 
+    /// Inserts outputs and inputs of valid block transactions into the address index
+    fn index_addresses<F>(
+        &self,
+        block: &IndexedBlock,
+        block_number: u32,
+        is_valid: &F,
+        update: &mut DBTransaction,
+    ) -> Result<(), Error>
+    where
+        F: Fn(usize) -> bool,
+    {
+        let (funding, spending) = self.address_index_entries(block, block_number, is_valid)?;
+        let mut stats: HashMap<H256, AddressStats> = HashMap::new();
+        for (hash, entry) in funding {
+            stats
+                .entry(hash.clone())
+                .or_insert_with(|| self.address_stats(&hash))
+                .funding += 1;
+            update.insert(KeyValue::AddressFunding(entry.key(hash), entry));
+        }
+        for (hash, entry) in spending {
+            stats
+                .entry(hash.clone())
+                .or_insert_with(|| self.address_stats(&hash))
+                .spending += 1;
+            update.insert(KeyValue::AddressSpending(entry.key(hash), entry));
+        }
+        for (hash, stats) in stats {
+            update.insert(KeyValue::AddressStats(hash, stats));
+        }
+        Ok(())
+    }
+
+    /// Address index entries of valid block transactions, along with the hashes of their scripts
+    fn address_index_entries<F>(
+        &self,
+        block: &IndexedBlock,
+        block_number: u32,
+        is_valid: &F,
+    ) -> Result<(Vec<(H256, AddressFunding)>, Vec<(H256, AddressSpending)>), Error>
+    where
+        F: Fn(usize) -> bool,
+    {
+        let mut funding = Vec::new();
+        let mut spending = Vec::new();
+        for (tx_index, tx) in block.transactions.iter().enumerate() {
+            // invalid transactions do not create or spend anything
+            if !is_valid(tx_index) {
+                continue;
+            }
+
+            if tx_index != 0 {
+                for (input_index, input) in tx.raw.inputs.iter().enumerate() {
+                    let output = self
+                        .spent_output(&input.previous_output)
+                        .ok_or(Error::CannotCanonize)?;
+                    spending.push((
+                        script_hash(&output.script_pubkey),
+                        AddressSpending {
+                            outpoint: input.previous_output.clone(),
+                            value: output.value,
+                            spender: tx.hash.clone(),
+                            input_index: input_index as u32,
+                            height: block_number,
+                        },
+                    ));
+                }
+            }
+
+            for (output_index, output) in tx.raw.outputs.iter().enumerate() {
+                funding.push((
+                    script_hash(&output.script_pubkey),
+                    AddressFunding {
+                        outpoint: OutPoint {
+                            hash: tx.hash.clone(),
+                            index: output_index as u32,
+                        },
+                        value: output.value,
+                        height: block_number,
+                    },
+                ));
+            }
+        }
+        Ok((funding, spending))
+    }
+
+    /// Builds basic filter of the block, which is being canonized.
+    /// Filters are chained from the genesis block, so nothing is built when the parent filter
+    /// is missing (i.e. for databases, created before filters were maintained).
+    fn build_block_filter(&self, block: &IndexedBlock, block_number: u32) -> Option<BlockFilter> {
+        let previous_header = if block_number == 0 {
+            H256::default()
+        } else {
+            self.block_filter(&block.header.raw.previous_header_hash)?
+                .header
+        };
+
+        // outputs, spent by invalid transactions, could be missing
+        let spent_scripts: Vec<Bytes> = block
+            .transactions
+            .iter()
+            .skip(1)
+            .flat_map(|tx| tx.raw.inputs.iter())
+            .filter_map(|input| self.spent_output(&input.previous_output))
+            .map(|output| output.script_pubkey)
+            .collect();
+
+        Some(BlockFilter::basic(block, &spent_scripts, &previous_header))
+    }
+
+    /// Removes entries of the best block from the address index
+    fn unindex_addresses(
+        &self,
+        block: &IndexedBlock,
+        block_number: u32,
+        tx_flags: &[bool],
+        update: &mut DBTransaction,
+    ) -> Result<(), Error> {
+        let is_valid = |tx_index: usize| tx_flags[tx_index];
+        let (funding, spending) = self.address_index_entries(block, block_number, &is_valid)?;
+        let mut stats: HashMap<H256, AddressStats> = HashMap::new();
+        for (hash, entry) in funding {
+            {
+                let stats = stats
+                    .entry(hash.clone())
+                    .or_insert_with(|| self.address_stats(&hash));
+                stats.funding = stats.funding.saturating_sub(1);
+            }
+            update.delete(Key::AddressFunding(entry.key(hash)));
+        }
+        for (hash, entry) in spending {
+            {
+                let stats = stats
+                    .entry(hash.clone())
+                    .or_insert_with(|| self.address_stats(&hash));
+                stats.spending = stats.spending.saturating_sub(1);
+            }
+            update.delete(Key::AddressSpending(entry.key(hash)));
+        }
+        for (hash, stats) in stats {
+            if stats == AddressStats::default() {
+                update.delete(Key::AddressStats(hash));
+            } else {
+                update.insert(KeyValue::AddressStats(hash, stats));
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes bodies of old canonical blocks according to the prune mode.
+    /// Transactions of pruned blocks are removed too, unless they have unspent outputs
+    /// or their outputs are spent by the blocks, which are kept (so these could be decanonized).
+    fn prune(&self) -> Result<(), Error> {
+        let prune_mode = match self.prune_mode {
+            Some(prune_mode) => prune_mode,
+            None => return Ok(()),
+        };
+        let best_block = self.best_block();
+        if best_block.number % PRUNE_INTERVAL != 0 {
+            return Ok(());
+        }
+
+        // find the oldest kept block, remembering transactions spent by kept blocks
+        let first_unpruned = self.pruned_height().map_or(0, |height| height + 1);
+        let mut kept_from = best_block.number + 1;
+        let mut kept_size = 0u64;
+        let mut spent_by_kept: HashSet<H256> = HashSet::new();
+        while kept_from > first_unpruned {
+            let kept_count = best_block.number + 1 - kept_from;
+            if let PruneMode::Depth(depth) = prune_mode {
+                if kept_count >= depth {
+                    break;
+                }
+            }
+
+            let block = self
+                .indexed_block((kept_from - 1).into())
+                .ok_or(Error::CannotCanonize)?;
+            if let PruneMode::Size(size) = prune_mode {
+                kept_size += block.size() as u64;
+                if kept_count >= MIN_BLOCKS_TO_KEEP && kept_size > size {
+                    break;
+                }
+            }
+
+            spent_by_kept.extend(block.transactions.iter().skip(1).flat_map(|tx| {
+                tx.raw
+                    .inputs
+                    .iter()
+                    .map(|input| input.previous_output.hash.clone())
+            }));
+            kept_from -= 1;
+        }
+
+        if kept_from == first_unpruned {
+            return Ok(());
+        }
+
+        trace!(target: "db", "prune blocks {}..{}", first_unpruned, kept_from);
+
+        let mut update = DBTransaction::new();
+        for number in first_unpruned..kept_from {
+            let block = self
+                .indexed_block(number.into())
+                .ok_or(Error::CannotCanonize)?;
+            update.delete(Key::BlockTransactions(block.hash().clone()));
+
+            // transactions of the block and transactions, spent by the block, could become prunable
+            let spent = block.transactions.iter().skip(1).flat_map(|tx| {
+                tx.raw
+                    .inputs
+                    .iter()
+                    .map(|input| input.previous_output.hash.clone())
+            });
+            let candidates = block
+                .transactions
+                .iter()
+                .map(|tx| tx.hash.clone())
+                .chain(spent)
+                .collect::<Vec<_>>();
+            for tx_hash in candidates {
+                if spent_by_kept.contains(&tx_hash) {
+                    continue;
+                }
+
+                match self.transaction_meta(&tx_hash) {
+                    Some(ref meta) if meta.is_valid() && !meta.is_fully_spent() => (),
+                    _ => update.delete(Key::Transaction(tx_hash)),
+                }
+            }
+        }
+        update.insert(KeyValue::Meta(KEY_PRUNED_HEIGHT, serialize(&(kept_from - 1))));
+
+        self.db.write(update).map_err(Error::DatabaseError)
+    }
+
+    /// Returns chain work up to the given block (inclusive).
+    /// Databases, created before chain work has been stored, have it recomputed from headers.
+    fn chain_work_inclusive(&self, hash: &H256) -> U256 {
+        let mut chain_work = U256::zero();
+        let mut hash = hash.clone();
+        while !hash.is_zero() {
+            if let Some(stored_chain_work) = self.block_chain_work(&hash) {
+                return chain_work + stored_chain_work;
+            }
+
+            let header = self
+                .block_header(hash.into())
+                .expect("ancestors of inserted blocks are always in db; qed");
+            chain_work = chain_work + header.bits.to_work();
+            hash = header.previous_header_hash;
+        }
+        chain_work
+    }
+
+    /// Returns output, referenced by the outpoint, even if it has been spent already
+    fn spent_output(&self, prevout: &OutPoint) -> Option<TransactionOutput> {
+        self.transaction(&prevout.hash)
+            .and_then(|tx| tx.outputs.into_iter().nth(prevout.index as usize))
+    }
+
     fn get(&self, key: Key) -> Option<Value> {
         self.db
             .get(&key)
@@ -534,6 +976,12 @@ where
             .into_option()
     }
 
+    fn block_transactions_validity(&self, hash: &H256) -> Option<Vec<bool>> {
+        self.get(Key::BlockTransactionsValidity(hash.clone()))
+            .and_then(Value::as_block_transactions_validity)
+            .map(List::into)
+    }
+
     fn resolve_hash(&self, block_ref: BlockRef) -> Option<H256> {
         match block_ref {
             BlockRef::Number(n) => self.block_hash(n),
@@ -572,8 +1020,17 @@ where
             .and_then(Value::as_block_hash)
     }
 
+    fn block_chain_work(&self, hash: &H256) -> Option<U256> {
+        self.get(Key::BlockChainWork(hash.clone()))
+            .and_then(Value::as_block_chain_work)
+    }
+
     fn block(&self, block_ref: BlockRef) -> Option<Block> {
         self.resolve_hash(block_ref).and_then(|block_hash| {
+            if self.is_pruned(block_hash.clone().into()) {
+                return None;
+            }
+
             self.block_header(block_hash.clone().into()).map(|header| {
                 let transactions = self.block_transactions(block_hash.into());
                 Block::new(header, transactions)
@@ -587,6 +1044,13 @@ where
             .is_some()
     }
 
+    fn is_pruned(&self, block_ref: BlockRef) -> bool {
+        self.resolve_hash(block_ref).map_or(false, |hash| {
+            self.contains_block(hash.clone().into())
+                && self.get(Key::BlockTransactions(hash)).is_none()
+        })
+    }
+
     fn block_transaction_hashes(&self, block_ref: BlockRef) -> Vec<H256> {
         self.resolve_hash(block_ref)
             .and_then(|hash| self.get(Key::BlockTransactions(hash)))
@@ -618,6 +1082,10 @@ where
 
     fn indexed_block(&self, block_ref: BlockRef) -> Option<IndexedBlock> {
         self.resolve_hash(block_ref).and_then(|block_hash| {
+            if self.is_pruned(block_hash.clone().into()) {
+                return None;
+            }
+
             self.indexed_block_header(block_hash.clone().into())
                 .map(|header| {
                     let transactions = self.indexed_block_transactions(block_hash.into());
@@ -648,6 +1116,129 @@ where
     }
 }
 
+impl<T> TransactionLocationProvider for BlockChainDatabase<T>
+where
+    T: KeyValueDatabase,
+{
+    fn transaction_location(&self, hash: &H256) -> Option<TransactionLocation> {
+        self.get(Key::TransactionLocation(hash.clone()))
+            .and_then(Value::as_transaction_location)
+    }
+}
+
+impl<T> BlockFilterProvider for BlockChainDatabase<T>
+where
+    T: KeyValueDatabase,
+{
+    fn block_filter(&self, block_hash: &H256) -> Option<BlockFilter> {
+        self.get(Key::BlockFilter(block_hash.clone()))
+            .and_then(Value::as_block_filter)
+    }
+}
+
+impl<T> TransactionErrorProvider for BlockChainDatabase<T>
+where
+    T: KeyValueDatabase,
+{
+    fn transaction_error(&self, hash: &H256) -> Option<TransactionErrorCode> {
+        self.get(Key::TransactionError(hash.clone()))
+            .and_then(Value::as_transaction_error)
+    }
+}
+
+impl<T> AddressIndexProvider for BlockChainDatabase<T>
+where
+    T: KeyValueDatabase,
+{
+    fn has_address_index(&self) -> bool {
+        self.address_index
+    }
+
+    fn address_stats(&self, script_hash: &H256) -> AddressStats {
+        self.get(Key::AddressStats(script_hash.clone()))
+            .and_then(Value::as_address_stats)
+            .unwrap_or_default()
+    }
+
+    fn address_funding<'a>(
+        &'a self,
+        script_hash: &H256,
+    ) -> Box<Iterator<Item = AddressFunding> + 'a> {
+        let entries = AddressIndexEntries::new(&self.db, Key::AddressFunding, script_hash.clone());
+        Box::new(entries.filter_map(|entry| match entry {
+            KeyValue::AddressFunding(_, funding) => Some(funding),
+            _ => None,
+        }))
+    }
+
+    fn address_spending<'a>(
+        &'a self,
+        script_hash: &H256,
+    ) -> Box<Iterator<Item = AddressSpending> + 'a> {
+        let entries = AddressIndexEntries::new(&self.db, Key::AddressSpending, script_hash.clone());
+        Box::new(entries.filter_map(|entry| match entry {
+            KeyValue::AddressSpending(_, spending) => Some(spending),
+            _ => None,
+        }))
+    }
+}
+
+/// Address index entries of the script, which are read from the database page by page
+struct AddressIndexEntries<'a, T>
+where
+    T: 'a + KeyValueDatabase,
+{
+    db: &'a T,
+    /// Address index column
+    column: fn(AddressIndexKey) -> Key,
+    /// The first key of the next page and true if the entry with this key has already been returned
+    next_page: Option<(AddressIndexKey, bool)>,
+    page: VecDeque<KeyValue>,
+}
+
+impl<'a, T> AddressIndexEntries<'a, T>
+where
+    T: 'a + KeyValueDatabase,
+{
+    fn new(db: &'a T, column: fn(AddressIndexKey) -> Key, script_hash: H256) -> Self {
+        AddressIndexEntries {
+            db: db,
+            column: column,
+            next_page: Some((AddressIndexKey::first(script_hash), false)),
+            page: VecDeque::new(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for AddressIndexEntries<'a, T>
+where
+    T: 'a + KeyValueDatabase,
+{
+    type Item = KeyValue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.page.is_empty() {
+            let (from, returned) = self.next_page.take()?;
+            let mut page: VecDeque<KeyValue> = self
+                .db
+                .address_index(&(self.column)(from.clone()), ADDRESS_INDEX_PAGE_SIZE)
+                .expect("db value to be fine")
+                .into();
+            if page.len() == ADDRESS_INDEX_PAGE_SIZE {
+                self.next_page = page
+                    .back()
+                    .and_then(KeyValue::address_index_key)
+                    .map(|key| (key.clone(), true));
+            }
+            if returned && page.front().and_then(KeyValue::address_index_key) == Some(&from) {
+                page.pop_front();
+            }
+            self.page = page;
+        }
+        self.page.pop_front()
+    }
+}
+
 impl<T> TransactionProvider for BlockChainDatabase<T>
 where
     T: KeyValueDatabase,
@@ -671,16 +1262,23 @@ where
         prevout: &OutPoint,
         _transaction_index: usize,
     ) -> Option<TransactionOutput> {
-        // return previous transaction outputs only for canon chain transactions
-        self.transaction_meta(&prevout.hash)
-            .and_then(|_| self.transaction(&prevout.hash))
-            .and_then(|tx| tx.outputs.into_iter().nth(prevout.index as usize))
+        // return previous transaction outputs only for valid canon chain transactions
+        match self.transaction_meta(&prevout.hash) {
+            Some(ref meta) if meta.is_valid() => self
+                .transaction(&prevout.hash)
+                .and_then(|tx| tx.outputs.into_iter().nth(prevout.index as usize)),
+            _ => None,
+        }
     }
 
     fn is_spent(&self, prevout: &OutPoint) -> bool {
-        self.transaction_meta(&prevout.hash)
-            .and_then(|meta| meta.is_spent(prevout.index as usize))
-            .unwrap_or(false)
+        // outputs of invalid transactions do not exist => could not be spent
+        match self.transaction_meta(&prevout.hash) {
+            Some(ref meta) if meta.is_valid() => {
+                meta.is_spent(prevout.index as usize).unwrap_or(false)
+            }
+            _ => false,
+        }
     }
 }
 
@@ -700,8 +1298,12 @@ where
         BlockChainDatabase::canonize(self, block_hash)
     }
 
-    fn canonize_with_invalid(&self, block_hash: &H256 , tx_flags: &Vec<bool>) -> Result<(), Error> {
-        BlockChainDatabase::canonize_with_invalid(self, block_hash, tx_flags)
+    fn canonize_with_invalid(
+        &self,
+        block_hash: &H256,
+        tx_errors: &[Option<TransactionErrorCode>],
+    ) -> Result<(), Error> {
+        BlockChainDatabase::canonize_with_invalid(self, block_hash, tx_errors)
     }
 
     fn decanonize(&self) -> Result<H256, Error> {
@@ -725,9 +1327,12 @@ where
     }
 
     fn switch_to_fork<'a>(&self, fork: Box<ForkChain + 'a>) -> Result<(), Error> {
-        let mut best_block = self.best_block.write();
-        *best_block = fork.store().best_block();
-        fork.flush()
+        {
+            let mut best_block = self.best_block.write();
+            *best_block = fork.store().best_block();
+            fork.flush()?;
+        }
+        self.prune()
     }
 }
 
@@ -758,6 +1363,13 @@ where
     fn difficulty(&self) -> f64 {
         self.best_header().bits.to_f64()
     }
+
+    /// get UTXO set statistics
+    fn utxo_stats(&self) -> Option<UtxoStats> {
+        self.get(Key::Meta(KEY_UTXO_STATS))
+            .and_then(Value::as_meta)
+            .map(|stats| deserialize(&**stats).expect("Inconsistent DB. Invalid UTXO stats."))
+    }
 }
 
 impl<T> ConfigStore for BlockChainDatabase<T>
@@ -788,3 +1400,245 @@ where
         self.db.write(update).map_err(Error::DatabaseError)
     }
 }
+
+impl<T> SanitizationStore for BlockChainDatabase<T>
+where
+    T: KeyValueDatabase,
+{
+    fn sanitized_block(&self, block_hash: &H256) -> Option<Vec<bool>> {
+        self.get(Key::SanitizedBlock(block_hash.clone()))
+            .and_then(Value::as_sanitized_block)
+            .map(List::into)
+    }
+
+    fn sanitized_transaction(&self, tx_hash: &H256) -> Option<bool> {
+        self.get(Key::SanitizedTransaction(tx_hash.clone()))
+            .and_then(Value::as_sanitized_transaction)
+    }
+
+    fn insert_sanitized_block(
+        &self,
+        block: &IndexedBlock,
+        transactions_validity: &[bool],
+    ) -> Result<(), Error> {
+        let mut update = DBTransaction::new();
+        for (tx, is_valid) in block.transactions.iter().zip(transactions_validity) {
+            update.insert(KeyValue::SanitizedTransaction(tx.hash.clone(), *is_valid));
+        }
+        update.insert(KeyValue::SanitizedBlock(
+            block.hash().clone(),
+            List::from(transactions_validity.to_vec()),
+        ));
+        self.db.write(update).map_err(Error::DatabaseError)
+    }
+}
+
+impl<T> UtxoSnapshotStore for BlockChainDatabase<T>
+where
+    T: KeyValueDatabase,
+{
+    fn dump_utxo_snapshot(&self, output: &mut io::Write) -> Result<UtxoSnapshotInfo, Error> {
+        if self.pruned_height().is_some() {
+            return Err(Error::BlockPruned);
+        }
+
+        let best_block = self.best_block();
+        let info = UtxoSnapshotInfo {
+            block_hash: best_block.hash.clone(),
+            height: best_block.number,
+            utxo_stats: self.utxo_stats().ok_or_else(|| {
+                snapshot_error("UTXO set statistics are not maintained by this database")
+            })?,
+        };
+        write_snapshot(output, &info)?;
+
+        for number in 0..best_block.number + 1 {
+            let header = self
+                .block_header(number.into())
+                .ok_or(Error::CannotCanonize)?;
+            write_snapshot(output, &header)?;
+        }
+
+        // genesis outputs are not spendable and are not a part of the set
+        let mut utxo_stats = UtxoStats::default();
+        for number in 1..best_block.number + 1 {
+            for tx in self.indexed_block_transactions(number.into()) {
+                let meta = match self.transaction_meta(&tx.hash) {
+                    Some(meta) => meta,
+                    None => continue,
+                };
+                // duplicate transactions are only written at the height of their last occurrence
+                if meta.height() != number || !meta.is_valid() || meta.is_fully_spent() {
+                    continue;
+                }
+
+                for (index, tx_output) in tx.raw.outputs.iter().enumerate() {
+                    if meta.is_spent(index) == Some(false) {
+                        let prevout = OutPoint {
+                            hash: tx.hash.clone(),
+                            index: index as u32,
+                        };
+                        utxo_stats.add(&prevout, number, meta.is_coinbase(), tx_output);
+                    }
+                }
+
+                write_snapshot(
+                    output,
+                    &UtxoSnapshotTransaction {
+                        meta: meta,
+                        transaction: tx.raw,
+                    },
+                )?;
+            }
+        }
+
+        if !info.matches(&utxo_stats) {
+            return Err(snapshot_error(
+                "unspent transactions do not match UTXO set statistics of the database",
+            ));
+        }
+
+        Ok(info)
+    }
+
+    fn load_utxo_snapshot(&self, input: &mut io::Read) -> Result<UtxoSnapshotInfo, Error> {
+        if self.is_snapshot_incomplete() {
+            return Err(snapshot_error(INCOMPLETE_SNAPSHOT));
+        }
+        if self.block_hash(0).is_some() {
+            return Err(snapshot_error("database is not empty"));
+        }
+        // index is maintained from the genesis block, but snapshotted blocks have no bodies
+        if self.address_index {
+            return Err(snapshot_error(
+                "address index can not be built from the snapshot",
+            ));
+        }
+
+        let mut reader = Reader::from_read(input);
+        let info: UtxoSnapshotInfo = reader
+            .read()
+            .map_err(|err| snapshot_error(&format!("invalid summary: {:?}", err)))?;
+
+        // headers are written last, along with the best block, so that partially loaded
+        // database is still considered empty
+        let mut headers_update = DBTransaction::new();
+        let mut previous_hash = H256::default();
+        let mut chain_work = U256::zero();
+        for number in 0..info.height + 1 {
+            let header: BlockHeader = reader
+                .read()
+                .map_err(|err| snapshot_error(&format!("invalid header {}: {:?}", number, err)))?;
+            if header.previous_header_hash != previous_hash {
+                return Err(snapshot_error(&format!(
+                    "header {} does not connect to its parent",
+                    number
+                )));
+            }
+
+            let hash = header.hash();
+            chain_work = chain_work + header.bits.to_work();
+            headers_update.insert(KeyValue::BlockChainWork(hash.clone(), chain_work));
+            headers_update.insert(KeyValue::BlockHeader(hash.clone(), header));
+            headers_update.insert(KeyValue::BlockHash(number, hash.clone()));
+            headers_update.insert(KeyValue::BlockNumber(hash.clone(), number));
+            previous_hash = hash;
+        }
+        if previous_hash != info.block_hash {
+            return Err(snapshot_error(
+                "header chain does not end at the snapshotted block",
+            ));
+        }
+
+        // transactions are written before the snapshot hash is verified. Marker is removed
+        // along with writing headers, so the database with unverified transactions is never used
+        let mut marker_update = DBTransaction::new();
+        marker_update.insert(KeyValue::Meta(
+            KEY_SNAPSHOT_LOADING,
+            serialize(&info.block_hash),
+        ));
+        self.db.write(marker_update).map_err(Error::DatabaseError)?;
+
+        let mut utxo_stats = UtxoStats::default();
+        let mut update = DBTransaction::new();
+        let mut batch_size = 0;
+        while !reader.is_finished() {
+            let entry: UtxoSnapshotTransaction = reader
+                .read()
+                .map_err(|err| snapshot_error(&format!("invalid transaction: {:?}", err)))?;
+            let height = entry.meta.height();
+            if height == 0 || height > info.height || !entry.meta.is_valid() {
+                return Err(snapshot_error(
+                    "transaction meta is out of the snapshot range",
+                ));
+            }
+
+            let tx: IndexedTransaction = entry.transaction.into();
+            for (index, tx_output) in tx.raw.outputs.iter().enumerate() {
+                if entry.meta.is_spent(index) == Some(false) {
+                    let prevout = OutPoint {
+                        hash: tx.hash.clone(),
+                        index: index as u32,
+                    };
+                    utxo_stats.add(&prevout, height, entry.meta.is_coinbase(), tx_output);
+                }
+            }
+
+            update.insert(KeyValue::TransactionMeta(tx.hash.clone(), entry.meta));
+            update.insert(KeyValue::Transaction(tx.hash, tx.raw));
+            batch_size += 1;
+            if batch_size == SNAPSHOT_WRITE_BATCH {
+                self.db.write(update).map_err(Error::DatabaseError)?;
+                update = DBTransaction::new();
+                batch_size = 0;
+            }
+        }
+        self.db.write(update).map_err(Error::DatabaseError)?;
+
+        if !info.matches(&utxo_stats) {
+            return Err(snapshot_error(
+                "unspent transactions do not match the snapshot hash",
+            ));
+        }
+
+        let mut best_block = self.best_block.write();
+        let new_best_block = BestBlock {
+            number: info.height,
+            hash: info.block_hash.clone(),
+        };
+        headers_update.insert(KeyValue::Meta(
+            KEY_BEST_BLOCK_HASH,
+            serialize(&new_best_block.hash),
+        ));
+        headers_update.insert(KeyValue::Meta(
+            KEY_BEST_BLOCK_NUMBER,
+            serialize(&new_best_block.number),
+        ));
+        headers_update.insert(KeyValue::Meta(KEY_UTXO_STATS, serialize(&utxo_stats)));
+        headers_update.insert(KeyValue::Meta(KEY_PRUNED_HEIGHT, serialize(&info.height)));
+        headers_update.delete(Key::Meta(KEY_SNAPSHOT_LOADING));
+        self.db
+            .write(headers_update)
+            .map_err(Error::DatabaseError)?;
+        *best_block = new_best_block;
+
+        Ok(info)
+    }
+}
+
+/// Reason of refusing to use the database with partially loaded snapshot
+const INCOMPLETE_SNAPSHOT: &'static str =
+    "loading of the previous snapshot has not been completed, database must be removed";
+
+fn snapshot_error(reason: &str) -> Error {
+    Error::InvalidSnapshot(reason.into())
+}
+
+fn write_snapshot<S>(output: &mut io::Write, value: &S) -> Result<(), Error>
+where
+    S: Serializable,
+{
+    output
+        .write_all(&serialize_with_flags(value, SERIALIZE_TRANSACTION_WITNESS))
+        .map_err(|err| Error::DatabaseError(err.to_string()))
+}
@@ -55,4 +55,8 @@ where
         }
         self.db.get(key)
     }
+
+    fn address_index(&self, from: &Key, limit: usize) -> Result<Vec<KeyValue>, String> {
+        self.db.address_index(from, limit)
+    }
 }
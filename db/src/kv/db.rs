@@ -1,7 +1,11 @@
-use kv::{Key, KeyState, Transaction, Value};
+use kv::{Key, KeyState, KeyValue, Transaction, Value};
 
 pub trait KeyValueDatabase: Send + Sync {
     fn write(&self, tx: Transaction) -> Result<(), String>;
 
     fn get(&self, key: &Key) -> Result<KeyState<Value>, String>;
+
+    /// Returns up to `limit` entries of the address index column of `from` key, in the key order.
+    /// Entries keys are not less than `from` and share its script hash.
+    fn address_index(&self, from: &Key, limit: usize) -> Result<Vec<KeyValue>, String>;
 }
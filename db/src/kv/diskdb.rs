@@ -2,12 +2,12 @@
 
 use bytes::Bytes;
 use kv::{
-    Key, KeyState, KeyValueDatabase, Location, RawKey, RawKeyValue, RawOperation, RawTransaction,
-    Transaction, Value,
+    Key, KeyState, KeyValue, KeyValueDatabase, Location, RawKey, RawKeyValue, RawOperation,
+    RawTransaction, Transaction, Value,
 };
 use rocksdb::{
-    BlockBasedOptions, Cache, Column, DBCompactionStyle, DBIterator, IteratorMode, Options,
-    ReadOptions, Writable, WriteBatch, WriteOptions, DB,
+    BlockBasedOptions, Cache, Column, DBCompactionStyle, DBIterator, Direction, IteratorMode,
+    Options, ReadOptions, Writable, WriteBatch, WriteOptions, DB,
 };
 use std::collections::HashMap;
 use std::path::Path;
@@ -134,6 +134,29 @@ impl KeyValueDatabase for Database {
             None => Ok(KeyState::Unknown),
         }
     }
+
+    fn address_index(&self, from: &Key, limit: usize) -> Result<Vec<KeyValue>, String> {
+        let script_hash = match from.address_index_key() {
+            Some(key) => key.script_hash.clone(),
+            None => return Err("key of the address index is expected".into()),
+        };
+        let from_key = RawKey::from(from);
+        let column = match from_key.location {
+            Location::Column(column) => column,
+            Location::DB => return Err("address index is stored in column".into()),
+        };
+
+        let DBAndColumns { ref db, ref cfs } = self.db;
+        let iter = db.iterator_cf_opt(
+            cfs[column as usize],
+            IteratorMode::From(&from_key.key, Direction::Forward),
+            &self.read_opts,
+        )?;
+        iter.take_while(|&(ref key, _)| key.starts_with(&*script_hash))
+            .take(limit)
+            .map(|(key, value)| KeyValue::for_address_index(from, &key, &value))
+            .collect()
+    }
 }
 
 impl Database {
@@ -226,18 +249,38 @@ impl Database {
                         assert!(cfs.len() == columns as usize);
                         Ok(db)
                     }
-                    Err(_) => {
-                        // retry and create CFs
-                        match DB::open_cf(&opts, &path, &[], &[]) {
-                            Ok(mut db) => {
-                                cfs = try!(cfnames
+                    Err(err) => {
+                        // database has been created with fewer columns (or it does not exist yet)
+                        // => retry with existing columns and create missing CFs
+                        let opened = (0..cfnames.len())
+                            .rev()
+                            .filter_map(|existing| {
+                                DB::open_cf(
+                                    &opts,
+                                    &path,
+                                    &cfnames[..existing],
+                                    &cf_options[..existing],
+                                )
+                                .ok()
+                                .map(|db| (existing, db))
+                            })
+                            .next();
+
+                        match opened {
+                            Some((existing, mut db)) => {
+                                cfs = cfnames[..existing]
                                     .iter()
-                                    .enumerate()
-                                    .map(|(i, n)| db.create_cf(n, &cf_options[i]))
-                                    .collect());
+                                    .map(|n| {
+                                        db.cf_handle(n)
+                                            .expect("rocksdb opens a cf_handle for each cfname; qed")
+                                    })
+                                    .collect();
+                                for (i, n) in cfnames.iter().enumerate().skip(existing) {
+                                    cfs.push(try!(db.create_cf(n, &cf_options[i])));
+                                }
                                 Ok(db)
                             }
-                            err @ Err(_) => err,
+                            None => Err(err),
                         }
                     }
                 }
@@ -3,11 +3,15 @@ use chain::{BlockHeader, Transaction as ChainTransaction};
 use hash::H256;
 use kv::{Key, KeyState, KeyValue, KeyValueDatabase, Operation, Transaction, Value};
 use parking_lot::RwLock;
+use primitives::bigint::U256;
 use ser::List;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::mem::replace;
 use std::sync::Arc;
-use storage::TransactionMeta;
+use storage::{
+    AddressFunding, AddressIndexKey, AddressSpending, AddressStats, BlockFilter,
+    TransactionErrorCode, TransactionLocation, TransactionMeta,
+};
 
 #[derive(Default, Debug)]
 struct InnerDatabase {
@@ -19,6 +23,16 @@ struct InnerDatabase {
     transaction_meta: HashMap<H256, KeyState<TransactionMeta>>,
     block_number: HashMap<H256, KeyState<u32>>,
     configuration: HashMap<&'static str, KeyState<Bytes>>,
+    block_transactions_validity: HashMap<H256, KeyState<List<bool>>>,
+    transaction_error: HashMap<H256, KeyState<TransactionErrorCode>>,
+    block_chain_work: HashMap<H256, KeyState<U256>>,
+    address_funding: BTreeMap<AddressIndexKey, KeyState<AddressFunding>>,
+    address_spending: BTreeMap<AddressIndexKey, KeyState<AddressSpending>>,
+    transaction_location: HashMap<H256, KeyState<TransactionLocation>>,
+    block_filter: HashMap<H256, KeyState<BlockFilter>>,
+    address_stats: HashMap<H256, KeyState<AddressStats>>,
+    sanitized_block: HashMap<H256, KeyState<List<bool>>>,
+    sanitized_transaction: HashMap<H256, KeyState<bool>>,
 }
 
 #[derive(Default, Debug)]
@@ -75,6 +89,75 @@ impl MemoryDatabase {
                 state.into_operation(key, KeyValue::Configuration, Key::Configuration)
             });
 
+        let block_transactions_validity =
+            replace(&mut db.block_transactions_validity, HashMap::default())
+                .into_iter()
+                .flat_map(|(key, state)| {
+                    state.into_operation(
+                        key,
+                        KeyValue::BlockTransactionsValidity,
+                        Key::BlockTransactionsValidity,
+                    )
+                });
+
+        let transaction_error = replace(&mut db.transaction_error, HashMap::default())
+            .into_iter()
+            .flat_map(|(key, state)| {
+                state.into_operation(key, KeyValue::TransactionError, Key::TransactionError)
+            });
+
+        let block_chain_work = replace(&mut db.block_chain_work, HashMap::default())
+            .into_iter()
+            .flat_map(|(key, state)| {
+                state.into_operation(key, KeyValue::BlockChainWork, Key::BlockChainWork)
+            });
+
+        let address_funding = replace(&mut db.address_funding, BTreeMap::default())
+            .into_iter()
+            .flat_map(|(key, state)| {
+                state.into_operation(key, KeyValue::AddressFunding, Key::AddressFunding)
+            });
+
+        let address_spending = replace(&mut db.address_spending, BTreeMap::default())
+            .into_iter()
+            .flat_map(|(key, state)| {
+                state.into_operation(key, KeyValue::AddressSpending, Key::AddressSpending)
+            });
+
+        let transaction_location = replace(&mut db.transaction_location, HashMap::default())
+            .into_iter()
+            .flat_map(|(key, state)| {
+                state.into_operation(key, KeyValue::TransactionLocation, Key::TransactionLocation)
+            });
+
+        let block_filter = replace(&mut db.block_filter, HashMap::default())
+            .into_iter()
+            .flat_map(|(key, state)| {
+                state.into_operation(key, KeyValue::BlockFilter, Key::BlockFilter)
+            });
+
+        let address_stats = replace(&mut db.address_stats, HashMap::default())
+            .into_iter()
+            .flat_map(|(key, state)| {
+                state.into_operation(key, KeyValue::AddressStats, Key::AddressStats)
+            });
+
+        let sanitized_block = replace(&mut db.sanitized_block, HashMap::default())
+            .into_iter()
+            .flat_map(|(key, state)| {
+                state.into_operation(key, KeyValue::SanitizedBlock, Key::SanitizedBlock)
+            });
+
+        let sanitized_transaction = replace(&mut db.sanitized_transaction, HashMap::default())
+            .into_iter()
+            .flat_map(|(key, state)| {
+                state.into_operation(
+                    key,
+                    KeyValue::SanitizedTransaction,
+                    Key::SanitizedTransaction,
+                )
+            });
+
         Transaction {
             operations: meta
                 .chain(block_hash)
@@ -84,9 +167,52 @@ impl MemoryDatabase {
                 .chain(transaction_meta)
                 .chain(block_number)
                 .chain(configuration)
+                .chain(block_transactions_validity)
+                .chain(transaction_error)
+                .chain(block_chain_work)
+                .chain(address_funding)
+                .chain(address_spending)
+                .chain(transaction_location)
+                .chain(block_filter)
+                .chain(address_stats)
+                .chain(sanitized_block)
+                .chain(sanitized_transaction)
                 .collect(),
         }
     }
+
+    /// Operations on the address index entries of the `from` key column, which keys are not less
+    /// than `from` and share its script hash, in the key order
+    pub fn address_index_operations(&self, from: &Key) -> Vec<Operation> {
+        let db = self.db.read();
+        match *from {
+            Key::AddressFunding(ref from) => db
+                .address_funding
+                .range(from.clone()..)
+                .take_while(|&(key, _)| key.script_hash == from.script_hash)
+                .flat_map(|(key, state)| {
+                    state.clone().into_operation(
+                        key.clone(),
+                        KeyValue::AddressFunding,
+                        Key::AddressFunding,
+                    )
+                })
+                .collect(),
+            Key::AddressSpending(ref from) => db
+                .address_spending
+                .range(from.clone()..)
+                .take_while(|&(key, _)| key.script_hash == from.script_hash)
+                .flat_map(|(key, state)| {
+                    state.clone().into_operation(
+                        key.clone(),
+                        KeyValue::AddressSpending,
+                        Key::AddressSpending,
+                    )
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
 }
 
 impl KeyValueDatabase for MemoryDatabase {
@@ -119,6 +245,37 @@ impl KeyValueDatabase for MemoryDatabase {
                     KeyValue::Configuration(key, value) => {
                         db.configuration.insert(key, KeyState::Insert(value));
                     }
+                    KeyValue::BlockTransactionsValidity(key, value) => {
+                        db.block_transactions_validity
+                            .insert(key, KeyState::Insert(value));
+                    }
+                    KeyValue::TransactionError(key, value) => {
+                        db.transaction_error.insert(key, KeyState::Insert(value));
+                    }
+                    KeyValue::BlockChainWork(key, value) => {
+                        db.block_chain_work.insert(key, KeyState::Insert(value));
+                    }
+                    KeyValue::AddressFunding(key, value) => {
+                        db.address_funding.insert(key, KeyState::Insert(value));
+                    }
+                    KeyValue::AddressSpending(key, value) => {
+                        db.address_spending.insert(key, KeyState::Insert(value));
+                    }
+                    KeyValue::TransactionLocation(key, value) => {
+                        db.transaction_location.insert(key, KeyState::Insert(value));
+                    }
+                    KeyValue::BlockFilter(key, value) => {
+                        db.block_filter.insert(key, KeyState::Insert(value));
+                    }
+                    KeyValue::AddressStats(key, value) => {
+                        db.address_stats.insert(key, KeyState::Insert(value));
+                    }
+                    KeyValue::SanitizedBlock(key, value) => {
+                        db.sanitized_block.insert(key, KeyState::Insert(value));
+                    }
+                    KeyValue::SanitizedTransaction(key, value) => {
+                        db.sanitized_transaction.insert(key, KeyState::Insert(value));
+                    }
                 },
                 Operation::Delete(delete) => match delete {
                     Key::Meta(key) => {
@@ -145,6 +302,37 @@ impl KeyValueDatabase for MemoryDatabase {
                     Key::Configuration(key) => {
                         db.configuration.insert(key, KeyState::Delete);
                     }
+                    Key::BlockTransactionsValidity(key) => {
+                        db.block_transactions_validity
+                            .insert(key, KeyState::Delete);
+                    }
+                    Key::TransactionError(key) => {
+                        db.transaction_error.insert(key, KeyState::Delete);
+                    }
+                    Key::BlockChainWork(key) => {
+                        db.block_chain_work.insert(key, KeyState::Delete);
+                    }
+                    Key::AddressFunding(key) => {
+                        db.address_funding.insert(key, KeyState::Delete);
+                    }
+                    Key::AddressSpending(key) => {
+                        db.address_spending.insert(key, KeyState::Delete);
+                    }
+                    Key::TransactionLocation(key) => {
+                        db.transaction_location.insert(key, KeyState::Delete);
+                    }
+                    Key::BlockFilter(key) => {
+                        db.block_filter.insert(key, KeyState::Delete);
+                    }
+                    Key::AddressStats(key) => {
+                        db.address_stats.insert(key, KeyState::Delete);
+                    }
+                    Key::SanitizedBlock(key) => {
+                        db.sanitized_block.insert(key, KeyState::Delete);
+                    }
+                    Key::SanitizedTransaction(key) => {
+                        db.sanitized_transaction.insert(key, KeyState::Delete);
+                    }
                 },
             }
         }
@@ -202,10 +390,86 @@ impl KeyValueDatabase for MemoryDatabase {
                 .cloned()
                 .unwrap_or_default()
                 .map(Value::Configuration),
+            Key::BlockTransactionsValidity(ref key) => db
+                .block_transactions_validity
+                .get(key)
+                .cloned()
+                .unwrap_or_default()
+                .map(Value::BlockTransactionsValidity),
+            Key::TransactionError(ref key) => db
+                .transaction_error
+                .get(key)
+                .cloned()
+                .unwrap_or_default()
+                .map(Value::TransactionError),
+            Key::BlockChainWork(ref key) => db
+                .block_chain_work
+                .get(key)
+                .cloned()
+                .unwrap_or_default()
+                .map(Value::BlockChainWork),
+            Key::AddressFunding(ref key) => db
+                .address_funding
+                .get(key)
+                .cloned()
+                .unwrap_or_default()
+                .map(Value::AddressFunding),
+            Key::AddressSpending(ref key) => db
+                .address_spending
+                .get(key)
+                .cloned()
+                .unwrap_or_default()
+                .map(Value::AddressSpending),
+            Key::TransactionLocation(ref key) => db
+                .transaction_location
+                .get(key)
+                .cloned()
+                .unwrap_or_default()
+                .map(Value::TransactionLocation),
+            Key::BlockFilter(ref key) => db
+                .block_filter
+                .get(key)
+                .cloned()
+                .unwrap_or_default()
+                .map(Value::BlockFilter),
+            Key::AddressStats(ref key) => db
+                .address_stats
+                .get(key)
+                .cloned()
+                .unwrap_or_default()
+                .map(Value::AddressStats),
+            Key::SanitizedBlock(ref key) => db
+                .sanitized_block
+                .get(key)
+                .cloned()
+                .unwrap_or_default()
+                .map(Value::SanitizedBlock),
+            Key::SanitizedTransaction(ref key) => db
+                .sanitized_transaction
+                .get(key)
+                .cloned()
+                .unwrap_or_default()
+                .map(Value::SanitizedTransaction),
         };
 
         Ok(result)
     }
+
+    fn address_index(&self, from: &Key, limit: usize) -> Result<Vec<KeyValue>, String> {
+        if from.address_index_key().is_none() {
+            return Err("key of the address index is expected".into());
+        }
+
+        Ok(self
+            .address_index_operations(from)
+            .into_iter()
+            .filter_map(|operation| match operation {
+                Operation::Insert(entry) => Some(entry),
+                Operation::Delete(_) => None,
+            })
+            .take(limit)
+            .collect())
+    }
 }
 
 #[derive(Debug)]
@@ -235,4 +499,8 @@ impl KeyValueDatabase for SharedMemoryDatabase {
     fn get(&self, key: &Key) -> Result<KeyState<Value>, String> {
         self.db.get(key)
     }
+
+    fn address_index(&self, from: &Key, limit: usize) -> Result<Vec<KeyValue>, String> {
+        self.db.address_index(from, limit)
+    }
 }
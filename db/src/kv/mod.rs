@@ -13,5 +13,8 @@ pub use self::overlaydb::{AutoFlushingOverlayDatabase, OverlayDatabase};
 pub use self::transaction::{
     Key, KeyState, KeyValue, Location, Operation, RawKey, RawKeyValue, RawOperation,
     RawTransaction, Transaction, Value, COL_BLOCK_HASHES, COL_BLOCK_HEADERS, COL_BLOCK_NUMBERS,
-    COL_BLOCK_TRANSACTIONS, COL_COUNT, COL_META, COL_TRANSACTIONS, COL_TRANSACTIONS_META,
+    COL_BLOCK_TRANSACTIONS, COL_BLOCK_TRANSACTIONS_VALIDITY, COL_COUNT, COL_META, COL_TRANSACTIONS, COL_TRANSACTIONS_META,
+    COL_TRANSACTION_ERRORS, COL_BLOCK_CHAIN_WORK, COL_ADDRESS_FUNDING, COL_ADDRESS_SPENDING,
+    COL_TRANSACTION_LOCATIONS, COL_BLOCK_FILTERS, COL_ADDRESS_STATS, COL_SANITIZED_BLOCKS,
+    COL_SANITIZED_TRANSACTIONS,
 };
@@ -1,5 +1,9 @@
-use kv::{Key, KeyState, KeyValueDatabase, MemoryDatabase, Transaction, Value};
+use kv::{
+    Key, KeyState, KeyValue, KeyValueDatabase, MemoryDatabase, Operation, Transaction, Value,
+};
 use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use storage::AddressIndexKey;
 
 pub struct OverlayDatabase<'a, T>
 where
@@ -39,6 +43,10 @@ where
             exists => Ok(exists),
         }
     }
+
+    fn address_index(&self, from: &Key, limit: usize) -> Result<Vec<KeyValue>, String> {
+        overlay_address_index(&self.overlay, self.db, from, limit)
+    }
 }
 
 pub struct AutoFlushingOverlayDatabase<T>
@@ -90,6 +98,73 @@ where
             exists => Ok(exists),
         }
     }
+
+    fn address_index(&self, from: &Key, limit: usize) -> Result<Vec<KeyValue>, String> {
+        overlay_address_index(&self.overlay, &self.db, from, limit)
+    }
+}
+
+/// Address index entries of the database, updated with operations, pending in the overlay
+fn overlay_address_index<T>(
+    overlay: &MemoryDatabase,
+    db: &T,
+    from: &Key,
+    limit: usize,
+) -> Result<Vec<KeyValue>, String>
+where
+    T: KeyValueDatabase,
+{
+    let operations = overlay.address_index_operations(from);
+    if operations.is_empty() {
+        return db.address_index(from, limit);
+    }
+
+    // entries, deleted in the overlay, are replaced with the following entries of the database
+    let deleted = operations
+        .iter()
+        .filter(|operation| match **operation {
+            Operation::Delete(_) => true,
+            Operation::Insert(_) => false,
+        })
+        .count();
+    let db_limit = limit.saturating_add(deleted);
+    let db_entries = db.address_index(from, db_limit)?;
+    // database entries, following the last entry of the full page, are not known
+    let last_key = if db_entries.len() < db_limit {
+        None
+    } else {
+        db_entries
+            .last()
+            .and_then(KeyValue::address_index_key)
+            .cloned()
+    };
+
+    let mut entries: BTreeMap<AddressIndexKey, KeyValue> = db_entries
+        .into_iter()
+        .filter_map(|entry| entry.address_index_key().cloned().map(|key| (key, entry)))
+        .collect();
+    for operation in operations {
+        match operation {
+            Operation::Insert(entry) => {
+                if let Some(key) = entry.address_index_key().cloned() {
+                    if last_key.as_ref().map_or(true, |last_key| key <= *last_key) {
+                        entries.insert(key, entry);
+                    }
+                }
+            }
+            Operation::Delete(key) => {
+                if let Some(key) = key.address_index_key() {
+                    entries.remove(key);
+                }
+            }
+        }
+    }
+
+    Ok(entries
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .take(limit)
+        .collect())
 }
 
 impl<T> Drop for AutoFlushingOverlayDatabase<T>
@@ -1,10 +1,14 @@
 use bytes::Bytes;
 use chain::{BlockHeader, Transaction as ChainTransaction};
 use hash::H256;
-use ser::{deserialize, serialize, List};
-use storage::TransactionMeta;
-
-pub const COL_COUNT: u32 = 10;
+use primitives::bigint::U256;
+use ser::{deserialize, serialize, serialize_with_flags, List, SERIALIZE_TRANSACTION_WITNESS};
+use storage::{
+    AddressFunding, AddressIndexKey, AddressSpending, AddressStats, BlockFilter,
+    TransactionErrorCode, TransactionLocation, TransactionMeta,
+};
+
+pub const COL_COUNT: u32 = 18;
 pub const COL_META: u32 = 0;
 pub const COL_BLOCK_HASHES: u32 = 1;
 pub const COL_BLOCK_HEADERS: u32 = 2;
@@ -13,6 +17,16 @@ pub const COL_TRANSACTIONS: u32 = 4;
 pub const COL_TRANSACTIONS_META: u32 = 5;
 pub const COL_BLOCK_NUMBERS: u32 = 6;
 pub const COL_CONFIGURATION: u32 = 7;
+pub const COL_BLOCK_TRANSACTIONS_VALIDITY: u32 = 8;
+pub const COL_TRANSACTION_ERRORS: u32 = 9;
+pub const COL_BLOCK_CHAIN_WORK: u32 = 10;
+pub const COL_ADDRESS_FUNDING: u32 = 11;
+pub const COL_ADDRESS_SPENDING: u32 = 12;
+pub const COL_TRANSACTION_LOCATIONS: u32 = 13;
+pub const COL_BLOCK_FILTERS: u32 = 14;
+pub const COL_ADDRESS_STATS: u32 = 15;
+pub const COL_SANITIZED_BLOCKS: u32 = 16;
+pub const COL_SANITIZED_TRANSACTIONS: u32 = 17;
 
 #[derive(Debug)]
 pub enum Operation {
@@ -30,6 +44,43 @@ pub enum KeyValue {
     TransactionMeta(H256, TransactionMeta),
     BlockNumber(H256, u32),
     Configuration(&'static str, Bytes),
+    BlockTransactionsValidity(H256, List<bool>),
+    TransactionError(H256, TransactionErrorCode),
+    BlockChainWork(H256, U256),
+    AddressFunding(AddressIndexKey, AddressFunding),
+    AddressSpending(AddressIndexKey, AddressSpending),
+    TransactionLocation(H256, TransactionLocation),
+    BlockFilter(H256, BlockFilter),
+    AddressStats(H256, AddressStats),
+    SanitizedBlock(H256, List<bool>),
+    SanitizedTransaction(H256, bool),
+}
+
+impl KeyValue {
+    /// Decodes raw entry of the address index column of `column_key`
+    pub fn for_address_index(column_key: &Key, key: &[u8], value: &[u8]) -> Result<Self, String> {
+        let key = deserialize(key).map_err(|e| format!("{:?}", e))?;
+        match *column_key {
+            Key::AddressFunding(_) => {
+                deserialize(value).map(|value| KeyValue::AddressFunding(key, value))
+            }
+            Key::AddressSpending(_) => {
+                deserialize(value).map(|value| KeyValue::AddressSpending(key, value))
+            }
+            _ => return Err("key of the address index is expected".into()),
+        }
+        .map_err(|e| format!("{:?}", e))
+    }
+
+    /// Key of the address index entry
+    pub fn address_index_key(&self) -> Option<&AddressIndexKey> {
+        match *self {
+            KeyValue::AddressFunding(ref key, _) | KeyValue::AddressSpending(ref key, _) => {
+                Some(key)
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -42,6 +93,26 @@ pub enum Key {
     TransactionMeta(H256),
     BlockNumber(H256),
     Configuration(&'static str),
+    BlockTransactionsValidity(H256),
+    TransactionError(H256),
+    BlockChainWork(H256),
+    AddressFunding(AddressIndexKey),
+    AddressSpending(AddressIndexKey),
+    TransactionLocation(H256),
+    BlockFilter(H256),
+    AddressStats(H256),
+    SanitizedBlock(H256),
+    SanitizedTransaction(H256),
+}
+
+impl Key {
+    /// Key of the address index entry
+    pub fn address_index_key(&self) -> Option<&AddressIndexKey> {
+        match *self {
+            Key::AddressFunding(ref key) | Key::AddressSpending(ref key) => Some(key),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -54,6 +125,16 @@ pub enum Value {
     TransactionMeta(TransactionMeta),
     BlockNumber(u32),
     Configuration(Bytes),
+    BlockTransactionsValidity(List<bool>),
+    TransactionError(TransactionErrorCode),
+    BlockChainWork(U256),
+    AddressFunding(AddressFunding),
+    AddressSpending(AddressSpending),
+    TransactionLocation(TransactionLocation),
+    BlockFilter(BlockFilter),
+    AddressStats(AddressStats),
+    SanitizedBlock(List<bool>),
+    SanitizedTransaction(bool),
 }
 
 impl Value {
@@ -67,6 +148,18 @@ impl Value {
             Key::TransactionMeta(_) => deserialize(bytes).map(Value::TransactionMeta),
             Key::BlockNumber(_) => deserialize(bytes).map(Value::BlockNumber),
             Key::Configuration(_) => deserialize(bytes).map(Value::Configuration),
+            Key::BlockTransactionsValidity(_) => {
+                deserialize(bytes).map(Value::BlockTransactionsValidity)
+            }
+            Key::TransactionError(_) => deserialize(bytes).map(Value::TransactionError),
+            Key::BlockChainWork(_) => deserialize(bytes).map(Value::BlockChainWork),
+            Key::AddressFunding(_) => deserialize(bytes).map(Value::AddressFunding),
+            Key::AddressSpending(_) => deserialize(bytes).map(Value::AddressSpending),
+            Key::TransactionLocation(_) => deserialize(bytes).map(Value::TransactionLocation),
+            Key::BlockFilter(_) => deserialize(bytes).map(Value::BlockFilter),
+            Key::AddressStats(_) => deserialize(bytes).map(Value::AddressStats),
+            Key::SanitizedBlock(_) => deserialize(bytes).map(Value::SanitizedBlock),
+            Key::SanitizedTransaction(_) => deserialize(bytes).map(Value::SanitizedTransaction),
         }
         .map_err(|e| format!("{:?}", e))
     }
@@ -126,6 +219,62 @@ impl Value {
             _ => None,
         }
     }
+
+    pub fn as_block_transactions_validity(self) -> Option<List<bool>> {
+        match self {
+            Value::BlockTransactionsValidity(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    pub fn as_transaction_error(self) -> Option<TransactionErrorCode> {
+        match self {
+            Value::TransactionError(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    pub fn as_block_chain_work(self) -> Option<U256> {
+        match self {
+            Value::BlockChainWork(work) => Some(work),
+            _ => None,
+        }
+    }
+
+    pub fn as_address_stats(self) -> Option<AddressStats> {
+        match self {
+            Value::AddressStats(stats) => Some(stats),
+            _ => None,
+        }
+    }
+
+    pub fn as_transaction_location(self) -> Option<TransactionLocation> {
+        match self {
+            Value::TransactionLocation(location) => Some(location),
+            _ => None,
+        }
+    }
+
+    pub fn as_block_filter(self) -> Option<BlockFilter> {
+        match self {
+            Value::BlockFilter(filter) => Some(filter),
+            _ => None,
+        }
+    }
+
+    pub fn as_sanitized_block(self) -> Option<List<bool>> {
+        match self {
+            Value::SanitizedBlock(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    pub fn as_sanitized_transaction(self) -> Option<bool> {
+        match self {
+            Value::SanitizedTransaction(is_valid) => Some(is_valid),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -237,9 +386,11 @@ impl<'a> From<&'a KeyValue> for RawKeyValue {
             KeyValue::BlockTransactions(ref key, ref value) => {
                 (COL_BLOCK_TRANSACTIONS, serialize(key), serialize(value))
             }
-            KeyValue::Transaction(ref key, ref value) => {
-                (COL_TRANSACTIONS, serialize(key), serialize(value))
-            }
+            KeyValue::Transaction(ref key, ref value) => (
+                COL_TRANSACTIONS,
+                serialize(key),
+                serialize_with_flags(value, SERIALIZE_TRANSACTION_WITNESS),
+            ),
             KeyValue::TransactionMeta(ref key, ref value) => {
                 (COL_TRANSACTIONS_META, serialize(key), serialize(value))
             }
@@ -249,6 +400,36 @@ impl<'a> From<&'a KeyValue> for RawKeyValue {
             KeyValue::Configuration(ref key, ref value) => {
                 (COL_CONFIGURATION, serialize(key), serialize(value))
             }
+            KeyValue::BlockTransactionsValidity(ref key, ref value) => {
+                (COL_BLOCK_TRANSACTIONS_VALIDITY, serialize(key), serialize(value))
+            }
+            KeyValue::TransactionError(ref key, ref value) => {
+                (COL_TRANSACTION_ERRORS, serialize(key), serialize(value))
+            }
+            KeyValue::BlockChainWork(ref key, ref value) => {
+                (COL_BLOCK_CHAIN_WORK, serialize(key), serialize(value))
+            }
+            KeyValue::AddressFunding(ref key, ref value) => {
+                (COL_ADDRESS_FUNDING, serialize(key), serialize(value))
+            }
+            KeyValue::AddressSpending(ref key, ref value) => {
+                (COL_ADDRESS_SPENDING, serialize(key), serialize(value))
+            }
+            KeyValue::TransactionLocation(ref key, ref value) => {
+                (COL_TRANSACTION_LOCATIONS, serialize(key), serialize(value))
+            }
+            KeyValue::BlockFilter(ref key, ref value) => {
+                (COL_BLOCK_FILTERS, serialize(key), serialize(value))
+            }
+            KeyValue::AddressStats(ref key, ref value) => {
+                (COL_ADDRESS_STATS, serialize(key), serialize(value))
+            }
+            KeyValue::SanitizedBlock(ref key, ref value) => {
+                (COL_SANITIZED_BLOCKS, serialize(key), serialize(value))
+            }
+            KeyValue::SanitizedTransaction(ref key, ref value) => {
+                (COL_SANITIZED_TRANSACTIONS, serialize(key), serialize(value))
+            }
         };
 
         RawKeyValue {
@@ -287,6 +468,18 @@ impl<'a> From<&'a Key> for RawKey {
             Key::TransactionMeta(ref key) => (COL_TRANSACTIONS_META, serialize(key)),
             Key::BlockNumber(ref key) => (COL_BLOCK_NUMBERS, serialize(key)),
             Key::Configuration(ref key) => (COL_CONFIGURATION, serialize(key)),
+            Key::BlockTransactionsValidity(ref key) => {
+                (COL_BLOCK_TRANSACTIONS_VALIDITY, serialize(key))
+            }
+            Key::TransactionError(ref key) => (COL_TRANSACTION_ERRORS, serialize(key)),
+            Key::BlockChainWork(ref key) => (COL_BLOCK_CHAIN_WORK, serialize(key)),
+            Key::AddressFunding(ref key) => (COL_ADDRESS_FUNDING, serialize(key)),
+            Key::AddressSpending(ref key) => (COL_ADDRESS_SPENDING, serialize(key)),
+            Key::TransactionLocation(ref key) => (COL_TRANSACTION_LOCATIONS, serialize(key)),
+            Key::BlockFilter(ref key) => (COL_BLOCK_FILTERS, serialize(key)),
+            Key::AddressStats(ref key) => (COL_ADDRESS_STATS, serialize(key)),
+            Key::SanitizedBlock(ref key) => (COL_SANITIZED_BLOCKS, serialize(key)),
+            Key::SanitizedTransaction(ref key) => (COL_SANITIZED_TRANSACTIONS, serialize(key)),
         };
 
         RawKey {
@@ -3,10 +3,19 @@ extern crate db;
 extern crate storage;
 extern crate test_data;
 
-use chain::IndexedBlock;
+use chain::{IndexedBlock, OutPoint};
 use db::kv::{MemoryDatabase, SharedMemoryDatabase};
-use db::BlockChainDatabase;
-use storage::{BlockProvider, ForkChain, SideChainOrigin};
+use db::{BlockChainDatabase, PruneMode};
+use storage::{
+    filter_header, script_hash, AddressIndexProvider, AddressStats, BlockChain,
+    BlockFilterProvider, BlockHeaderProvider, BlockProvider, BlockRef, Error, ForkChain,
+    SideChainOrigin, Store, TransactionErrorCode, TransactionErrorProvider, TransactionLocation,
+    TransactionLocationProvider, TransactionMetaProvider, TransactionOutputProvider,
+    TransactionProvider, UtxoSnapshotStore, UtxoStats,
+};
+
+/// Error of the invalid transaction of the mixed validity chain
+const INVALID: Option<TransactionErrorCode> = Some(TransactionErrorCode::UsingSpentOutput);
 
 #[test]
 fn insert_block() {
@@ -127,3 +136,739 @@ fn switch_to_simple_fork() {
     assert_eq!(b2.hash(), &store.best_block().hash);
     assert_eq!(store.best_block().hash, store.block_hash(2).unwrap());
 }
+
+#[test]
+fn transaction_locations_follow_canon_chain() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    let b0: IndexedBlock = test_data::block_h0().into();
+    let b1: IndexedBlock = test_data::block_h1().into();
+
+    store.insert(b0.clone()).unwrap();
+    store.insert(b1.clone()).unwrap();
+    store.canonize(b0.hash()).unwrap();
+
+    // inserted, but not yet canonized transactions are not located
+    let tx1 = &b1.transactions[0].hash;
+    assert!(store.transaction(tx1).is_some());
+    assert_eq!(store.transaction_location(tx1), None);
+
+    store.canonize(b1.hash()).unwrap();
+    assert_eq!(
+        store.transaction_location(tx1),
+        Some(TransactionLocation {
+            block_hash: b1.hash().clone(),
+            index: 0,
+        })
+    );
+
+    store.decanonize().unwrap();
+    assert_eq!(store.transaction_location(tx1), None);
+    assert!(store.transaction_location(&b0.transactions[0].hash).is_some());
+}
+
+#[test]
+fn block_filters_are_chained() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    let b0: IndexedBlock = test_data::block_h0().into();
+    let b1: IndexedBlock = test_data::block_h1().into();
+
+    store.insert(b0.clone()).unwrap();
+    store.insert(b1.clone()).unwrap();
+    store.canonize(b0.hash()).unwrap();
+
+    // filters are only built for canonized blocks
+    let f0 = store.block_filter(b0.hash()).unwrap();
+    assert_eq!(store.block_filter(b1.hash()), None);
+
+    store.canonize(b1.hash()).unwrap();
+    let f1 = store.block_filter(b1.hash()).unwrap();
+    assert_eq!(f1.header, filter_header(&f1.filter_hash(), &f0.header));
+    let script: &[u8] = &b1.transactions[0].raw.outputs[0].script_pubkey;
+    assert!(f1.match_any(b1.hash(), &[script]));
+
+    // filter commits to the block and its ancestors, so it is kept after decanonize
+    store.decanonize().unwrap();
+    assert_eq!(store.block_filter(b1.hash()), Some(f1));
+}
+
+fn outpoint(block: &IndexedBlock, tx_index: usize) -> OutPoint {
+    OutPoint {
+        hash: block.transactions[tx_index].hash.clone(),
+        index: 0,
+    }
+}
+
+// b0: coinbase, tx0
+// b1: coinbase, tx1 (spends tx0)
+// b2: coinbase, tx2 (spends tx0, double spend), tx3 (spends tx1)
+fn mixed_validity_chain() -> (IndexedBlock, IndexedBlock, IndexedBlock) {
+    let b0: IndexedBlock = test_data::block_builder()
+        .transaction()
+        .coinbase()
+        .output()
+        .value(1)
+        .build()
+        .build()
+        .transaction()
+        .output()
+        .value(50)
+        .build()
+        .build()
+        .merkled_header()
+        .build()
+        .build()
+        .into();
+    let b1: IndexedBlock = test_data::block_builder()
+        .transaction()
+        .coinbase()
+        .output()
+        .value(2)
+        .build()
+        .build()
+        .transaction()
+        .input()
+        .hash(b0.transactions[1].hash.clone())
+        .build()
+        .output()
+        .value(40)
+        .build()
+        .build()
+        .merkled_header()
+        .parent(b0.hash().clone())
+        .build()
+        .build()
+        .into();
+    let b2: IndexedBlock = test_data::block_builder()
+        .transaction()
+        .coinbase()
+        .output()
+        .value(3)
+        .build()
+        .build()
+        .transaction()
+        .input()
+        .hash(b0.transactions[1].hash.clone())
+        .build()
+        .output()
+        .value(30)
+        .build()
+        .build()
+        .transaction()
+        .input()
+        .hash(b1.transactions[1].hash.clone())
+        .build()
+        .output()
+        .value(20)
+        .build()
+        .build()
+        .merkled_header()
+        .parent(b1.hash().clone())
+        .build()
+        .build()
+        .into();
+    (b0, b1, b2)
+}
+
+#[test]
+fn canonize_with_invalid_transactions() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    let (b0, b1, b2) = mixed_validity_chain();
+
+    store.insert(b0.clone()).unwrap();
+    store.insert(b1.clone()).unwrap();
+    store.insert(b2.clone()).unwrap();
+
+    store.canonize(b0.hash()).unwrap();
+    store.canonize(b1.hash()).unwrap();
+    // errors count must match transactions count
+    assert!(store.canonize_with_invalid(b2.hash(), &[None, INVALID]).is_err());
+    store
+        .canonize_with_invalid(b2.hash(), &[None, INVALID, None])
+        .unwrap();
+
+    assert!(store.transaction_meta(&b2.transactions[0].hash).unwrap().is_valid());
+    assert!(!store.transaction_meta(&b2.transactions[1].hash).unwrap().is_valid());
+    assert!(store.transaction_meta(&b2.transactions[2].hash).unwrap().is_valid());
+
+    // invalid transaction outputs do not exist
+    assert!(store.transaction_output(&outpoint(&b2, 1), 0).is_none());
+    assert!(!store.is_spent(&outpoint(&b2, 1)));
+    assert!(store.transaction_output(&outpoint(&b2, 2), 0).is_some());
+    // tx0 output is spent by tx1 only, tx1 output is spent by tx3
+    assert!(store.is_spent(&outpoint(&b0, 1)));
+    assert!(store.is_spent(&outpoint(&b1, 1)));
+}
+
+#[test]
+fn decanonize_block_with_invalid_transactions() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    let (b0, b1, b2) = mixed_validity_chain();
+
+    store.insert(b0.clone()).unwrap();
+    store.insert(b1.clone()).unwrap();
+    store.insert(b2.clone()).unwrap();
+
+    store.canonize(b0.hash()).unwrap();
+    store.canonize(b1.hash()).unwrap();
+    store
+        .canonize_with_invalid(b2.hash(), &[None, INVALID, None])
+        .unwrap();
+
+    // invalid tx2 has never spent tx0 output => it is still spent by tx1
+    assert_eq!(b2.hash(), &store.decanonize().unwrap());
+    assert!(store.is_spent(&outpoint(&b0, 1)));
+    assert!(!store.is_spent(&outpoint(&b1, 1)));
+    assert!(store.transaction_meta(&b2.transactions[1].hash).is_none());
+
+    // re-canonized block keeps transactions validity
+    store.canonize(b2.hash()).unwrap();
+    assert!(!store.transaction_meta(&b2.transactions[1].hash).unwrap().is_valid());
+    assert!(store.is_spent(&outpoint(&b1, 1)));
+
+    assert_eq!(b1.hash(), &BlockChain::rollback_best(&store).unwrap());
+    assert!(store.is_spent(&outpoint(&b0, 1)));
+    assert!(!store.is_spent(&outpoint(&b1, 1)));
+    assert!(!store.contains_block(b2.hash().clone().into()));
+
+    assert_eq!(b0.hash(), &BlockChain::rollback_best(&store).unwrap());
+    assert!(!store.is_spent(&outpoint(&b0, 1)));
+}
+
+#[test]
+fn block_chain_work() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    let b0: IndexedBlock = test_data::block_h0().into();
+    let b1: IndexedBlock = test_data::block_h1().into();
+    let b2: IndexedBlock = test_data::block_h2().into();
+
+    store.insert(b0.clone()).unwrap();
+    store.insert(b1.clone()).unwrap();
+    store.insert(b2.clone()).unwrap();
+
+    // chain work is known for all inserted blocks, including non-canon ones
+    assert_eq!(store.block_chain_work(b0.hash()), Some(0x100010001u64.into()));
+    assert_eq!(store.block_chain_work(b1.hash()), Some(0x200020002u64.into()));
+    assert_eq!(store.block_chain_work(b2.hash()), Some(0x300030003u64.into()));
+
+    store.canonize(b0.hash()).unwrap();
+    store.canonize(b1.hash()).unwrap();
+    store.canonize(b2.hash()).unwrap();
+    assert_eq!(b1.hash(), &BlockChain::rollback_best(&store).unwrap());
+    assert_eq!(store.block_chain_work(b2.hash()), None);
+}
+
+#[test]
+fn utxo_stats_follow_canon_chain() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    let (b0, b1, b2) = mixed_validity_chain();
+
+    store.insert(b0.clone()).unwrap();
+    store.insert(b1.clone()).unwrap();
+    store.insert(b2.clone()).unwrap();
+
+    assert_eq!(store.utxo_stats(), None);
+
+    // genesis outputs are not a part of UTXO set
+    store.canonize(b0.hash()).unwrap();
+    assert_eq!(store.utxo_stats(), Some(UtxoStats::default()));
+
+    store.canonize(b1.hash()).unwrap();
+    let b1_stats = store.utxo_stats().unwrap();
+    assert_eq!(b1_stats.outputs, 2);
+    assert_eq!(b1_stats.total_amount, 42);
+
+    // invalid tx2 neither creates nor spends outputs, tx3 spends tx1
+    store
+        .canonize_with_invalid(b2.hash(), &[None, INVALID, None])
+        .unwrap();
+    let b2_stats = store.utxo_stats().unwrap();
+    assert_eq!(b2_stats.outputs, 3);
+    assert_eq!(b2_stats.total_amount, 25);
+
+    let mut expected = UtxoStats::default();
+    expected.add(&outpoint(&b1, 0), 1, true, &b1.transactions[0].raw.outputs[0]);
+    expected.add(&outpoint(&b2, 0), 2, true, &b2.transactions[0].raw.outputs[0]);
+    expected.add(&outpoint(&b2, 2), 2, false, &b2.transactions[2].raw.outputs[0]);
+    assert_eq!(b2_stats.hash(), expected.hash());
+
+    store.decanonize().unwrap();
+    assert_eq!(store.utxo_stats(), Some(b1_stats));
+
+    store.canonize(b2.hash()).unwrap();
+    assert_eq!(store.utxo_stats(), Some(b2_stats));
+}
+
+#[test]
+fn address_index_follows_canon_chain() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default())
+        .with_address_index()
+        .unwrap();
+    let (b0, b1, b2) = mixed_validity_chain();
+    // all outputs of the test chain pay to the same script
+    let script_hash = script_hash(&b0.transactions[0].raw.outputs[0].script_pubkey);
+
+    store.insert(b0.clone()).unwrap();
+    store.insert(b1.clone()).unwrap();
+    store.insert(b2.clone()).unwrap();
+    store.canonize(b0.hash()).unwrap();
+    store.canonize(b1.hash()).unwrap();
+    store
+        .canonize_with_invalid(b2.hash(), &[None, INVALID, None])
+        .unwrap();
+    assert!(store.has_address_index());
+
+    // invalid tx2 neither creates nor spends outputs
+    let funding: Vec<_> = store.address_funding(&script_hash).collect();
+    assert_eq!(
+        funding.iter().map(|entry| (entry.height, entry.value)).collect::<Vec<_>>(),
+        vec![(0, 1), (0, 50), (1, 2), (1, 40), (2, 3), (2, 20)]
+    );
+    let spending: Vec<_> = store.address_spending(&script_hash).collect();
+    assert_eq!(spending.len(), 2);
+    assert_eq!(spending[0].outpoint, outpoint(&b0, 1));
+    assert_eq!(&spending[0].spender, &b1.transactions[1].hash);
+    assert_eq!(spending[1].outpoint, outpoint(&b1, 1));
+    assert_eq!(spending[1].value, 40);
+    assert_eq!(spending[1].height, 2);
+    assert_eq!(
+        store.address_stats(&script_hash),
+        AddressStats {
+            funding: 6,
+            spending: 2,
+        }
+    );
+
+    store.decanonize().unwrap();
+    assert_eq!(store.address_funding(&script_hash).count(), 4);
+    assert_eq!(store.address_spending(&script_hash).count(), 1);
+    assert_eq!(
+        store.address_stats(&script_hash),
+        AddressStats {
+            funding: 4,
+            spending: 1,
+        }
+    );
+}
+
+#[test]
+fn address_index_of_fork_merges_overlay() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default())
+        .with_address_index()
+        .unwrap();
+    let (b0, b1, b2) = mixed_validity_chain();
+    let script_hash = script_hash(&b0.transactions[0].raw.outputs[0].script_pubkey);
+    let b2_side: IndexedBlock = test_data::block_builder()
+        .transaction()
+        .coinbase()
+        .output()
+        .value(4)
+        .build()
+        .build()
+        .merkled_header()
+        .parent(b1.hash().clone())
+        .build()
+        .build()
+        .into();
+
+    store.insert(b0.clone()).unwrap();
+    store.insert(b1.clone()).unwrap();
+    store.insert(b2.clone()).unwrap();
+    store.insert(b2_side.clone()).unwrap();
+    store.canonize(b0.hash()).unwrap();
+    store.canonize(b1.hash()).unwrap();
+    store
+        .canonize_with_invalid(b2.hash(), &[None, INVALID, None])
+        .unwrap();
+
+    let fork = store
+        .fork(SideChainOrigin {
+            ancestor: 1,
+            canonized_route: vec![b2_side.hash().clone()],
+            decanonized_route: vec![b2.hash().clone()],
+            block_number: 2,
+        })
+        .unwrap();
+    let index = fork.store().as_address_index_provider();
+    assert_eq!(
+        index
+            .address_funding(&script_hash)
+            .map(|entry| (entry.height, entry.value))
+            .collect::<Vec<_>>(),
+        vec![(0, 1), (0, 50), (1, 2), (1, 40), (2, 4)]
+    );
+    assert_eq!(index.address_spending(&script_hash).count(), 1);
+    assert_eq!(
+        index.address_stats(&script_hash),
+        AddressStats {
+            funding: 5,
+            spending: 1,
+        }
+    );
+}
+
+#[test]
+fn address_index_is_disabled_by_default() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    let (b0, _, _) = mixed_validity_chain();
+    store.insert(b0.clone()).unwrap();
+    store.canonize(b0.hash()).unwrap();
+
+    assert!(!store.has_address_index());
+    let script_hash = script_hash(&b0.transactions[0].raw.outputs[0].script_pubkey);
+    assert_eq!(store.address_funding(&script_hash).count(), 0);
+    assert_eq!(store.address_stats(&script_hash), AddressStats::default());
+
+    // index could not be enabled for non-empty database
+    assert_eq!(
+        store.with_address_index().err(),
+        Some(Error::AddressIndexUnavailable)
+    );
+}
+
+#[test]
+fn fork_keeps_transactions_validity() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    let (b0, b1, b2) = mixed_validity_chain();
+    let b2_side: IndexedBlock = test_data::block_builder()
+        .transaction()
+        .coinbase()
+        .output()
+        .value(4)
+        .build()
+        .build()
+        .merkled_header()
+        .parent(b1.hash().clone())
+        .build()
+        .build()
+        .into();
+    let b3_side: IndexedBlock = test_data::block_builder()
+        .transaction()
+        .coinbase()
+        .output()
+        .value(5)
+        .build()
+        .build()
+        .merkled_header()
+        .parent(b2_side.hash().clone())
+        .build()
+        .build()
+        .into();
+
+    store.insert(b0.clone()).unwrap();
+    store.insert(b1.clone()).unwrap();
+    store.insert(b2.clone()).unwrap();
+    store.insert(b2_side.clone()).unwrap();
+    store.insert(b3_side.clone()).unwrap();
+
+    store.canonize(b0.hash()).unwrap();
+    store.canonize(b1.hash()).unwrap();
+    store
+        .canonize_with_invalid(b2.hash(), &[None, INVALID, None])
+        .unwrap();
+
+    // switch to the side chain
+    let fork = store
+        .fork(SideChainOrigin {
+            ancestor: 1,
+            canonized_route: vec![b2_side.hash().clone()],
+            decanonized_route: vec![b2.hash().clone()],
+            block_number: 3,
+        })
+        .unwrap();
+    fork.store().canonize(b3_side.hash()).unwrap();
+    store.switch_to_fork(fork).unwrap();
+
+    assert_eq!(b3_side.hash(), &store.best_block().hash);
+    assert!(store.is_spent(&outpoint(&b0, 1)));
+    assert!(!store.is_spent(&outpoint(&b1, 1)));
+    assert!(store.transaction_meta(&b2.transactions[1].hash).is_none());
+
+    // and back to the original chain
+    let fork = store
+        .fork(SideChainOrigin {
+            ancestor: 1,
+            canonized_route: vec![b2.hash().clone()],
+            decanonized_route: vec![b2_side.hash().clone(), b3_side.hash().clone()],
+            block_number: 2,
+        })
+        .unwrap();
+    store.switch_to_fork(fork).unwrap();
+
+    assert_eq!(b2.hash(), &store.best_block().hash);
+    assert!(store.transaction_meta(&b2.transactions[0].hash).unwrap().is_valid());
+    assert!(!store.transaction_meta(&b2.transactions[1].hash).unwrap().is_valid());
+    assert!(store.transaction_output(&outpoint(&b2, 1), 0).is_none());
+    assert!(store.is_spent(&outpoint(&b0, 1)));
+    assert!(store.is_spent(&outpoint(&b1, 1)));
+}
+
+#[test]
+fn transaction_errors_are_kept_after_decanonize() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    let (b0, b1, b2) = mixed_validity_chain();
+
+    store.insert(b0.clone()).unwrap();
+    store.insert(b1.clone()).unwrap();
+    store.insert(b2.clone()).unwrap();
+
+    store.canonize(b0.hash()).unwrap();
+    store.canonize(b1.hash()).unwrap();
+    store
+        .canonize_with_invalid(b2.hash(), &[None, INVALID, None])
+        .unwrap();
+
+    assert_eq!(store.transaction_error(&b2.transactions[1].hash), INVALID);
+    assert_eq!(store.transaction_error(&b2.transactions[2].hash), None);
+
+    assert_eq!(b2.hash(), &store.decanonize().unwrap());
+    assert_eq!(store.transaction_error(&b2.transactions[1].hash), INVALID);
+
+    // errors are not written, if canonization fails
+    assert!(store.canonize_with_invalid(b1.hash(), &[INVALID]).is_err());
+    assert_eq!(store.transaction_error(&b1.transactions[0].hash), None);
+}
+
+// b0..bN: coinbase, except for:
+// b1: coinbase, tx0 (two outputs)
+// b2: coinbase, tx1 (spends first output of tx0)
+// b3: coinbase, tx2 (spends tx1)
+fn prunable_chain(len: usize) -> Vec<IndexedBlock> {
+    let b0: IndexedBlock = test_data::block_builder()
+        .transaction()
+        .coinbase()
+        .output()
+        .value(0)
+        .build()
+        .build()
+        .merkled_header()
+        .build()
+        .build()
+        .into();
+    let b1: IndexedBlock = test_data::block_builder()
+        .transaction()
+        .coinbase()
+        .output()
+        .value(1)
+        .build()
+        .build()
+        .transaction()
+        .output()
+        .value(10)
+        .build()
+        .output()
+        .value(20)
+        .build()
+        .build()
+        .merkled_header()
+        .parent(b0.hash().clone())
+        .build()
+        .build()
+        .into();
+    let b2: IndexedBlock = test_data::block_builder()
+        .transaction()
+        .coinbase()
+        .output()
+        .value(2)
+        .build()
+        .build()
+        .transaction()
+        .input()
+        .hash(b1.transactions[1].hash.clone())
+        .index(0)
+        .build()
+        .output()
+        .value(10)
+        .build()
+        .build()
+        .merkled_header()
+        .parent(b1.hash().clone())
+        .build()
+        .build()
+        .into();
+    let b3: IndexedBlock = test_data::block_builder()
+        .transaction()
+        .coinbase()
+        .output()
+        .value(3)
+        .build()
+        .build()
+        .transaction()
+        .input()
+        .hash(b2.transactions[1].hash.clone())
+        .build()
+        .output()
+        .value(10)
+        .build()
+        .build()
+        .merkled_header()
+        .parent(b2.hash().clone())
+        .build()
+        .build()
+        .into();
+
+    let mut blocks = vec![b0, b1, b2, b3];
+    while blocks.len() < len {
+        let block: IndexedBlock = test_data::block_builder()
+            .transaction()
+            .coinbase()
+            .output()
+            .value(blocks.len() as u64)
+            .build()
+            .build()
+            .merkled_header()
+            .parent(blocks[blocks.len() - 1].hash().clone())
+            .build()
+            .build()
+            .into();
+        blocks.push(block);
+    }
+    blocks
+}
+
+#[test]
+fn prune_old_blocks() {
+    let store =
+        BlockChainDatabase::open(MemoryDatabase::default()).with_prune_mode(PruneMode::Depth(4));
+    let blocks = prunable_chain(33);
+    for block in &blocks {
+        store.insert(block.clone()).unwrap();
+        store.canonize(block.hash()).unwrap();
+    }
+
+    // pruning is performed when block 32 is canonized
+    assert_eq!(store.pruned_height(), Some(28));
+    assert!(store.is_pruned(BlockRef::Number(2)));
+    assert!(store.block(BlockRef::Number(2)).is_none());
+    assert!(store.block_header(BlockRef::Number(2)).is_some());
+    assert!(!store.is_pruned(BlockRef::Number(29)));
+    assert!(store.block(BlockRef::Number(29)).is_some());
+    assert!(!store.is_pruned(BlockRef::Number(33)));
+
+    // only transactions with unspent outputs are kept
+    let tx0 = &blocks[1].transactions[1].hash;
+    let tx1 = &blocks[2].transactions[1].hash;
+    let tx2 = &blocks[3].transactions[1].hash;
+    assert!(store.transaction(tx0).is_some());
+    assert!(store.transaction(tx1).is_none());
+    assert!(store.transaction_meta(tx1).is_some());
+    assert_eq!(
+        store.transaction_location(tx1),
+        Some(TransactionLocation {
+            block_hash: blocks[2].hash().clone(),
+            index: 1,
+        })
+    );
+    assert!(store.transaction(tx2).is_some());
+    assert!(store.transaction(&blocks[1].transactions[0].hash).is_some());
+
+    // kept blocks could be decanonized, pruned blocks could not
+    for _ in 0..4 {
+        store.decanonize().unwrap();
+    }
+    assert_eq!(store.best_block().number, 28);
+    assert_eq!(store.decanonize(), Err(Error::BlockPruned));
+    assert_eq!(BlockChain::rollback_best(&store), Err(Error::BlockPruned));
+}
+
+#[test]
+fn no_pruning_by_default() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    for block in prunable_chain(33) {
+        let hash = block.hash().clone();
+        store.insert(block).unwrap();
+        store.canonize(&hash).unwrap();
+    }
+
+    assert_eq!(store.pruned_height(), None);
+    assert!(!store.is_pruned(BlockRef::Number(0)));
+    assert!(store.block(BlockRef::Number(2)).is_some());
+}
+
+#[test]
+fn utxo_snapshot_round_trip() {
+    let blocks = prunable_chain(7);
+    let source = BlockChainDatabase::open(MemoryDatabase::default());
+    for block in &blocks[0..6] {
+        source.insert(block.clone()).unwrap();
+        source.canonize(block.hash()).unwrap();
+    }
+
+    let mut snapshot = Vec::new();
+    let info = source.dump_utxo_snapshot(&mut snapshot).unwrap();
+    assert_eq!(info.block_hash, *blocks[5].hash());
+    assert_eq!(info.height, 5);
+    assert_eq!(Some(info.utxo_stats.clone()), source.utxo_stats());
+
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    assert_eq!(store.load_utxo_snapshot(&mut snapshot.as_slice()), Ok(info));
+    assert_eq!(store.best_block(), source.best_block());
+    assert_eq!(store.utxo_stats(), source.utxo_stats());
+    assert_eq!(store.pruned_height(), Some(5));
+    assert!(!store.is_snapshot_incomplete());
+
+    // snapshotted blocks have headers, but no bodies
+    assert_eq!(store.block_hash(5), Some(blocks[5].hash().clone()));
+    assert!(store.block_header(BlockRef::Number(0)).is_some());
+    assert!(store.is_pruned(BlockRef::Number(3)));
+
+    // only transactions with unspent outputs are loaded
+    let unspent = OutPoint {
+        hash: blocks[1].transactions[1].hash.clone(),
+        index: 1,
+    };
+    assert!(store.transaction_output(&unspent, 0).is_some());
+    assert!(!store.is_spent(&unspent));
+    assert!(store.is_spent(&outpoint(&blocks[1], 1)));
+    assert!(store.transaction(&blocks[2].transactions[1].hash).is_none());
+    assert!(store.transaction(&blocks[0].transactions[0].hash).is_none());
+
+    // new blocks are canonized on top of the snapshot
+    for db in &[&source, &store] {
+        db.insert(blocks[6].clone()).unwrap();
+        db.canonize(blocks[6].hash()).unwrap();
+    }
+    assert_eq!(store.utxo_stats(), source.utxo_stats());
+
+    // the same set could not be loaded twice, snapshot of the snapshot could not be made
+    let error = store.load_utxo_snapshot(&mut snapshot.as_slice()).unwrap_err();
+    assert_eq!(error, Error::InvalidSnapshot("database is not empty".into()));
+    assert_eq!(store.dump_utxo_snapshot(&mut Vec::new()), Err(Error::BlockPruned));
+}
+
+#[test]
+fn corrupted_utxo_snapshot_is_rejected() {
+    let source = BlockChainDatabase::open(MemoryDatabase::default());
+    for block in prunable_chain(6) {
+        let hash = block.hash().clone();
+        source.insert(block).unwrap();
+        source.canonize(&hash).unwrap();
+    }
+
+    let mut snapshot = Vec::new();
+    source.dump_utxo_snapshot(&mut snapshot).unwrap();
+    // lock time of the last transaction is changed => its outputs are different set elements
+    let last = snapshot.len() - 1;
+    snapshot[last] ^= 1;
+
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    let error = store.load_utxo_snapshot(&mut snapshot.as_slice()).unwrap_err();
+    assert_eq!(
+        error,
+        Error::InvalidSnapshot("unspent transactions do not match the snapshot hash".into())
+    );
+    assert_eq!(store.block_hash(0), None);
+    assert!(store.best_block().hash.is_zero());
+
+    // transactions of the rejected snapshot are already written => nothing is loaded on top of them
+    assert!(store.is_snapshot_incomplete());
+    let mut valid_snapshot = Vec::new();
+    source.dump_utxo_snapshot(&mut valid_snapshot).unwrap();
+    let error = store.load_utxo_snapshot(&mut valid_snapshot.as_slice()).unwrap_err();
+    assert_eq!(
+        error,
+        Error::InvalidSnapshot(
+            "loading of the previous snapshot has not been completed, database must be removed"
+                .into()
+        )
+    );
+}
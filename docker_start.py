@@ -1,4 +1,5 @@
 #!/usr/bin/env python
+import os
 import subprocess
 print("hello world")
 print("second")
@@ -19,6 +20,10 @@ pbtc_cmd = ['/build/parity-bitcoin/target/debug/pbtc', '--btc', '--regtest', int
 pbtc_str = " ".join(x for x in pbtc_cmd);
 print('run cmd" ' + pbtc_str)
 
-subprocess.call(pbtc_cmd)
+# stdin is not a terminal in the container, so the wallet passphrase is passed in the environment
+pbtc_env = dict(os.environ)
+pbtc_env.setdefault('PBTC_WALLET_PASSPHRASE', 'regtest')
+
+subprocess.call(pbtc_cmd, env=pbtc_env)
 
 
@@ -2,20 +2,25 @@
 //!
 //! A Bitcoin address, or simply address, is an identifier of 26-35 alphanumeric characters, beginning with the number 1
 //! or 3, that represents a possible destination for a bitcoin payment.
+//! Segregated witness addresses are bech32 (witness v0) or bech32m (witness v1) encoded
+//! and begin with `bc1` (`tb1` on testnet).
 //!
 //! https://en.bitcoin.it/wiki/Address
+//! https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki
+//! https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki
 
 use base58::{FromBase58, ToBase58};
+use bech32::{self, Variant};
 use crypto::checksum;
+use hash::H256;
 use network::Network;
 use std::fmt;
-use std::ops::Deref;
 use std::str::FromStr;
-use {AddressHash, DisplayLayout, Error};
+use {AddressHash, Error};
 
-/// There are two address formats currently in use.
+/// There are two legacy address formats and three segregated witness address formats currently in use.
 /// https://bitcoin.org/en/developer-reference#address-conversion
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Type {
     /// Pay to PubKey Hash
     /// Common P2PKH which begin with the number 1, eg: 1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2.
@@ -25,6 +30,57 @@ pub enum Type {
     /// Newer P2SH type starting with the number 3, eg: 3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy.
     /// https://bitcoin.org/en/glossary/p2sh-address
     P2SH,
+    /// Pay to Witness PubKey Hash
+    /// Witness v0 20-byte program, eg: bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4.
+    P2WPKH,
+    /// Pay to Witness Script Hash
+    /// Witness v0 32-byte program, eg: bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3.
+    P2WSH,
+    /// Pay to Taproot
+    /// Witness v1 32-byte program, eg: bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0.
+    P2TR,
+}
+
+impl Type {
+    /// Witness version of segregated witness address types
+    pub fn witness_version(&self) -> Option<u8> {
+        match *self {
+            Type::P2PKH | Type::P2SH => None,
+            Type::P2WPKH | Type::P2WSH => Some(0),
+            Type::P2TR => Some(1),
+        }
+    }
+}
+
+/// Hash (or witness program), committed to by the address
+#[derive(Debug, PartialEq, Clone)]
+pub enum AddressPayload {
+    /// Public key or script hash of P2PKH, P2SH and P2WPKH addresses.
+    Hash(AddressHash),
+    /// Witness program of P2WSH and P2TR addresses: script hash or taproot output key.
+    WitnessProgram(H256),
+}
+
+impl AddressPayload {
+    /// Payload bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        match *self {
+            AddressPayload::Hash(ref hash) => &**hash,
+            AddressPayload::WitnessProgram(ref program) => &**program,
+        }
+    }
+}
+
+impl From<AddressHash> for AddressPayload {
+    fn from(hash: AddressHash) -> Self {
+        AddressPayload::Hash(hash)
+    }
+}
+
+impl From<H256> for AddressPayload {
+    fn from(program: H256) -> Self {
+        AddressPayload::WitnessProgram(program)
+    }
 }
 
 /// `AddressHash` with network identifier and format type
@@ -34,24 +90,20 @@ pub struct Address {
     pub kind: Type,
     /// The network of the address.
     pub network: Network,
-    /// Public key hash.
-    pub hash: AddressHash,
+    /// Public key hash, script hash or witness program.
+    pub hash: AddressPayload,
 }
 
-pub struct AddressDisplayLayout([u8; 25]);
-
-impl Deref for AddressDisplayLayout {
-    type Target = [u8];
-
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl Address {
+    /// Human-readable part of bech32 addresses of the network
+    fn bech32_hrp(network: Network) -> &'static str {
+        match network {
+            Network::Mainnet => "bc",
+            Network::Testnet => "tb",
+        }
     }
-}
 
-impl DisplayLayout for Address {
-    type Target = AddressDisplayLayout;
-
-    fn layout(&self) -> Self::Target {
+    fn to_base58(&self) -> String {
         let mut result = [0u8; 25];
 
         result[0] = match (self.network, self.kind) {
@@ -59,18 +111,17 @@ impl DisplayLayout for Address {
             (Network::Mainnet, Type::P2SH) => 5,
             (Network::Testnet, Type::P2PKH) => 111,
             (Network::Testnet, Type::P2SH) => 196,
+            _ => unreachable!("called for legacy addresses only; qed"),
         };
 
-        result[1..21].copy_from_slice(&*self.hash);
+        result[1..21].copy_from_slice(self.hash.as_bytes());
         let cs = checksum(&result[0..21]);
         result[21..25].copy_from_slice(&*cs);
-        AddressDisplayLayout(result)
+        result.to_base58()
     }
 
-    fn from_layout(data: &[u8]) -> Result<Self, Error>
-    where
-        Self: Sized,
-    {
+    fn from_base58(s: &str) -> Result<Self, Error> {
+        let data = try!(s.from_base58().map_err(|_| Error::InvalidAddress));
         if data.len() != 25 {
             return Err(Error::InvalidAddress);
         }
@@ -91,6 +142,48 @@ impl DisplayLayout for Address {
         let mut hash = AddressHash::default();
         hash.copy_from_slice(&data[1..21]);
 
+        let address = Address {
+            kind: kind,
+            network: network,
+            hash: hash.into(),
+        };
+
+        Ok(address)
+    }
+
+    fn to_bech32(&self, version: u8) -> String {
+        let variant = if version == 0 { Variant::Bech32 } else { Variant::Bech32m };
+        let mut data = vec![version];
+        data.extend(bech32::convert_bits(self.hash.as_bytes(), 8, 5, true).expect("bytes are 8-bit values; qed"));
+        bech32::encode(Address::bech32_hrp(self.network), &data, variant)
+    }
+
+    fn from_bech32(s: &str) -> Result<Self, Error> {
+        let (hrp, data, variant) = try!(bech32::decode(s));
+        let network = match hrp.as_str() {
+            "bc" => Network::Mainnet,
+            "tb" => Network::Testnet,
+            _ => return Err(Error::InvalidAddress),
+        };
+
+        let (version, program) = match data.split_first() {
+            Some((&version, program)) => (version, try!(bech32::convert_bits(program, 5, 8, false))),
+            None => return Err(Error::InvalidAddress),
+        };
+
+        let expected_variant = if version == 0 { Variant::Bech32 } else { Variant::Bech32m };
+        if variant != expected_variant {
+            return Err(Error::InvalidChecksum);
+        }
+
+        // only standard witness programs are supported
+        let (kind, hash) = match (version, program.len()) {
+            (0, 20) => (Type::P2WPKH, AddressPayload::Hash(program[..].into())),
+            (0, 32) => (Type::P2WSH, AddressPayload::WitnessProgram(program[..].into())),
+            (1, 32) => (Type::P2TR, AddressPayload::WitnessProgram(program[..].into())),
+            _ => return Err(Error::InvalidAddress),
+        };
+
         let address = Address {
             kind: kind,
             network: network,
@@ -103,7 +196,10 @@ impl DisplayLayout for Address {
 
 impl fmt::Display for Address {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.layout().to_base58().fmt(f)
+        match self.kind.witness_version() {
+            Some(version) => self.to_bech32(version).fmt(f),
+            None => self.to_base58().fmt(f),
+        }
     }
 }
 
@@ -114,8 +210,12 @@ impl FromStr for Address {
     where
         Self: Sized,
     {
-        let hex = try!(s.from_base58().map_err(|_| Error::InvalidAddress));
-        Address::from_layout(&hex)
+        let lowercase = s.to_ascii_lowercase();
+        if lowercase.starts_with("bc1") || lowercase.starts_with("tb1") {
+            Address::from_bech32(s)
+        } else {
+            Address::from_base58(s)
+        }
     }
 }
 
@@ -127,15 +227,16 @@ impl From<&'static str> for Address {
 
 #[cfg(test)]
 mod tests {
-    use super::{Address, Type};
+    use super::{Address, AddressPayload, Type};
     use network::Network;
+    use Error;
 
     #[test]
     fn test_address_to_string() {
         let address = Address {
             kind: Type::P2PKH,
             network: Network::Mainnet,
-            hash: "3f4aa1fedf1f54eeb03b759deadb36676b184911".into(),
+            hash: AddressPayload::Hash("3f4aa1fedf1f54eeb03b759deadb36676b184911".into()),
         };
 
         assert_eq!(
@@ -149,9 +250,61 @@ mod tests {
         let address = Address {
             kind: Type::P2PKH,
             network: Network::Mainnet,
-            hash: "3f4aa1fedf1f54eeb03b759deadb36676b184911".into(),
+            hash: AddressPayload::Hash("3f4aa1fedf1f54eeb03b759deadb36676b184911".into()),
         };
 
         assert_eq!(address, "16meyfSoQV6twkAAxPe51RtMVz7PGRmWna".into());
     }
+
+    #[test]
+    fn test_segwit_address_to_string_and_from_str() {
+        let p2wpkh = Address {
+            kind: Type::P2WPKH,
+            network: Network::Mainnet,
+            hash: AddressPayload::Hash("751e76e8199196d454941c45d1b3a323f1433bd6".into()),
+        };
+        assert_eq!(p2wpkh.to_string(), "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+        assert_eq!(p2wpkh, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4".into());
+        assert_eq!(p2wpkh, "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4".into());
+
+        let p2wsh = Address {
+            kind: Type::P2WSH,
+            network: Network::Testnet,
+            hash: AddressPayload::WitnessProgram("1863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262".into()),
+        };
+        assert_eq!(p2wsh.to_string(), "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7");
+        assert_eq!(p2wsh, "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7".into());
+
+        let p2tr = Address {
+            kind: Type::P2TR,
+            network: Network::Mainnet,
+            hash: AddressPayload::WitnessProgram("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798".into()),
+        };
+        assert_eq!(p2tr.to_string(), "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0");
+        assert_eq!(p2tr, "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0".into());
+    }
+
+    #[test]
+    fn test_segwit_address_from_invalid_str() {
+        // witness v0 with bech32m checksum
+        assert_eq!(
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh".parse::<Address>(),
+            Err(Error::InvalidChecksum)
+        );
+        // witness v1 with bech32 checksum
+        assert_eq!(
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd".parse::<Address>(),
+            Err(Error::InvalidChecksum)
+        );
+        // unknown human-readable part
+        assert_eq!(
+            "tc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq5zuyut".parse::<Address>(),
+            Err(Error::InvalidAddress)
+        );
+        // invalid checksum
+        assert_eq!(
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5".parse::<Address>(),
+            Err(Error::InvalidChecksum)
+        );
+    }
 }
@@ -0,0 +1,192 @@
+//! Bech32 and Bech32m encoding of segregated witness addresses
+//!
+//! https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki
+//! https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki
+
+use Error;
+
+/// Characters, encoding 5-bit values
+const CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+/// Generator of BCH checksum
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+/// Maximal length of encoded string
+const MAX_LENGTH: usize = 90;
+/// Length of encoded checksum
+const CHECKSUM_LENGTH: usize = 6;
+
+/// Checksum variant
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Variant {
+    /// BIP173 checksum, used by witness v0 addresses
+    Bech32,
+    /// BIP350 checksum, used by witness v1+ addresses
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(&self) -> u32 {
+        match *self {
+            Variant::Bech32 => 1,
+            Variant::Bech32m => 0x2bc830a3,
+        }
+    }
+}
+
+/// Encode human-readable part and 5-bit values
+pub fn encode(hrp: &str, data: &[u8], variant: Variant) -> String {
+    let checksum = create_checksum(hrp, data, variant);
+    let mut result = String::with_capacity(hrp.len() + 1 + data.len() + CHECKSUM_LENGTH);
+    result.push_str(hrp);
+    result.push('1');
+    result.extend(data.iter().chain(checksum.iter()).map(|&value| CHARSET[value as usize] as char));
+    result
+}
+
+/// Decode string into lowercase human-readable part and 5-bit values
+pub fn decode(s: &str) -> Result<(String, Vec<u8>, Variant), Error> {
+    if s.len() > MAX_LENGTH || s.bytes().any(|c| c < 33 || c > 126) {
+        return Err(Error::InvalidAddress);
+    }
+    if s.bytes().any(|c| c.is_ascii_lowercase()) && s.bytes().any(|c| c.is_ascii_uppercase()) {
+        return Err(Error::InvalidAddress);
+    }
+
+    let s = s.to_ascii_lowercase();
+    let separator = try!(s.rfind('1').ok_or(Error::InvalidAddress));
+    if separator == 0 || separator + 1 + CHECKSUM_LENGTH > s.len() {
+        return Err(Error::InvalidAddress);
+    }
+
+    let hrp = &s[..separator];
+    let values = try!(s[separator + 1..]
+        .bytes()
+        .map(|c| CHARSET.iter().position(|&x| x == c).map(|value| value as u8).ok_or(Error::InvalidAddress))
+        .collect::<Result<Vec<_>, _>>());
+
+    let variant = match polymod(&[&hrp_expand(hrp)[..], &values[..]].concat()) {
+        x if x == Variant::Bech32.constant() => Variant::Bech32,
+        x if x == Variant::Bech32m.constant() => Variant::Bech32m,
+        _ => return Err(Error::InvalidChecksum),
+    };
+
+    let data = values[..values.len() - CHECKSUM_LENGTH].to_vec();
+    Ok((hrp.to_owned(), data, variant))
+}
+
+/// Regroup bits of values. Incomplete group is zero-padded if `pad` is true,
+/// otherwise it must consist of less than `from` zero bits.
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, Error> {
+    let mut acc = 0u32;
+    let mut bits = 0u32;
+    let max_value = (1u32 << to) - 1;
+    let max_acc = (1u32 << (from + to - 1)) - 1;
+    let mut result = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for &value in data {
+        if (value as u32) >> from != 0 {
+            return Err(Error::InvalidAddress);
+        }
+
+        acc = ((acc << from) | value as u32) & max_acc;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            result.push(((acc >> bits) & max_value) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            result.push(((acc << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max_value) != 0 {
+        return Err(Error::InvalidAddress);
+    }
+
+    Ok(result)
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    values.iter().fold(1u32, |chk, &value| {
+        let top = chk >> 25;
+        let chk = (chk & 0x1ffffff) << 5 ^ value as u32;
+        GENERATOR
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| (top >> i) & 1 == 1)
+            .fold(chk, |chk, (_, generator)| chk ^ generator)
+    })
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    hrp.bytes()
+        .map(|c| c >> 5)
+        .chain(Some(0))
+        .chain(hrp.bytes().map(|c| c & 31))
+        .collect()
+}
+
+fn create_checksum(hrp: &str, data: &[u8], variant: Variant) -> Vec<u8> {
+    let values = [&hrp_expand(hrp)[..], data, &[0u8; CHECKSUM_LENGTH][..]].concat();
+    let polymod = polymod(&values) ^ variant.constant();
+    (0..CHECKSUM_LENGTH)
+        .map(|i| ((polymod >> (5 * (5 - i))) & 31) as u8)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{convert_bits, decode, encode, Variant};
+    use Error;
+
+    fn check_valid(s: &str, variant: Variant) {
+        let (hrp, data, decoded_variant) = decode(s).unwrap();
+        assert_eq!(decoded_variant, variant);
+        assert_eq!(encode(&hrp, &data, variant), s.to_lowercase());
+    }
+
+    #[test]
+    fn test_bech32_valid_checksum() {
+        check_valid("A12UEL5L", Variant::Bech32);
+        check_valid("a12uel5l", Variant::Bech32);
+        check_valid(
+            "an83characterlonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1tt5tgs",
+            Variant::Bech32,
+        );
+        check_valid("abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw", Variant::Bech32);
+        check_valid("split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w", Variant::Bech32);
+        check_valid("?1ezyfcl", Variant::Bech32);
+    }
+
+    #[test]
+    fn test_bech32m_valid_checksum() {
+        check_valid("A1LQFN3A", Variant::Bech32m);
+        check_valid("a1lqfn3a", Variant::Bech32m);
+        check_valid("abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx", Variant::Bech32m);
+        check_valid("split1checkupstagehandshakeupstreamerranterredcaperredlc445v", Variant::Bech32m);
+        check_valid("?1v759aa", Variant::Bech32m);
+    }
+
+    #[test]
+    fn test_bech32_invalid() {
+        // invalid character
+        assert_eq!(decode("\x201nwldj5").err(), Some(Error::InvalidAddress));
+        // empty hrp
+        assert_eq!(decode("1pzry9x0s0muk").err(), Some(Error::InvalidAddress));
+        // too short checksum
+        assert_eq!(decode("li1dgmt3").err(), Some(Error::InvalidAddress));
+        // mixed case
+        assert_eq!(decode("A12uEL5L").err(), Some(Error::InvalidAddress));
+        // invalid checksum
+        assert_eq!(decode("a12uel5m").err(), Some(Error::InvalidChecksum));
+    }
+
+    #[test]
+    fn test_convert_bits() {
+        let data = [0xffu8, 0x00, 0x80];
+        let converted = convert_bits(&data, 8, 5, true).unwrap();
+        assert_eq!(converted, vec![31, 28, 0, 8, 0]);
+        assert_eq!(convert_bits(&converted, 5, 8, false).unwrap(), data.to_vec());
+        // non-zero padding
+        assert_eq!(convert_bits(&[31, 31], 5, 8, false).err(), Some(Error::InvalidAddress));
+    }
+}
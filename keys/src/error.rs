@@ -12,6 +12,10 @@ pub enum Error {
     InvalidPrivate,
     InvalidAddress,
     FailedKeyGeneration,
+    InvalidExtendedKey,
+    InvalidDerivationPath,
+    HardenedPublicDerivation,
+    InvalidMnemonic,
 }
 
 impl fmt::Display for Error {
@@ -26,6 +30,10 @@ impl fmt::Display for Error {
             Error::InvalidPrivate => "Invalid Private",
             Error::InvalidAddress => "Invalid Address",
             Error::FailedKeyGeneration => "Key generation failed",
+            Error::InvalidExtendedKey => "Invalid Extended Key",
+            Error::InvalidDerivationPath => "Invalid Derivation Path",
+            Error::HardenedPublicDerivation => "Hardened derivation from public key",
+            Error::InvalidMnemonic => "Invalid Mnemonic",
         };
 
         msg.fmt(f)
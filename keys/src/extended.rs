@@ -0,0 +1,607 @@
+//! Hierarchical deterministic keys
+//!
+//! https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki
+//! https://github.com/bitcoin/bips/blob/master/bip-0044.mediawiki
+
+use base58::{FromBase58, ToBase58};
+use crypto::{checksum, dhash160, hmac_sha512};
+use hash::{H256, H264, H32, H512};
+use network::Network;
+use secp256k1::key;
+use std::fmt;
+use std::str::FromStr;
+use {DisplayLayout, Error, KeyPair, Private, Public, Secret, SECP256K1};
+
+/// Child indexes, starting from this one, are hardened
+pub const HARDENED_INDEX: u32 = 0x8000_0000;
+
+const MAINNET_PRIVATE_VERSION: u32 = 0x0488_ADE4;
+const MAINNET_PUBLIC_VERSION: u32 = 0x0488_B21E;
+const TESTNET_PRIVATE_VERSION: u32 = 0x0435_8394;
+const TESTNET_PUBLIC_VERSION: u32 = 0x0435_87CF;
+
+/// HMAC key, used to generate master key from seed
+const MASTER_KEY_HMAC_KEY: &'static [u8] = b"Bitcoin seed";
+/// Size of serialized extended key without checksum
+const EXTENDED_KEY_SIZE: usize = 78;
+
+/// Path of the key in hierarchy, e.g. m/44'/0'/0'/0/1
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct DerivationPath(pub Vec<u32>);
+
+/// Extended private key
+#[derive(PartialEq, Clone)]
+pub struct ExtendedPrivateKey {
+    /// The network on which this key should be used.
+    pub network: Network,
+    /// Depth in hierarchy. Master key has depth 0.
+    pub depth: u8,
+    /// First 4 bytes of the parent public key hash.
+    pub parent_fingerprint: H32,
+    /// Index of this key in the parent key children.
+    pub child_number: u32,
+    /// Chain code.
+    pub chain_code: H256,
+    /// ECDSA key.
+    pub secret: Secret,
+}
+
+/// Extended public key
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExtendedPublicKey {
+    /// The network on which this key should be used.
+    pub network: Network,
+    /// Depth in hierarchy. Master key has depth 0.
+    pub depth: u8,
+    /// First 4 bytes of the parent public key hash.
+    pub parent_fingerprint: H32,
+    /// Index of this key in the parent key children.
+    pub child_number: u32,
+    /// Chain code.
+    pub chain_code: H256,
+    /// Compressed public key.
+    pub public: H264,
+}
+
+impl DerivationPath {
+    /// BIP44 path of the account: m/44'/coin_type'/account'
+    pub fn bip44_account(network: Network, account: u32) -> Self {
+        let coin_type = match network {
+            Network::Mainnet => 0,
+            Network::Testnet => 1,
+        };
+
+        DerivationPath(vec![
+            44 | HARDENED_INDEX,
+            coin_type | HARDENED_INDEX,
+            account | HARDENED_INDEX,
+        ])
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(f.write_str("m"));
+        for index in &self.0 {
+            if *index >= HARDENED_INDEX {
+                try!(write!(f, "/{}'", index - HARDENED_INDEX));
+            } else {
+                try!(write!(f, "/{}", index));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut parts = s.split('/');
+        if parts.next() != Some("m") {
+            return Err(Error::InvalidDerivationPath);
+        }
+
+        parts
+            .map(|part| {
+                let (number, offset) = if part.ends_with('\'') || part.ends_with('h') {
+                    (&part[..part.len() - 1], HARDENED_INDEX)
+                } else {
+                    (part, 0)
+                };
+
+                match number.parse::<u32>() {
+                    Ok(index) if index < HARDENED_INDEX => Ok(index | offset),
+                    _ => Err(Error::InvalidDerivationPath),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(DerivationPath)
+    }
+}
+
+impl ExtendedPrivateKey {
+    /// Generate master key from seed
+    pub fn master(seed: &[u8], network: Network) -> Result<Self, Error> {
+        let hash = hmac_sha512(MASTER_KEY_HMAC_KEY, seed);
+        let (secret, chain_code) = split_hash(&hash);
+        try!(key::SecretKey::from_slice(&SECP256K1, &*secret));
+
+        Ok(ExtendedPrivateKey {
+            network: network,
+            depth: 0,
+            parent_fingerprint: H32::default(),
+            child_number: 0,
+            chain_code: chain_code,
+            secret: secret,
+        })
+    }
+
+    /// Derive child key. Indexes, starting from `HARDENED_INDEX`, are producing hardened keys.
+    pub fn derive_child(&self, index: u32) -> Result<Self, Error> {
+        let context = &SECP256K1;
+        let public = try!(self.public());
+
+        let mut data = Vec::with_capacity(37);
+        if index >= HARDENED_INDEX {
+            data.push(0);
+            data.extend_from_slice(&*self.secret);
+        } else {
+            data.extend_from_slice(&*public);
+        }
+        data.extend_from_slice(&u32_to_be(index));
+
+        let hash = hmac_sha512(&*self.chain_code, &data);
+        let (tweak, chain_code) = split_hash(&hash);
+        let tweak = try!(key::SecretKey::from_slice(context, &*tweak));
+        let mut secret = try!(key::SecretKey::from_slice(context, &*self.secret));
+        try!(secret.add_assign(context, &tweak));
+
+        let mut child_secret = Secret::default();
+        child_secret.copy_from_slice(&secret[0..32]);
+
+        Ok(ExtendedPrivateKey {
+            network: self.network,
+            depth: try!(self.depth.checked_add(1).ok_or(Error::InvalidDerivationPath)),
+            parent_fingerprint: fingerprint(&public),
+            child_number: index,
+            chain_code: chain_code,
+            secret: child_secret,
+        })
+    }
+
+    /// Derive descendant key, following the path from this key
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<Self, Error> {
+        path.0
+            .iter()
+            .fold(Ok(self.clone()), |key, index| key.and_then(|key| key.derive_child(*index)))
+    }
+
+    /// Compressed public key
+    pub fn public(&self) -> Result<H264, Error> {
+        let context = &SECP256K1;
+        let secret = try!(key::SecretKey::from_slice(context, &*self.secret));
+        let public = try!(key::PublicKey::from_secret_key(context, &secret));
+        let mut result = H264::default();
+        result.copy_from_slice(&public.serialize_vec(context, true)[0..33]);
+        Ok(result)
+    }
+
+    /// Extended public key, which is able to derive public keys of non-hardened children
+    pub fn extended_public(&self) -> Result<ExtendedPublicKey, Error> {
+        Ok(ExtendedPublicKey {
+            network: self.network,
+            depth: self.depth,
+            parent_fingerprint: self.parent_fingerprint.clone(),
+            child_number: self.child_number,
+            chain_code: self.chain_code.clone(),
+            public: try!(self.public()),
+        })
+    }
+
+    /// Key pair with compressed public key
+    pub fn key_pair(&self) -> Result<KeyPair, Error> {
+        KeyPair::from_private(Private {
+            network: self.network,
+            secret: self.secret.clone(),
+            compressed: true,
+        })
+    }
+}
+
+impl ExtendedPublicKey {
+    /// Derive non-hardened child key
+    pub fn derive_child(&self, index: u32) -> Result<Self, Error> {
+        if index >= HARDENED_INDEX {
+            return Err(Error::HardenedPublicDerivation);
+        }
+
+        let context = &SECP256K1;
+        let mut data = Vec::with_capacity(37);
+        data.extend_from_slice(&*self.public);
+        data.extend_from_slice(&u32_to_be(index));
+
+        let hash = hmac_sha512(&*self.chain_code, &data);
+        let (tweak, chain_code) = split_hash(&hash);
+        let tweak = try!(key::SecretKey::from_slice(context, &*tweak));
+        let mut public = try!(key::PublicKey::from_slice(context, &*self.public));
+        try!(public.add_exp_assign(context, &tweak));
+
+        let mut child_public = H264::default();
+        child_public.copy_from_slice(&public.serialize_vec(context, true)[0..33]);
+
+        Ok(ExtendedPublicKey {
+            network: self.network,
+            depth: try!(self.depth.checked_add(1).ok_or(Error::InvalidDerivationPath)),
+            parent_fingerprint: fingerprint(&self.public),
+            child_number: index,
+            chain_code: chain_code,
+            public: child_public,
+        })
+    }
+
+    /// Derive descendant key, following the path from this key
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<Self, Error> {
+        path.0
+            .iter()
+            .fold(Ok(self.clone()), |key, index| key.and_then(|key| key.derive_child(*index)))
+    }
+
+    /// Public key
+    pub fn public(&self) -> Public {
+        Public::Compressed(self.public.clone())
+    }
+}
+
+impl DisplayLayout for ExtendedPrivateKey {
+    type Target = Vec<u8>;
+
+    fn layout(&self) -> Self::Target {
+        let version = match self.network {
+            Network::Mainnet => MAINNET_PRIVATE_VERSION,
+            Network::Testnet => TESTNET_PRIVATE_VERSION,
+        };
+
+        let mut key_data = [0u8; 33];
+        key_data[1..].copy_from_slice(&*self.secret);
+        extended_key_layout(
+            version,
+            self.depth,
+            &self.parent_fingerprint,
+            self.child_number,
+            &self.chain_code,
+            &key_data,
+        )
+    }
+
+    fn from_layout(data: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let (version, depth, parent_fingerprint, child_number, chain_code, key_data) =
+            try!(parse_extended_key_layout(data));
+        let network = match version {
+            MAINNET_PRIVATE_VERSION => Network::Mainnet,
+            TESTNET_PRIVATE_VERSION => Network::Testnet,
+            _ => return Err(Error::InvalidExtendedKey),
+        };
+
+        if key_data[0] != 0 {
+            return Err(Error::InvalidExtendedKey);
+        }
+
+        let mut secret = Secret::default();
+        secret.copy_from_slice(&key_data[1..]);
+        try!(key::SecretKey::from_slice(&SECP256K1, &*secret));
+
+        Ok(ExtendedPrivateKey {
+            network: network,
+            depth: depth,
+            parent_fingerprint: parent_fingerprint,
+            child_number: child_number,
+            chain_code: chain_code,
+            secret: secret,
+        })
+    }
+}
+
+impl DisplayLayout for ExtendedPublicKey {
+    type Target = Vec<u8>;
+
+    fn layout(&self) -> Self::Target {
+        let version = match self.network {
+            Network::Mainnet => MAINNET_PUBLIC_VERSION,
+            Network::Testnet => TESTNET_PUBLIC_VERSION,
+        };
+
+        extended_key_layout(
+            version,
+            self.depth,
+            &self.parent_fingerprint,
+            self.child_number,
+            &self.chain_code,
+            &self.public,
+        )
+    }
+
+    fn from_layout(data: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let (version, depth, parent_fingerprint, child_number, chain_code, key_data) =
+            try!(parse_extended_key_layout(data));
+        let network = match version {
+            MAINNET_PUBLIC_VERSION => Network::Mainnet,
+            TESTNET_PUBLIC_VERSION => Network::Testnet,
+            _ => return Err(Error::InvalidExtendedKey),
+        };
+
+        let mut public = H264::default();
+        public.copy_from_slice(key_data);
+        try!(key::PublicKey::from_slice(&SECP256K1, &*public).map_err(|_| Error::InvalidExtendedKey));
+
+        Ok(ExtendedPublicKey {
+            network: network,
+            depth: depth,
+            parent_fingerprint: parent_fingerprint,
+            child_number: child_number,
+            chain_code: chain_code,
+            public: public,
+        })
+    }
+}
+
+/// Secret is never printed, so the key could be safely logged
+impl fmt::Debug for ExtendedPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ExtendedPrivateKey")
+            .field("network", &self.network)
+            .field("depth", &self.depth)
+            .field("parent_fingerprint", &self.parent_fingerprint)
+            .field("child_number", &self.child_number)
+            .field("chain_code", &self.chain_code)
+            .field("secret", &"<redacted>")
+            .finish()
+    }
+}
+
+impl fmt::Display for ExtendedPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.layout().to_base58().fmt(f)
+    }
+}
+
+impl fmt::Display for ExtendedPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.layout().to_base58().fmt(f)
+    }
+}
+
+impl FromStr for ExtendedPrivateKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let data = try!(s.from_base58().map_err(|_| Error::InvalidExtendedKey));
+        ExtendedPrivateKey::from_layout(&data)
+    }
+}
+
+impl FromStr for ExtendedPublicKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let data = try!(s.from_base58().map_err(|_| Error::InvalidExtendedKey));
+        ExtendedPublicKey::from_layout(&data)
+    }
+}
+
+/// Split HMAC-SHA512 result into key (left half) and chain code (right half)
+fn split_hash(hash: &H512) -> (H256, H256) {
+    let mut key = H256::default();
+    let mut chain_code = H256::default();
+    key.copy_from_slice(&hash[0..32]);
+    chain_code.copy_from_slice(&hash[32..64]);
+    (key, chain_code)
+}
+
+/// Key identifier prefix, identifying parent of the derived key
+fn fingerprint(public: &H264) -> H32 {
+    let mut result = H32::default();
+    result.copy_from_slice(&dhash160(&**public)[0..4]);
+    result
+}
+
+fn u32_to_be(value: u32) -> [u8; 4] {
+    [(value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8, value as u8]
+}
+
+fn u32_from_be(data: &[u8]) -> u32 {
+    (data[0] as u32) << 24 | (data[1] as u32) << 16 | (data[2] as u32) << 8 | data[3] as u32
+}
+
+fn extended_key_layout(
+    version: u32,
+    depth: u8,
+    parent_fingerprint: &H32,
+    child_number: u32,
+    chain_code: &H256,
+    key_data: &[u8],
+) -> Vec<u8> {
+    let mut result = Vec::with_capacity(EXTENDED_KEY_SIZE + 4);
+    result.extend_from_slice(&u32_to_be(version));
+    result.push(depth);
+    result.extend_from_slice(&**parent_fingerprint);
+    result.extend_from_slice(&u32_to_be(child_number));
+    result.extend_from_slice(&**chain_code);
+    result.extend_from_slice(key_data);
+    let cs = checksum(&result);
+    result.extend_from_slice(&*cs);
+    result
+}
+
+/// Parse serialized extended key into version, depth, parent fingerprint, child number, chain code and key data
+fn parse_extended_key_layout(data: &[u8]) -> Result<(u32, u8, H32, u32, H256, &[u8]), Error> {
+    if data.len() != EXTENDED_KEY_SIZE + 4 {
+        return Err(Error::InvalidExtendedKey);
+    }
+
+    let cs = checksum(&data[0..EXTENDED_KEY_SIZE]);
+    if &data[EXTENDED_KEY_SIZE..] != &*cs {
+        return Err(Error::InvalidChecksum);
+    }
+
+    let depth = data[4];
+    let mut parent_fingerprint = H32::default();
+    parent_fingerprint.copy_from_slice(&data[5..9]);
+    let child_number = u32_from_be(&data[9..13]);
+    if depth == 0 && (parent_fingerprint != H32::default() || child_number != 0) {
+        return Err(Error::InvalidExtendedKey);
+    }
+
+    let mut chain_code = H256::default();
+    chain_code.copy_from_slice(&data[13..45]);
+
+    Ok((
+        u32_from_be(&data[0..4]),
+        depth,
+        parent_fingerprint,
+        child_number,
+        chain_code,
+        &data[45..EXTENDED_KEY_SIZE],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DerivationPath, ExtendedPrivateKey, ExtendedPublicKey, HARDENED_INDEX};
+    use hex::FromHex;
+    use network::Network;
+    use Error;
+
+    /// Check derivation of the path from the seed against expected extended keys
+    fn check_vector(seed: &str, path: &str, xpub: &str, xprv: &str) {
+        let seed: Vec<u8> = seed.from_hex().unwrap();
+        let master = ExtendedPrivateKey::master(&seed, Network::Mainnet).unwrap();
+        let key = master.derive_path(&path.parse().unwrap()).unwrap();
+
+        assert_eq!(key.to_string(), xprv);
+        assert_eq!(key.extended_public().unwrap().to_string(), xpub);
+        assert_eq!(xprv.parse::<ExtendedPrivateKey>(), Ok(key.clone()));
+        assert_eq!(xpub.parse::<ExtendedPublicKey>(), Ok(key.extended_public().unwrap()));
+    }
+
+    /// https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#test-vector-1
+    #[test]
+    fn test_bip32_vector_1() {
+        let seed = "000102030405060708090a0b0c0d0e0f";
+        check_vector(seed, "m",
+            "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8",
+            "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi");
+        check_vector(seed, "m/0'",
+            "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw",
+            "xprv9uHRZZhk6KAJC1avXpDAp4MDc3sQKNxDiPvvkX8Br5ngLNv1TxvUxt4cV1rGL5hj6KCesnDYUhd7oWgT11eZG7XnxHrnYeSvkzY7d2bhkJ7");
+        check_vector(seed, "m/0'/1",
+            "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ",
+            "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs");
+        check_vector(seed, "m/0'/1/2'",
+            "xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5",
+            "xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM");
+        check_vector(seed, "m/0'/1/2'/2",
+            "xpub6FHa3pjLCk84BayeJxFW2SP4XRrFd1JYnxeLeU8EqN3vDfZmbqBqaGJAyiLjTAwm6ZLRQUMv1ZACTj37sR62cfN7fe5JnJ7dh8zL4fiyLHV",
+            "xprvA2JDeKCSNNZky6uBCviVfJSKyQ1mDYahRjijr5idH2WwLsEd4Hsb2Tyh8RfQMuPh7f7RtyzTtdrbdqqsunu5Mm3wDvUAKRHSC34sJ7in334");
+        check_vector(seed, "m/0'/1/2'/2/1000000000",
+            "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy",
+            "xprvA41z7zogVVwxVSgdKUHDy1SKmdb533PjDz7J6N6mV6uS3ze1ai8FHa8kmHScGpWmj4WggLyQjgPie1rFSruoUihUZREPSL39UNdE3BBDu76");
+    }
+
+    /// https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#test-vector-2
+    #[test]
+    fn test_bip32_vector_2() {
+        let seed = "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a29f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542";
+        check_vector(seed, "m",
+            "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB",
+            "xprv9s21ZrQH143K31xYSDQpPDxsXRTUcvj2iNHm5NUtrGiGG5e2DtALGdso3pGz6ssrdK4PFmM8NSpSBHNqPqm55Qn3LqFtT2emdEXVYsCzC2U");
+        check_vector(seed, "m/0",
+            "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH",
+            "xprv9vHkqa6EV4sPZHYqZznhT2NPtPCjKuDKGY38FBWLvgaDx45zo9WQRUT3dKYnjwih2yJD9mkrocEZXo1ex8G81dwSM1fwqWpWkeS3v86pgKt");
+        check_vector(seed, "m/0/2147483647'",
+            "xpub6ASAVgeehLbnwdqV6UKMHVzgqAG8Gr6riv3Fxxpj8ksbH9ebxaEyBLZ85ySDhKiLDBrQSARLq1uNRts8RuJiHjaDMBU4Zn9h8LZNnBC5y4a",
+            "xprv9wSp6B7kry3Vj9m1zSnLvN3xH8RdsPP1Mh7fAaR7aRLcQMKTR2vidYEeEg2mUCTAwCd6vnxVrcjfy2kRgVsFawNzmjuHc2YmYRmagcEPdU9");
+        check_vector(seed, "m/0/2147483647'/1",
+            "xpub6DF8uhdarytz3FWdA8TvFSvvAh8dP3283MY7p2V4SeE2wyWmG5mg5EwVvmdMVCQcoNJxGoWaU9DCWh89LojfZ537wTfunKau47EL2dhHKon",
+            "xprv9zFnWC6h2cLgpmSA46vutJzBcfJ8yaJGg8cX1e5StJh45BBciYTRXSd25UEPVuesF9yog62tGAQtHjXajPPdbRCHuWS6T8XA2ECKADdw4Ef");
+        check_vector(seed, "m/0/2147483647'/1/2147483646'",
+            "xpub6ERApfZwUNrhLCkDtcHTcxd75RbzS1ed54G1LkBUHQVHQKqhMkhgbmJbZRkrgZw4koxb5JaHWkY4ALHY2grBGRjaDMzQLcgJvLJuZZvRcEL",
+            "xprvA1RpRA33e1JQ7ifknakTFpgNXPmW2YvmhqLQYMmrj4xJXXWYpDPS3xz7iAxn8L39njGVyuoseXzU6rcxFLJ8HFsTjSyQbLYnMpCqE2VbFWc");
+        check_vector(seed, "m/0/2147483647'/1/2147483646'/2",
+            "xpub6FnCn6nSzZAw5Tw7cgR9bi15UV96gLZhjDstkXXxvCLsUXBGXPdSnLFbdpq8p9HmGsApME5hQTZ3emM2rnY5agb9rXpVGyy3bdW6EEgAtqt",
+            "xprvA2nrNbFZABcdryreWet9Ea4LvTJcGsqrMzxHx98MMrotbir7yrKCEXw7nadnHM8Dq38EGfSh6dqA9QWTyefMLEcBYJUuekgW4BYPJcr9E7j");
+    }
+
+    /// https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#test-vector-3
+    #[test]
+    fn test_bip32_vector_3_retains_leading_zeros() {
+        let seed = "4b381541583be4423346c643850da4b320e46a87ae3d2a4e6da11eba819cd4acba45d239319ac14f863b8d5ab5a0d0c64d2e8a1e7d1457df2e5a3c51c73235be";
+        check_vector(seed, "m",
+            "xpub661MyMwAqRbcEZVB4dScxMAdx6d4nFc9nvyvH3v4gJL378CSRZiYmhRoP7mBy6gSPSCYk6SzXPTf3ND1cZAceL7SfJ1Z3GC8vBgp2epUt13",
+            "xprv9s21ZrQH143K25QhxbucbDDuQ4naNntJRi4KUfWT7xo4EKsHt2QJDu7KXp1A3u7Bi1j8ph3EGsZ9Xvz9dGuVrtHHs7pXeTzjuxBrCmmhgC6");
+        check_vector(seed, "m/0'",
+            "xpub68NZiKmJWnxxS6aaHmn81bvJeTESw724CRDs6HbuccFQN9Ku14VQrADWgqbhhTHBaohPX4CjNLf9fq9MYo6oDaPPLPxSb7gwQN3ih19Zm4Y",
+            "xprv9uPDJpEQgRQfDcW7BkF7eTya6RPxXeJCqCJGHuCJ4GiRVLzkTXBAJMu2qaMWPrS7AANYqdq6vcBcBUdJCVVFceUvJFjaPdGZ2y9WACViL4L");
+    }
+
+    #[test]
+    fn test_public_derivation_matches_private_derivation() {
+        let xprv: ExtendedPrivateKey = "xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM".parse().unwrap();
+        let xpub = xprv.extended_public().unwrap();
+        let path: DerivationPath = "m/2/1000000000".parse().unwrap();
+
+        assert_eq!(
+            xpub.derive_path(&path).unwrap(),
+            xprv.derive_path(&path).unwrap().extended_public().unwrap()
+        );
+        assert_eq!(xpub.derive_child(HARDENED_INDEX), Err(Error::HardenedPublicDerivation));
+    }
+
+    #[test]
+    fn test_key_pair_of_extended_key() {
+        let xprv: ExtendedPrivateKey = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi".parse().unwrap();
+        let key_pair = xprv.key_pair().unwrap();
+        assert!(key_pair.private().compressed);
+        assert_eq!(key_pair.public(), &xprv.extended_public().unwrap().public());
+    }
+
+    #[test]
+    fn test_extended_key_from_invalid_str() {
+        // checksum of the first vector key with the last character changed
+        assert_eq!(
+            "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHj".parse::<ExtendedPrivateKey>(),
+            Err(Error::InvalidChecksum)
+        );
+        // private key can not be parsed as public
+        assert_eq!(
+            "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi".parse::<ExtendedPublicKey>(),
+            Err(Error::InvalidExtendedKey)
+        );
+    }
+
+    #[test]
+    fn test_derivation_path() {
+        let path: DerivationPath = "m/44'/1h/0'/0/5".parse().unwrap();
+        assert_eq!(path, DerivationPath(vec![44 | HARDENED_INDEX, 1 | HARDENED_INDEX, HARDENED_INDEX, 0, 5]));
+        assert_eq!(path.to_string(), "m/44'/1'/0'/0/5");
+        assert_eq!(DerivationPath::bip44_account(Network::Testnet, 0).to_string(), "m/44'/1'/0'");
+        assert_eq!("m".parse(), Ok(DerivationPath::default()));
+        assert_eq!("44'/0".parse::<DerivationPath>(), Err(Error::InvalidDerivationPath));
+        assert_eq!("m/2147483648".parse::<DerivationPath>(), Err(Error::InvalidDerivationPath));
+    }
+
+    #[test]
+    fn test_extended_private_key_debug_hides_secret() {
+        let seed: Vec<u8> = "000102030405060708090a0b0c0d0e0f".from_hex().unwrap();
+        let master = ExtendedPrivateKey::master(&seed, Network::Mainnet).unwrap();
+        let debug = format!("{:?}", master);
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains(&format!("{}", master.secret)));
+    }
+}
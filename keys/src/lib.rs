@@ -10,10 +10,13 @@ extern crate primitives;
 extern crate secp256k1;
 
 mod address;
+mod bech32;
 mod display;
 mod error;
+mod extended;
 pub mod generator;
 mod keypair;
+mod mnemonic;
 mod network;
 mod private;
 mod public;
@@ -21,10 +24,12 @@ mod signature;
 
 pub use primitives::{bytes, hash};
 
-pub use address::{Address, Type};
+pub use address::{Address, AddressPayload, Type};
 pub use display::DisplayLayout;
 pub use error::Error;
+pub use extended::{DerivationPath, ExtendedPrivateKey, ExtendedPublicKey, HARDENED_INDEX};
 pub use keypair::KeyPair;
+pub use mnemonic::Mnemonic;
 pub use network::Network;
 pub use private::Private;
 pub use public::Public;
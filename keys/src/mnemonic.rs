@@ -0,0 +1,190 @@
+//! Mnemonic code for generating deterministic keys
+//!
+//! https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki
+
+use crypto::{pbkdf2_hmac_sha512, sha256};
+use hash::H512;
+use rand::os::OsRng;
+use rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+use Error;
+
+/// Number of PBKDF2 rounds, used to generate seed
+const PBKDF2_ROUNDS: u32 = 2048;
+/// Number of bits, encoded by the single word
+const BITS_PER_WORD: usize = 11;
+
+lazy_static! {
+    /// English BIP39 wordlist
+    static ref WORDS: Vec<&'static str> = include_str!("bip39_english.txt").split_whitespace().collect();
+}
+
+/// Mnemonic sentence of english BIP39 words
+#[derive(Debug, PartialEq, Clone)]
+pub struct Mnemonic {
+    words: Vec<&'static str>,
+}
+
+impl Mnemonic {
+    /// Encode entropy of 16, 20, 24, 28 or 32 bytes
+    pub fn from_entropy(entropy: &[u8]) -> Result<Self, Error> {
+        if entropy.len() < 16 || entropy.len() > 32 || entropy.len() % 4 != 0 {
+            return Err(Error::InvalidMnemonic);
+        }
+
+        // entropy is followed by (entropy length / 32) bits of its hash
+        let checksum = sha256(entropy);
+        let checksum_bits = entropy.len() / 4;
+        let bits: Vec<bool> = bytes_to_bits(entropy)
+            .into_iter()
+            .chain(bytes_to_bits(&checksum[0..1]).into_iter().take(checksum_bits))
+            .collect();
+
+        let words = bits
+            .chunks(BITS_PER_WORD)
+            .map(|chunk| WORDS[chunk.iter().fold(0, |acc, &bit| (acc << 1) | bit as usize)])
+            .collect();
+
+        Ok(Mnemonic { words: words })
+    }
+
+    /// Generate random mnemonic of 12, 15, 18, 21 or 24 words
+    pub fn generate(word_count: usize) -> Result<Self, Error> {
+        if word_count % 3 != 0 {
+            return Err(Error::InvalidMnemonic);
+        }
+
+        let mut rng = try!(OsRng::new().map_err(|_| Error::FailedKeyGeneration));
+        let mut entropy = vec![0u8; word_count / 3 * 4];
+        rng.fill_bytes(&mut entropy);
+        Mnemonic::from_entropy(&entropy)
+    }
+
+    /// Entropy, encoded by this mnemonic
+    pub fn entropy(&self) -> Vec<u8> {
+        let bits: Vec<bool> = self
+            .words
+            .iter()
+            .flat_map(|word| {
+                let index = WORDS.binary_search(word).expect("mnemonic consists of wordlist words; qed");
+                (0..BITS_PER_WORD).rev().map(move |bit| index & (1 << bit) != 0)
+            })
+            .collect();
+
+        let entropy_bits = bits.len() * 32 / 33;
+        bits[0..entropy_bits]
+            .chunks(8)
+            .map(|byte| byte.iter().fold(0u8, |acc, &bit| (acc << 1) | bit as u8))
+            .collect()
+    }
+
+    /// Seed of hierarchical deterministic keys. Passphrase is not normalized,
+    /// so seeds of non-ASCII passphrases are incompatible with other implementations.
+    pub fn to_seed(&self, passphrase: &str) -> H512 {
+        let salt = format!("mnemonic{}", passphrase);
+        pbkdf2_hmac_sha512(self.to_string().as_bytes(), salt.as_bytes(), PBKDF2_ROUNDS)
+    }
+}
+
+impl fmt::Display for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.words.join(" ").fmt(f)
+    }
+}
+
+impl FromStr for Mnemonic {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let words = try!(s
+            .split_whitespace()
+            .map(|word| WORDS.binary_search(&word).map(|index| WORDS[index]).map_err(|_| Error::InvalidMnemonic))
+            .collect::<Result<Vec<_>, _>>());
+
+        if words.len() < 12 || words.len() > 24 || words.len() % 3 != 0 {
+            return Err(Error::InvalidMnemonic);
+        }
+
+        // re-encode entropy to verify checksum
+        let mnemonic = Mnemonic { words: words };
+        if try!(Mnemonic::from_entropy(&mnemonic.entropy())) != mnemonic {
+            return Err(Error::InvalidMnemonic);
+        }
+
+        Ok(mnemonic)
+    }
+}
+
+fn bytes_to_bits(bytes: &[u8]) -> Vec<bool> {
+    bytes
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |bit| byte & (1 << bit) != 0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mnemonic;
+    use hex::FromHex;
+    use Error;
+
+    /// https://github.com/trezor/python-mnemonic/blob/master/vectors.json
+    fn check_vector(entropy: &str, phrase: &str, seed: &'static str) {
+        let entropy: Vec<u8> = entropy.from_hex().unwrap();
+        let mnemonic = Mnemonic::from_entropy(&entropy).unwrap();
+
+        assert_eq!(mnemonic.to_string(), phrase);
+        assert_eq!(phrase.parse(), Ok(mnemonic.clone()));
+        assert_eq!(mnemonic.entropy(), entropy);
+        assert_eq!(mnemonic.to_seed("TREZOR"), seed.into());
+    }
+
+    #[test]
+    fn test_bip39_vectors() {
+        check_vector(
+            "00000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+        );
+        check_vector(
+            "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+            "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+        );
+        check_vector(
+            "9e885d952ad362caeb4efe34a8e91bd2",
+            "ozone drill grab fiber curtain grace pudding thank cruise elder eight picnic",
+            "274ddc525802f7c828d8ef7ddbcdc5304e87ac3535913611fbbfa986d0c9e5476c91689f9c8a54fd55bd38606aa6a8595ad213d4c9c9f9aca3fb217069a41028",
+        );
+        check_vector(
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+            "dd48c104698c30cfe2b6142103248622fb7bb0ff692eebb00089b32d22484e1613912f0a5b694407be899ffd31ed3992c456cdf60f5d4564b8ba3f05a69890ad",
+        );
+    }
+
+    #[test]
+    fn test_mnemonic_from_invalid_str() {
+        // invalid checksum
+        assert_eq!(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon".parse::<Mnemonic>(),
+            Err(Error::InvalidMnemonic)
+        );
+        // unknown word
+        assert_eq!(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon bitcoin".parse::<Mnemonic>(),
+            Err(Error::InvalidMnemonic)
+        );
+        // too short
+        assert_eq!("abandon about".parse::<Mnemonic>(), Err(Error::InvalidMnemonic));
+    }
+
+    #[test]
+    fn test_generate_mnemonic() {
+        let mnemonic = Mnemonic::generate(24).unwrap();
+        assert_eq!(mnemonic.entropy().len(), 32);
+        assert_eq!(mnemonic.to_string().parse(), Ok(mnemonic));
+        assert_eq!(Mnemonic::generate(13), Err(Error::InvalidMnemonic));
+    }
+}
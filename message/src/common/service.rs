@@ -68,6 +68,24 @@ impl Services {
         self
     }
 
+    pub fn compact_filters(&self) -> bool {
+        self.bit_at(6)
+    }
+
+    pub fn with_compact_filters(mut self, v: bool) -> Self {
+        self.set_bit(6, v);
+        self
+    }
+
+    pub fn network_limited(&self) -> bool {
+        self.bit_at(10)
+    }
+
+    pub fn with_network_limited(mut self, v: bool) -> Self {
+        self.set_bit(10, v);
+        self
+    }
+
     pub fn includes(&self, other: &Self) -> bool {
         self.0 & other.0 == other.0
     }
@@ -101,4 +119,25 @@ mod test {
         assert!(s1.includes(&s2));
         assert!(!s2.includes(&s1));
     }
+
+    #[test]
+    fn test_services_network_limited() {
+        let pruned = Services::default()
+            .with_network(false)
+            .with_network_limited(true);
+
+        assert!(!pruned.network());
+        assert!(pruned.network_limited());
+        assert_eq!(u64::from(pruned), 1 << 10);
+    }
+
+    #[test]
+    fn test_services_compact_filters() {
+        let services = Services::default()
+            .with_network(true)
+            .with_compact_filters(true);
+
+        assert!(services.compact_filters());
+        assert_eq!(u64::from(services), 1 | 1 << 6);
+    }
 }
@@ -0,0 +1,45 @@
+use hash::H256;
+use ser::{Reader, Stream};
+use std::io;
+use {MessageResult, Payload};
+
+pub const CFCHECKPT_INTERVAL: u32 = 1_000;
+
+/// Compact filter headers at every `CFCHECKPT_INTERVAL` block (BIP157)
+#[derive(Debug, PartialEq)]
+pub struct CFCheckpt {
+    pub filter_type: u8,
+    pub stop_hash: H256,
+    pub filter_headers: Vec<H256>,
+}
+
+impl Payload for CFCheckpt {
+    fn version() -> u32 {
+        0
+    }
+
+    fn command() -> &'static str {
+        "cfcheckpt"
+    }
+
+    fn deserialize_payload<T>(reader: &mut Reader<T>, _version: u32) -> MessageResult<Self>
+    where
+        T: io::Read,
+    {
+        let cfcheckpt = CFCheckpt {
+            filter_type: try!(reader.read()),
+            stop_hash: try!(reader.read()),
+            filter_headers: try!(reader.read_list()),
+        };
+
+        Ok(cfcheckpt)
+    }
+
+    fn serialize_payload(&self, stream: &mut Stream, _version: u32) -> MessageResult<()> {
+        stream
+            .append(&self.filter_type)
+            .append(&self.stop_hash)
+            .append_list(&self.filter_headers);
+        Ok(())
+    }
+}
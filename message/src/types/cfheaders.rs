@@ -0,0 +1,46 @@
+use hash::H256;
+use ser::{Reader, Stream};
+use std::io;
+use {MessageResult, Payload};
+
+/// Compact filter hashes of the range of blocks (BIP157)
+#[derive(Debug, PartialEq)]
+pub struct CFHeaders {
+    pub filter_type: u8,
+    pub stop_hash: H256,
+    pub previous_filter_header: H256,
+    pub filter_hashes: Vec<H256>,
+}
+
+impl Payload for CFHeaders {
+    fn version() -> u32 {
+        0
+    }
+
+    fn command() -> &'static str {
+        "cfheaders"
+    }
+
+    fn deserialize_payload<T>(reader: &mut Reader<T>, _version: u32) -> MessageResult<Self>
+    where
+        T: io::Read,
+    {
+        let cfheaders = CFHeaders {
+            filter_type: try!(reader.read()),
+            stop_hash: try!(reader.read()),
+            previous_filter_header: try!(reader.read()),
+            filter_hashes: try!(reader.read_list_max(2000)),
+        };
+
+        Ok(cfheaders)
+    }
+
+    fn serialize_payload(&self, stream: &mut Stream, _version: u32) -> MessageResult<()> {
+        stream
+            .append(&self.filter_type)
+            .append(&self.stop_hash)
+            .append(&self.previous_filter_header)
+            .append_list(&self.filter_hashes);
+        Ok(())
+    }
+}
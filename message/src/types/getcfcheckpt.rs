@@ -0,0 +1,38 @@
+use hash::H256;
+use ser::{Reader, Stream};
+use std::io;
+use {MessageResult, Payload};
+
+/// Requests evenly spaced compact filter headers (BIP157)
+#[derive(Debug, PartialEq)]
+pub struct GetCFCheckpt {
+    pub filter_type: u8,
+    pub stop_hash: H256,
+}
+
+impl Payload for GetCFCheckpt {
+    fn version() -> u32 {
+        0
+    }
+
+    fn command() -> &'static str {
+        "getcfcheckpt"
+    }
+
+    fn deserialize_payload<T>(reader: &mut Reader<T>, _version: u32) -> MessageResult<Self>
+    where
+        T: io::Read,
+    {
+        let get_cfcheckpt = GetCFCheckpt {
+            filter_type: try!(reader.read()),
+            stop_hash: try!(reader.read()),
+        };
+
+        Ok(get_cfcheckpt)
+    }
+
+    fn serialize_payload(&self, stream: &mut Stream, _version: u32) -> MessageResult<()> {
+        stream.append(&self.filter_type).append(&self.stop_hash);
+        Ok(())
+    }
+}
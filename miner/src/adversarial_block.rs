@@ -0,0 +1,632 @@
+use block_assembler::BlockTemplate;
+use chain::{
+    merkle_root, BlockHeader, IndexedBlock, IndexedBlockHeader, IndexedTransaction, OutPoint,
+    Transaction, TransactionOutput,
+};
+use cpu_miner::{find_solution, CoinbaseTransactionBuilder, Solution};
+use keys::KeyPair;
+use primitives::bigint::{Uint, U256};
+use primitives::bytes::Bytes;
+use script::{
+    Builder, Script, SignatureVersion, TransactionInputSigner, UnsignedTransactionInput,
+    MAX_SCRIPT_ELEMENT_SIZE, MAX_SCRIPT_SIZE,
+};
+use std::collections::HashSet;
+use storage::SharedStore;
+use verification::constants::COINBASE_MATURITY;
+
+/// How many canon chain blocks are scanned when looking for outputs to spend
+const MAX_SCANNED_BLOCKS: u32 = 1000;
+/// Distance (in blocks) between the block and the lock time of non-final transaction
+const NON_FINAL_LOCK_TIME_DISTANCE: u32 = 1000;
+/// Faulty transactions sign all inputs and outputs
+const SIGHASH_ALL: u32 = 1;
+
+/// Fault that could be injected into the generated block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockFault {
+    /// Transaction spends output, which is already spent by the canon chain transaction.
+    ChainDoubleSpend,
+    /// Transaction spends the same output as memory pool transaction, which is also included
+    /// into the block.
+    MempoolDoubleSpend,
+    /// Transaction signature is made by the right key, but over the different transaction.
+    BadSignature,
+    /// Transaction creates more value than it spends.
+    Overspend,
+    /// Transaction spends coinbase output of the most recent block.
+    /// It is only rejected if `COINBASE_MATURITY` is not zero.
+    ImmatureCoinbaseSpend,
+    /// Transaction lock time is far in the future.
+    NonFinalLocktime,
+    /// Transaction signature script is larger than `MAX_SCRIPT_SIZE`.
+    OversizedScript,
+    /// Valid transaction is included into the block twice.
+    DuplicateTxid,
+}
+
+/// Fault that has been injected into the generated block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InjectedFault {
+    /// Kind of the fault.
+    pub fault: BlockFault,
+    /// Transaction, carrying the fault.
+    pub transaction: IndexedTransaction,
+}
+
+/// Adversarial block generation error.
+#[derive(Debug, PartialEq)]
+pub enum AdversarialBlockError {
+    /// There are no transactions or outputs, suitable for injecting the fault.
+    NoSuitableInput(BlockFault),
+    /// Proof of work solution has not been found.
+    NoNonceSolution,
+}
+
+/// Generates blocks with chosen mix of invalid transactions.
+///
+/// Faults are appended to the block template (usually built by `BlockAssembler`)
+/// in given order and the block is mined using `find_solution`.
+/// Faulty transactions spend P2PKH outputs of the generator key and are properly signed,
+/// so that the injected fault is the only defect of every transaction.
+pub struct AdversarialBlockGenerator<'a> {
+    /// Canon chain is used to find outputs to spend
+    store: &'a SharedStore,
+    /// Key, which outputs are spent by faulty transactions
+    keypair: &'a KeyPair,
+    /// P2PKH script of the generator key
+    script_pubkey: Bytes,
+    /// Outputs, which are already spent by transactions of the block
+    used_outputs: HashSet<OutPoint>,
+}
+
+impl<'a> AdversarialBlockGenerator<'a> {
+    pub fn new(store: &'a SharedStore, keypair: &'a KeyPair) -> Self {
+        AdversarialBlockGenerator {
+            store: store,
+            keypair: keypair,
+            script_pubkey: Builder::build_p2pkh(&keypair.public().address_hash()).into(),
+            used_outputs: HashSet::new(),
+        }
+    }
+
+    /// Injects faults into the block template and mines the block.
+    pub fn generate<T>(
+        &mut self,
+        mut template: BlockTemplate,
+        coinbase_transaction_builder: T,
+        faults: &[BlockFault],
+    ) -> Result<(IndexedBlock, Vec<InjectedFault>), AdversarialBlockError>
+    where
+        T: CoinbaseTransactionBuilder,
+    {
+        self.used_outputs = template
+            .transactions
+            .iter()
+            .flat_map(|tx| {
+                tx.raw
+                    .inputs
+                    .iter()
+                    .map(|input| input.previous_output.clone())
+            })
+            .collect();
+
+        // every memory pool transaction is only double spent once
+        let mut double_spent_transactions = HashSet::new();
+        let mut injected = Vec::with_capacity(faults.len());
+        for fault in faults {
+            let transaction: IndexedTransaction = match *fault {
+                BlockFault::MempoolDoubleSpend => {
+                    let (outpoint, output) = self
+                        .mempool_spent_output(&template, &mut double_spent_transactions)
+                        .ok_or(AdversarialBlockError::NoSuitableInput(*fault))?;
+                    let value = output.value;
+                    self.signed_transaction(outpoint, value, value, 0, false)
+                        .into()
+                }
+                BlockFault::DuplicateTxid => {
+                    // the first copy is valid, the second one is the fault
+                    let transaction: IndexedTransaction =
+                        self.faulty_transaction(&template, *fault)?.into();
+                    template.transactions.push(transaction.clone());
+                    transaction
+                }
+                _ => self.faulty_transaction(&template, *fault)?.into(),
+            };
+
+            template.transactions.push(transaction.clone());
+            injected.push(InjectedFault {
+                fault: *fault,
+                transaction: transaction,
+            });
+        }
+
+        let solution = find_solution(&template, coinbase_transaction_builder, U256::max_value())
+            .ok_or(AdversarialBlockError::NoNonceSolution)?;
+        Ok((block_from_solution(template, solution), injected))
+    }
+
+    /// Creates transaction, carrying given fault
+    fn faulty_transaction(
+        &mut self,
+        template: &BlockTemplate,
+        fault: BlockFault,
+    ) -> Result<Transaction, AdversarialBlockError> {
+        let (outpoint, output) = match fault {
+            BlockFault::ChainDoubleSpend => self.spent_output(),
+            BlockFault::ImmatureCoinbaseSpend => self.unspent_output(template.height, true),
+            _ => self.unspent_output(template.height, false),
+        }
+        .ok_or(AdversarialBlockError::NoSuitableInput(fault))?;
+        self.used_outputs.insert(outpoint.clone());
+
+        let spent_value = output.value;
+        let transaction = match fault {
+            BlockFault::Overspend => {
+                self.signed_transaction(outpoint, spent_value, spent_value + 1, 0, false)
+            }
+            BlockFault::NonFinalLocktime => {
+                let lock_time = template.height + NON_FINAL_LOCK_TIME_DISTANCE;
+                self.signed_transaction(outpoint, spent_value, spent_value, lock_time, false)
+            }
+            BlockFault::BadSignature => {
+                self.signed_transaction(outpoint, spent_value, spent_value, 0, true)
+            }
+            BlockFault::OversizedScript => {
+                let mut transaction =
+                    self.signed_transaction(outpoint, spent_value, spent_value, 0, false);
+                // extra pushes are left on the stack, which is not checked after P2PKH evaluation
+                let mut script_sig = Builder::default();
+                for _ in 0..MAX_SCRIPT_SIZE / MAX_SCRIPT_ELEMENT_SIZE + 1 {
+                    script_sig = script_sig.push_data(&[0u8; MAX_SCRIPT_ELEMENT_SIZE]);
+                }
+                let mut script_sig = script_sig.into_bytes().take();
+                script_sig.extend_from_slice(&transaction.inputs[0].script_sig);
+                transaction.inputs[0].script_sig = script_sig.into();
+                transaction
+            }
+            _ => self.signed_transaction(outpoint, spent_value, spent_value, 0, false),
+        };
+        Ok(transaction)
+    }
+
+    /// Creates transaction, spending output of the generator key back to the same key.
+    /// If `bad_signature` is true, the signature is made over the transaction with different lock time.
+    fn signed_transaction(
+        &self,
+        outpoint: OutPoint,
+        spent_value: u64,
+        value: u64,
+        lock_time: u32,
+        bad_signature: bool,
+    ) -> Transaction {
+        let signer = |lock_time: u32| TransactionInputSigner {
+            version: 1,
+            inputs: vec![UnsignedTransactionInput {
+                previous_output: outpoint.clone(),
+                sequence: 0,
+            }],
+            outputs: vec![TransactionOutput {
+                value: value,
+                script_pubkey: self.script_pubkey.clone(),
+            }],
+            lock_time: lock_time,
+        };
+        let signed_lock_time = if bad_signature {
+            lock_time + 1
+        } else {
+            lock_time
+        };
+        let script_pubkey: Script = self.script_pubkey.clone().into();
+        let input = signer(signed_lock_time).signed_input(
+            self.keypair,
+            0,
+            spent_value,
+            &script_pubkey,
+            SignatureVersion::Base,
+            SIGHASH_ALL,
+        );
+
+        let signer = signer(lock_time);
+        Transaction {
+            version: signer.version,
+            inputs: vec![input],
+            outputs: signer.outputs,
+            lock_time: lock_time,
+        }
+    }
+
+    /// Finds output of the generator key, which is spent by the memory pool transaction of the template
+    fn mempool_spent_output(
+        &self,
+        template: &BlockTemplate,
+        double_spent_transactions: &mut HashSet<usize>,
+    ) -> Option<(OutPoint, TransactionOutput)> {
+        for (index, tx) in template.transactions.iter().enumerate() {
+            if double_spent_transactions.contains(&index) {
+                continue;
+            }
+
+            for input in &tx.raw.inputs {
+                let output = match self.store.transaction_output(&input.previous_output, 0) {
+                    Some(ref output) if output.script_pubkey != self.script_pubkey => continue,
+                    Some(output) => output,
+                    None => continue,
+                };
+
+                double_spent_transactions.insert(index);
+                return Some((input.previous_output.clone(), output));
+            }
+        }
+
+        None
+    }
+
+    /// Finds output of the generator key, which has already been spent by canon chain transaction
+    fn spent_output(&self) -> Option<(OutPoint, TransactionOutput)> {
+        let best_block = self.store.best_block();
+        for number in scanned_blocks(best_block.number) {
+            let block = match self.store.block(number.into()) {
+                Some(block) => block,
+                None => continue,
+            };
+
+            for tx in block.transactions.iter().skip(1) {
+                for input in &tx.inputs {
+                    let outpoint = &input.previous_output;
+                    if self.used_outputs.contains(outpoint) || !self.store.is_spent(outpoint) {
+                        continue;
+                    }
+
+                    match self.store.transaction_output(outpoint, 0) {
+                        Some(output) if output.script_pubkey == self.script_pubkey => {
+                            return Some((outpoint.clone(), output))
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Finds unspent canon chain output of the generator key.
+    /// If `immature_coinbase` is true, only looks for coinbase outputs of the most recent blocks.
+    /// Otherwise immature coinbase outputs are ignored.
+    fn unspent_output(
+        &self,
+        height: u32,
+        immature_coinbase: bool,
+    ) -> Option<(OutPoint, TransactionOutput)> {
+        let best_block = self.store.best_block();
+        for number in scanned_blocks(best_block.number) {
+            let block = match self.store.block(number.into()) {
+                Some(block) => block,
+                None => continue,
+            };
+
+            let is_mature = height >= number + COINBASE_MATURITY;
+            for (tx_index, tx) in block.transactions.iter().enumerate() {
+                let is_coinbase = tx_index == 0;
+                let is_suitable = if immature_coinbase {
+                    is_coinbase
+                } else {
+                    !is_coinbase || is_mature
+                };
+                if !is_suitable {
+                    continue;
+                }
+
+                let hash = tx.hash();
+                for (output_index, output) in tx.outputs.iter().enumerate() {
+                    let outpoint = OutPoint {
+                        hash: hash.clone(),
+                        index: output_index as u32,
+                    };
+                    if output.value == 0
+                        || output.script_pubkey != self.script_pubkey
+                        || self.used_outputs.contains(&outpoint)
+                        || self.store.is_spent(&outpoint)
+                        || self.store.transaction_output(&outpoint, 0).is_none()
+                    {
+                        continue;
+                    }
+
+                    return Some((outpoint, output.clone()));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Numbers of canon blocks to scan, starting from the best block
+fn scanned_blocks(best_block_number: u32) -> ::std::iter::Rev<::std::ops::Range<u32>> {
+    let first = best_block_number.saturating_sub(MAX_SCANNED_BLOCKS - 1);
+    (first..best_block_number + 1).rev()
+}
+
+/// Builds block from the template and found solution
+fn block_from_solution(template: BlockTemplate, solution: Solution) -> IndexedBlock {
+    let coinbase: IndexedTransaction = solution.coinbase_transaction.into();
+    let merkle_root_hash = {
+        let mut merkle_tree = vec![&coinbase.hash];
+        merkle_tree.extend(template.transactions.iter().map(|tx| &tx.hash));
+        merkle_root(&merkle_tree)
+    };
+
+    let header = BlockHeader {
+        version: template.version,
+        previous_header_hash: template.previous_header_hash,
+        merkle_root_hash: merkle_root_hash,
+        time: solution.time,
+        bits: template.bits,
+        nonce: solution.nonce,
+    };
+
+    let mut transactions = Vec::with_capacity(template.transactions.len() + 1);
+    transactions.push(coinbase);
+    transactions.extend(template.transactions);
+    IndexedBlock::new(IndexedBlockHeader::from(header), transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_data;
+
+    use super::{AdversarialBlockError, AdversarialBlockGenerator, BlockFault};
+    use block_assembler::BlockTemplate;
+    use chain::{IndexedBlock, IndexedTransaction, OutPoint, Transaction};
+    use cpu_miner::Sh_CoinbaseTransactionBuilder;
+    use db::BlockChainDatabase;
+    use keys::{KeyPair, Network, Private};
+    use primitives::bigint::{Uint, U256};
+    use primitives::bytes::Bytes;
+    use script::{
+        verify_script, Builder, Error, SignatureVersion, TransactionSignatureChecker,
+        VerificationFlags, MAX_SCRIPT_SIZE,
+    };
+    use std::sync::Arc;
+    use storage::SharedStore;
+
+    fn keypair() -> KeyPair {
+        KeyPair::from_private(Private {
+            network: Network::Mainnet,
+            secret: 1.into(),
+            compressed: true,
+        })
+        .unwrap()
+    }
+
+    // b0: coinbase, tx0
+    // b1: coinbase, tx1 (spends tx0)
+    // all outputs are paid to the generator key
+    fn prepare_store(keypair: &KeyPair) -> (SharedStore, IndexedBlock, IndexedBlock) {
+        let script: Bytes = Builder::build_p2pkh(&keypair.public().address_hash()).into();
+        let b0: IndexedBlock = test_data::block_builder()
+            .transaction()
+            .coinbase()
+            .output()
+            .value(10)
+            .script_pubkey_bytes(script.clone())
+            .build()
+            .build()
+            .transaction()
+            .output()
+            .value(50)
+            .script_pubkey_bytes(script.clone())
+            .build()
+            .output()
+            .value(60)
+            .script_pubkey_bytes(script.clone())
+            .build()
+            .output()
+            .value(70)
+            .script_pubkey_bytes(script.clone())
+            .build()
+            .build()
+            .merkled_header()
+            .build()
+            .build()
+            .into();
+        let b1: IndexedBlock = test_data::block_builder()
+            .transaction()
+            .coinbase()
+            .output()
+            .value(20)
+            .script_pubkey_bytes(script.clone())
+            .build()
+            .build()
+            .transaction()
+            .input()
+            .hash(b0.transactions[1].hash.clone())
+            .build()
+            .output()
+            .value(40)
+            .script_pubkey_bytes(script)
+            .build()
+            .build()
+            .merkled_header()
+            .parent(b0.hash().clone())
+            .build()
+            .build()
+            .into();
+
+        let store: SharedStore = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            b0.clone(),
+            b1.clone(),
+        ]));
+        (store, b0, b1)
+    }
+
+    fn template(b1: &IndexedBlock, transactions: Vec<IndexedTransaction>) -> BlockTemplate {
+        BlockTemplate {
+            version: 0,
+            previous_header_hash: b1.hash().clone(),
+            time: 0,
+            bits: U256::max_value().into(),
+            height: 2,
+            transactions: transactions,
+            coinbase_value: 10,
+            size_limit: 1_000_000,
+            sigop_limit: 20_000,
+        }
+    }
+
+    /// Verifies the only input of the transaction
+    fn verify(store: &SharedStore, transaction: &Transaction) -> Result<(), Error> {
+        let input = &transaction.inputs[0];
+        let spent_output = store.transaction_output(&input.previous_output, 0).unwrap();
+        let checker = TransactionSignatureChecker {
+            signer: transaction.clone().into(),
+            input_index: 0,
+            input_amount: spent_output.value,
+        };
+        verify_script(
+            &input.script_sig.clone().into(),
+            &spent_output.script_pubkey.into(),
+            &input.script_witness,
+            &VerificationFlags::default().verify_p2sh(true),
+            &checker,
+            SignatureVersion::Base,
+        )
+    }
+
+    #[test]
+    fn adversarial_block_contains_requested_faults() {
+        let keypair = keypair();
+        let (store, b0, b1) = prepare_store(&keypair);
+        let faults = vec![
+            BlockFault::ChainDoubleSpend,
+            BlockFault::Overspend,
+            BlockFault::BadSignature,
+            BlockFault::NonFinalLocktime,
+            BlockFault::OversizedScript,
+            BlockFault::DuplicateTxid,
+        ];
+
+        let coinbase_builder = Sh_CoinbaseTransactionBuilder::new(&Default::default(), 10);
+        let (block, injected) = AdversarialBlockGenerator::new(&store, &keypair)
+            .generate(template(&b1, vec![]), coinbase_builder, &faults)
+            .unwrap();
+
+        assert_eq!(block.header.raw.previous_header_hash, b1.hash().clone());
+        assert_eq!(block.header.raw.merkle_root_hash, block.merkle_root());
+        // duplicate transaction is included twice
+        assert_eq!(block.transactions.len(), 8);
+        assert_eq!(injected.iter().map(|f| f.fault).collect::<Vec<_>>(), faults);
+        for (fault, tx) in injected.iter().zip(block.transactions.iter().skip(1)) {
+            assert_eq!(fault.transaction, *tx);
+        }
+        assert_eq!(block.transactions[7], injected[5].transaction);
+
+        // tx0 output is spent by tx1
+        let double_spend = &injected[0].transaction.raw;
+        assert_eq!(
+            double_spend.inputs[0].previous_output,
+            OutPoint {
+                hash: b0.transactions[1].hash.clone(),
+                index: 0,
+            }
+        );
+        // overspend creates more than spends
+        let overspend = &injected[1].transaction.raw;
+        let spent_output = store
+            .transaction_output(&overspend.inputs[0].previous_output, 0)
+            .unwrap();
+        assert_eq!(overspend.outputs[0].value, spent_output.value + 1);
+        // faults never spend the same output
+        let spent: ::std::collections::HashSet<_> = injected
+            .iter()
+            .map(|f| f.transaction.raw.inputs[0].previous_output.clone())
+            .collect();
+        assert_eq!(spent.len(), faults.len());
+
+        // the injected fault is the only defect of every transaction
+        assert_eq!(verify(&store, double_spend), Ok(()));
+        assert_eq!(verify(&store, overspend), Ok(()));
+        assert_eq!(
+            verify(&store, &injected[2].transaction.raw),
+            Err(Error::EvalFalse)
+        );
+        assert_eq!(injected[3].transaction.raw.lock_time, 1002);
+        assert_eq!(verify(&store, &injected[3].transaction.raw), Ok(()));
+        assert!(injected[4].transaction.raw.inputs[0].script_sig.len() > MAX_SCRIPT_SIZE);
+        assert_eq!(
+            verify(&store, &injected[4].transaction.raw),
+            Err(Error::ScriptSize)
+        );
+        assert_eq!(verify(&store, &injected[5].transaction.raw), Ok(()));
+    }
+
+    #[test]
+    fn adversarial_block_double_spends_mempool_transaction() {
+        let keypair = keypair();
+        let (store, _, b1) = prepare_store(&keypair);
+        let mempool_tx: IndexedTransaction = test_data::TransactionBuilder::with_output(30)
+            .add_input(&b1.transactions[1].raw, 0)
+            .into();
+
+        let coinbase_builder = Sh_CoinbaseTransactionBuilder::new(&Default::default(), 10);
+        let (block, injected) = AdversarialBlockGenerator::new(&store, &keypair)
+            .generate(
+                template(&b1, vec![mempool_tx.clone()]),
+                coinbase_builder,
+                &[BlockFault::MempoolDoubleSpend],
+            )
+            .unwrap();
+
+        // both the memory pool transaction and the conflicting one are in the block
+        assert_eq!(block.transactions.len(), 3);
+        assert_eq!(block.transactions[1], mempool_tx);
+        let double_spend = &block.transactions[2];
+        assert!(double_spend.hash != mempool_tx.hash);
+        assert_eq!(
+            double_spend.raw.inputs[0].previous_output,
+            mempool_tx.raw.inputs[0].previous_output
+        );
+        assert_eq!(verify(&store, &double_spend.raw), Ok(()));
+        assert_eq!(injected[0].transaction, *double_spend);
+    }
+
+    #[test]
+    fn adversarial_block_requires_suitable_inputs() {
+        let keypair = keypair();
+        let (store, _, b1) = prepare_store(&keypair);
+
+        let coinbase_builder = Sh_CoinbaseTransactionBuilder::new(&Default::default(), 10);
+        let result = AdversarialBlockGenerator::new(&store, &keypair).generate(
+            template(&b1, vec![]),
+            coinbase_builder,
+            &[BlockFault::MempoolDoubleSpend],
+        );
+        assert_eq!(
+            result.map(|_| ()),
+            Err(AdversarialBlockError::NoSuitableInput(
+                BlockFault::MempoolDoubleSpend
+            ))
+        );
+
+        // outputs of other keys are never spent
+        let other = KeyPair::from_private(Private {
+            network: Network::Mainnet,
+            secret: 2.into(),
+            compressed: true,
+        })
+        .unwrap();
+        let coinbase_builder = Sh_CoinbaseTransactionBuilder::new(&Default::default(), 10);
+        let result = AdversarialBlockGenerator::new(&store, &other).generate(
+            template(&b1, vec![]),
+            coinbase_builder,
+            &[BlockFault::Overspend],
+        );
+        assert_eq!(
+            result.map(|_| ()),
+            Err(AdversarialBlockError::NoSuitableInput(
+                BlockFault::Overspend
+            ))
+        );
+    }
+}
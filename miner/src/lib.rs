@@ -13,17 +13,22 @@ extern crate serialization as ser;
 extern crate storage;
 extern crate verification;
 
+mod adversarial_block;
 mod block_assembler;
 mod cpu_miner;
 mod fee;
 mod memory_pool;
 
+pub use adversarial_block::{
+    AdversarialBlockError, AdversarialBlockGenerator, BlockFault, InjectedFault,
+};
 pub use block_assembler::{BlockAssembler, BlockTemplate};
 pub use cpu_miner::find_solution;
-pub use fee::{transaction_fee, transaction_fee_rate, FeeCalculator};
+pub use fee::{transaction_fee, transaction_fee_rate, FeeCalculator, MemoryPoolFeeCalculator};
 pub use memory_pool::{
-    DoubleSpendCheckResult, HashedOutPoint, Information as MemoryPoolInformation, MemoryPool,
-    NonFinalDoubleSpendSet, OrderingStrategy as MemoryPoolOrderingStrategy,
+    DoubleSpendCheckResult, Entry as MemoryPoolEntry, HashedOutPoint,
+    Information as MemoryPoolInformation, MemoryPool, NonFinalDoubleSpendSet,
+    OrderingStrategy as MemoryPoolOrderingStrategy,
 };
 
 pub use cpu_miner::Sh_CoinbaseTransactionBuilder;
@@ -38,6 +38,8 @@ pub struct Information {
     pub transactions_count: usize,
     /// Total number of bytes occupied by transactions from the `MemoryPool`
     pub transactions_size_in_bytes: usize,
+    /// Total number of bytes of heap memory, used by the `MemoryPool`
+    pub memory_usage_in_bytes: usize,
 }
 
 /// Transactions memory pool
@@ -903,9 +905,62 @@ impl MemoryPool {
         Information {
             transactions_count: self.storage.by_hash.len(),
             transactions_size_in_bytes: self.storage.transactions_size_in_bytes,
+            memory_usage_in_bytes: self.heap_size_of_children(),
         }
     }
 
+    /// Get entry of transaction by hash
+    pub fn entry(&self, hash: &H256) -> Option<&Entry> {
+        self.storage.get_by_hash(hash)
+    }
+
+    /// Returns entries of all in-pool ancestors of transaction (as in GetMemPoolAncestors RPC)
+    pub fn ancestors(&self, hash: &H256) -> Option<Vec<&Entry>> {
+        // ancestors of removed transactions are not updated => filter out these
+        self.storage.get_by_hash(hash).map(|entry| {
+            entry
+                .ancestors
+                .iter()
+                .filter_map(|ancestor| self.storage.get_by_hash(ancestor))
+                .collect()
+        })
+    }
+
+    /// Returns entries of all in-pool descendants of transaction (as in GetMemPoolDescendants RPC)
+    pub fn descendants(&self, hash: &H256) -> Option<Vec<&Entry>> {
+        if !self.storage.contains(hash) {
+            return None;
+        }
+
+        // follow spenders of outputs, so that only the descendants themselves are visited
+        let mut descendants = Vec::new();
+        let mut visited = HashSet::new();
+        let mut queue = vec![hash.clone()];
+        while let Some(hash) = queue.pop() {
+            let outputs_count = match self.storage.get_by_hash(&hash) {
+                Some(entry) => entry.transaction.outputs.len(),
+                None => continue,
+            };
+            for index in 0..outputs_count {
+                let prevout: HashedOutPoint = OutPoint {
+                    hash: hash.clone(),
+                    index: index as u32,
+                }
+                .into();
+                let spender = match self.storage.by_previous_output.get(&prevout) {
+                    Some(spender) if visited.insert(spender.clone()) => spender,
+                    _ => continue,
+                };
+                if let Some(entry) = self.storage.get_by_hash(spender) {
+                    descendants.push(entry);
+                    queue.push(spender.clone());
+                }
+            }
+        }
+
+        Some(descendants)
+    }
+
     /// Returns TXIDs of all transactions in `MemoryPool` (as in GetRawMemPool RPC)
     /// https://bitcoin.org/en/developer-reference#getrawmempool
     pub fn get_transactions_ids(&self) -> Vec<H256> {
@@ -1079,10 +1134,12 @@ pub mod tests {
     extern crate test_data;
 
     use self::test_data::{ChainBuilder, TransactionBuilder};
-    use super::{DoubleSpendCheckResult, MemoryPool, OrderingStrategy};
+    use super::{DoubleSpendCheckResult, Entry, MemoryPool, OrderingStrategy};
     use chain::{OutPoint, Transaction};
     use fee::NonZeroFeeCalculator;
     use heapsize::HeapSizeOf;
+    use primitives::hash::H256;
+    use std::collections::HashSet;
 
     fn to_memory_pool(chain: &mut ChainBuilder) -> MemoryPool {
         let mut pool = MemoryPool::new();
@@ -1307,6 +1364,77 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_memory_pool_ancestors_and_descendants() {
+        let chain = &mut ChainBuilder::new();
+        TransactionBuilder::with_output(10)
+            .store(chain)
+            .into_input(0)
+            .add_output(20)
+            .store(chain)
+            .into_input(0)
+            .add_output(30)
+            .store(chain)
+            .reset()
+            .set_output(40)
+            .store(chain);
+        let mut pool = to_memory_pool(chain);
+
+        fn hashes(entries: Option<Vec<&Entry>>) -> HashSet<H256> {
+            entries.unwrap().into_iter().map(|e| e.hash.clone()).collect()
+        }
+        fn set(hashes: Vec<H256>) -> HashSet<H256> {
+            hashes.into_iter().collect()
+        }
+
+        assert_eq!(hashes(pool.ancestors(&chain.hash(0))), set(vec![]));
+        assert_eq!(
+            hashes(pool.descendants(&chain.hash(0))),
+            set(vec![chain.hash(1), chain.hash(2)])
+        );
+        assert_eq!(
+            hashes(pool.ancestors(&chain.hash(2))),
+            set(vec![chain.hash(0), chain.hash(1)])
+        );
+        assert_eq!(hashes(pool.descendants(&chain.hash(2))), set(vec![]));
+        assert_eq!(hashes(pool.ancestors(&chain.hash(3))), set(vec![]));
+        assert!(pool.ancestors(&H256::default()).is_none());
+        assert!(pool.descendants(&H256::default()).is_none());
+
+        // removed ancestors are not reported
+        pool.remove_by_hash(&chain.hash(0));
+        assert_eq!(hashes(pool.ancestors(&chain.hash(2))), set(vec![chain.hash(1)]));
+        assert_eq!(pool.entry(&chain.hash(1)).map(|e| e.size), Some(chain.size(1)));
+        assert!(pool.entry(&chain.hash(0)).is_none());
+    }
+
+    #[test]
+    fn test_memory_pool_descendants_spending_several_outputs() {
+        let chain = &mut ChainBuilder::new();
+        TransactionBuilder::with_output(10)
+            .add_output(20)
+            .store(chain)
+            .into_input(0)
+            .add_input(&chain.at(0), 1)
+            .set_output(25)
+            .store(chain)
+            .into_input(0)
+            .set_output(20)
+            .store(chain);
+        let pool = to_memory_pool(chain);
+
+        // the child spends both outputs, but is reported once
+        let descendants: Vec<H256> = pool
+            .descendants(&chain.hash(0))
+            .unwrap()
+            .into_iter()
+            .map(|e| e.hash.clone())
+            .collect();
+        assert_eq!(descendants.len(), 2);
+        assert!(descendants.contains(&chain.hash(1)));
+        assert!(descendants.contains(&chain.hash(2)));
+    }
+
     #[test]
     fn test_memory_pool_timestamp_ordering_strategy() {
         let chain = &mut ChainBuilder::new();
@@ -28,6 +28,8 @@ pub struct ConsensusParams {
     pub csv_deployment: Option<Deployment>,
     /// BIP141, BIP143, BIP147 deployment
     pub segwit_deployment: Option<Deployment>,
+    /// When transactions of the block are verified.
+    pub tx_validity: TransactionValidity,
 }
 
 #[derive(Debug, Clone)]
@@ -60,6 +62,16 @@ pub enum ConsensusFork {
     BitcoinCash(BitcoinCashConsensusParams),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Describes when validity of block transactions is decided.
+pub enum TransactionValidity {
+    /// Block is accepted only if all its transactions are valid.
+    Strict,
+    /// Only header, merkle root and coinbase rules are enforced before the block
+    /// is accepted. Validity of every transaction is decided after ordering.
+    Lazy,
+}
+
 #[derive(Debug, Clone, Copy)]
 /// Describes the ordering of transactions within single block.
 pub enum TransactionOrdering {
@@ -100,6 +112,7 @@ impl ConsensusParams {
                     timeout: 1493596800,
                     activation: Some(419328),
                 }),
+                tx_validity: TransactionValidity::Strict,
             },
             Network::Testnet => ConsensusParams {
                 network: network,
@@ -127,6 +140,7 @@ impl ConsensusParams {
                     timeout: 1493596800,
                     activation: Some(770112),
                 }),
+                tx_validity: TransactionValidity::Strict,
             },
             Network::Regtest | Network::Unitest => ConsensusParams {
                 network: network,
@@ -154,6 +168,7 @@ impl ConsensusParams {
                     timeout: 0,
                     activation: Some(0),
                 }),
+                tx_validity: TransactionValidity::Strict,
             },
         }
     }
@@ -12,6 +12,7 @@ pub use primitives::{compact, hash};
 
 pub use consensus::{
     BitcoinCashConsensusParams, ConsensusFork, ConsensusParams, TransactionOrdering,
+    TransactionValidity,
 };
 pub use deployments::Deployment;
 pub use network::{Magic, Network};
@@ -39,6 +39,9 @@ pub trait InboundSyncConnection: Send + Sync {
     fn on_compact_block(&self, message: types::CompactBlock);
     fn on_get_block_txn(&self, message: types::GetBlockTxn);
     fn on_block_txn(&self, message: types::BlockTxn);
+    fn on_get_cfilters(&self, message: types::GetCFilters);
+    fn on_get_cfheaders(&self, message: types::GetCFHeaders);
+    fn on_get_cfcheckpt(&self, message: types::GetCFCheckpt);
     fn on_notfound(&self, message: types::NotFound);
 }
 
@@ -64,6 +67,9 @@ pub trait OutboundSyncConnection: Send + Sync {
     fn send_compact_block(&self, message: &types::CompactBlock);
     fn send_get_block_txn(&self, message: &types::GetBlockTxn);
     fn send_block_txn(&self, message: &types::BlockTxn);
+    fn send_cfilter(&self, message: &types::CFilter);
+    fn send_cfheaders(&self, message: &types::CFHeaders);
+    fn send_cfcheckpt(&self, message: &types::CFCheckpt);
     fn send_notfound(&self, message: &types::NotFound);
     fn ignored(&self, id: u32);
     fn close(&self);
@@ -166,6 +172,18 @@ impl OutboundSyncConnection for OutboundSync {
         self.context.send_request(message);
     }
 
+    fn send_cfilter(&self, message: &types::CFilter) {
+        self.context.send_request(message);
+    }
+
+    fn send_cfheaders(&self, message: &types::CFHeaders) {
+        self.context.send_request(message);
+    }
+
+    fn send_cfcheckpt(&self, message: &types::CFCheckpt) {
+        self.context.send_request(message);
+    }
+
     fn send_notfound(&self, message: &types::NotFound) {
         self.context.send_request(message);
     }
@@ -274,6 +292,15 @@ impl Protocol for SyncProtocol {
         } else if command == &types::BlockTxn::command() {
             let message: types::BlockTxn = try!(deserialize_payload(payload, version));
             self.inbound_connection.on_block_txn(message);
+        } else if command == &types::GetCFilters::command() {
+            let message: types::GetCFilters = try!(deserialize_payload(payload, version));
+            self.inbound_connection.on_get_cfilters(message);
+        } else if command == &types::GetCFHeaders::command() {
+            let message: types::GetCFHeaders = try!(deserialize_payload(payload, version));
+            self.inbound_connection.on_get_cfheaders(message);
+        } else if command == &types::GetCFCheckpt::command() {
+            let message: types::GetCFCheckpt = try!(deserialize_payload(payload, version));
+            self.inbound_connection.on_get_cfcheckpt(message);
         } else if command == &types::NotFound::command() {
             let message: types::NotFound = try!(deserialize_payload(payload, version));
             self.inbound_connection.on_notfound(message);
@@ -0,0 +1,90 @@
+use clap::ArgMatches;
+use config::Config;
+use ethcore_rpc::v1::{BlockChain, BlockChainClient, BlockChainClientCore, MAX_BLOCK_RANGE_COUNT};
+use miner::MemoryPool;
+use parking_lot::RwLock;
+use serde_json;
+use std::cmp;
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::Arc;
+use util::init_db;
+
+/// Format of the chain dump
+enum DumpFormat {
+    /// Single JSON array of blocks
+    Json,
+    /// Single JSON block per line
+    JsonLines,
+}
+
+pub fn dump_chain(cfg: Config, matches: &ArgMatches) -> Result<(), String> {
+    try!(init_db(&cfg));
+
+    let format = match matches.value_of("format").unwrap_or("json") {
+        "json" => DumpFormat::Json,
+        "jsonl" => DumpFormat::JsonLines,
+        format => return Err(format!("Unknown dump format: {}", format)),
+    };
+    let from: u32 = match matches.value_of("from") {
+        Some(from) => from.parse().map_err(|e| format!("Invalid start height: {}", e))?,
+        None => 0,
+    };
+    let verbosity: u8 = match matches.value_of("verbosity") {
+        Some(verbosity) => verbosity.parse().map_err(|e| format!("Invalid verbosity: {}", e))?,
+        None => 0,
+    };
+    let best_height = cfg.db.best_block().number;
+    let to = match matches.value_of("count") {
+        Some(count) => {
+            let count: u32 = count.parse().map_err(|e| format!("Invalid block count: {}", e))?;
+            cmp::min(from.saturating_add(count), best_height + 1)
+        }
+        None => best_height + 1,
+    };
+
+    let mut output: Box<Write> = match matches.value_of("output") {
+        Some(path) => Box::new(File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?),
+        None => Box::new(io::stdout()),
+    };
+
+    // blocks are read with the RPC client, so that the dump is the same as getblockrange responses
+    let memory_pool = Arc::new(RwLock::new(MemoryPool::new()));
+    let client = BlockChainClient::new(BlockChainClientCore::new(cfg.network, cfg.db.clone(), memory_pool));
+
+    if let DumpFormat::Json = format {
+        try!(write!(output, "[").map_err(write_error));
+    }
+
+    let mut height = from;
+    while height < to {
+        let count = cmp::min(to - height, MAX_BLOCK_RANGE_COUNT);
+        let blocks = client
+            .block_range(height, count, Some(verbosity).into())
+            .map_err(|e| format!("Failed to read blocks at height {}: {:?}", height, e))?;
+        // canonical chain ends before the best block => nothing more to dump
+        if blocks.is_empty() {
+            break;
+        }
+
+        for block in blocks {
+            let block = serde_json::to_string(&block).map_err(|e| e.to_string())?;
+            try!(match format {
+                DumpFormat::Json if height == from => write!(output, "{}", block),
+                DumpFormat::Json => write!(output, ",{}", block),
+                DumpFormat::JsonLines => writeln!(output, "{}", block),
+            }.map_err(write_error));
+            height += 1;
+        }
+    }
+
+    if let DumpFormat::Json = format {
+        try!(writeln!(output, "]").map_err(write_error));
+    }
+
+    output.flush().map_err(write_error)
+}
+
+fn write_error(err: io::Error) -> String {
+    format!("Failed to write chain dump: {}", err)
+}
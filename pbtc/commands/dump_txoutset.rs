@@ -0,0 +1,39 @@
+use clap::ArgMatches;
+use config::Config;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use util::init_db;
+
+pub fn dump_txoutset(cfg: Config, matches: &ArgMatches) -> Result<(), String> {
+    try!(init_db(&cfg));
+
+    let path = matches
+        .value_of("FILE")
+        .expect("FILE is required in cli.yml; qed");
+    let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    let mut output = BufWriter::new(file);
+
+    let result = cfg
+        .db
+        .dump_utxo_snapshot(&mut output)
+        .map_err(String::from)
+        .and_then(|info| output.flush().map(|_| info).map_err(|e| e.to_string()));
+    let info = match result {
+        Ok(info) => info,
+        Err(err) => {
+            // incomplete snapshot is never left behind
+            let _ = fs::remove_file(path);
+            return Err(format!("Failed to dump UTXO set: {}", err));
+        }
+    };
+
+    // the hash is the same as gettxoutsetinfo RPC returns
+    info!(
+        "Dumped {} unspent outputs at block {} ({}). UTXO set hash: {}",
+        info.utxo_stats.outputs,
+        info.height,
+        info.block_hash.reversed(),
+        info.utxo_stats.hash()
+    );
+    Ok(())
+}
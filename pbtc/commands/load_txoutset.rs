@@ -0,0 +1,86 @@
+use chain::BlockHeader;
+use clap::ArgMatches;
+use config::Config;
+use primitives::hash::H256;
+use ser::Reader;
+use std::fs::File;
+use std::io::BufReader;
+use storage::UtxoSnapshotInfo;
+use util::init_db;
+use verification::is_valid_proof_of_work;
+
+pub fn load_txoutset(cfg: Config, matches: &ArgMatches) -> Result<(), String> {
+    // genesis block is inserted by init_db, once the snapshot is loaded
+    if cfg.db.block_hash(0).is_some() {
+        return Err("UTXO set could only be loaded into an empty database".into());
+    }
+
+    let path = matches
+        .value_of("FILE")
+        .expect("FILE is required in cli.yml; qed");
+    let expected_hash: Option<H256> = match matches.value_of("utxo-hash") {
+        Some(hash) => Some(
+            hash.parse()
+                .map_err(|e| format!("Invalid UTXO set hash: {}", e))?,
+        ),
+        None => None,
+    };
+    let open = || File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e));
+
+    // check that the snapshot belongs to our network before anything is written
+    {
+        let mut reader = Reader::from_read(BufReader::new(open()?));
+        let info: UtxoSnapshotInfo = reader
+            .read()
+            .map_err(|e| format!("Invalid UTXO set snapshot: {:?}", e))?;
+        let max_work_bits = cfg.network.max_bits().into();
+        for number in 0..info.height + 1 {
+            let header: BlockHeader = reader
+                .read()
+                .map_err(|e| format!("Invalid UTXO set snapshot: {:?}", e))?;
+            if number == 0 && header.hash() != cfg.network.genesis_block().hash() {
+                return Err(
+                    "UTXO set snapshot is made for the chain with different genesis block".into(),
+                );
+            }
+            if !is_valid_proof_of_work(max_work_bits, header.bits, &header.hash()) {
+                return Err(format!(
+                    "UTXO set snapshot header {} has invalid proof of work",
+                    number
+                ));
+            }
+        }
+        if let Some(expected_hash) = expected_hash {
+            if info.utxo_stats.hash() != expected_hash {
+                return Err(format!(
+                    "UTXO set hash mismatch: expected {}, snapshot has {}",
+                    expected_hash,
+                    info.utxo_stats.hash()
+                ));
+            }
+        } else {
+            warn!(
+                "UTXO set hash is not passed with --utxo-hash. Snapshot {} is NOT verified \
+                 against a trusted hash: its unspent outputs are only checked against the hash, \
+                 stored in the snapshot itself",
+                info.utxo_stats.hash()
+            );
+        }
+    }
+
+    let mut input = BufReader::new(open()?);
+    let info = cfg
+        .db
+        .load_utxo_snapshot(&mut input)
+        .map_err(|e| format!("Failed to load UTXO set: {}", e))?;
+    try!(init_db(&cfg));
+
+    info!(
+        "Loaded {} unspent outputs at block {} ({}). UTXO set hash: {}",
+        info.utxo_stats.outputs,
+        info.height,
+        info.block_hash.reversed(),
+        info.utxo_stats.hash()
+    );
+    Ok(())
+}
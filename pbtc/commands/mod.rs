@@ -1,7 +1,13 @@
+mod dump_chain;
+mod dump_txoutset;
 mod import;
+mod load_txoutset;
 mod rollback;
 mod start;
 
+pub use self::dump_chain::dump_chain;
+pub use self::dump_txoutset::dump_txoutset;
 pub use self::import::import;
+pub use self::load_txoutset::load_txoutset;
 pub use self::rollback::rollback;
 pub use self::start::start;
@@ -30,6 +30,17 @@ pub fn rollback(cfg: Config, matches: &ArgMatches) -> Result<(), String> {
         .block_header(block_ref.clone())
         .ok_or(format!("Block {:?} is unknown", block_ref))?
         .hash();
+
+    // blocks could only be reverted while their bodies are in the database
+    if let Some(required_block_number) = cfg.db.block_number(&required_block_hash) {
+        if cfg.db.is_pruned((required_block_number + 1).into()) {
+            return Err(format!(
+                "Failed to revert to block {:?}. Block at height {} has been pruned",
+                block_ref,
+                required_block_number + 1
+            ));
+        }
+    }
     let genesis_hash = cfg.network.genesis_block().hash();
 
     let mut best_block_hash = cfg.db.best_block().hash;
@@ -7,9 +7,10 @@ use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread;
 use sync::{
-    create_local_sync_node, create_sync_connection_factory, create_sync_peers, SyncListener, create_sync_wallet
+    create_local_sync_node, create_sync_connection_factory, create_sync_peers, SyncListener, create_sync_wallet,
+    create_sync_attacker,
 };
-use util::{init_db, node_table_path};
+use util::{init_db, node_table_path, open_wallet_db, read_passphrase};
 use {config, p2p, PROTOCOL_MINIMUM, PROTOCOL_VERSION};
 
 enum BlockNotifierTask {
@@ -123,8 +124,17 @@ pub fn start(cfg: config::Config) -> Result<(), String> {
         cfg.verification_params,
     );
 
-    let local_wallet = create_sync_wallet(local_sync_node.clone());
-    let covetous_wallet = create_sync_wallet(local_sync_node.clone());
+    let wallet_passphrase = match cfg.wallet_passphrase {
+        Some(passphrase) => passphrase,
+        None => read_passphrase("Wallet passphrase: ")?,
+    };
+    config::check_wallet_passphrase(&wallet_passphrase)?;
+    let wallet_db = open_wallet_db(&cfg.data_dir, "wallet", &wallet_passphrase)?;
+    let local_wallet = create_sync_wallet(local_sync_node.clone(), wallet_db)
+        .map_err(|err| format!("Failed to load wallet: {:?}", err))?;
+    let adversary_wallet_db = open_wallet_db(&cfg.data_dir, "adversary-wallet", &wallet_passphrase)?;
+    let attacker = create_sync_attacker(local_sync_node.clone(), adversary_wallet_db, cfg.adversary)
+        .map_err(|err| format!("Failed to load adversary wallet: {:?}", err))?;
 
     let sync_connection_factory =
         create_sync_connection_factory(sync_peers.clone(), local_sync_node.clone());
@@ -143,7 +153,8 @@ pub fn start(cfg: config::Config) -> Result<(), String> {
         p2p_context: p2p.context().clone(),
         remote: el.remote(),
         wallet: Arc::new(Mutex::new(local_wallet)),
-        covetous_wallet: Arc::new(Mutex::new(covetous_wallet)),
+        attacker: Arc::new(Mutex::new(attacker)),
+        wallet_mnemonic_rpc: cfg.wallet_mnemonic_rpc,
     };
     let _rpc_server = try!(rpc::new_http(cfg.rpc_config, rpc_deps));
 
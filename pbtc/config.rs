@@ -1,6 +1,9 @@
 use clap;
+use db::{PruneMode, MIN_BLOCKS_TO_KEEP};
 use message::Services;
-use network::{BitcoinCashConsensusParams, ConsensusFork, ConsensusParams, Network};
+use network::{
+    BitcoinCashConsensusParams, ConsensusFork, ConsensusParams, Network, TransactionValidity,
+};
 use p2p::InternetProtocol;
 use primitives::hash::H256;
 use rpc::HttpConfiguration as RpcHttpConfig;
@@ -8,9 +11,12 @@ use rpc_apis::ApiSet;
 use seednodes::{
     bitcoin_cash_seednodes, bitcoin_cash_testnet_seednodes, mainnet_seednodes, testnet_seednodes,
 };
+use std::env;
+use std::fs::File;
+use std::io::Read;
 use std::net;
 use storage;
-use sync::VerificationParameters;
+use sync::{AdversaryStrategy, VerificationParameters};
 use util::open_db;
 use verification::VerificationLevel;
 use {REGTEST_USER_AGENT, USER_AGENT};
@@ -35,9 +41,17 @@ pub struct Config {
     pub block_notify_command: Option<String>,
     pub verification_params: VerificationParameters,
     pub db: storage::SharedStore,
+    pub adversary: AdversaryStrategy,
+    /// Wallet passphrase, given on the command line, in the file or in the environment.
+    /// Passphrase is asked on the terminal if none is given
+    pub wallet_passphrase: Option<String>,
+    /// True if getmnemonic RPC is allowed to return the wallet mnemonic
+    pub wallet_mnemonic_rpc: bool,
 }
 
 pub const DEFAULT_DB_CACHE: usize = 512;
+/// Environment variable with the wallet passphrase
+pub const WALLET_PASSPHRASE_ENV: &'static str = "PBTC_WALLET_PASSPHRASE";
 
 pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
     let db_cache = match matches.value_of("db-cache") {
@@ -52,7 +66,13 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
         None => None,
     };
 
-    let db = open_db(&data_dir, db_cache);
+    let prune_mode = match matches.value_of("prune") {
+        Some(s) => Some(parse_prune_mode(s)?),
+        None => None,
+    };
+
+    let address_index = matches.is_present("addressindex");
+    let db = open_db(&data_dir, db_cache, prune_mode, address_index)?;
 
     let quiet = matches.is_present("quiet");
     let network = match (matches.is_present("testnet"), matches.is_present("regtest")) {
@@ -63,7 +83,13 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
     };
 
     let consensus_fork = parse_consensus_fork(network, &db, &matches)?;
-    let consensus = ConsensusParams::new(network, consensus_fork);
+    let mut consensus = ConsensusParams::new(network, consensus_fork);
+    consensus.tx_validity = match matches.value_of("tx-validity") {
+        Some(s) if s == "strict" => TransactionValidity::Strict,
+        Some(s) if s == "lazy" => TransactionValidity::Lazy,
+        Some(s) => return Err(format!("Invalid transaction validity mode: {}", s)),
+        None => TransactionValidity::Strict,
+    };
 
     let (in_connections, out_connections) = match network {
         Network::Testnet | Network::Mainnet | Network::Other(_) => (10, 10),
@@ -145,7 +171,17 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
         None => None,
     };
 
-    let services = Services::default().with_network(true);
+    // pruned node is only able to serve recent blocks
+    let pruned = prune_mode.is_some() || db.is_pruned(storage::BlockRef::Number(0));
+    // compact filters are chained from the genesis block, so databases, created before
+    // filters were maintained, are unable to serve them
+    let compact_filters = db
+        .block_hash(0)
+        .map_or(true, |hash| db.block_filter(&hash).is_some());
+    let services = Services::default()
+        .with_network(!pruned)
+        .with_network_limited(pruned)
+        .with_compact_filters(compact_filters);
     let services = match &consensus.fork {
         &ConsensusFork::BitcoinCash(_) => services.with_bitcoin_cash(true),
         &ConsensusFork::BitcoinCore => services.with_witness(true),
@@ -169,6 +205,23 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
         _ => network.default_verification_edge(),
     };
 
+    let adversary = match matches.value_of("adversary") {
+        Some(s) => s.parse()?,
+        None => AdversaryStrategy::DoubleSpendRace,
+    };
+
+    let wallet_passphrase = match (
+        matches.value_of("wallet-passphrase"),
+        matches.value_of("wallet-passphrase-file"),
+    ) {
+        (Some(passphrase), _) => Some(passphrase.to_owned()),
+        (None, Some(path)) => Some(read_passphrase_file(path)?),
+        (None, None) => env::var(WALLET_PASSPHRASE_ENV).ok(),
+    };
+    if let Some(ref passphrase) = wallet_passphrase {
+        check_wallet_passphrase(passphrase)?;
+    }
+
     let config = Config {
         quiet: quiet,
         network: network,
@@ -192,11 +245,57 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
             verification_edge: verification_edge,
         },
         db: db,
+        adversary: adversary,
+        wallet_passphrase: wallet_passphrase,
+        wallet_mnemonic_rpc: matches.is_present("wallet-mnemonic-rpc"),
     };
 
     Ok(config)
 }
 
+/// Wallet passphrase must not be empty
+pub fn check_wallet_passphrase(passphrase: &str) -> Result<(), String> {
+    if passphrase.is_empty() {
+        return Err("Wallet passphrase must not be empty".into());
+    }
+    Ok(())
+}
+
+fn read_passphrase_file(path: &str) -> Result<String, String> {
+    let mut contents = String::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut contents))
+        .map_err(|err| format!("Failed to read wallet passphrase file: {}", err))?;
+    Ok(contents.lines().next().unwrap_or("").to_owned())
+}
+
+fn parse_prune_mode(value: &str) -> Result<PruneMode, String> {
+    if value.ends_with("blocks") {
+        let depth: u32 = value[..value.len() - "blocks".len()]
+            .parse()
+            .map_err(|_| format!("Invalid prune depth: {}", value))?;
+        if depth < MIN_BLOCKS_TO_KEEP {
+            return Err(format!(
+                "Prune depth should be at least {} blocks",
+                MIN_BLOCKS_TO_KEEP
+            ));
+        }
+        return Ok(PruneMode::Depth(depth));
+    }
+
+    let megabytes = if value.ends_with("MB") {
+        &value[..value.len() - "MB".len()]
+    } else {
+        value
+    };
+    megabytes
+        .parse::<u64>()
+        .ok()
+        .and_then(|megabytes| megabytes.checked_mul(1024 * 1024))
+        .map(PruneMode::Size)
+        .ok_or_else(|| format!("Invalid prune size: {}", value))
+}
+
 fn parse_consensus_fork(
     network: Network,
     db: &storage::SharedStore,
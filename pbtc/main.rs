@@ -14,14 +14,19 @@ extern crate import;
 extern crate keys;
 extern crate logs;
 extern crate message;
+extern crate miner;
 extern crate network;
 extern crate p2p;
+extern crate parking_lot;
 extern crate primitives;
 extern crate rpc as ethcore_rpc;
 extern crate script;
+extern crate serde_json;
+extern crate serialization as ser;
 extern crate storage;
 extern crate sync;
 extern crate verification;
+extern crate wallet;
 
 mod commands;
 mod config;
@@ -56,8 +61,6 @@ fn run() -> Result<(), String> {
     let matches = clap::App::from_yaml(yaml).get_matches();
     let cfg = try!(config::parse(&matches));
 
-    println!("start pbtc unison  ");
-
     if !cfg.quiet {
         if cfg!(windows) {
             logs::init(LOG_INFO, logs::DateLogFormatter);
@@ -71,6 +74,12 @@ fn run() -> Result<(), String> {
     match matches.subcommand() {
         ("import", Some(import_matches)) => commands::import(cfg, import_matches),
         ("rollback", Some(rollback_matches)) => commands::rollback(cfg, rollback_matches),
-        _ => commands::start(cfg),
+        ("dump-chain", Some(dump_matches)) => commands::dump_chain(cfg, dump_matches),
+        ("dumptxoutset", Some(dump_matches)) => commands::dump_txoutset(cfg, dump_matches),
+        ("loadtxoutset", Some(load_matches)) => commands::load_txoutset(cfg, load_matches),
+        _ => {
+            println!("start pbtc unison  ");
+            commands::start(cfg)
+        }
     }
 }
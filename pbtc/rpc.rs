@@ -8,7 +8,7 @@ use std::sync::Arc;
 use std::sync::Mutex;
 use storage;
 use sync;
-use sync::Wallet;
+use sync::{Attacker, Wallet};
 
 pub struct Dependencies {
     pub network: Network,
@@ -17,7 +17,8 @@ pub struct Dependencies {
     pub p2p_context: Arc<p2p::Context>,
     pub remote: Remote,
     pub wallet: Arc<Mutex<Wallet>>,
-    pub covetous_wallet: Arc<Mutex<Wallet>>
+    pub attacker: Arc<Mutex<Attacker>>,
+    pub wallet_mnemonic_rpc: bool,
 }
 
 #[derive(Debug, PartialEq)]
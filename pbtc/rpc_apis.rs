@@ -73,21 +73,28 @@ pub fn setup_rpc(
                 .to_delegate(),
             ),
             Api::Miner => handler.extend_with(
-                MinerClient::new(MinerClientCore::new(deps.local_sync_node.clone())).to_delegate(),
+                MinerClient::new(MinerClientCore::new(
+                    deps.local_sync_node.clone(),
+                    deps.storage.clone(),
+                ))
+                .to_delegate(),
             ),
             Api::BlockChain => handler.extend_with(
                 BlockChainClient::new(BlockChainClientCore::new(
                     deps.network,
                     deps.storage.clone(),
+                    deps.local_sync_node.memory_pool(),
                 ))
                 .to_delegate(),
             ),
             Api::Network => handler.extend_with(
                 NetworkClient::new(NetworkClientCore::new(deps.p2p_context.clone())).to_delegate(),
             ),
-            Api::Wallet => handler.extend_with(
-                WalletClient::new(WalletClientCore::new(deps.wallet.clone(), deps.covetous_wallet.clone())).to_delegate(),
-            )
+            Api::Wallet => {
+                let client = WalletClient::new(WalletClientCore::new(deps.wallet.clone(), deps.attacker.clone()));
+                let client = if deps.wallet_mnemonic_rpc { client.with_mnemonic_export() } else { client };
+                handler.extend_with(client.to_delegate())
+            }
 
         }
     }
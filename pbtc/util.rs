@@ -3,20 +3,79 @@ use chain::IndexedBlock;
 use config::Config;
 use db;
 use std::fs::create_dir_all;
+use std::io;
 use std::path::PathBuf;
 use std::sync::Arc;
+use wallet::WalletDb;
 use {storage, APP_INFO};
 
-pub fn open_db(data_dir: &Option<String>, db_cache: usize) -> storage::SharedStore {
+pub fn open_db(
+    data_dir: &Option<String>,
+    db_cache: usize,
+    prune_mode: Option<db::PruneMode>,
+    address_index: bool,
+) -> Result<storage::SharedStore, String> {
     let db_path = match *data_dir {
         Some(ref data_dir) => custom_path(&data_dir, "db"),
         None => app_dir(AppDataType::UserData, &APP_INFO, "db").expect("Failed to get app dir"),
     };
-    Arc::new(
-        db::BlockChainDatabase::open_at_path(db_path, db_cache).expect("Failed to open database"),
-    )
+    let mut db = db::BlockChainDatabase::open_at_path(db_path, db_cache)
+        .map_err(|err| format!("Failed to open database: {}", err))?;
+    if let Some(prune_mode) = prune_mode {
+        db = db.with_prune_mode(prune_mode);
+    }
+    if address_index {
+        db = db.with_address_index().map_err(|err| err.to_string())?;
+    }
+    Ok(Arc::new(db))
+}
+
+pub fn open_wallet_db(data_dir: &Option<String>, sub_dir: &str, passphrase: &str) -> Result<WalletDb, String> {
+    let wallet_path = match *data_dir {
+        Some(ref data_dir) => custom_path(&data_dir, sub_dir),
+        None => app_dir(AppDataType::UserData, &APP_INFO, sub_dir).expect("Failed to get app dir"),
+    };
+    WalletDb::open(wallet_path, passphrase)
+        .map_err(|err| format!("Failed to open wallet database: {:?}", err))
+}
+
+/// Read passphrase from the terminal, without echoing it
+pub fn read_passphrase(prompt: &str) -> Result<String, String> {
+    eprint!("{}", prompt);
+    set_stdin_echo(false);
+    let mut passphrase = String::new();
+    let result = io::stdin().read_line(&mut passphrase);
+    set_stdin_echo(true);
+    eprintln!();
+    result.map_err(|err| format!("Failed to read wallet passphrase: {}", err))?;
+    Ok(passphrase
+        .trim_right_matches(|c| c == '\r' || c == '\n')
+        .to_owned())
 }
 
+#[cfg(unix)]
+fn set_stdin_echo(enabled: bool) {
+    use libc::{tcgetattr, tcsetattr, termios, ECHO, STDIN_FILENO, TCSANOW};
+    use std::mem;
+
+    unsafe {
+        let mut attributes: termios = mem::zeroed();
+        // stdin is not a terminal
+        if tcgetattr(STDIN_FILENO, &mut attributes) != 0 {
+            return;
+        }
+        if enabled {
+            attributes.c_lflag |= ECHO;
+        } else {
+            attributes.c_lflag &= !ECHO;
+        }
+        tcsetattr(STDIN_FILENO, TCSANOW, &attributes);
+    }
+}
+
+#[cfg(not(unix))]
+fn set_stdin_echo(_enabled: bool) {}
+
 pub fn node_table_path(cfg: &Config) -> PathBuf {
     let mut node_table = match cfg.data_dir {
         Some(ref data_dir) => custom_path(&data_dir, "p2p"),
@@ -83,6 +83,21 @@ impl Compact {
         Compact(compact | (size << 24) as u32)
     }
 
+    /// Expected number of hashes required to find a block with this target.
+    /// Returns zero for negative, overflown and zero targets.
+    pub fn to_work(&self) -> U256 {
+        match self.to_u256() {
+            Ok(ref target) if !target.is_zero() => {
+                // We need to compute 2**256 / (target + 1), but we can't represent 2**256
+                // as it's too large for U256. However, as 2**256 is at least as
+                // large as target + 1, it is equal to ((2**256 - target - 1) /
+                // (target + 1)) + 1, or !target / (target + 1) + 1.
+                (!*target / (*target + U256::one())) + U256::one()
+            }
+            _ => U256::zero(),
+        }
+    }
+
     pub fn to_f64(&self) -> f64 {
         let mut shift = (self.0 >> 24) & 0xff;
         let mut diff = f64::from(0x0000ffffu32) / f64::from(self.0 & 0x00ffffffu32);
@@ -135,6 +150,15 @@ mod tests {
         assert_eq!(compact, compact2);
     }
 
+    #[test]
+    fn test_compact_to_work() {
+        assert_eq!(Compact::new(0x1d00ffff).to_work(), 0x100010001u64.into());
+        assert_eq!(Compact::new(0x207fffff).to_work(), 2.into());
+        // zero and negative targets
+        assert_eq!(Compact::new(0x01003456).to_work(), 0.into());
+        assert_eq!(Compact::new(0x04923456).to_work(), 0.into());
+    }
+
     #[test]
     fn difficulty() {
         fn compare_f64(v1: f64, v2: f64) -> bool {
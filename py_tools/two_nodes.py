@@ -16,7 +16,6 @@ def init_addr_with_fund(url):
     print("my addresshash", address_hash)
     txid = mine_block(url, address_hash, 1) # gen 1 block
     print("mined a block with coinbase txid " + txid)
-    wallet_add_tx(url, txid, 0) # first tx
     update_wallet(url)
 
 def pay_peer(my_url, peer_url, amount):
@@ -30,7 +29,6 @@ def pay_peer(my_url, peer_url, amount):
     update_wallet(my_url)
     txid = shard_pay(my_url, peer_addresshash, amount)
     print('pay to peer txid', txid)
-    wallet_add_tx(peer_url, txid, 0) # first tx
 
 def print_all_balance(all_url):
     for url in all_url:
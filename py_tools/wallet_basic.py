@@ -43,24 +43,57 @@ def update_wallet(url):
     payload = {"jsonrpc": "2.0", "method": "updatewallet", "params": [], "id":1 }
     get_result_or_exit(send_json(url, payload))
 
-def log_blocks(url):
-    payload = {"jsonrpc": "2.0", "method": "logblocks", "params": [], "id":1 }
-    get_result_or_exit(send_json(url, payload))
+def rescan_wallet(url, from_height):
+    payload = {"jsonrpc": "2.0", "method": "rescanwallet", "params": [from_height], "id":1 }
+    return get_result_or_exit(send_json(url, payload))
 
-def log_mempool(url):
-    payload = {"jsonrpc": "2.0", "method": "logmempool", "params": [], "id":1 }
-    get_result_or_exit(send_json(url, payload))
+def get_block_range(url, from_height, count, verbosity=0):
+    payload = {"jsonrpc": "2.0", "method": "getblockrange", "params": [from_height, count, verbosity], "id":1 }
+    return get_result_or_exit(send_json(url, payload))
+
+def get_address_utxos(url, address, from_index=0, count=1000):
+    payload = {"jsonrpc": "2.0", "method": "getaddressutxos", "params": [address, {"from": from_index, "count": count}], "id":1 }
+    return get_result_or_exit(send_json(url, payload))
+
+def get_address_history(url, address, from_index=0, count=1000):
+    payload = {"jsonrpc": "2.0", "method": "getaddresshistory", "params": [address, {"from": from_index, "count": count}], "id":1 }
+    return get_result_or_exit(send_json(url, payload))
+
+def get_address_balance(url, address):
+    # sum of all confirmed unspent outputs of the address, read page by page
+    balance = 0
+    from_index = 0
+    while True:
+        page = get_address_utxos(url, address, from_index)
+        balance += sum(utxo["satoshis"] for utxo in page["utxos"])
+        from_index += len(page["utxos"])
+        if not page["utxos"] or from_index >= page["total"]:
+            return balance
+
+def get_raw_mempool(url, verbose=False):
+    payload = {"jsonrpc": "2.0", "method": "getrawmempool", "params": [verbose], "id":1 }
+    return get_result_or_exit(send_json(url, payload))
 
 def get_balance(url):
     update_wallet(url)
     payload = {"jsonrpc": "2.0", "method": "getbalance", "params": [], "id":1 }
     return get_result_or_exit(send_json(url, payload))
 
-def print_coins(url):
+def list_unspent(url):
+    update_wallet(url)
+    payload = {"jsonrpc": "2.0", "method": "listunspent", "params": [], "id":1 }
+    return get_result_or_exit(send_json(url, payload))
+
+def list_wallet_coins(url):
     update_wallet(url)
-    payload = {"jsonrpc": "2.0", "method": "printcoins", "params": [], "id":1 }
+    payload = {"jsonrpc": "2.0", "method": "listwalletcoins", "params": [], "id":1 }
     return get_result_or_exit(send_json(url, payload))
 
+def print_coins(url):
+    coins = list_wallet_coins(url)
+    print(json.dumps(coins, indent=2))
+    return coins
+
 def get_addresshash(url):
     payload = {"jsonrpc": "2.0", "method": "getaddresshash", "params": [], "id":1 }
     return  get_result_or_exit(send_json(url, payload))
@@ -124,7 +157,7 @@ def get_other_ips(all_ip):
 
 if __name__ == '__main__':
     num_args = len(sys.argv)
-    cmd_list = "gen_keypair mine_block wallet_add_tx update_wallet print_blocks get_balance get_addresshash shard_pay"
+    cmd_list = "gen_keypair mine_block wallet_add_tx update_wallet rescan_wallet print_blocks get_balance get_addresshash shard_pay"
     if num_args<2:
         print("Arg error")
         print("    ip:port method ..., ... is method specific")
@@ -144,6 +177,9 @@ if __name__ == '__main__':
         print(add_tx_to_wallet(url, txid, out_index))
     elif method == "update_wallet":
         print(update_wallet(url))
+    elif method == "rescan_wallet":
+        from_height = int(sys.argv[3])
+        print(rescan_wallet(url, from_height))
     elif method == "print_blocks":
         print(print_blocks(url))
     elif method == "get_balance":
@@ -14,7 +14,6 @@ def init_addr_with_fund(url):
     txid = mine_block(url, address_hash, 1) # gen 1 block
     print("mined a block with coinbase txid " + txid)
     #print_blocks(url)
-    wallet_add_tx(url, txid, 0) # first tx
     update_wallet(url)
 
 def pay_peer(my_url, peer_url, amount):
@@ -28,7 +27,6 @@ def pay_peer(my_url, peer_url, amount):
     update_wallet(my_url)
     txid = shard_pay(my_url, peer_addresshash, amount)
     print('pay to peer txid', txid)
-    wallet_add_tx(peer_url, txid, 0)
 
 if __name__ == '__main__':
     num_args = len(sys.argv)
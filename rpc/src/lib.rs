@@ -1,3 +1,4 @@
+extern crate base64;
 extern crate log;
 extern crate rustc_hex as hex;
 extern crate serde;
@@ -4,6 +4,11 @@ mod codes {
     // NOTE [ToDr] Codes from [-32099, -32000]
     pub const UNKNOWN: i64 = -32000;
     pub const EXECUTION_ERROR: i64 = -32015;
+    pub const MNEMONIC_EXPORT_DISABLED: i64 = -32091;
+    pub const BLOCK_FILTER_NOT_FOUND: i64 = -32092;
+    pub const ADDRESS_INDEX_DISABLED: i64 = -32093;
+    pub const BLOCK_PRUNED: i64 = -32094;
+    pub const TRANSACTION_NOT_IN_MEMORY_POOL: i64 = -32095;
     pub const TRANSACTION_NOT_FOUND: i64 = -32096;
     pub const TRANSACTION_OUTPUT_NOT_FOUND: i64 = -32097;
     pub const TRANSACTION_OF_SIDE_BRANCH: i64 = -32098;
@@ -62,6 +67,38 @@ pub fn block_at_height_not_found<T: fmt::Debug>(data: T) -> Error {
     }
 }
 
+pub fn block_pruned<T: fmt::Debug>(data: T) -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::BLOCK_PRUNED),
+        message: "Block not available (pruned data)".into(),
+        data: Some(Value::String(format!("{:?}", data))),
+    }
+}
+
+pub fn block_filter_not_found<T: fmt::Debug>(data: T) -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::BLOCK_FILTER_NOT_FOUND),
+        message: "Block filter is not available".into(),
+        data: Some(Value::String(format!("{:?}", data))),
+    }
+}
+
+pub fn address_index_disabled() -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::ADDRESS_INDEX_DISABLED),
+        message: "Address index is not enabled".into(),
+        data: None,
+    }
+}
+
+pub fn mnemonic_export_disabled() -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::MNEMONIC_EXPORT_DISABLED),
+        message: "Wallet mnemonic export over RPC is not enabled".into(),
+        data: None,
+    }
+}
+
 pub fn transaction_not_found<T: fmt::Debug>(data: T) -> Error {
     Error {
         code: ErrorCode::ServerError(codes::TRANSACTION_NOT_FOUND),
@@ -70,6 +107,14 @@ pub fn transaction_not_found<T: fmt::Debug>(data: T) -> Error {
     }
 }
 
+pub fn transaction_not_in_memory_pool<T: fmt::Debug>(data: T) -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::TRANSACTION_NOT_IN_MEMORY_POOL),
+        message: "Transaction is not in memory pool".into(),
+        data: Some(Value::String(format!("{:?}", data))),
+    }
+}
+
 pub fn transaction_output_not_found<T: fmt::Debug>(data: T) -> Error {
     Error {
         code: ErrorCode::ServerError(codes::TRANSACTION_OUTPUT_NOT_FOUND),
@@ -1,23 +1,38 @@
 use chain::OutPoint;
+use global_script::Builder as ScriptBuilder;
 use global_script::Script;
 use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
 use keys::{self, Address};
+use miner::{MemoryPool, MemoryPoolOrderingStrategy};
 use network::Network;
 use primitives::hash::H256 as GlobalH256;
-use ser::serialize;
+use ser::{serialize, serialize_with_flags, SERIALIZE_TRANSACTION_WITNESS};
+use std::collections::BTreeMap;
+use storage::TransactionOutputProvider;
+use sync::{MemoryPoolRef, MemoryPoolTransactionOutputProvider};
 use v1::helpers::errors::{
-    block_at_height_not_found, block_not_found, transaction_not_found, transaction_of_side_branch,
+    address_index_disabled, block_at_height_not_found, block_filter_not_found, block_not_found,
+    block_pruned, execution, invalid_params, transaction_not_found, transaction_not_in_memory_pool, transaction_of_side_branch,
     transaction_output_not_found,
 };
 use v1::traits::BlockChain;
+use v1::types::{
+    AddressDelta, AddressIndexPage, AddressUtxo, GetAddressHistoryResponse, GetAddressUtxosResponse,
+};
+use v1::types::{BlockRangeBlock, BlockRangeTransaction};
 use v1::types::GetTxOutSetInfoResponse;
 use v1::types::H256;
-use v1::types::U256;
-use v1::types::{GetBlockResponse, RawBlock, VerboseBlock};
+use v1::types::{GetRawMemoryPoolResponse, MemoryPoolEntry, MemoryPoolInfo};
+use v1::types::{GetBlockFilterResponse, GetBlockResponse, RawBlock, VerboseBlock};
 use v1::types::{GetTxOutResponse, TransactionOutputScript};
 use verification;
-use {chain, storage};
+use {chain, miner, storage};
+
+/// Max number of blocks, returned by single getblockrange request
+pub const MAX_BLOCK_RANGE_COUNT: u32 = 1000;
+/// Max number of entries, returned by single getaddressutxos or getaddresshistory request
+const MAX_ADDRESS_INDEX_PAGE_COUNT: usize = 1000;
 
 pub struct BlockChainClient<T: BlockChainClientCoreApi> {
     core: T,
@@ -30,23 +45,191 @@ pub trait BlockChainClientCoreApi: Send + Sync + 'static {
     fn difficulty(&self) -> f64;
     fn raw_block(&self, hash: GlobalH256) -> Option<RawBlock>;
     fn verbose_block(&self, hash: GlobalH256) -> Option<VerboseBlock>;
-    fn verbose_transaction_out(&self, prev_out: OutPoint) -> Result<GetTxOutResponse, Error>;
+    fn is_block_pruned(&self, hash: GlobalH256) -> bool;
+    fn block_range(
+        &self,
+        from: u32,
+        count: u32,
+        with_transactions: bool,
+    ) -> Result<Vec<BlockRangeBlock>, Error>;
+    fn block_filter(&self, hash: GlobalH256) -> Result<GetBlockFilterResponse, Error>;
+    fn verbose_transaction_out(
+        &self,
+        prev_out: OutPoint,
+        include_mempool: bool,
+    ) -> Result<GetTxOutResponse, Error>;
+    fn transaction_out_set_info(&self) -> Result<GetTxOutSetInfoResponse, Error>;
+    fn raw_memory_pool(&self, verbose: bool) -> GetRawMemoryPoolResponse;
+    fn memory_pool_info(&self) -> MemoryPoolInfo;
+    fn memory_pool_entry(&self, hash: GlobalH256) -> Result<MemoryPoolEntry, Error>;
+    fn memory_pool_ancestors(
+        &self,
+        hash: GlobalH256,
+        verbose: bool,
+    ) -> Result<GetRawMemoryPoolResponse, Error>;
+    fn memory_pool_descendants(
+        &self,
+        hash: GlobalH256,
+        verbose: bool,
+    ) -> Result<GetRawMemoryPoolResponse, Error>;
+    fn address_utxos(
+        &self,
+        script_hash: GlobalH256,
+        from: usize,
+        count: usize,
+    ) -> Result<GetAddressUtxosResponse, Error>;
+    fn address_history(
+        &self,
+        script_hash: GlobalH256,
+        from: usize,
+        count: usize,
+    ) -> Result<GetAddressHistoryResponse, Error>;
 }
 
 pub struct BlockChainClientCore {
     network: Network,
     storage: storage::SharedStore,
+    memory_pool: MemoryPoolRef,
 }
 
 impl BlockChainClientCore {
-    pub fn new(network: Network, storage: storage::SharedStore) -> Self {
+    pub fn new(
+        network: Network,
+        storage: storage::SharedStore,
+        memory_pool: MemoryPoolRef,
+    ) -> Self {
         BlockChainClientCore {
             network: network,
             storage: storage,
+            memory_pool: memory_pool,
+        }
+    }
+
+    /// Height of side chain block is its distance from the canon chain fork point
+    fn side_block_height(&self, hash: &GlobalH256) -> Option<u32> {
+        let mut distance = 0;
+        let mut hash = hash.clone();
+        loop {
+            if let Some(number) = self.storage.block_number(&hash) {
+                return Some(number + distance);
+            }
+
+            hash = self.storage.block_header(hash.into())?.previous_header_hash;
+            distance += 1;
+        }
+    }
+
+    fn transaction_out_response(
+        &self,
+        transaction: &chain::Transaction,
+        index: u32,
+        bestblock: GlobalH256,
+        confirmations: u32,
+        valid: bool,
+    ) -> GetTxOutResponse {
+        let ref script_bytes = transaction.outputs[index as usize].script_pubkey;
+        let script: Script = script_bytes.clone().into();
+        let script_asm = format!("{}", script);
+        let script_addresses = script.extract_destinations().unwrap_or(vec![]);
+
+        GetTxOutResponse {
+            bestblock: bestblock.into(),
+            confirmations: confirmations,
+            value: 0.00000001f64 * (transaction.outputs[index as usize].value as f64),
+            script: TransactionOutputScript {
+                asm: script_asm,
+                hex: script_bytes.clone().into(),
+                req_sigs: script.num_signatures_required() as u32,
+                script_type: script.script_type().into(),
+                addresses: script_addresses
+                    .into_iter()
+                    .map(|a| Address {
+                        network: match self.network {
+                            Network::Mainnet => keys::Network::Mainnet,
+                            // there's no correct choices for Regtests && Other networks
+                            // => let's just make Testnet key
+                            _ => keys::Network::Testnet,
+                        },
+                        hash: a.hash,
+                        kind: a.kind,
+                    })
+                    .collect(),
+            },
+            version: transaction.version,
+            coinbase: transaction.is_coinbase(),
+            valid: valid,
         }
     }
 }
 
+/// Memory pool entry with ancestors and descendants information, as bitcoind reports it
+fn memory_pool_entry(memory_pool: &MemoryPool, entry: &miner::MemoryPoolEntry) -> MemoryPoolEntry {
+    let modified_fee = |entry: &miner::MemoryPoolEntry| entry.miner_fee as i64 + entry.miner_virtual_fee;
+    let ancestors = memory_pool.ancestors(&entry.hash).unwrap_or_default();
+    let descendants = memory_pool.descendants(&entry.hash).unwrap_or_default();
+
+    let mut depends: Vec<H256> = ancestors
+        .iter()
+        .filter(|ancestor| {
+            entry
+                .transaction
+                .inputs
+                .iter()
+                .any(|input| input.previous_output.hash == ancestor.hash)
+        })
+        .map(|ancestor| ancestor.hash.clone().into())
+        .collect();
+    depends.sort();
+    let mut spentby: Vec<H256> = descendants
+        .iter()
+        .filter(|descendant| {
+            descendant
+                .transaction
+                .inputs
+                .iter()
+                .any(|input| input.previous_output.hash == entry.hash)
+        })
+        .map(|descendant| descendant.hash.clone().into())
+        .collect();
+    spentby.sort();
+
+    MemoryPoolEntry {
+        size: entry.size,
+        fee: 0.00000001f64 * (entry.miner_fee as f64),
+        modifiedfee: 0.00000001f64 * (modified_fee(entry) as f64),
+        descendantcount: descendants.len() + 1,
+        descendantsize: entry.size + descendants.iter().map(|d| d.size).sum::<usize>(),
+        descendantfees: modified_fee(entry) + descendants.iter().map(|d| modified_fee(*d)).sum::<i64>(),
+        ancestorcount: ancestors.len() + 1,
+        ancestorsize: entry.size + ancestors.iter().map(|a| a.size).sum::<usize>(),
+        ancestorfees: modified_fee(entry) + ancestors.iter().map(|a| modified_fee(*a)).sum::<i64>(),
+        depends: depends,
+        spentby: spentby,
+    }
+}
+
+fn memory_pool_response(
+    memory_pool: &MemoryPool,
+    entries: Vec<&miner::MemoryPoolEntry>,
+    verbose: bool,
+) -> GetRawMemoryPoolResponse {
+    if verbose {
+        GetRawMemoryPoolResponse::Verbose(
+            entries
+                .into_iter()
+                .map(|entry| (entry.hash.clone().into(), memory_pool_entry(memory_pool, entry)))
+                .collect(),
+        )
+    } else {
+        GetRawMemoryPoolResponse::Hashes(
+            entries
+                .into_iter()
+                .map(|entry| entry.hash.clone().into())
+                .collect(),
+        )
+    }
+}
+
 impl BlockChainClientCoreApi for BlockChainClientCore {
     fn best_block_hash(&self) -> GlobalH256 {
         self.storage.best_block().hash
@@ -73,28 +256,34 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
     fn verbose_block(&self, hash: GlobalH256) -> Option<VerboseBlock> {
         self.storage.block(hash.into()).map(|block| {
             let block: chain::IndexedBlock = block.into();
-            let height = self.storage.block_number(block.hash());
-            let confirmations = match height {
+            let canon_height = self.storage.block_number(block.hash());
+            let confirmations = match canon_height {
                 Some(block_number) => (self.storage.best_block().number - block_number + 1) as i64,
                 None => -1,
             };
-            let block_size = block.size();
-            let median_time = verification::median_timestamp(
-                &block.header.raw,
+            let stripped_size = block.size();
+            let size = block.size_with_witness();
+            let median_time = verification::median_timestamp_inclusive(
+                block.hash().clone(),
                 self.storage.as_block_header_provider(),
             );
 
             VerboseBlock {
                 confirmations: confirmations,
-                size: block_size as u32,
-                strippedsize: block_size as u32, // TODO: segwit
-                weight: block_size as u32,       // TODO: segwit
-                height: height,
+                size: size as u32,
+                strippedsize: stripped_size as u32,
+                weight: (stripped_size * 3 + size) as u32,
+                height: canon_height.or_else(|| self.side_block_height(block.hash())),
                 mediantime: Some(median_time),
                 difficulty: block.header.raw.bits.to_f64(),
-                chainwork: U256::default(), // TODO: read from storage
+                chainwork: self
+                    .storage
+                    .block_chain_work(block.hash())
+                    .map(Into::into)
+                    .unwrap_or_default(),
                 previousblockhash: Some(block.header.raw.previous_header_hash.clone().into()),
-                nextblockhash: height
+                // only canon blocks have next block
+                nextblockhash: canon_height
                     .and_then(|h| self.storage.block_hash(h + 1).map(|h| h.into())),
                 bits: block.header.raw.bits.into(),
                 hash: block.hash().clone().into(),
@@ -112,11 +301,111 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
         })
     }
 
-    fn verbose_transaction_out(&self, prev_out: OutPoint) -> Result<GetTxOutResponse, Error> {
+    fn is_block_pruned(&self, hash: GlobalH256) -> bool {
+        self.storage.is_pruned(hash.into())
+    }
+
+    fn block_range(
+        &self,
+        from: u32,
+        count: u32,
+        with_transactions: bool,
+    ) -> Result<Vec<BlockRangeBlock>, Error> {
+        let mut blocks = Vec::new();
+        for height in from..from.saturating_add(count) {
+            let block = match self.storage.indexed_block(height.into()) {
+                Some(block) => block,
+                None if self.storage.is_pruned(height.into()) => {
+                    return Err(block_pruned(height))
+                }
+                None => break,
+            };
+
+            let tx = block
+                .transactions
+                .iter()
+                .map(|tx| {
+                    // transactions of blocks, verified in lazy mode, could be canonized as invalid
+                    let valid = self
+                        .storage
+                        .transaction_meta(&tx.hash)
+                        .map_or(true, |meta| meta.is_valid());
+                    BlockRangeTransaction {
+                        txid: tx.hash.clone().into(),
+                        coinbase: tx.raw.is_coinbase(),
+                        valid: valid,
+                        error: if valid {
+                            None
+                        } else {
+                            self.storage
+                                .transaction_error(&tx.hash)
+                                .map(|error| format!("{:?}", error))
+                        },
+                        hex: if with_transactions {
+                            Some(serialize_with_flags(&tx.raw, SERIALIZE_TRANSACTION_WITNESS).into())
+                        } else {
+                            None
+                        },
+                    }
+                })
+                .collect();
+
+            blocks.push(BlockRangeBlock {
+                hash: block.hash().clone().into(),
+                height: height,
+                time: block.header.raw.time,
+                previousblockhash: block.header.raw.previous_header_hash.clone().into(),
+                tx: tx,
+            });
+        }
+        Ok(blocks)
+    }
+
+    fn verbose_transaction_out(
+        &self,
+        prev_out: OutPoint,
+        include_mempool: bool,
+    ) -> Result<GetTxOutResponse, Error> {
+        // spent outputs are not a part of the UTXO set
+        let is_spent = if include_mempool {
+            MemoryPoolTransactionOutputProvider::for_outpoints(
+                self.storage.clone(),
+                &self.memory_pool,
+                &[prev_out.clone()],
+            )
+            .is_spent(&prev_out)
+        } else {
+            self.storage.as_transaction_output_provider().is_spent(&prev_out)
+        };
+        if is_spent {
+            return Err(transaction_output_not_found(prev_out));
+        }
+
         let transaction = match self.storage.transaction(&prev_out.hash) {
             Some(transaction) => transaction,
-            // no transaction => no response
-            None => return Err(transaction_not_found(prev_out.hash)),
+            None => {
+                let mempool_transaction = if include_mempool {
+                    self.memory_pool.read().read_by_hash(&prev_out.hash).cloned()
+                } else {
+                    None
+                };
+
+                return match mempool_transaction {
+                    Some(ref transaction) if prev_out.index >= transaction.outputs.len() as u32 => {
+                        Err(transaction_output_not_found(prev_out))
+                    }
+                    // unconfirmed transaction output
+                    Some(ref transaction) => Ok(self.transaction_out_response(
+                        transaction,
+                        prev_out.index,
+                        self.storage.best_block().hash,
+                        0,
+                        true,
+                    )),
+                    // no transaction => no response
+                    None => Err(transaction_not_found(prev_out.hash)),
+                };
+            }
         };
 
         if prev_out.index >= transaction.outputs.len() as u32 {
@@ -141,36 +430,195 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
             return Err(transaction_not_found(prev_out.hash));
         }
 
-        let ref script_bytes = transaction.outputs[prev_out.index as usize].script_pubkey;
-        let script: Script = script_bytes.clone().into();
-        let script_asm = format!("{}", script);
-        let script_addresses = script.extract_destinations().unwrap_or(vec![]);
+        Ok(self.transaction_out_response(
+            &transaction,
+            prev_out.index,
+            block_header.hash(),
+            best_block.number - meta.height() + 1,
+            meta.is_valid(),
+        ))
+    }
 
-        Ok(GetTxOutResponse {
-            bestblock: block_header.hash().into(),
-            confirmations: best_block.number - meta.height() + 1,
-            value: 0.00000001f64 * (transaction.outputs[prev_out.index as usize].value as f64),
-            script: TransactionOutputScript {
-                asm: script_asm,
-                hex: script_bytes.clone().into(),
-                req_sigs: script.num_signatures_required() as u32,
-                script_type: script.script_type().into(),
-                addresses: script_addresses
-                    .into_iter()
-                    .map(|a| Address {
-                        network: match self.network {
-                            Network::Mainnet => keys::Network::Mainnet,
-                            // there's no correct choices for Regtests && Other networks
-                            // => let's just make Testnet key
-                            _ => keys::Network::Testnet,
-                        },
-                        hash: a.hash,
-                        kind: a.kind,
-                    })
-                    .collect(),
-            },
-            version: transaction.version,
-            coinbase: transaction.is_coinbase(),
+    fn transaction_out_set_info(&self) -> Result<GetTxOutSetInfoResponse, Error> {
+        let best_block = self.storage.best_block();
+        let stats = match self.storage.utxo_stats() {
+            Some(stats) => stats,
+            // database has been created before statistics were maintained
+            None => {
+                return Err(execution(
+                    "UTXO set statistics are missing, blockchain resync is required",
+                ))
+            }
+        };
+
+        Ok(GetTxOutSetInfoResponse {
+            height: best_block.number,
+            bestblock: best_block.hash.into(),
+            txouts: stats.outputs,
+            bogosize: stats.bogosize,
+            muhash: stats.hash().into(),
+            total_amount: 0.00000001f64 * (stats.total_amount as f64),
+        })
+    }
+
+    fn raw_memory_pool(&self, verbose: bool) -> GetRawMemoryPoolResponse {
+        let memory_pool = self.memory_pool.read();
+        let entries = memory_pool
+            .iter(MemoryPoolOrderingStrategy::ByTimestamp)
+            .collect();
+        memory_pool_response(&memory_pool, entries, verbose)
+    }
+
+    fn memory_pool_info(&self) -> MemoryPoolInfo {
+        let information = self.memory_pool.read().information();
+        MemoryPoolInfo {
+            size: information.transactions_count,
+            bytes: information.transactions_size_in_bytes,
+            usage: information.memory_usage_in_bytes,
+        }
+    }
+
+    fn memory_pool_entry(&self, hash: GlobalH256) -> Result<MemoryPoolEntry, Error> {
+        let memory_pool = self.memory_pool.read();
+        memory_pool
+            .entry(&hash)
+            .map(|entry| memory_pool_entry(&memory_pool, entry))
+            .ok_or(transaction_not_in_memory_pool(hash))
+    }
+
+    fn memory_pool_ancestors(
+        &self,
+        hash: GlobalH256,
+        verbose: bool,
+    ) -> Result<GetRawMemoryPoolResponse, Error> {
+        let memory_pool = self.memory_pool.read();
+        memory_pool
+            .ancestors(&hash)
+            .map(|ancestors| memory_pool_response(&memory_pool, ancestors, verbose))
+            .ok_or(transaction_not_in_memory_pool(hash))
+    }
+
+    fn memory_pool_descendants(
+        &self,
+        hash: GlobalH256,
+        verbose: bool,
+    ) -> Result<GetRawMemoryPoolResponse, Error> {
+        let memory_pool = self.memory_pool.read();
+        memory_pool
+            .descendants(&hash)
+            .map(|descendants| memory_pool_response(&memory_pool, descendants, verbose))
+            .ok_or(transaction_not_in_memory_pool(hash))
+    }
+
+    fn block_filter(&self, hash: GlobalH256) -> Result<GetBlockFilterResponse, Error> {
+        match self.storage.block_filter(&hash) {
+            Some(filter) => Ok(GetBlockFilterResponse {
+                filter: filter.filter.into(),
+                header: filter.header.into(),
+            }),
+            // filters are only built for canonized blocks of databases, which have all filters since genesis
+            None if self.storage.block_header(hash.clone().into()).is_some() => {
+                Err(block_filter_not_found(hash.reversed()))
+            }
+            None => Err(block_not_found(hash.reversed())),
+        }
+    }
+
+    fn address_utxos(
+        &self,
+        script_hash: GlobalH256,
+        from: usize,
+        count: usize,
+    ) -> Result<GetAddressUtxosResponse, Error> {
+        if !self.storage.has_address_index() {
+            return Err(address_index_disabled());
+        }
+
+        // every spending entry spends one of the funding entries
+        let stats = self.storage.address_stats(&script_hash);
+        let storage = &self.storage;
+        let utxos = storage
+            .address_funding(&script_hash)
+            .filter(|funding| {
+                // meta of the fully spent transaction could be pruned
+                storage
+                    .transaction_meta(&funding.outpoint.hash)
+                    .and_then(|meta| meta.is_spent(funding.outpoint.index as usize))
+                    == Some(false)
+            })
+            .skip(from)
+            .take(count)
+            .map(|funding| AddressUtxo {
+                txid: funding.outpoint.hash.into(),
+                vout: funding.outpoint.index,
+                satoshis: funding.value,
+                height: funding.height,
+            })
+            .collect();
+        Ok(GetAddressUtxosResponse {
+            total: stats.funding.saturating_sub(stats.spending) as usize,
+            utxos: utxos,
+        })
+    }
+
+    fn address_history(
+        &self,
+        script_hash: GlobalH256,
+        from: usize,
+        count: usize,
+    ) -> Result<GetAddressHistoryResponse, Error> {
+        if !self.storage.has_address_index() {
+            return Err(address_index_disabled());
+        }
+
+        let stats = self.storage.address_stats(&script_hash);
+        let mut funding = self
+            .storage
+            .address_funding(&script_hash)
+            .map(|funding| AddressDelta {
+                txid: funding.outpoint.hash.into(),
+                index: funding.outpoint.index,
+                satoshis: funding.value as i64,
+                height: funding.height,
+            })
+            .peekable();
+        let mut spending = self
+            .storage
+            .address_spending(&script_hash)
+            .map(|spending| AddressDelta {
+                txid: spending.spender.into(),
+                index: spending.input_index,
+                satoshis: -(spending.value as i64),
+                height: spending.height,
+            })
+            .peekable();
+
+        // both iterators are in the chain order => merge them by height, funding entries first
+        let mut to_skip = from;
+        let mut history = Vec::new();
+        while history.len() < count {
+            let is_funding_next = match (funding.peek(), spending.peek()) {
+                (Some(funding), Some(spending)) => funding.height <= spending.height,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            let delta = if is_funding_next {
+                funding.next()
+            } else {
+                spending.next()
+            }
+            .expect("peeked above; qed");
+            if to_skip != 0 {
+                to_skip -= 1;
+            } else {
+                history.push(delta);
+            }
+        }
+
+        Ok(GetAddressHistoryResponse {
+            total: (stats.funding + stats.spending) as usize,
+            history: history,
         })
     }
 }
@@ -227,22 +675,85 @@ where
                 .raw_block(global_hash.reversed())
                 .map(|block| GetBlockResponse::Raw(block))
         }
-        .ok_or(block_not_found(hash))
+        .ok_or_else(|| {
+            if self.core.is_block_pruned(global_hash.reversed()) {
+                block_pruned(hash)
+            } else {
+                block_not_found(hash)
+            }
+        })
+    }
+
+    fn block_range(
+        &self,
+        from: u32,
+        count: u32,
+        verbosity: Trailing<u8>,
+    ) -> Result<Vec<BlockRangeBlock>, Error> {
+        let verbosity = verbosity.unwrap_or_default();
+        if verbosity > 1 {
+            return Err(invalid_params("verbosity", verbosity));
+        }
+        if count > MAX_BLOCK_RANGE_COUNT {
+            return Err(invalid_params(
+                "count",
+                format!("at most {} blocks could be requested", MAX_BLOCK_RANGE_COUNT),
+            ));
+        }
+        if from > self.core.block_count() {
+            return Err(block_at_height_not_found(from));
+        }
+
+        Ok(self
+            .core
+            .block_range(from, count, verbosity == 1)?
+            .into_iter()
+            .map(reversed_block_range_block)
+            .collect())
+    }
+
+    fn block_filter(
+        &self,
+        hash: H256,
+        filter_type: Trailing<String>,
+    ) -> Result<GetBlockFilterResponse, Error> {
+        let filter_type: Option<String> = filter_type.into();
+        if let Some(filter_type) = filter_type {
+            if filter_type != "basic" {
+                return Err(invalid_params(
+                    "filtertype",
+                    format!("unsupported filter type: {}", filter_type),
+                ));
+            }
+        }
+
+        let global_hash: GlobalH256 = hash.into();
+        self.core
+            .block_filter(global_hash.reversed())
+            .map(|mut response| {
+                response.header = response.header.reversed();
+                response
+            })
     }
 
     fn transaction_out(
         &self,
         transaction_hash: H256,
         out_index: u32,
-        _include_mempool: Trailing<bool>,
+        include_mempool: Trailing<bool>,
     ) -> Result<GetTxOutResponse, Error> {
-        // TODO: include_mempool
+        // bitcoind also looks into memory pool by default
+        let include_mempool: Option<bool> = include_mempool.into();
+        let include_mempool = include_mempool.unwrap_or(true);
         let transaction_hash: GlobalH256 = transaction_hash.into();
         self.core
-            .verbose_transaction_out(OutPoint {
-                hash: transaction_hash.reversed(),
-                index: out_index,
-            })
+            .verbose_transaction_out(
+                OutPoint {
+                    hash: transaction_hash.reversed(),
+                    index: out_index,
+                },
+                include_mempool,
+            )
             .map(|mut response| {
                 response.bestblock = response.bestblock.reversed();
                 response
@@ -250,30 +761,165 @@ where
     }
 
     fn transaction_out_set_info(&self) -> Result<GetTxOutSetInfoResponse, Error> {
-        rpc_unimplemented!()
+        self.core.transaction_out_set_info().map(|mut response| {
+            response.bestblock = response.bestblock.reversed();
+            response.muhash = response.muhash.reversed();
+            response
+        })
+    }
+
+    fn raw_memory_pool(&self, verbose: Trailing<bool>) -> Result<GetRawMemoryPoolResponse, Error> {
+        Ok(reversed_memory_pool_response(
+            self.core.raw_memory_pool(verbose.unwrap_or_default()),
+        ))
+    }
+
+    fn memory_pool_info(&self) -> Result<MemoryPoolInfo, Error> {
+        Ok(self.core.memory_pool_info())
+    }
+
+    fn memory_pool_entry(&self, hash: H256) -> Result<MemoryPoolEntry, Error> {
+        let global_hash: GlobalH256 = hash.into();
+        self.core
+            .memory_pool_entry(global_hash.reversed())
+            .map(reversed_memory_pool_entry)
+    }
+
+    fn memory_pool_ancestors(
+        &self,
+        hash: H256,
+        verbose: Trailing<bool>,
+    ) -> Result<GetRawMemoryPoolResponse, Error> {
+        let global_hash: GlobalH256 = hash.into();
+        self.core
+            .memory_pool_ancestors(global_hash.reversed(), verbose.unwrap_or_default())
+            .map(reversed_memory_pool_response)
+    }
+
+    fn memory_pool_descendants(
+        &self,
+        hash: H256,
+        verbose: Trailing<bool>,
+    ) -> Result<GetRawMemoryPoolResponse, Error> {
+        let global_hash: GlobalH256 = hash.into();
+        self.core
+            .memory_pool_descendants(global_hash.reversed(), verbose.unwrap_or_default())
+            .map(reversed_memory_pool_response)
+    }
+
+    fn address_utxos(
+        &self,
+        address: String,
+        page: Trailing<AddressIndexPage>,
+    ) -> Result<GetAddressUtxosResponse, Error> {
+        let script_hash = address_script_hash(&address)?;
+        let (from, count) = address_index_page(page.unwrap_or_default())?;
+        let mut response = self.core.address_utxos(script_hash, from, count)?;
+        for utxo in &mut response.utxos {
+            utxo.txid = utxo.txid.reversed();
+        }
+        Ok(response)
+    }
+
+    fn address_history(
+        &self,
+        address: String,
+        page: Trailing<AddressIndexPage>,
+    ) -> Result<GetAddressHistoryResponse, Error> {
+        let script_hash = address_script_hash(&address)?;
+        let (from, count) = address_index_page(page.unwrap_or_default())?;
+        let mut response = self.core.address_history(script_hash, from, count)?;
+        for delta in &mut response.history {
+            delta.txid = delta.txid.reversed();
+        }
+        Ok(response)
+    }
+}
+
+/// Hash of the address output script, which is the key of the address index
+fn address_script_hash(address: &str) -> Result<GlobalH256, Error> {
+    let address: Address = address.parse().map_err(|e| invalid_params("address", e))?;
+    let script = ScriptBuilder::build_address(&address)
+        .ok_or_else(|| invalid_params("address", "Address type does not match its payload"))?;
+    Ok(storage::script_hash(&script.to_bytes()))
+}
+
+/// Returns number of entries to skip and max number of entries to return
+fn address_index_page(page: AddressIndexPage) -> Result<(usize, usize), Error> {
+    let count = page.count.unwrap_or(MAX_ADDRESS_INDEX_PAGE_COUNT);
+    if count > MAX_ADDRESS_INDEX_PAGE_COUNT {
+        return Err(invalid_params(
+            "count",
+            format!("at most {} entries could be requested", MAX_ADDRESS_INDEX_PAGE_COUNT),
+        ));
+    }
+    Ok((page.from, count))
+}
+
+fn reversed_block_range_block(mut block: BlockRangeBlock) -> BlockRangeBlock {
+    block.hash = block.hash.reversed();
+    block.previousblockhash = block.previousblockhash.reversed();
+    for tx in &mut block.tx {
+        tx.txid = tx.txid.reversed();
+    }
+    block
+}
+
+fn reversed_memory_pool_entry(mut entry: MemoryPoolEntry) -> MemoryPoolEntry {
+    entry.depends = entry.depends.into_iter().map(|h| h.reversed()).collect();
+    entry.spentby = entry.spentby.into_iter().map(|h| h.reversed()).collect();
+    entry
+}
+
+fn reversed_memory_pool_response(response: GetRawMemoryPoolResponse) -> GetRawMemoryPoolResponse {
+    match response {
+        GetRawMemoryPoolResponse::Hashes(hashes) => {
+            GetRawMemoryPoolResponse::Hashes(hashes.into_iter().map(|h| h.reversed()).collect())
+        }
+        GetRawMemoryPoolResponse::Verbose(entries) => GetRawMemoryPoolResponse::Verbose(
+            entries
+                .into_iter()
+                .map(|(h, entry)| (h.reversed(), reversed_memory_pool_entry(entry)))
+                .collect::<BTreeMap<_, _>>(),
+        ),
     }
 }
 
 #[cfg(test)]
 pub mod tests {
+    extern crate parking_lot;
     extern crate test_data;
 
     use super::*;
     use chain::OutPoint;
+    use db::kv::MemoryDatabase;
     use db::BlockChainDatabase;
     use jsonrpc_core::Error;
     use jsonrpc_core::IoHandler;
+    use miner::{MemoryPool, NonZeroFeeCalculator};
     use network::Network;
     use primitives::bytes::Bytes as GlobalBytes;
     use primitives::hash::H256 as GlobalH256;
+    use ser::serialize;
     use std::sync::Arc;
-    use v1::helpers::errors::block_not_found;
+    use v1::helpers::errors::{
+        address_index_disabled, block_filter_not_found, block_not_found, execution,
+        transaction_not_found, transaction_not_in_memory_pool, transaction_output_not_found,
+    };
     use v1::traits::BlockChain;
     use v1::types::Bytes;
     use v1::types::ScriptType;
     use v1::types::H256;
-    use v1::types::{GetTxOutResponse, TransactionOutputScript};
-    use v1::types::{RawBlock, VerboseBlock};
+    use v1::types::{GetRawMemoryPoolResponse, MemoryPoolEntry, MemoryPoolInfo};
+    use v1::types::{GetTxOutResponse, GetTxOutSetInfoResponse, TransactionOutputScript};
+    use v1::types::{BlockRangeBlock, BlockRangeTransaction, RawBlock, VerboseBlock};
+    use v1::types::{
+        AddressDelta, AddressUtxo, GetAddressHistoryResponse, GetAddressUtxosResponse,
+    };
+
+    fn memory_pool() -> MemoryPoolRef {
+        Arc::new(parking_lot::RwLock::new(MemoryPool::new()))
+    }
 
     #[derive(Default)]
     struct SuccessBlockChainClientCore;
@@ -331,7 +977,48 @@ pub mod tests {
             })
         }
 
-        fn verbose_transaction_out(&self, _prev_out: OutPoint) -> Result<GetTxOutResponse, Error> {
+        fn is_block_pruned(&self, _hash: GlobalH256) -> bool {
+            false
+        }
+
+        fn block_range(
+            &self,
+            from: u32,
+            _count: u32,
+            with_transactions: bool,
+        ) -> Result<Vec<BlockRangeBlock>, Error> {
+            Ok(vec![BlockRangeBlock {
+                hash: "bddd99ccfda39da1b108ce1a5d70038d0a967bacb68b6b63065f626a00000000".into(),
+                height: from,
+                time: 1231469744,
+                previousblockhash: "4860eb18bf1b1620e37e9490fc8a427514416fd75159ab86688e9a8300000000"
+                    .into(),
+                tx: vec![BlockRangeTransaction {
+                    txid: "d5fdcc541e25de1c7a5addedf24858b8bb665c9f36ef744ee42c316022c90f9b".into(),
+                    coinbase: true,
+                    valid: true,
+                    error: None,
+                    hex: if with_transactions {
+                        Some(Bytes::new(vec![1, 2, 3, 4]))
+                    } else {
+                        None
+                    },
+                }],
+            }])
+        }
+
+        fn block_filter(&self, _hash: GlobalH256) -> Result<GetBlockFilterResponse, Error> {
+            Ok(GetBlockFilterResponse {
+                filter: Bytes::new(vec![0x01, 0x9d, 0xfc, 0xa8]),
+                header: H256::from(0x21),
+            })
+        }
+
+        fn verbose_transaction_out(
+            &self,
+            _prev_out: OutPoint,
+            _include_mempool: bool,
+        ) -> Result<GetTxOutResponse, Error> {
             Ok(GetTxOutResponse {
                 bestblock: H256::from(0x56),
                 confirmations: 777,
@@ -348,6 +1035,114 @@ pub mod tests {
                 },
                 version: 33,
                 coinbase: false,
+                valid: true,
+            })
+        }
+
+        fn transaction_out_set_info(&self) -> Result<GetTxOutSetInfoResponse, Error> {
+            Ok(GetTxOutSetInfoResponse {
+                height: 2,
+                bestblock: H256::from(0x56),
+                txouts: 3,
+                bogosize: 159,
+                muhash: H256::from(0x78),
+                total_amount: 150.5,
+            })
+        }
+
+        fn raw_memory_pool(&self, _verbose: bool) -> GetRawMemoryPoolResponse {
+            GetRawMemoryPoolResponse::Hashes(vec![H256::from(0x56)])
+        }
+
+        fn memory_pool_info(&self) -> MemoryPoolInfo {
+            MemoryPoolInfo {
+                size: 1,
+                bytes: 85,
+                usage: 1024,
+            }
+        }
+
+        fn memory_pool_entry(&self, _hash: GlobalH256) -> Result<MemoryPoolEntry, Error> {
+            Ok(MemoryPoolEntry {
+                size: 85,
+                fee: 0.0001,
+                modifiedfee: 0.0001,
+                descendantcount: 1,
+                descendantsize: 85,
+                descendantfees: 10000,
+                ancestorcount: 2,
+                ancestorsize: 170,
+                ancestorfees: 20000,
+                depends: vec![H256::from(0x56)],
+                spentby: vec![],
+            })
+        }
+
+        fn memory_pool_ancestors(
+            &self,
+            _hash: GlobalH256,
+            _verbose: bool,
+        ) -> Result<GetRawMemoryPoolResponse, Error> {
+            Ok(GetRawMemoryPoolResponse::Hashes(vec![H256::from(0x56)]))
+        }
+
+        fn memory_pool_descendants(
+            &self,
+            _hash: GlobalH256,
+            _verbose: bool,
+        ) -> Result<GetRawMemoryPoolResponse, Error> {
+            Ok(GetRawMemoryPoolResponse::Hashes(vec![]))
+        }
+
+        fn address_utxos(
+            &self,
+            _script_hash: GlobalH256,
+            from: usize,
+            count: usize,
+        ) -> Result<GetAddressUtxosResponse, Error> {
+            let utxos = vec![
+                AddressUtxo {
+                    txid: H256::from(0x56),
+                    vout: 0,
+                    satoshis: 5000,
+                    height: 10,
+                },
+                AddressUtxo {
+                    txid: H256::from(0x78),
+                    vout: 1,
+                    satoshis: 7000,
+                    height: 12,
+                },
+            ];
+            Ok(GetAddressUtxosResponse {
+                total: utxos.len(),
+                utxos: utxos.into_iter().skip(from).take(count).collect(),
+            })
+        }
+
+        fn address_history(
+            &self,
+            _script_hash: GlobalH256,
+            from: usize,
+            count: usize,
+        ) -> Result<GetAddressHistoryResponse, Error> {
+            let history = vec![
+                AddressDelta {
+                    txid: H256::from(0x56),
+                    index: 0,
+                    satoshis: 5000,
+                    height: 10,
+                },
+                AddressDelta {
+                    txid: H256::from(0x78),
+                    index: 0,
+                    satoshis: -5000,
+                    height: 12,
+                },
+            ];
+            Ok(GetAddressHistoryResponse {
+                total: history.len(),
+                history: history.into_iter().skip(from).take(count).collect(),
             })
         }
     }
@@ -377,16 +1172,89 @@ pub mod tests {
             None
         }
 
-        fn verbose_transaction_out(&self, prev_out: OutPoint) -> Result<GetTxOutResponse, Error> {
+        fn is_block_pruned(&self, _hash: GlobalH256) -> bool {
+            false
+        }
+
+        fn block_range(
+            &self,
+            _from: u32,
+            _count: u32,
+            _with_transactions: bool,
+        ) -> Result<Vec<BlockRangeBlock>, Error> {
+            Ok(vec![])
+        }
+
+        fn block_filter(&self, hash: GlobalH256) -> Result<GetBlockFilterResponse, Error> {
+            Err(block_filter_not_found(hash.reversed()))
+        }
+
+        fn verbose_transaction_out(
+            &self,
+            prev_out: OutPoint,
+            _include_mempool: bool,
+        ) -> Result<GetTxOutResponse, Error> {
             Err(block_not_found(prev_out.hash))
         }
-    }
 
-    #[test]
-    fn best_block_hash_success() {
-        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
-        let mut handler = IoHandler::new();
-        handler.extend_with(client.to_delegate());
+        fn transaction_out_set_info(&self) -> Result<GetTxOutSetInfoResponse, Error> {
+            Err(execution(
+                "UTXO set statistics are missing, blockchain resync is required",
+            ))
+        }
+
+        fn raw_memory_pool(&self, _verbose: bool) -> GetRawMemoryPoolResponse {
+            GetRawMemoryPoolResponse::Hashes(vec![])
+        }
+
+        fn memory_pool_info(&self) -> MemoryPoolInfo {
+            MemoryPoolInfo::default()
+        }
+
+        fn memory_pool_entry(&self, hash: GlobalH256) -> Result<MemoryPoolEntry, Error> {
+            Err(transaction_not_in_memory_pool(hash))
+        }
+
+        fn memory_pool_ancestors(
+            &self,
+            hash: GlobalH256,
+            _verbose: bool,
+        ) -> Result<GetRawMemoryPoolResponse, Error> {
+            Err(transaction_not_in_memory_pool(hash))
+        }
+
+        fn memory_pool_descendants(
+            &self,
+            hash: GlobalH256,
+            _verbose: bool,
+        ) -> Result<GetRawMemoryPoolResponse, Error> {
+            Err(transaction_not_in_memory_pool(hash))
+        }
+
+        fn address_utxos(
+            &self,
+            _script_hash: GlobalH256,
+            _from: usize,
+            _count: usize,
+        ) -> Result<GetAddressUtxosResponse, Error> {
+            Err(address_index_disabled())
+        }
+
+        fn address_history(
+            &self,
+            _script_hash: GlobalH256,
+            _from: usize,
+            _count: usize,
+        ) -> Result<GetAddressHistoryResponse, Error> {
+            Err(address_index_disabled())
+        }
+    }
+
+    #[test]
+    fn best_block_hash_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
 
         let sample = handler
             .handle_request_sync(
@@ -499,7 +1367,7 @@ pub mod tests {
             test_data::block_h2().into(),
         ]));
 
-        let core = BlockChainClientCore::new(Network::Mainnet, storage);
+        let core = BlockChainClientCore::new(Network::Mainnet, storage, memory_pool());
 
         // get info on block #1:
         // https://blockexplorer.com/block/00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048
@@ -515,7 +1383,7 @@ pub mod tests {
                 confirmations: 2, // h1 + h2
                 size: 215,
                 strippedsize: 215,
-                weight: 215,
+                weight: 860,
                 height: Some(1),
                 version: 1,
                 version_hex: "1".to_owned(),
@@ -523,11 +1391,11 @@ pub mod tests {
                     .into(),
                 tx: vec!["982051fd1e4ba744bbbe680e1fee14677ba1a3c3540bf7b1cdb606e857233e0e".into()],
                 time: 1231469665,
-                mediantime: Some(1231006505),
+                mediantime: Some(1231469665),
                 nonce: 2573394689,
                 bits: 486604799,
                 difficulty: 1.0,
-                chainwork: 0.into(),
+                chainwork: 0x200020002u64.into(),
                 previousblockhash: Some(
                     "6fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000".into()
                 ),
@@ -551,7 +1419,7 @@ pub mod tests {
                 confirmations: 1, // h2
                 size: 215,
                 strippedsize: 215,
-                weight: 215,
+                weight: 860,
                 height: Some(2),
                 version: 1,
                 version_hex: "1".to_owned(),
@@ -563,7 +1431,7 @@ pub mod tests {
                 nonce: 1639830024,
                 bits: 486604799,
                 difficulty: 1.0,
-                chainwork: 0.into(),
+                chainwork: 0x300030003u64.into(),
                 previousblockhash: Some(
                     "4860eb18bf1b1620e37e9490fc8a427514416fd75159ab86688e9a8300000000".into()
                 ),
@@ -572,6 +1440,32 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn verbose_block_side_chain_contents() {
+        let storage = BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+            test_data::block_h1().into(),
+        ]);
+        let side_block: chain::IndexedBlock = test_data::block_builder()
+            .transaction()
+            .coinbase()
+            .build()
+            .merkled_header()
+            .parent(test_data::genesis().hash())
+            .build()
+            .build()
+            .into();
+        storage.insert(side_block.clone()).unwrap();
+        let core = BlockChainClientCore::new(Network::Mainnet, Arc::new(storage), memory_pool());
+
+        let verbose_block = core.verbose_block(side_block.hash().clone()).unwrap();
+        assert_eq!(verbose_block.confirmations, -1);
+        assert_eq!(verbose_block.height, Some(1));
+        assert_eq!(verbose_block.nextblockhash, None);
+        // genesis block work + minimal work of the side block
+        assert_eq!(verbose_block.chainwork, 0x100010002u64.into());
+    }
+
     #[test]
     fn raw_block_success() {
         let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
@@ -647,7 +1541,7 @@ pub mod tests {
             )
             .unwrap();
 
-        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"bits":486604799,"chainwork":"0","confirmations":1,"difficulty":1.0,"hash":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd","height":2,"mediantime":null,"merkleroot":"9b0fc92260312ce44e74ef369f5c66bbb85848f2eddd5a7a1cde251e54ccfdd5","nextblockhash":null,"nonce":1639830024,"previousblockhash":"00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048","size":215,"strippedsize":215,"time":1231469744,"tx":["9b0fc92260312ce44e74ef369f5c66bbb85848f2eddd5a7a1cde251e54ccfdd5"],"version":1,"versionHex":"1","weight":215},"id":1}"#);
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"bits":486604799,"chainwork":"0000000000000000000000000000000000000000000000000000000000000000","confirmations":1,"difficulty":1.0,"hash":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd","height":2,"mediantime":null,"merkleroot":"9b0fc92260312ce44e74ef369f5c66bbb85848f2eddd5a7a1cde251e54ccfdd5","nextblockhash":null,"nonce":1639830024,"previousblockhash":"00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048","size":215,"strippedsize":215,"time":1231469744,"tx":["9b0fc92260312ce44e74ef369f5c66bbb85848f2eddd5a7a1cde251e54ccfdd5"],"version":1,"versionHex":"1","weight":215},"id":1}"#);
     }
 
     #[test]
@@ -671,19 +1565,133 @@ pub mod tests {
         assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32099,"message":"Block with given hash is not found","data":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd"},"id":1}"#);
     }
 
+    #[test]
+    fn block_range_contents() {
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+            test_data::block_h1().into(),
+            test_data::block_h2().into(),
+        ]));
+
+        let core = BlockChainClientCore::new(Network::Mainnet, storage, memory_pool());
+
+        // range is truncated at the best block
+        let blocks = core.block_range(1, 5, false).unwrap();
+        assert_eq!(
+            blocks,
+            vec![
+                BlockRangeBlock {
+                    hash: "4860eb18bf1b1620e37e9490fc8a427514416fd75159ab86688e9a8300000000".into(),
+                    height: 1,
+                    time: 1231469665,
+                    previousblockhash: "6fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000"
+                        .into(),
+                    tx: vec![BlockRangeTransaction {
+                        txid: "982051fd1e4ba744bbbe680e1fee14677ba1a3c3540bf7b1cdb606e857233e0e".into(),
+                        coinbase: true,
+                        valid: true,
+                        error: None,
+                        hex: None,
+                    }],
+                },
+                BlockRangeBlock {
+                    hash: "bddd99ccfda39da1b108ce1a5d70038d0a967bacb68b6b63065f626a00000000".into(),
+                    height: 2,
+                    time: 1231469744,
+                    previousblockhash: "4860eb18bf1b1620e37e9490fc8a427514416fd75159ab86688e9a8300000000"
+                        .into(),
+                    tx: vec![BlockRangeTransaction {
+                        txid: "d5fdcc541e25de1c7a5addedf24858b8bb665c9f36ef744ee42c316022c90f9b".into(),
+                        coinbase: true,
+                        valid: true,
+                        error: None,
+                        hex: None,
+                    }],
+                },
+            ]
+        );
+
+        // serialized transactions are returned on request
+        let blocks = core.block_range(2, 1, true).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(
+            blocks[0].tx[0].hex,
+            Some(serialize(&test_data::block_h2().transactions[0]).into())
+        );
+    }
+
+    #[test]
+    fn block_range_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockrange",
+				"params": [1, 1, 1],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":[{"hash":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd","height":1,"previousblockhash":"00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048","time":1231469744,"tx":[{"coinbase":true,"error":null,"hex":"01020304","txid":"9b0fc92260312ce44e74ef369f5c66bbb85848f2eddd5a7a1cde251e54ccfdd5","valid":true}]}],"id":1}"#);
+    }
+
+    #[test]
+    fn block_range_error() {
+        let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockrange",
+				"params": [5, 10],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32099,"message":"Block at given height is not found","data":"5"},"id":1}"#);
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockrange",
+				"params": [0, 1001],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: count","data":"\"at most 1000 blocks could be requested\""},"id":1}"#);
+    }
+
     #[test]
     fn verbose_transaction_out_contents() {
         let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
             test_data::genesis().into(),
         ]));
-        let core = BlockChainClientCore::new(Network::Mainnet, storage);
+        let core = BlockChainClientCore::new(Network::Mainnet, storage, memory_pool());
 
         // get info on tx from genesis block:
         // https://blockchain.info/ru/tx/4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b
-        let verbose_transaction_out = core.verbose_transaction_out(OutPoint {
-            hash: "3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a".into(),
-            index: 0,
-        });
+        let verbose_transaction_out = core.verbose_transaction_out(
+            OutPoint {
+                hash: "3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a".into(),
+                index: 0,
+            },
+            true,
+        );
         assert_eq!(verbose_transaction_out, Ok(GetTxOutResponse {
 				bestblock: "6fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000".into(),
 				confirmations: 1,
@@ -696,10 +1704,58 @@ pub mod tests {
 					addresses: vec!["1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".into()]
 				},
 				version: 1,
-				coinbase: true
+				coinbase: true,
+				valid: true
 			}));
     }
 
+    #[test]
+    fn verbose_transaction_out_memory_pool_contents() {
+        let genesis = test_data::genesis();
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![genesis
+            .clone()
+            .into()]));
+        // unconfirmed transaction, spending genesis coinbase output
+        let transaction: chain::Transaction = test_data::TransactionBuilder::with_output(10)
+            .set_input(&genesis.transactions[0], 0)
+            .into();
+        let memory_pool = memory_pool();
+        memory_pool
+            .write()
+            .insert_verified(transaction.clone().into(), &NonZeroFeeCalculator);
+        let core = BlockChainClientCore::new(Network::Mainnet, storage, memory_pool);
+
+        // output, spent by memory pool transaction, is hidden only when memory pool is included
+        let genesis_out = OutPoint {
+            hash: genesis.transactions[0].hash(),
+            index: 0,
+        };
+        assert_eq!(
+            core.verbose_transaction_out(genesis_out.clone(), true),
+            Err(transaction_output_not_found(genesis_out.clone()))
+        );
+        assert_eq!(
+            core.verbose_transaction_out(genesis_out, false)
+                .map(|out| out.confirmations),
+            Ok(1)
+        );
+
+        // unconfirmed output is visible only when memory pool is included
+        let mempool_out = OutPoint {
+            hash: transaction.hash(),
+            index: 0,
+        };
+        assert_eq!(
+            core.verbose_transaction_out(mempool_out.clone(), false),
+            Err(transaction_not_found(mempool_out.hash.clone()))
+        );
+        let verbose_transaction_out = core.verbose_transaction_out(mempool_out, true).unwrap();
+        assert_eq!(verbose_transaction_out.confirmations, 0);
+        assert_eq!(verbose_transaction_out.bestblock, genesis.hash().into());
+        assert_eq!(verbose_transaction_out.coinbase, false);
+        assert_eq!(verbose_transaction_out.valid, true);
+    }
+
     #[test]
     fn transaction_out_success() {
         let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
@@ -718,7 +1774,7 @@ pub mod tests {
             )
             .unwrap();
 
-        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"bestblock":"0000000000000000000000000000000000000000000000000000000000000056","coinbase":false,"confirmations":777,"scriptPubKey":{"addresses":["1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa","1H5m1XzvHsjWX3wwU781ubctznEpNACrNC"],"asm":"Hello, world!!!","hex":"01020304","reqSigs":777,"type":"multisig"},"value":100000.56,"version":33},"id":1}"#);
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"bestblock":"0000000000000000000000000000000000000000000000000000000000000056","coinbase":false,"confirmations":777,"scriptPubKey":{"addresses":["1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa","1H5m1XzvHsjWX3wwU781ubctznEpNACrNC"],"asm":"Hello, world!!!","hex":"01020304","reqSigs":777,"type":"multisig"},"valid":true,"value":100000.56,"version":33},"id":1}"#);
     }
 
     #[test]
@@ -741,4 +1797,499 @@ pub mod tests {
 
         assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32099,"message":"Block with given hash is not found","data":"3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a"},"id":1}"#);
     }
+
+    #[test]
+    fn transaction_out_set_info_contents() {
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let core = BlockChainClientCore::new(Network::Mainnet, storage, memory_pool());
+
+        // genesis outputs are not a part of UTXO set
+        assert_eq!(
+            core.transaction_out_set_info(),
+            Ok(GetTxOutSetInfoResponse {
+                height: 0,
+                bestblock: test_data::genesis().hash().into(),
+                txouts: 0,
+                bogosize: 0,
+                muhash: storage::UtxoStats::default().hash().into(),
+                total_amount: 0.0,
+            })
+        );
+    }
+
+    #[test]
+    fn transaction_out_set_info_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "gettxoutsetinfo",
+				"params": [],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"bestblock":"0000000000000000000000000000000000000000000000000000000000000056","bogosize":159,"height":2,"muhash":"0000000000000000000000000000000000000000000000000000000000000078","total_amount":150.5,"txouts":3},"id":1}"#);
+    }
+
+    #[test]
+    fn transaction_out_set_info_failure() {
+        let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "gettxoutsetinfo",
+				"params": [],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32015,"message":"Execution error.","data":"\"UTXO set statistics are missing, blockchain resync is required\""},"id":1}"#);
+    }
+
+    #[test]
+    fn memory_pool_contents() {
+        let chain = &mut test_data::ChainBuilder::new();
+        test_data::TransactionBuilder::with_output(10)
+            .store(chain)
+            .into_input(0)
+            .add_output(20)
+            .store(chain)
+            .into_input(0)
+            .add_output(30)
+            .store(chain);
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let memory_pool = memory_pool();
+        for transaction in chain.transactions.iter().cloned() {
+            memory_pool
+                .write()
+                .insert_verified(transaction.into(), &NonZeroFeeCalculator);
+        }
+        let core = BlockChainClientCore::new(Network::Mainnet, storage, memory_pool);
+
+        assert_eq!(
+            core.raw_memory_pool(false),
+            GetRawMemoryPoolResponse::Hashes(vec![
+                chain.hash(0).into(),
+                chain.hash(1).into(),
+                chain.hash(2).into(),
+            ])
+        );
+        assert_eq!(core.memory_pool_info().size, 3);
+        assert_eq!(
+            core.memory_pool_info().bytes,
+            chain.size(0) + chain.size(1) + chain.size(2)
+        );
+
+        // NonZeroFeeCalculator fee is 100_000_000 + sum of outputs
+        let entry = core.memory_pool_entry(chain.hash(1)).unwrap();
+        assert_eq!(entry.size, chain.size(1));
+        assert_eq!(entry.ancestorcount, 2);
+        assert_eq!(entry.ancestorsize, chain.size(0) + chain.size(1));
+        assert_eq!(entry.ancestorfees, 200_000_030);
+        assert_eq!(entry.descendantcount, 2);
+        assert_eq!(entry.descendantsize, chain.size(1) + chain.size(2));
+        assert_eq!(entry.descendantfees, 200_000_050);
+        assert_eq!(entry.depends, vec![chain.hash(0).into()]);
+        assert_eq!(entry.spentby, vec![chain.hash(2).into()]);
+
+        assert_eq!(
+            core.memory_pool_ancestors(chain.hash(0), false),
+            Ok(GetRawMemoryPoolResponse::Hashes(vec![]))
+        );
+        assert_eq!(
+            core.memory_pool_descendants(chain.hash(1), false),
+            Ok(GetRawMemoryPoolResponse::Hashes(vec![chain.hash(2).into()]))
+        );
+        match core.memory_pool_ancestors(chain.hash(2), true) {
+            Ok(GetRawMemoryPoolResponse::Verbose(ancestors)) => {
+                let parent: H256 = chain.hash(1).into();
+                assert_eq!(ancestors.len(), 2);
+                assert_eq!(ancestors[&parent], entry);
+            }
+            _ => panic!("unexpected"),
+        }
+        assert_eq!(
+            core.memory_pool_entry(test_data::genesis().hash()),
+            Err(transaction_not_in_memory_pool(test_data::genesis().hash()))
+        );
+    }
+
+    #[test]
+    fn raw_memory_pool_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getrawmempool",
+				"params": [],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":["0000000000000000000000000000000000000000000000000000000000000056"],"id":1}"#);
+    }
+
+    #[test]
+    fn memory_pool_info_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getmempoolinfo",
+				"params": [],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"bytes":85,"size":1,"usage":1024},"id":1}"#);
+    }
+
+    #[test]
+    fn memory_pool_entry_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getmempoolentry",
+				"params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"ancestorcount":2,"ancestorfees":20000,"ancestorsize":170,"depends":["0000000000000000000000000000000000000000000000000000000000000056"],"descendantcount":1,"descendantfees":10000,"descendantsize":85,"fee":0.0001,"modifiedfee":0.0001,"size":85,"spentby":[]},"id":1}"#);
+    }
+
+    #[test]
+    fn memory_pool_entry_failure() {
+        let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getmempoolentry",
+				"params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32095,"message":"Transaction is not in memory pool","data":"3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a"},"id":1}"#);
+    }
+
+    #[test]
+    fn memory_pool_ancestors_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getmempoolancestors",
+				"params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":["0000000000000000000000000000000000000000000000000000000000000056"],"id":1}"#);
+    }
+
+    #[test]
+    fn memory_pool_descendants_failure() {
+        let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getmempooldescendants",
+				"params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b", true],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32095,"message":"Transaction is not in memory pool","data":"3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a"},"id":1}"#);
+    }
+
+    #[test]
+    fn block_filter_contents() {
+        use storage::BlockFilterProvider;
+
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let core = BlockChainClientCore::new(Network::Mainnet, storage.clone(), memory_pool());
+
+        let genesis_hash = test_data::genesis().hash();
+        let filter = storage.block_filter(&genesis_hash).unwrap();
+        assert_eq!(
+            core.block_filter(genesis_hash),
+            Ok(GetBlockFilterResponse {
+                filter: filter.filter.into(),
+                header: filter.header.into(),
+            })
+        );
+        assert_eq!(
+            core.block_filter(GlobalH256::from(1)),
+            Err(block_not_found(GlobalH256::from(1).reversed()))
+        );
+    }
+
+    #[test]
+    fn block_filter_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockfilter",
+				"params": ["000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"filter":"019dfca8","header":"0000000000000000000000000000000000000000000000000000000000000021"},"id":1}"#);
+    }
+
+    #[test]
+    fn block_filter_unsupported_type() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockfilter",
+				"params": ["000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f", "extended"],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: filtertype","data":"\"unsupported filter type: extended\""},"id":1}"#);
+    }
+
+    #[test]
+    fn block_filter_error() {
+        let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getblockfilter",
+				"params": ["000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f", "basic"],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32092,"message":"Block filter is not available","data":"000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"},"id":1}"#);
+    }
+
+    #[test]
+    fn address_index_contents() {
+        let storage = BlockChainDatabase::open(MemoryDatabase::default())
+            .with_address_index()
+            .unwrap();
+        for block in vec![test_data::genesis(), test_data::block_h1(), test_data::block_h2()] {
+            let hash = block.hash();
+            storage.insert(block.into()).unwrap();
+            storage.canonize(&hash).unwrap();
+        }
+        let core = BlockChainClientCore::new(Network::Mainnet, Arc::new(storage), memory_pool());
+
+        let block_h1 = test_data::block_h1();
+        let coinbase = &block_h1.transactions[0];
+        let script_hash = storage::script_hash(&coinbase.outputs[0].script_pubkey);
+        assert_eq!(
+            core.address_utxos(script_hash.clone(), 0, 10),
+            Ok(GetAddressUtxosResponse {
+                total: 1,
+                utxos: vec![AddressUtxo {
+                    txid: coinbase.hash().into(),
+                    vout: 0,
+                    satoshis: 5_000_000_000,
+                    height: 1,
+                }],
+            })
+        );
+        assert_eq!(
+            core.address_history(script_hash.clone(), 0, 10),
+            Ok(GetAddressHistoryResponse {
+                total: 1,
+                history: vec![AddressDelta {
+                    txid: coinbase.hash().into(),
+                    index: 0,
+                    satoshis: 5_000_000_000,
+                    height: 1,
+                }],
+            })
+        );
+        assert_eq!(
+            core.address_history(script_hash, 1, 10),
+            Ok(GetAddressHistoryResponse {
+                total: 1,
+                history: vec![],
+            })
+        );
+        assert_eq!(
+            core.address_utxos(GlobalH256::from(1), 0, 10),
+            Ok(GetAddressUtxosResponse {
+                total: 0,
+                utxos: vec![],
+            })
+        );
+
+        // address index is not maintained by default
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let core = BlockChainClientCore::new(Network::Mainnet, storage, memory_pool());
+        assert_eq!(
+            core.address_history(GlobalH256::from(1), 0, 10),
+            Err(address_index_disabled())
+        );
+    }
+
+    #[test]
+    fn address_utxos_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getaddressutxos",
+				"params": ["1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa", {"from": 1}],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"total":2,"utxos":[{"height":12,"satoshis":7000,"txid":"0000000000000000000000000000000000000000000000000000000000000078","vout":1}]},"id":1}"#);
+    }
+
+    #[test]
+    fn address_utxos_error() {
+        let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getaddressutxos",
+				"params": ["1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32093,"message":"Address index is not enabled"},"id":1}"#);
+    }
+
+    #[test]
+    fn address_history_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getaddresshistory",
+				"params": ["1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa", {"count": 1}],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"history":[{"height":10,"index":0,"satoshis":5000,"txid":"0000000000000000000000000000000000000000000000000000000000000056"}],"total":2},"id":1}"#);
+    }
+
+    #[test]
+    fn address_history_error() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "getaddresshistory",
+				"params": ["1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa", {"count": 1001}],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: count","data":"\"at most 1000 entries could be requested\""},"id":1}"#);
+    }
 }
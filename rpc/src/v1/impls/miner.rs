@@ -2,9 +2,14 @@ use jsonrpc_core::Error;
 use miner;
 use sync;
 use v1::traits::Miner;
-use v1::types::{BlockTemplate, BlockTemplateRequest};
-
-use miner::Sh_CoinbaseTransactionBuilder;
+use v1::helpers::errors::{execution, invalid_params};
+use v1::types::{
+    BlockFault, BlockTemplate, BlockTemplateRequest, GenerateInvalidBlockResponse,
+    InjectedBlockFault,
+};
+
+use miner::{AdversarialBlockError, AdversarialBlockGenerator, InjectedFault, Sh_CoinbaseTransactionBuilder};
+use keys::{KeyPair, Private};
 use primitives::bigint::{Uint, U256};
 use v1::types::H160;
 use v1::types::H256;
@@ -15,10 +20,8 @@ use chain::BlockHeader;
 use chain::IndexedBlock;
 use std::{thread, time};
 //use global_script::Script;
-use chain::{merkle_root, Transaction, TransactionInput, TransactionOutput};
-use script::Builder;
-use chain::OutPoint;
-use primitives::bytes::Bytes;
+use chain::merkle_root;
+use storage;
 
 //use chain::IndexedBlockHeader;
 
@@ -36,19 +39,24 @@ pub trait MinerClientCoreApi: Send + Sync + 'static {
     fn get_block_template(&self) -> miner::BlockTemplate;
     fn insert_block(&self, indexed_block: IndexedBlock);
     fn execute_broadcast_block(&self, indexed_block: IndexedBlock);
-    fn log_blocks(&self);
-    fn log_mempool(&self);
+    fn generate_invalid_block(
+        &self,
+        keypair: &KeyPair,
+        faults: &[miner::BlockFault],
+    ) -> Result<(IndexedBlock, Vec<InjectedFault>), AdversarialBlockError>;
     fn signal_sanitize(&self);
 }
 
 pub struct MinerClientCore {
     pub local_sync_node: sync::LocalNodeRef,
+    storage: storage::SharedStore,
 }
 
 impl MinerClientCore {
-    pub fn new(local_sync_node: sync::LocalNodeRef) -> Self {
+    pub fn new(local_sync_node: sync::LocalNodeRef, storage: storage::SharedStore) -> Self {
         MinerClientCore {
             local_sync_node: local_sync_node,
+            storage: storage,
         }
     }
 }
@@ -66,12 +74,15 @@ impl MinerClientCoreApi for MinerClientCore {
         self.local_sync_node.unsolicited_block(0, indexed_block);
     }
 
-    fn log_blocks(&self) {
-        self.local_sync_node.log_blocks();
-    }
-
-    fn log_mempool(&self) {
-        self.local_sync_node.log_mempool();
+    fn generate_invalid_block(
+        &self,
+        keypair: &KeyPair,
+        faults: &[miner::BlockFault],
+    ) -> Result<(IndexedBlock, Vec<InjectedFault>), AdversarialBlockError> {
+        // coinbase is paid to the same key, so that its output could be spent by the next faults
+        let coinbase_builder = Sh_CoinbaseTransactionBuilder::new(&keypair.public().address_hash(), 10);
+        let block_template = self.local_sync_node.get_block_template();
+        AdversarialBlockGenerator::new(&self.storage, keypair).generate(block_template, coinbase_builder, faults)
     }
 
     fn signal_sanitize(&self) {
@@ -92,16 +103,6 @@ impl<T> Miner for MinerClient<T>
 where
     T: MinerClientCoreApi,
 {
-    fn log_blocks(&self) -> Result<(), Error> {
-        self.core.log_blocks();
-        Ok(())
-    }
-
-    fn log_mempool(&self) -> Result<(), Error> {
-        self.core.log_mempool();
-        Ok(())
-    }
-
     fn get_block_template(&self, _request: BlockTemplateRequest) -> Result<BlockTemplate, Error> {
         Ok(self.core.get_block_template().into())
     }
@@ -181,111 +182,52 @@ where
         Ok(coinbase_txid_ser)
     }
 
-    fn generate_invalid_blocks(&self, addrhash: H160, num_blocks: u32) -> Result<H256, Error> {
-
-        let mut hash: primitives::hash::H160 = addrhash.clone().into();
-
-        let mut coinbase_txid_ser = H256::default();
-
-
-        for _i in 0..num_blocks {
-            let peer_index = 0;
-
-            let coinbase_builder = Sh_CoinbaseTransactionBuilder::new(&hash, 10);
-            // take all transaction in mempool
-            let mut block_template = self.core.get_block_template(); //.into()
-            //let addr: chain::hash::H160 = addrhash
-            let script = Builder::build_p2pkh(&addrhash.clone().into());
-            //tx output currently, single only
-            let mut transaction_outputs = vec![
-                TransactionOutput {
-                    value: 100000,
-                    script_pubkey: script.to_bytes(),
-            }];
-
-            //void Coin
-            let mut void_coin = OutPoint::null();
-            void_coin.hash = "fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f".into();
-            let mut unsigned_inputs: Vec<TransactionInput> = vec![];
-
-            //println!("use void coin");
-            unsigned_inputs.push(TransactionInput {
-                    previous_output: void_coin,
-                    script_sig: Bytes::new(),
-                    sequence: 0x00,
-                    script_witness: vec![],
-                }
-            );
-
-            let transaction = Transaction {
-                version: 1,
-                inputs: unsigned_inputs,
-                outputs: transaction_outputs,
-                lock_time: 0,
-            };
-
-            block_template.transactions.clear();
-            block_template.transactions.push(transaction.into());
-
-            let solution = match miner::find_solution(&block_template, coinbase_builder, U256::max_value()) {
-                None => {
-                    println!("NoNonceSolution");
-                    let mut err_with_message = Error::invalid_request();
-                    err_with_message.message = "NoNonceSolution".to_string();
-                    return Err(err_with_message)
-                },
-                Some(s) => s,
-            };
-
-            let coinbase_txid = solution.coinbase_transaction.hash().clone();
-            coinbase_txid_ser = coinbase_txid.clone().into();
-            //println!("coinbase_txid {:?}", coinbase_txid);
-            //println!("non coinbase tx len {}", block_template.transactions.len());
-
-            let mut merkle_root_hash = solution.coinbase_transaction.hash().clone();
-            if block_template.transactions.len() >= 1 {
-                let mut merkle_tree = vec![&coinbase_txid];
-                merkle_tree.extend(block_template.transactions.iter().map(|tx| &tx.hash));
-                merkle_root_hash = merkle_root(&merkle_tree);
-            }
-
-            let block_header = BlockHeader {
-                version: block_template.version,
-                previous_header_hash: block_template.previous_header_hash.clone(),
-                merkle_root_hash: merkle_root_hash,
-                time: solution.time.clone(),
-                bits: block_template.bits.clone(),
-                nonce: solution.nonce.clone(),
-            };
-
-            //println!("block_header {:?}", block_header);
-
-            let mut transactions = vec![solution.coinbase_transaction.clone()];
-            if block_template.transactions.len() >= 1 {
-                let mut non_coinbase_txs = block_template.transactions.iter().map(|tx| tx.raw.clone()).collect();
-                transactions.append(&mut non_coinbase_txs);
-            }
-
-            let block = Block::new(block_header.clone(), transactions);
-            let indexed_block = IndexedBlock::from(block);
+    fn generate_invalid_blocks(
+        &self,
+        private_key: String,
+        blocks: Vec<Vec<BlockFault>>,
+    ) -> Result<Vec<GenerateInvalidBlockResponse>, Error> {
+        let keypair = try!(private_key
+            .parse::<Private>()
+            .and_then(KeyPair::from_private)
+            .map_err(|e| invalid_params("privkey", e)));
+        let mut responses = Vec::with_capacity(blocks.len());
+        for faults in blocks {
+            let faults: Vec<miner::BlockFault> = faults.into_iter().map(Into::into).collect();
+            let (block, injected) = self
+                .core
+                .generate_invalid_block(&keypair, &faults)
+                .map_err(execution)?;
+            let hash = block.hash().clone();
 
             // insert to local
-            self.core.insert_block(indexed_block.clone());
+            self.core.insert_block(block.clone());
             //broadcast
-            self.core.execute_broadcast_block(indexed_block);
+            self.core.execute_broadcast_block(block);
 
             // necessary, experiment shows block is show to append without it
-            let ten_millis = time::Duration::from_millis(300);
-            thread::sleep(ten_millis);
-
+            thread::sleep(time::Duration::from_millis(300));
+
+            responses.push(GenerateInvalidBlockResponse {
+                hash: hash.reversed().into(),
+                faults: injected
+                    .into_iter()
+                    .map(|injected| InjectedBlockFault {
+                        fault: injected.fault.into(),
+                        txid: injected.transaction.hash.reversed().into(),
+                    })
+                    .collect(),
+            });
         }
 
-        Ok(coinbase_txid_ser)
+        Ok(responses)
     }
 }
 
 #[cfg(test)]
 pub mod tests {
+    extern crate test_data;
+
     use super::*;
     use chain;
     use jsonrpc_core::IoHandler;
@@ -312,13 +254,54 @@ pub mod tests {
             }
         }
 
-        fn insert_block(&self, indexed_block: IndexedBlock) {
+        fn insert_block(&self, _indexed_block: IndexedBlock) {}
+
+        fn execute_broadcast_block(&self, _indexed_block: IndexedBlock) {}
+
+        fn generate_invalid_block(
+            &self,
+            _keypair: &KeyPair,
+            faults: &[miner::BlockFault],
+        ) -> Result<(IndexedBlock, Vec<InjectedFault>), AdversarialBlockError> {
+            let block: IndexedBlock = test_data::genesis().into();
+            let injected = faults
+                .iter()
+                .map(|fault| InjectedFault {
+                    fault: *fault,
+                    transaction: block.transactions[0].clone(),
+                })
+                .collect();
+            Ok((block, injected))
+        }
+
+        fn signal_sanitize(&self) {}
+    }
+
+    #[derive(Default)]
+    struct ErrorMinerClientCore;
+
+    impl MinerClientCoreApi for ErrorMinerClientCore {
+        fn get_block_template(&self) -> miner::BlockTemplate {
+            SuccessMinerClientCore.get_block_template()
+        }
+
+        fn insert_block(&self, _indexed_block: IndexedBlock) {
             unimplemented!();
         }
 
-        fn execute_broadcast_block(&self, indexed_block: IndexedBlock) {
+        fn execute_broadcast_block(&self, _indexed_block: IndexedBlock) {
             unimplemented!();
         }
+
+        fn generate_invalid_block(
+            &self,
+            _keypair: &KeyPair,
+            faults: &[miner::BlockFault],
+        ) -> Result<(IndexedBlock, Vec<InjectedFault>), AdversarialBlockError> {
+            Err(AdversarialBlockError::NoSuitableInput(faults[0]))
+        }
+
+        fn signal_sanitize(&self) {}
     }
 
     #[test]
@@ -343,4 +326,46 @@ pub mod tests {
         // but client expects reverse hash
         assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":{"bits":44,"coinbaseaux":null,"coinbasetxn":null,"coinbasevalue":66,"curtime":33,"height":55,"mintime":null,"mutable":null,"noncerange":null,"previousblockhash":"0000000000000000000000000000000000000000000000000000000000000001","rules":null,"sigoplimit":88,"sizelimit":77,"target":"0000000000000000000000000000000000000000000000000000000000000000","transactions":[{"data":"00000000013ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a0000000000000000000101000000000000000000000000","depends":null,"fee":null,"hash":null,"required":false,"sigops":null,"txid":null,"weight":null}],"vbavailable":null,"vbrequired":null,"version":777,"weightlimit":null},"id":1}"#);
     }
+
+    #[test]
+    fn generateinvalidblocks_success() {
+        let client = MinerClient::new(SuccessMinerClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "generateinvalidblocks",
+				"params": ["KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn", [["overspend", "duplicatetxid"], []]],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":[{"faults":[{"fault":"overspend","txid":"4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"},{"fault":"duplicatetxid","txid":"4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"}],"hash":"000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"},{"faults":[],"hash":"000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"}],"id":1}"#);
+    }
+
+    #[test]
+    fn generateinvalidblocks_error() {
+        let client = MinerClient::new(ErrorMinerClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "generateinvalidblocks",
+				"params": ["KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn", [["mempooldoublespend"]]],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32015,"message":"Execution error.","data":"NoSuitableInput(MempoolDoubleSpend)"},"id":1}"#);
+    }
 }
@@ -4,7 +4,7 @@ mod network;
 mod raw;
 mod wallet;
 
-pub use self::blockchain::{BlockChainClient, BlockChainClientCore};
+pub use self::blockchain::{BlockChainClient, BlockChainClientCore, MAX_BLOCK_RANGE_COUNT};
 pub use self::miner::{MinerClient, MinerClientCore};
 pub use self::network::{NetworkClient, NetworkClientCore};
 pub use self::raw::{RawClient, RawClientCore};
@@ -1,28 +1,35 @@
+use chain::constants::SATOSHIS_IN_COIN;
 use chain::Transaction as GlobalTransaction;
-use global_script::Script;
+use chain::{OutPoint as GlobalOutPoint, TransactionOutput as GlobalTransactionOutput};
+use global_script::Psbt as GlobalPsbt;
+use global_script::{
+    verify_script, PsbtInput, Script, SignatureVersion, TransactionInputSigner,
+    TransactionSignatureChecker, VerificationFlags,
+};
 use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
-use keys::Address;
-use network::Network;
+use keys::{Address, AddressPayload, KeyPair, Private};
+use network::{ConsensusFork, Network};
 use primitives::bytes::Bytes as GlobalBytes;
 use primitives::hash::H256 as GlobalH256;
-use ser::{deserialize, serialize, Reader, Serializable, SERIALIZE_TRANSACTION_WITNESS};
+use ser::{deserialize, serialize, serialize_with_flags, Reader, Serializable, SERIALIZE_TRANSACTION_WITNESS};
 use storage;
 use sync;
 use v1::helpers::errors::{
-    execution, invalid_params, transaction_not_found, transaction_of_side_branch,
+    block_pruned, execution, invalid_params, transaction_not_found, transaction_of_side_branch,
 };
 use v1::traits::Raw;
 use v1::types::H256;
+use v1::types::{DecodePsbtResponse, FinalizePsbtResponse, Psbt};
 use v1::types::{
-    GetRawTransactionResponse, RawTransaction, SignedTransactionInput, SignedTransactionOutput,
-    Transaction, TransactionInput, TransactionInputScript, TransactionOutput,
-    TransactionOutputScript, TransactionOutputs,
+    GetRawTransactionResponse, GetTxValidityResponse, PreviousTransactionOutput, RawTransaction,
+    SignRawTransactionResponse, SignedTransactionInput, SignedTransactionOutput, Transaction,
+    TransactionInput, TransactionInputScript, TransactionOutput, TransactionOutputScript,
+    TransactionOutputs,
 };
-
-use keys::generator::*;
 use keys::Network as Key_Network;
-use primitives::bytes::Bytes;
+
+const SIGHASH_ALL: u32 = 1;
 
 pub struct RawClient<T: RawClientCoreApi> {
     core: T,
@@ -41,6 +48,9 @@ pub trait RawClientCoreApi: Send + Sync + 'static {
         hash: GlobalH256,
         verbose: bool,
     ) -> Result<GetRawTransactionResponse, Error>;
+    fn get_tx_validity(&self, hash: GlobalH256) -> Result<GetTxValidityResponse, Error>;
+    fn decode_raw_transaction(&self, transaction: GlobalTransaction) -> Transaction;
+    fn transaction_output(&self, outpoint: &GlobalOutPoint) -> Option<GlobalTransactionOutput>;
 }
 
 pub struct RawClientCore {
@@ -101,28 +111,28 @@ impl RawClientCore {
                 TransactionOutput::Address(with_address) => {
                     let amount_in_satoshis =
                         (with_address.amount * (chain::constants::SATOSHIS_IN_COIN as f64)) as u64;
-                    let script = match with_address.address.kind {
-                        keys::Type::P2PKH => ScriptBuilder::build_p2pkh(&with_address.address.hash),
-                        keys::Type::P2SH => ScriptBuilder::build_p2sh(&with_address.address.hash),
+                    let script = match ScriptBuilder::build_address(&with_address.address) {
+                        Some(script) => script,
+                        None => return Err("Address type does not match its payload".to_owned()),
                     };
 
-                    chain::TransactionOutput {
+                    Ok(chain::TransactionOutput {
                         value: amount_in_satoshis,
                         script_pubkey: script.to_bytes(),
-                    }
+                    })
                 }
                 TransactionOutput::ScriptData(with_script_data) => {
                     let script = ScriptBuilder::default()
                         .return_bytes(&*with_script_data.script_data)
                         .into_script();
 
-                    chain::TransactionOutput {
+                    Ok(chain::TransactionOutput {
                         value: 0,
                         script_pubkey: script.to_bytes(),
-                    }
+                    })
                 }
             })
-            .collect();
+            .collect::<Result<Vec<_>, String>>()?;
 
         // now construct && serialize transaction
         let transaction = GlobalTransaction {
@@ -134,6 +144,200 @@ impl RawClientCore {
 
         Ok(transaction)
     }
+
+    /// Verbose representation of the transaction. Fields of the including block are left empty
+    pub fn do_decode_raw_transaction(transaction: &GlobalTransaction, network: Network) -> Transaction {
+        let txid: H256 = transaction.hash().into();
+        let hash: H256 = transaction.witness_hash().into();
+        let base_size = transaction.serialized_size();
+        let total_size = transaction.serialized_size_with_flags(SERIALIZE_TRANSACTION_WITNESS);
+        let weight = base_size * (ConsensusFork::witness_scale_factor() - 1) + total_size;
+        let address_network = match network {
+            Network::Mainnet => Key_Network::Mainnet,
+            _ => Key_Network::Testnet,
+        };
+
+        let inputs = transaction
+            .inputs
+            .iter()
+            .map(|input| {
+                let txid: H256 = input.previous_output.hash.clone().into();
+                let script_sig: Script = input.script_sig.clone().into();
+                SignedTransactionInput {
+                    txid: txid.reversed(),
+                    vout: input.previous_output.index,
+                    script_sig: TransactionInputScript {
+                        asm: format!("{}", script_sig),
+                        hex: input.script_sig.clone().into(),
+                    },
+                    sequence: input.sequence,
+                    txinwitness: input
+                        .script_witness
+                        .iter()
+                        .map(|s| s.clone().into())
+                        .collect(),
+                }
+            })
+            .collect();
+
+        let outputs = transaction
+            .outputs
+            .iter()
+            .enumerate()
+            .map(|(index, output)| {
+                let script_pubkey: Script = output.script_pubkey.clone().into();
+                let script_addresses = script_pubkey.extract_destinations().unwrap_or(vec![]);
+                SignedTransactionOutput {
+                    value: 0.00000001f64 * output.value as f64,
+                    n: index as u32,
+                    script: TransactionOutputScript {
+                        asm: format!("{}", script_pubkey),
+                        hex: output.script_pubkey.clone().into(),
+                        req_sigs: script_pubkey.num_signatures_required() as u32,
+                        script_type: script_pubkey.script_type().into(),
+                        addresses: script_addresses
+                            .into_iter()
+                            .map(|address| Address {
+                                hash: address.hash,
+                                kind: address.kind,
+                                network: address_network,
+                            })
+                            .collect(),
+                    },
+                }
+            })
+            .collect();
+
+        Transaction {
+            hex: serialize_with_flags(transaction, SERIALIZE_TRANSACTION_WITNESS).into(),
+            txid: txid.reversed(),
+            hash: hash.reversed(),
+            size: total_size,
+            vsize: (weight + ConsensusFork::witness_scale_factor() - 1) / ConsensusFork::witness_scale_factor(),
+            weight: weight,
+            version: transaction.version,
+            locktime: transaction.lock_time as i32,
+            vin: inputs,
+            vout: outputs,
+            blockhash: None,
+            confirmations: None,
+            time: None,
+            blocktime: None,
+            valid: None,
+        }
+    }
+
+    /// Sign transaction inputs with given keys. Spent outputs and scripts of inputs are passed as PSBT inputs.
+    /// Inputs, which are already signed, are kept. Returns transaction and true if all its inputs are signed
+    pub fn do_sign_raw_transaction(
+        mut transaction: GlobalTransaction,
+        keypairs: &[KeyPair],
+        inputs: Vec<PsbtInput>,
+    ) -> (GlobalTransaction, bool) {
+        let mut unsigned_transaction = transaction.clone();
+        for input in &mut unsigned_transaction.inputs {
+            input.script_sig = GlobalBytes::new();
+            input.script_witness.clear();
+        }
+        let mut psbt = GlobalPsbt::from_unsigned_tx(unsigned_transaction)
+            .expect("input scripts are cleared; qed");
+        psbt.inputs = inputs;
+
+        let signed_inputs: Vec<bool> = (0..transaction.inputs.len())
+            .map(|index| is_input_signed(&transaction, index, psbt.spent_output(index)))
+            .collect();
+        let signer: TransactionInputSigner = transaction.clone().into();
+        for index in (0..transaction.inputs.len()).filter(|index| !signed_inputs[*index]) {
+            let (script_code, sigversion) = match psbt.signing_script(index) {
+                Some(signing_script) => signing_script,
+                None => continue,
+            };
+
+            let amount = psbt.spent_output(index).map_or(0, |output| output.value);
+            let hash = signer.signature_hash(index, amount, &script_code, sigversion, SIGHASH_ALL);
+            // P2PKH script code and multisig script have destinations of their keys
+            for destination in script_code.extract_destinations().unwrap_or_default() {
+                let key_hash = match destination.hash {
+                    AddressPayload::Hash(key_hash) => key_hash,
+                    AddressPayload::WitnessProgram(_) => continue,
+                };
+                for keypair in keypairs.iter().filter(|keypair| keypair.public().address_hash() == key_hash) {
+                    let mut signature: Vec<u8> = match keypair.private().sign(&hash) {
+                        Ok(signature) => signature.into(),
+                        Err(_) => continue,
+                    };
+                    signature.push(SIGHASH_ALL as u8);
+                    psbt.inputs[index]
+                        .partial_sigs
+                        .insert(keypair.public().to_vec().into(), signature.into());
+                }
+            }
+        }
+
+        psbt.finalize();
+        for (index, input) in transaction.inputs.iter_mut().enumerate() {
+            let psbt_input = &psbt.inputs[index];
+            if !signed_inputs[index] && psbt_input.is_finalized() {
+                input.script_sig = psbt_input.final_script_sig.clone().unwrap_or_default();
+                input.script_witness = psbt_input.final_script_witness.clone().unwrap_or_default();
+            }
+        }
+
+        let complete = (0..transaction.inputs.len())
+            .all(|index| is_input_signed(&transaction, index, psbt.spent_output(index)));
+        (transaction, complete)
+    }
+}
+
+/// Is the output, passed to signrawtransaction, a witness v0 or P2SH-wrapped witness output?
+fn is_witness_prevtx(prevtx: &PreviousTransactionOutput) -> bool {
+    let is_witness_v0 = |script: &Script| script.parse_witness_program().map_or(false, |(version, _)| version == 0);
+    let script_pubkey: Script = prevtx.script_pubkey.0.clone().into();
+    if is_witness_v0(&script_pubkey) {
+        return true;
+    }
+
+    script_pubkey.is_pay_to_script_hash()
+        && (prevtx.witness_script.is_some()
+            || prevtx
+                .redeem_script
+                .as_ref()
+                .map_or(false, |redeem_script| is_witness_v0(&redeem_script.0.clone().into())))
+}
+
+/// Does the input script satisfy the spent output?
+fn is_input_signed(
+    transaction: &GlobalTransaction,
+    index: usize,
+    spent_output: Option<GlobalTransactionOutput>,
+) -> bool {
+    let spent_output = match spent_output {
+        Some(spent_output) => spent_output,
+        None => return false,
+    };
+
+    let input = &transaction.inputs[index];
+    if input.script_sig.is_empty() && !input.has_witness() {
+        return false;
+    }
+
+    let checker = TransactionSignatureChecker {
+        signer: transaction.clone().into(),
+        input_index: index,
+        input_amount: spent_output.value,
+    };
+    let flags = VerificationFlags::default()
+        .verify_p2sh(true)
+        .verify_witness(true);
+    verify_script(
+        &input.script_sig.clone().into(),
+        &spent_output.script_pubkey.into(),
+        &input.script_witness,
+        &flags,
+        &checker,
+        SignatureVersion::Base,
+    )
+    .is_ok()
 }
 
 impl RawClientCoreApi for RawClientCore {
@@ -150,6 +354,14 @@ impl RawClientCoreApi for RawClientCore {
         RawClientCore::do_create_raw_transaction(inputs, outputs, lock_time)
     }
 
+    fn decode_raw_transaction(&self, transaction: GlobalTransaction) -> Transaction {
+        RawClientCore::do_decode_raw_transaction(&transaction, self.network)
+    }
+
+    fn transaction_output(&self, outpoint: &GlobalOutPoint) -> Option<GlobalTransactionOutput> {
+        self.local_sync_node.transaction_output(outpoint)
+    }
+
     fn get_raw_transaction(
         &self,
         hash: GlobalH256,
@@ -157,7 +369,11 @@ impl RawClientCoreApi for RawClientCore {
     ) -> Result<GetRawTransactionResponse, Error> {
         let transaction = match self.storage.transaction(&hash) {
             Some(transaction) => transaction,
-            None => return Err(transaction_not_found(hash)),
+            // canonical transaction is located, but its body has been pruned
+            None => match self.storage.transaction_location(&hash) {
+                Some(location) => return Err(block_pruned(location.block_hash)),
+                None => return Err(transaction_not_found(hash)),
+            },
         };
 
         let transaction_bytes = serialize(&transaction);
@@ -169,7 +385,12 @@ impl RawClientCoreApi for RawClientCore {
                 None => return Err(transaction_of_side_branch(hash)),
             };
 
-            let block_header = match self.storage.block_header(meta.height().into()) {
+            // transactions, canonized by older versions, are only located by the block height
+            let block_ref: storage::BlockRef = match self.storage.transaction_location(&hash) {
+                Some(location) => location.block_hash.into(),
+                None => meta.height().into(),
+            };
+            let block_header = match self.storage.block_header(block_ref) {
                 Some(block_header) => block_header,
                 None => return Err(transaction_not_found(hash)),
             };
@@ -179,89 +400,56 @@ impl RawClientCoreApi for RawClientCore {
                 return Err(transaction_not_found(hash));
             }
 
-            let txid: H256 = transaction.witness_hash().into();
-            let hash: H256 = transaction.hash().into();
             let blockhash: H256 = block_header.hash().into();
-
-            let inputs = transaction
-                .clone()
-                .inputs
-                .into_iter()
-                .map(|input| {
-                    let txid: H256 = input.previous_output.hash.into();
-                    let script_sig_bytes = input.script_sig;
-                    let script_sig: Script = script_sig_bytes.clone().into();
-                    let script_sig_asm = format!("{}", script_sig);
-                    SignedTransactionInput {
-                        txid: txid.reversed(),
-                        vout: input.previous_output.index,
-                        script_sig: TransactionInputScript {
-                            asm: script_sig_asm,
-                            hex: script_sig_bytes.clone().into(),
-                        },
-                        sequence: input.sequence,
-                        txinwitness: input
-                            .script_witness
-                            .into_iter()
-                            .map(|s| s.clone().into())
-                            .collect(),
-                    }
-                })
-                .collect();
-
-            let outputs = transaction
-                .clone()
-                .outputs
-                .into_iter()
-                .enumerate()
-                .map(|(index, output)| {
-                    let script_pubkey_bytes = output.script_pubkey;
-                    let script_pubkey: Script = script_pubkey_bytes.clone().into();
-                    let script_pubkey_asm = format!("{}", script_pubkey);
-                    let script_addresses = script_pubkey.extract_destinations().unwrap_or(vec![]);
-                    SignedTransactionOutput {
-                        value: 0.00000001f64 * output.value as f64,
-                        n: index as u32,
-                        script: TransactionOutputScript {
-                            asm: script_pubkey_asm,
-                            hex: script_pubkey_bytes.clone().into(),
-                            req_sigs: script_pubkey.num_signatures_required() as u32,
-                            script_type: script_pubkey.script_type().into(),
-                            addresses: script_addresses
-                                .into_iter()
-                                .map(|address| Address {
-                                    hash: address.hash,
-                                    kind: address.kind,
-                                    network: match self.network {
-                                        Network::Mainnet => keys::Network::Mainnet,
-                                        _ => keys::Network::Testnet,
-                                    },
-                                })
-                                .collect(),
-                        },
-                    }
-                })
-                .collect();
-
-            Ok(GetRawTransactionResponse::Verbose(Transaction {
-                hex: raw_transaction,
-                txid: txid.reversed(),
-                hash: hash.reversed(),
-                size: transaction.serialized_size(),
-                vsize: transaction.serialized_size_with_flags(SERIALIZE_TRANSACTION_WITNESS),
-                version: transaction.version,
-                locktime: transaction.lock_time as i32,
-                vin: inputs,
-                vout: outputs,
-                blockhash: blockhash.reversed(),
-                confirmations: best_block.number - meta.height() + 1,
-                time: block_header.time,
-                blocktime: block_header.time,
-            }))
+            let mut verbose = RawClientCore::do_decode_raw_transaction(&transaction, self.network);
+            verbose.blockhash = Some(blockhash.reversed());
+            verbose.confirmations = Some(best_block.number - meta.height() + 1);
+            verbose.time = Some(block_header.time);
+            verbose.blocktime = Some(block_header.time);
+            verbose.valid = Some(meta.is_valid());
+            Ok(GetRawTransactionResponse::Verbose(verbose))
         } else {
             Ok(GetRawTransactionResponse::Raw(raw_transaction))
         }
     }
+
+    fn get_tx_validity(&self, hash: GlobalH256) -> Result<GetTxValidityResponse, Error> {
+        let meta = match self.storage.transaction_meta(&hash) {
+            Some(meta) => meta,
+            None if self.storage.contains_transaction(&hash) => {
+                return Err(transaction_of_side_branch(hash))
+            }
+            None => return Err(transaction_not_found(hash)),
+        };
+
+        let block_hash = match self.storage.transaction_location(&hash) {
+            Some(location) => Some(location.block_hash),
+            None => self.storage.block_hash(meta.height()),
+        };
+        let block_hash = match block_hash {
+            Some(block_hash) => block_hash,
+            // this is possible during reorgs
+            None => return Err(transaction_not_found(hash)),
+        };
+
+        // errors are only remembered for transactions, verified in lazy mode
+        let error = if meta.is_valid() {
+            None
+        } else {
+            self.storage
+                .transaction_error(&hash)
+                .map(|error| format!("{:?}", error))
+        };
+
+        let blockhash: H256 = block_hash.into();
+        Ok(GetTxValidityResponse {
+            blockhash: blockhash.reversed(),
+            height: meta.height(),
+            valid: meta.is_valid(),
+            error: error,
+            sanitized: self.local_sync_node.transaction_validity(&hash),
+        })
+    }
 }
 
 impl<T> RawClient<T>
@@ -311,8 +499,12 @@ where
         Ok(transaction.into())
     }
 
-    fn decode_raw_transaction(&self, _transaction: RawTransaction) -> Result<Transaction, Error> {
-        rpc_unimplemented!()
+    fn decode_raw_transaction(&self, raw_transaction: RawTransaction) -> Result<Transaction, Error> {
+        let raw_transaction_data: Vec<u8> = raw_transaction.into();
+        let transaction =
+            try!(deserialize(Reader::new(&raw_transaction_data))
+                .map_err(|e| invalid_params("tx", e)));
+        Ok(self.core.decode_raw_transaction(transaction))
     }
 
     fn get_raw_transaction(
@@ -325,16 +517,81 @@ where
             .get_raw_transaction(global_hash.reversed(), verbose.unwrap_or_default())
     }
 
+    fn get_tx_validity(&self, hash: H256) -> Result<GetTxValidityResponse, Error> {
+        let global_hash: GlobalH256 = hash.clone().into();
+        self.core.get_tx_validity(global_hash.reversed())
+    }
+
     
 
     fn sign_raw_transaction(
+        &self,
+        raw_transaction: RawTransaction,
+        private_keys: Vec<String>,
+        prevtxs: Trailing<Vec<PreviousTransactionOutput>>,
+    ) -> Result<SignRawTransactionResponse, Error> {
+        let raw_transaction_data: Vec<u8> = raw_transaction.into();
+        let transaction: GlobalTransaction =
+            try!(deserialize(Reader::new(&raw_transaction_data))
+                .map_err(|e| invalid_params("tx", e)));
+        let keypairs = try!(private_keys
+            .into_iter()
+            .map(|key| key.parse::<Private>().and_then(KeyPair::from_private))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| invalid_params("privkeys", e)));
+
+        // outputs, which are not passed as prevtxs, are searched in the chain and the memory pool
+        let prevtxs = prevtxs.unwrap_or_default();
+        let inputs = try!(transaction
+            .inputs
+            .iter()
+            .map(|input| {
+                let prevtx = prevtxs.iter().find(|prevtx| {
+                    prevtx.vout == input.previous_output.index
+                        && Into::<GlobalH256>::into(prevtx.txid.clone()).reversed() == input.previous_output.hash
+                });
+                match prevtx {
+                    Some(prevtx) => {
+                        // witness signatures commit to the spent amount
+                        let amount = match prevtx.amount {
+                            Some(amount) => amount,
+                            None if is_witness_prevtx(prevtx) => {
+                                return Err(invalid_params("prevtxs", "amount is required for witness inputs"))
+                            }
+                            None => 0.0,
+                        };
+                        Ok(PsbtInput {
+                            witness_utxo: Some(GlobalTransactionOutput {
+                                value: (amount * SATOSHIS_IN_COIN as f64).round() as u64,
+                                script_pubkey: prevtx.script_pubkey.0.clone().into(),
+                            }),
+                            redeem_script: prevtx.redeem_script.clone().map(|script| script.0.into()),
+                            witness_script: prevtx.witness_script.clone().map(|script| script.0.into()),
+                            ..Default::default()
+                        })
+                    }
+                    None => Ok(PsbtInput {
+                        witness_utxo: self.core.transaction_output(&input.previous_output),
+                        ..Default::default()
+                    }),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>());
+
+        let (transaction, complete) = RawClientCore::do_sign_raw_transaction(transaction, &keypairs, inputs);
+        Ok(SignRawTransactionResponse {
+            hex: serialize_with_flags(&transaction, SERIALIZE_TRANSACTION_WITNESS).into(),
+            complete: complete,
+        })
+    }
+
+    fn create_psbt(
         &self,
         inputs: Vec<TransactionInput>,
         outputs: TransactionOutputs,
         lock_time: Trailing<u32>,
-    ) -> Result<RawTransaction, Error> {
-        println!("sign_raw_transaction");
-
+    ) -> Result<Psbt, Error> {
+        // reverse hashes of inputs
         let inputs: Vec<_> = inputs
             .into_iter()
             .map(|mut input| {
@@ -343,34 +600,50 @@ where
             })
             .collect();
 
-        let mut transaction = try!(self
+        let transaction = try!(self
             .core
             .create_raw_transaction(inputs, outputs, lock_time)
             .map_err(|e| execution(e)));
+        GlobalPsbt::from_unsigned_tx(transaction)
+            .map(Psbt)
+            .map_err(|e| execution(e))
+    }
 
-        let kp_generator = Random::new(Key_Network::Testnet);
-        let kp = kp_generator.generate().unwrap();
-        //let tx = transaction.clone();
-
-        println!("unsigned transaction {:?}", transaction);
-
-        //let mut tx_mut = transaction.clone();
-
-        for input in &mut transaction.inputs {
-            let txid = input.previous_output.hash.clone().into();
-            let signature = kp.private().sign(&txid).unwrap();
+    fn combine_psbt(&self, psbts: Vec<Psbt>) -> Result<Psbt, Error> {
+        let mut psbts = psbts.into_iter();
+        let mut combined = match psbts.next() {
+            Some(Psbt(psbt)) => psbt,
+            None => return Err(invalid_params("psbts", "empty list")),
+        };
+        for Psbt(psbt) in psbts {
+            try!(combined.combine(psbt).map_err(|e| execution(e)));
+        }
+        Ok(Psbt(combined))
+    }
 
-            let mut sig_byte = signature.take();
-            println!("signature {:?}", sig_byte);
-            input.script_sig = Bytes::from(sig_byte);
+    fn finalize_psbt(&self, psbt: Psbt, extract: Trailing<bool>) -> Result<FinalizePsbtResponse, Error> {
+        let extract: Option<bool> = extract.into();
+        let Psbt(mut psbt) = psbt;
+        let complete = psbt.finalize();
+        if complete && extract.unwrap_or(true) {
+            let transaction = try!(psbt.extract_transaction().map_err(|e| execution(e)));
+            let raw_transaction = serialize_with_flags(&transaction, SERIALIZE_TRANSACTION_WITNESS);
+            return Ok(FinalizePsbtResponse {
+                psbt: None,
+                hex: Some(raw_transaction.into()),
+                complete: true,
+            });
         }
 
-        println!("signed transaction {:?}", transaction);
+        Ok(FinalizePsbtResponse {
+            psbt: Some(Psbt(psbt)),
+            hex: None,
+            complete: complete,
+        })
+    }
 
-        let raw_transaction = serialize(&transaction);
-        let raw_transaction: RawTransaction = raw_transaction.into();
-        self.send_raw_transaction(raw_transaction.clone());
-        Ok(raw_transaction)
+    fn decode_psbt(&self, psbt: Psbt) -> Result<DecodePsbtResponse, Error> {
+        Ok(DecodePsbtResponse::from(&psbt.0))
     }
 }
 
@@ -382,7 +655,18 @@ pub mod tests {
     use jsonrpc_macros::Trailing;
     use primitives::hash::H256 as GlobalH256;
     use v1::traits::Raw;
-    use v1::types::{Bytes, TransactionInput, TransactionOutputs};
+    use global_script::Builder as ScriptBuilder;
+    use keys::Network as KeysNetwork;
+    use v1::types::{Bytes, Transaction as TransactionVerbose, TransactionInput, TransactionOutputs};
+
+    fn keypair(secret: u8) -> KeyPair {
+        KeyPair::from_private(Private {
+            network: KeysNetwork::Mainnet,
+            secret: secret.into(),
+            compressed: true,
+        })
+        .unwrap()
+    }
 
     #[derive(Default)]
     struct SuccessRawClientCore;
@@ -411,6 +695,27 @@ pub mod tests {
         ) -> Result<GetRawTransactionResponse, Error> {
             Ok(GetRawTransactionResponse::Raw(Bytes::from("0100000001273d7b971b6788f911038f917dfa9ba85980b018a80b2e8caa4fca85475afdaf010000008b48304502205eb82fbb78f3467269c64ebb48c66567b11b1ebfa9cf4dd793d1482e46d3851c022100d18e2091becaea279f6f896825e7ca669ee0607b30007ca88b43d1de91359ba9014104a208236447f5c93972a739105abb8292613eef741cab36a1b98fa4fcc2989add0e5dc6cda9127a2bf0b18357210ba0119ad700e1fa495143262720067f4fbf83ffffffff02003b5808000000001976a9147793078b2ebc6ab7b7fd213789912f1deb03a97088ac404b4c00000000001976a914ffc2838f7aeed00857dbbfc70d9830c6968aca5688ac00000000")))
         }
+
+        fn get_tx_validity(&self, _hash: GlobalH256) -> Result<GetTxValidityResponse, Error> {
+            Ok(GetTxValidityResponse {
+                blockhash: "000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd".into(),
+                height: 2,
+                valid: false,
+                error: Some("Overspend".to_owned()),
+                sanitized: Some(false),
+            })
+        }
+
+        fn decode_raw_transaction(&self, transaction: Transaction) -> TransactionVerbose {
+            RawClientCore::do_decode_raw_transaction(&transaction, Network::Mainnet)
+        }
+
+        fn transaction_output(&self, _outpoint: &GlobalOutPoint) -> Option<GlobalTransactionOutput> {
+            Some(GlobalTransactionOutput {
+                value: 2_000_000,
+                script_pubkey: ScriptBuilder::build_p2pkh(&keypair(2).public().address_hash()).to_bytes(),
+            })
+        }
     }
 
     impl RawClientCoreApi for ErrorRawClientCore {
@@ -434,6 +739,18 @@ pub mod tests {
         ) -> Result<GetRawTransactionResponse, Error> {
             Err(transaction_not_found(hash))
         }
+
+        fn get_tx_validity(&self, hash: GlobalH256) -> Result<GetTxValidityResponse, Error> {
+            Err(transaction_not_found(hash))
+        }
+
+        fn decode_raw_transaction(&self, transaction: Transaction) -> TransactionVerbose {
+            RawClientCore::do_decode_raw_transaction(&transaction, Network::Mainnet)
+        }
+
+        fn transaction_output(&self, _outpoint: &GlobalOutPoint) -> Option<GlobalTransactionOutput> {
+            None
+        }
     }
 
     #[test]
@@ -492,6 +809,21 @@ pub mod tests {
         assert_eq!(r#"{"jsonrpc":"2.0","result":"0100000001ad9d38823d95f31dc6c0cb0724c11a3cf5a466ca4147254a10cd94aade6eb5b3230000006b483045022100b7683165c3ecd57b0c44bf6a0fb258dc08c328458321c8fadc2b9348d4e66bd502204fd164c58d1a949a4d39bb380f8f05c9f6b3e9417f06bf72e5c068428ca3578601210391c35ac5ee7cf82c5015229dcff89507f83f9b8c952b8fecfa469066c1cb44ccffffffff0170f30500000000001976a914801da3cb2ed9e44540f4b982bde07cd3fbae264288ac00000000","id":1}"#, &sample);
     }
 
+    #[test]
+    fn createrawtransaction_pays_to_segwit_addresses() {
+        let outputs: TransactionOutputs = ::serde_json::from_str(r#"{
+			"bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4":0.01,
+			"tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7":0.01,
+			"bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0":0.01
+		}"#).unwrap();
+        let transaction = RawClientCore::do_create_raw_transaction(vec![], outputs, Trailing::from(None)).unwrap();
+
+        assert_eq!(transaction.outputs.len(), 3);
+        assert_eq!(transaction.outputs[0].script_pubkey, "0014751e76e8199196d454941c45d1b3a323f1433bd6".into());
+        assert_eq!(transaction.outputs[1].script_pubkey, "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262".into());
+        assert_eq!(transaction.outputs[2].script_pubkey, "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798".into());
+    }
+
     #[test]
     fn createrawtransaction_error() {
         let client = RawClient::new(ErrorRawClientCore::default());
@@ -551,4 +883,259 @@ pub mod tests {
 
         assert_eq!(r#"{"jsonrpc":"2.0","error":{"code":-32096,"message":"Transaction with given hash is not found","data":"3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a"},"id":1}"#, &sample);
     }
+
+    #[test]
+    fn gettxvalidity_success() {
+        let client = RawClient::new(SuccessRawClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "gettxvalidity",
+				"params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(r#"{"jsonrpc":"2.0","result":{"blockhash":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd","error":"Overspend","height":2,"sanitized":false,"valid":false},"id":1}"#, &sample);
+    }
+
+    #[test]
+    fn gettxvalidity_error() {
+        let client = RawClient::new(ErrorRawClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "gettxvalidity",
+				"params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"],
+				"id": 1
+			}"#),
+            )
+            .unwrap();
+
+        assert_eq!(r#"{"jsonrpc":"2.0","error":{"code":-32096,"message":"Transaction with given hash is not found","data":"3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a"},"id":1}"#, &sample);
+    }
+
+    #[test]
+    fn createpsbt_rejects_signed_transaction() {
+        let client = RawClient::new(SuccessRawClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "createpsbt",
+				"params": [[{"txid":"4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b","vout":0}],{"1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa":0.01}],
+				"id": 1
+			}"#)
+		).unwrap();
+
+        assert_eq!(r#"{"jsonrpc":"2.0","error":{"code":-32015,"message":"Execution error.","data":"SignedTransaction"},"id":1}"#, &sample);
+    }
+
+    #[test]
+    fn decodepsbt_success() {
+        let client = RawClient::new(SuccessRawClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "decodepsbt",
+				"params": ["cHNidP8BAFUBAAAAATuj7f16exKyescsPmd2j2F/yBvDiIpRMjqfuKpLHl5KAAAAAAD/////AUBCDwAAAAAAGXapFGLpB7FcvyfVQlOZ6/bw+1DruI8YiKwAAAAAAAAA"],
+				"id": 1
+			}"#)
+		).unwrap();
+
+        assert_eq!(r#"{"jsonrpc":"2.0","result":{"tx":"01000000013ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a0000000000ffffffff0140420f00000000001976a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac00000000","txid":"8ac607008d95e6e712be9986370c0b6ead7b972fcd93f039383682152397aa55","unknown":{},"inputs":[{"non_witness_utxo":null,"witness_utxo":null,"partial_signatures":{},"sighash":null,"redeem_script":null,"witness_script":null,"bip32_derivs":[],"final_scriptSig":null,"final_scriptwitness":null,"unknown":{}}],"outputs":[{"redeem_script":null,"witness_script":null,"bip32_derivs":[],"unknown":{}}],"fee":null},"id":1}"#, &sample);
+    }
+
+    #[test]
+    fn finalizepsbt_incomplete() {
+        let client = RawClient::new(SuccessRawClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "finalizepsbt",
+				"params": ["cHNidP8BAFUBAAAAATuj7f16exKyescsPmd2j2F/yBvDiIpRMjqfuKpLHl5KAAAAAAD/////AUBCDwAAAAAAGXapFGLpB7FcvyfVQlOZ6/bw+1DruI8YiKwAAAAAAAAA"],
+				"id": 1
+			}"#)
+		).unwrap();
+
+        assert_eq!(r#"{"jsonrpc":"2.0","result":{"psbt":"cHNidP8BAFUBAAAAATuj7f16exKyescsPmd2j2F/yBvDiIpRMjqfuKpLHl5KAAAAAAD/////AUBCDwAAAAAAGXapFGLpB7FcvyfVQlOZ6/bw+1DruI8YiKwAAAAAAAAA","hex":null,"complete":false},"id":1}"#, &sample);
+    }
+
+    #[test]
+    fn combinepsbt_empty() {
+        let client = RawClient::new(SuccessRawClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "combinepsbt",
+				"params": [[]],
+				"id": 1
+			}"#)
+		).unwrap();
+
+        assert_eq!(r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: psbts","data":"\"empty list\""},"id":1}"#, &sample);
+    }
+
+    #[test]
+    fn decoderawtransaction_success() {
+        let client = RawClient::new(SuccessRawClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "decoderawtransaction",
+				"params": ["01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000"],
+				"id": 1
+			}"#)
+		).unwrap();
+
+        let response: ::serde_json::Value = ::serde_json::from_str(&sample).unwrap();
+        let result = &response["result"];
+        assert_eq!(result["txid"], "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b");
+        assert_eq!(result["hash"], "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b");
+        assert_eq!(result["size"], 204);
+        assert_eq!(result["vsize"], 204);
+        assert_eq!(result["weight"], 816);
+        assert_eq!(result["vin"][0]["vout"], 4294967295u32);
+        assert_eq!(result["vout"][0]["value"], 50.0);
+        assert_eq!(result["vout"][0]["scriptPubKey"]["type"], "pubkey");
+        assert_eq!(result["vout"][0]["scriptPubKey"]["addresses"][0], "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+        // fields of the chain transaction are omitted
+        let result = result.as_object().unwrap();
+        assert!(!result.contains_key("blockhash"));
+        assert!(!result.contains_key("confirmations"));
+    }
+
+    #[test]
+    fn decoderawtransaction_error() {
+        let client = RawClient::new(SuccessRawClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "decoderawtransaction",
+				"params": ["0100"],
+				"id": 1
+			}"#)
+		).unwrap();
+
+        assert!(sample.contains(r#""code":-32602"#));
+    }
+
+    #[test]
+    fn signrawtransaction_signs_known_outputs() {
+        let client = RawClient::new(SuccessRawClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "signrawtransaction",
+				"params": ["01000000013ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a0000000000ffffffff0140420f00000000001976a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac00000000", ["KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU74NMTptX4"]],
+				"id": 1
+			}"#)
+		).unwrap();
+
+        let response: ::serde_json::Value = ::serde_json::from_str(&sample).unwrap();
+        assert_eq!(response["result"]["complete"], true);
+    }
+
+    #[test]
+    fn signrawtransaction_signs_prevtxs() {
+        let client = RawClient::new(ErrorRawClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "signrawtransaction",
+				"params": ["01000000013ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a0000000000ffffffff0140420f00000000001976a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac00000000", ["KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn"], [{"txid":"4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b","vout":0,"scriptPubKey":"0014751e76e8199196d454941c45d1b3a323f1433bd6","amount":0.02}]],
+				"id": 1
+			}"#)
+		).unwrap();
+
+        let response: ::serde_json::Value = ::serde_json::from_str(&sample).unwrap();
+        assert_eq!(response["result"]["complete"], true);
+        // witness input is signed with empty script_sig
+        assert!(response["result"]["hex"].as_str().unwrap().starts_with("010000000001013ba3edfd"));
+    }
+
+    #[test]
+    fn signrawtransaction_requires_amount_of_witness_prevtxs() {
+        let client = RawClient::new(ErrorRawClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler.handle_request_sync(&(r#"
+			{
+				"jsonrpc": "2.0",
+				"method": "signrawtransaction",
+				"params": ["01000000013ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a0000000000ffffffff0140420f00000000001976a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac00000000", ["KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn"], [{"txid":"4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b","vout":0,"scriptPubKey":"0014751e76e8199196d454941c45d1b3a323f1433bd6"}]],
+				"id": 1
+			}"#)
+		).unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: prevtxs","data":"\"amount is required for witness inputs\""},"id":1}"#);
+    }
+
+    #[test]
+    fn sign_raw_transaction_keeps_signed_inputs() {
+        let p2pkh = GlobalTransactionOutput {
+            value: 2_000_000,
+            script_pubkey: ScriptBuilder::build_p2pkh(&keypair(1).public().address_hash()).to_bytes(),
+        };
+        let p2wpkh = GlobalTransactionOutput {
+            value: 3_000_000,
+            script_pubkey: ScriptBuilder::build_p2wpkh(&keypair(2).public().address_hash()).to_bytes(),
+        };
+        let transaction: Transaction = "0100000002ad9d38823d95f31dc6c0cb0724c11a3cf5a466ca4147254a10cd94aade6eb5b30000000000ffffffffad9d38823d95f31dc6c0cb0724c11a3cf5a466ca4147254a10cd94aade6eb5b30100000000ffffffff0140420f00000000001976a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac00000000".into();
+        let inputs = || {
+            vec![
+                PsbtInput { witness_utxo: Some(p2pkh.clone()), ..Default::default() },
+                PsbtInput { witness_utxo: Some(p2wpkh.clone()), ..Default::default() },
+            ]
+        };
+
+        // only the first input is signed by the first key
+        let (transaction, complete) = RawClientCore::do_sign_raw_transaction(transaction, &[keypair(1)], inputs());
+        assert!(!complete);
+        assert!(!transaction.inputs[0].script_sig.is_empty());
+        assert!(!transaction.inputs[1].has_witness());
+
+        // signature of the first input is kept
+        let (signed_transaction, complete) =
+            RawClientCore::do_sign_raw_transaction(transaction.clone(), &[keypair(2)], inputs());
+        assert!(complete);
+        assert_eq!(signed_transaction.inputs[0].script_sig, transaction.inputs[0].script_sig);
+        assert!(signed_transaction.inputs[1].script_sig.is_empty());
+        assert_eq!(signed_transaction.inputs[1].script_witness.len(), 2);
+    }
 }
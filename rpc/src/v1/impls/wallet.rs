@@ -5,17 +5,45 @@ use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use std::sync::Mutex;
 use v1::helpers::errors;
-use sync::WalletError;
+use sync::{AddressType, AdversaryAttempt, AdversaryError, AdversaryStrategy, Attacker, CoinSelectionStrategy, CoinState, WalletError, MAX_FEE_RATE, MAX_RESTORED_KEYS};
 use v1::traits::Wallet;
 use sync::Wallet as LocalWallet;
 use v1::types::H160 as AddressHash_ser;
-use keys::AddressHash;
+use keys::{Address, AddressHash, Mnemonic, Public};
 use primitives::hash::H256;
 use v1::types::H256 as H256_ser;
-use chain::OutPoint;
+use v1::types::{AdversaryAttempt as AdversaryAttemptSer, AdversaryStrategy as AdversaryStrategySer};
+use v1::types::CoinSelectionStrategy as CoinSelectionStrategySer;
+use v1::types::AddressType as AddressTypeSer;
+use v1::types::{AddressInfo, Bytes, MultisigAddress, RawTransaction, SignMultisigTransactionResponse};
+use v1::types::{Psbt, WalletCoin, WalletProcessPsbtResponse};
+use chain::{OutPoint, Transaction};
+use ser::{deserialize, serialize, Reader};
+use global_script::{Psbt as GlobalPsbt, Script};
+
+const SIGHASH_ALL: u32 = 1;
+const SIGHASH_ANYONECANPAY: u32 = 0x80;
+
+/// Sighash type, named as in bitcoind: ALL, NONE or SINGLE, optionally followed by |ANYONECANPAY
+fn parse_sighash_type(name: &str) -> Option<u32> {
+    let (base, anyone_can_pay) = match name.find('|') {
+        Some(index) if &name[index + 1..] == "ANYONECANPAY" => (&name[..index], true),
+        Some(_) => return None,
+        None => (name, false),
+    };
+    let base = match base {
+        "ALL" => SIGHASH_ALL,
+        "NONE" => 2,
+        "SINGLE" => 3,
+        _ => return None,
+    };
+    Some(if anyone_can_pay { base | SIGHASH_ANYONECANPAY } else { base })
+}
 
 pub struct WalletClient<T: WalletClientCoreApi> {
     core: T,
+    /// true if getmnemonic is allowed to return the wallet mnemonic
+    mnemonic_export: bool,
 }
 
 pub trait WalletClientCoreApi: Send + Sync + 'static {
@@ -23,62 +51,105 @@ pub trait WalletClientCoreApi: Send + Sync + 'static {
     fn get_balance(&self) -> Result<u64, WalletError>;
     // return transaction id, outpoint index is default to be 0
     fn shard_pay(&self, recipient: AddressHash, value: u64) -> Result<H256, WalletError>;
+
+    fn send_to_address(&self, recipient: Address, value: u64) -> Result<H256, WalletError>;
+
+    fn get_new_address(&self, address_type: Option<AddressType>) -> Result<Address, WalletError>;
+
+    fn set_address_type(&self, address_type: AddressType);
+    // m-of-n multisig address and its redeem script
+    fn create_multisig(&self, required: u8, keys: Vec<Public>) -> Result<(Address, Script), WalletError>;
+    // add m-of-n multisig address to the wallet
+    fn add_multisig_address(&self, required: u8, keys: Vec<Public>) -> Result<(Address, Script), WalletError>;
+    // add wallet signatures to multisig inputs, returning whether all inputs are completely signed
+    fn sign_multisig_transaction(&self, transaction: Transaction) -> Result<(Transaction, bool), WalletError>;
+    // wallet public key and redeem script of the address
+    fn address_info(&self, address: &Address) -> (Option<Public>, Option<Script>);
+    /// Fill PSBT with data, known to the wallet, and sign it with given sighash type if `sign` is true
+    fn process_psbt(&self, psbt: GlobalPsbt, sign: bool, sighash_type: u32) -> Result<GlobalPsbt, WalletError>;
     // check with blockchain to get all spendable coins
     fn update_wallet(&self) -> Result<(), WalletError>;
+    // track all wallet outputs of canonical blocks, starting from given height, and return new balance
+    fn rescan_wallet(&self, from_height: u32) -> Result<u64, WalletError>;
+    // mnemonic, wallet keys are derived from
+    fn get_mnemonic(&self) -> Result<String, WalletError>;
+    // restore wallet keys from mnemonic and return new balance
+    fn import_mnemonic(&self, phrase: String, key_count: u32) -> Result<u64, WalletError>;
+    // select coin selection strategy of payments
+    fn set_coin_selection(&self, strategy: CoinSelectionStrategy);
+    // set fee rate of payments, satoshis per byte
+    fn set_fee_rate(&self, fee_rate: u64);
     // add txid and outpoint to wallet candidate set, so that it can use update_wallet
     fn wallet_add_tx(&self, H256, u32) -> Result<(), WalletError>;
     // generate pub pri key pair for using blockchain
     fn generate_keypair(&self) -> Result<AddressHash, WalletError>;
     // get one pub key hash address from wallet
     fn get_addresshash(&self) -> Result<AddressHash, WalletError>;
-    // debug get coin
-    fn print_coins(&self);
+    // spendable coins of the wallet
+    fn list_unspent(&self) -> Vec<CoinState>;
+    // all outpoints, tracked by the wallet
+    fn list_coins(&self) -> Vec<CoinState>;
 }
 
-pub trait CovetWalletClientCoreApi {
-    fn covet_generate_keypair(&self) -> Result<AddressHash, WalletError>;
-    fn covet_get_addresshash(&self) -> Result<AddressHash, WalletError>;
-    fn covet_pay(&self, addrhash: AddressHash, value: u64) -> Result<H256, WalletError>;
-    fn covet_wallet_add_tx(&self, txid: H256, index: u32) -> Result<(), WalletError>;
+pub trait AdversaryClientCoreApi {
+    // generate pub pri key pair for adversary wallet
+    fn adversary_generate_keypair(&self) -> Result<AddressHash, WalletError>;
+    // get one pub key hash address from adversary wallet
+    fn adversary_get_addresshash(&self) -> Result<AddressHash, WalletError>;
+    // add txid and outpoint to adversary wallet candidate set
+    fn adversary_wallet_add_tx(&self, txid: H256, index: u32) -> Result<(), WalletError>;
+    // select adversary strategy
+    fn set_adversary(&self, strategy: AdversaryStrategy);
+    // attack the network, using selected strategy
+    fn adversary_pay(&self, addrhash: AddressHash, value: u64) -> Result<AdversaryAttempt, AdversaryError>;
+    // most recent attack attempts
+    fn adversary_log(&self) -> Vec<AdversaryAttempt>;
 }
 
-impl CovetWalletClientCoreApi for WalletClientCore {
+impl AdversaryClientCoreApi for WalletClientCore {
 
-    fn covet_generate_keypair(&self) -> Result<AddressHash, WalletError> {
-        let mut wallet = self.covetous_wallet.lock().unwrap();
-        Ok(wallet.generate_keypair().unwrap())
+    fn adversary_generate_keypair(&self) -> Result<AddressHash, WalletError> {
+        let mut attacker = self.attacker.lock().unwrap();
+        attacker.wallet().generate_keypair()
     }
 
-    fn covet_get_addresshash(&self) -> Result<AddressHash, WalletError> {
-        let wallet = self.covetous_wallet.lock().unwrap();
-        Ok(wallet.get_addresshash().unwrap())
+    fn adversary_get_addresshash(&self) -> Result<AddressHash, WalletError> {
+        let mut attacker = self.attacker.lock().unwrap();
+        attacker.wallet().get_addresshash()
     }
 
-    fn covet_pay(&self, addrhash: AddressHash, value: u64) -> Result<H256, WalletError> {
-        let mut wallet = self.covetous_wallet.lock().unwrap();
-        wallet.covet_pay(addrhash, value)
+    fn adversary_wallet_add_tx(&self, txid: H256, index: u32) -> Result<(), WalletError> {
+        let mut attacker = self.attacker.lock().unwrap();
+        attacker.wallet().wallet_add_tx(txid, index)
     }
 
-    fn covet_wallet_add_tx(&self, txid: H256, index: u32) -> Result<(), WalletError> {
-        let mut wallet = self.covetous_wallet.lock().unwrap();
-        let balance = wallet.wallet_add_tx(txid, index);
-        Ok(())
+    fn set_adversary(&self, strategy: AdversaryStrategy) {
+        let mut attacker = self.attacker.lock().unwrap();
+        attacker.set_strategy(strategy);
     }
-}
 
+    fn adversary_pay(&self, addrhash: AddressHash, value: u64) -> Result<AdversaryAttempt, AdversaryError> {
+        let mut attacker = self.attacker.lock().unwrap();
+        attacker.attack(addrhash, value)
+    }
 
+    fn adversary_log(&self) -> Vec<AdversaryAttempt> {
+        let attacker = self.attacker.lock().unwrap();
+        attacker.log()
+    }
+}
 
 pub struct WalletClientCore {
-    // contain two wallets
+    // own wallet and adversary with its own wallet
     pub wallet: Arc<Mutex<LocalWallet>>,
-    pub covetous_wallet: Arc<Mutex<LocalWallet>>,
+    pub attacker: Arc<Mutex<Attacker>>,
 }
 
 impl WalletClientCore{
-    pub fn new(wallet: Arc<Mutex<LocalWallet>>, covetous_wallet: Arc<Mutex<LocalWallet>>) -> Self {
+    pub fn new(wallet: Arc<Mutex<LocalWallet>>, attacker: Arc<Mutex<Attacker>>) -> Self {
         WalletClientCore {
             wallet: wallet,
-            covetous_wallet: covetous_wallet,
+            attacker: attacker,
         }
     }
 }
@@ -94,22 +165,90 @@ impl WalletClientCoreApi for WalletClientCore {
         wallet.pay(addrhash, value)
     }
 
+    fn send_to_address(&self, recipient: Address, value: u64) -> Result<H256, WalletError> {
+        let mut wallet = self.wallet.lock().unwrap();
+        wallet.pay_to_address(&recipient, value)
+    }
+
+    fn get_new_address(&self, address_type: Option<AddressType>) -> Result<Address, WalletError> {
+        let mut wallet = self.wallet.lock().unwrap();
+        let address_type = address_type.unwrap_or(wallet.address_type());
+        wallet.generate_address(address_type)
+    }
+
+    fn set_address_type(&self, address_type: AddressType) {
+        let mut wallet = self.wallet.lock().unwrap();
+        wallet.set_address_type(address_type);
+    }
+
+    fn create_multisig(&self, required: u8, keys: Vec<Public>) -> Result<(Address, Script), WalletError> {
+        let wallet = self.wallet.lock().unwrap();
+        wallet.multisig_address(required, &keys)
+    }
+
+    fn add_multisig_address(&self, required: u8, keys: Vec<Public>) -> Result<(Address, Script), WalletError> {
+        let mut wallet = self.wallet.lock().unwrap();
+        wallet.add_multisig_address(required, &keys)
+    }
+
+    fn sign_multisig_transaction(&self, transaction: Transaction) -> Result<(Transaction, bool), WalletError> {
+        let wallet = self.wallet.lock().unwrap();
+        wallet.sign_multisig_transaction(transaction)
+    }
+
+    fn address_info(&self, address: &Address) -> (Option<Public>, Option<Script>) {
+        let wallet = self.wallet.lock().unwrap();
+        let public = wallet.address_public_key(address).map(|public| Public::from_slice(&**public).expect("wallet keys are valid; qed"));
+        (public, wallet.address_redeem_script(address).cloned())
+    }
+
+    fn process_psbt(&self, mut psbt: GlobalPsbt, sign: bool, sighash_type: u32) -> Result<GlobalPsbt, WalletError> {
+        let wallet = self.wallet.lock().unwrap();
+        wallet.process_psbt(&mut psbt, sign, sighash_type)?;
+        Ok(psbt)
+    }
+
     fn update_wallet(&self) -> Result<(), WalletError> {
         let mut wallet = self.wallet.lock().unwrap();
-        let balance = wallet.update_wallet();
-        Ok(())
+        wallet.update_wallet()
     }
 
-    fn wallet_add_tx(&self, txid: H256, index: u32) -> Result<(), WalletError> {
+    fn rescan_wallet(&self, from_height: u32) -> Result<u64, WalletError> {
         let mut wallet = self.wallet.lock().unwrap();
+        wallet.rescan(from_height)?;
+        Ok(wallet.get_balance())
+    }
 
-        let balance = wallet.wallet_add_tx(txid, index);
-        Ok(())
+    fn get_mnemonic(&self) -> Result<String, WalletError> {
+        let wallet = self.wallet.lock().unwrap();
+        Ok(wallet.mnemonic()?.to_string())
+    }
+
+    fn import_mnemonic(&self, phrase: String, key_count: u32) -> Result<u64, WalletError> {
+        let mnemonic: Mnemonic = phrase.parse()?;
+        let mut wallet = self.wallet.lock().unwrap();
+        wallet.restore(mnemonic, key_count)?;
+        Ok(wallet.get_balance())
+    }
+
+    fn set_coin_selection(&self, strategy: CoinSelectionStrategy) {
+        let mut wallet = self.wallet.lock().unwrap();
+        wallet.set_coin_selection(strategy);
+    }
+
+    fn set_fee_rate(&self, fee_rate: u64) {
+        let mut wallet = self.wallet.lock().unwrap();
+        wallet.set_fee_rate(fee_rate);
+    }
+
+    fn wallet_add_tx(&self, txid: H256, index: u32) -> Result<(), WalletError> {
+        let mut wallet = self.wallet.lock().unwrap();
+        wallet.wallet_add_tx(txid, index)
     }
 
     fn generate_keypair(&self) -> Result<AddressHash, WalletError> {
         let mut wallet = self.wallet.lock().unwrap();
-        Ok(wallet.generate_keypair().unwrap())
+        wallet.generate_keypair()
     }
 
     fn get_addresshash(&self) -> Result<AddressHash, WalletError> {
@@ -117,9 +256,14 @@ impl WalletClientCoreApi for WalletClientCore {
         Ok(wallet.get_addresshash().unwrap())
     }
 
-    fn print_coins(&self) {
+    fn list_unspent(&self) -> Vec<CoinState> {
         let wallet = self.wallet.lock().unwrap();
-        wallet.print_coins();
+        wallet.list_unspent()
+    }
+
+    fn list_coins(&self) -> Vec<CoinState> {
+        let wallet = self.wallet.lock().unwrap();
+        wallet.list_coins()
     }
 
 }
@@ -129,16 +273,28 @@ where
     T: WalletClientCoreApi,
 {
     pub fn new(core: T) -> Self {
-        WalletClient { core: core }
+        WalletClient { core: core, mnemonic_export: false }
+    }
+
+    /// Allows getmnemonic to return the wallet mnemonic
+    pub fn with_mnemonic_export(mut self) -> Self {
+        self.mnemonic_export = true;
+        self
     }
 
     pub fn format_error_msg(&self, e: WalletError) -> Error {
         let mut err_with_message = Error::invalid_request();
         match e {
-            InsufficientMoney => err_with_message.message = "InsufficientMoney".to_string(),
-            EmptyKeySpace => err_with_message.message = "EmptyKeySpace".to_string(),
-            MissingKeypairForAddressHash => err_with_message.message = "MissingKeypairForAddressHash".to_string(),
-            DuplicatePublicKey =>err_with_message.message = "DuplicatePublicKey".to_string(),
+            WalletError::InsufficientMoney => err_with_message.message = "InsufficientMoney".to_string(),
+            WalletError::EmptyKeySpace => err_with_message.message = "EmptyKeySpace".to_string(),
+            WalletError::MissingKeypairForAddressHash => err_with_message.message = "MissingKeypairForAddressHash".to_string(),
+            WalletError::DustPayment => err_with_message.message = "DustPayment".to_string(),
+            WalletError::InsufficientFee => err_with_message.message = "InsufficientFee".to_string(),
+            WalletError::InvalidMultisig => err_with_message.message = "InvalidMultisig".to_string(),
+            WalletError::Database(err) => err_with_message.message = format!("WalletDatabase: {:?}", err),
+            WalletError::Keys(err) => err_with_message.message = format!("Keys: {}", err),
+            WalletError::BlockPruned(height) => err_with_message.message = format!("BlockPruned: {}", height),
+            WalletError::SighashTypeMismatch(index) => err_with_message.message = format!("SighashTypeMismatch: input {}", index),
         }
         err_with_message
     }
@@ -146,7 +302,7 @@ where
 
 impl<T> Wallet for WalletClient<T>
 where
-    T: WalletClientCoreApi + CovetWalletClientCoreApi
+    T: WalletClientCoreApi + AdversaryClientCoreApi
 {
     fn get_balance(&self) -> Result<u64, Error> {
         match self.core.get_balance() {
@@ -163,6 +319,90 @@ where
         }
     }
 
+    fn send_to_address(&self, address: String, value: u64) -> Result<H256_ser, Error> {
+        let recipient: Address = match address.parse() {
+            Ok(address) => address,
+            Err(e) => return Err(self.format_error_msg(WalletError::Keys(e))),
+        };
+        match self.core.send_to_address(recipient, value) {
+            Ok(result) => Ok(result.into()),
+            Err(e) => Err(self.format_error_msg(e)),
+        }
+    }
+
+    fn create_multisig(&self, required: u8, keys: Vec<Bytes>) -> Result<MultisigAddress, Error> {
+        let keys = parse_public_keys(keys)?;
+        match self.core.create_multisig(required, keys) {
+            Ok((address, redeem_script)) => Ok(multisig_address(address, redeem_script)),
+            Err(e) => Err(self.format_error_msg(e)),
+        }
+    }
+
+    fn add_multisig_address(&self, required: u8, keys: Vec<Bytes>) -> Result<MultisigAddress, Error> {
+        let keys = parse_public_keys(keys)?;
+        match self.core.add_multisig_address(required, keys) {
+            Ok((address, redeem_script)) => Ok(multisig_address(address, redeem_script)),
+            Err(e) => Err(self.format_error_msg(e)),
+        }
+    }
+
+    fn sign_multisig_transaction(&self, raw_transaction: RawTransaction) -> Result<SignMultisigTransactionResponse, Error> {
+        let raw_transaction_data: Vec<u8> = raw_transaction.into();
+        let transaction = deserialize(Reader::new(&raw_transaction_data))
+            .map_err(|e| errors::invalid_params("tx", e))?;
+        match self.core.sign_multisig_transaction(transaction) {
+            Ok((transaction, complete)) => Ok(SignMultisigTransactionResponse {
+                hex: RawTransaction::new(serialize(&transaction).take()),
+                complete: complete,
+            }),
+            Err(e) => Err(self.format_error_msg(e)),
+        }
+    }
+
+    fn wallet_process_psbt(&self, psbt: Psbt, sign: bool, sighash_type: Trailing<String>) -> Result<WalletProcessPsbtResponse, Error> {
+        let sighash_type: Option<String> = sighash_type.into();
+        let sighash_type = match sighash_type {
+            Some(sighash_type) => parse_sighash_type(&sighash_type)
+                .ok_or_else(|| errors::invalid_params("sighashtype", sighash_type))?,
+            None => SIGHASH_ALL,
+        };
+        match self.core.process_psbt(psbt.0, sign, sighash_type) {
+            Ok(psbt) => {
+                // inputs are finalized by finalizepsbt, here we only check if they could be
+                let complete = psbt.clone().finalize();
+                Ok(WalletProcessPsbtResponse {
+                    psbt: Psbt(psbt),
+                    complete: complete,
+                })
+            },
+            Err(e) => Err(self.format_error_msg(e)),
+        }
+    }
+
+    fn get_address_info(&self, address: String) -> Result<AddressInfo, Error> {
+        let address: Address = address.parse().map_err(|e| errors::invalid_params("address", e))?;
+        let (public, redeem_script) = self.core.address_info(&address);
+        Ok(AddressInfo {
+            address: address,
+            ismine: public.is_some() || redeem_script.is_some(),
+            pubkey: public.map(|public| Bytes::new(public.to_vec())),
+            redeem_script: redeem_script.map(|script| Bytes::new(script.to_bytes().take())),
+        })
+    }
+
+    fn get_new_address(&self, address_type: Trailing<AddressTypeSer>) -> Result<String, Error> {
+        let address_type: Option<AddressTypeSer> = address_type.into();
+        match self.core.get_new_address(address_type.map(Into::into)) {
+            Ok(address) => Ok(address.to_string()),
+            Err(e) => Err(self.format_error_msg(e)),
+        }
+    }
+
+    fn set_address_type(&self, address_type: AddressTypeSer) -> Result<(), Error> {
+        self.core.set_address_type(address_type.into());
+        Ok(())
+    }
+
     fn update_wallet(&self) -> Result<(), Error> {
         match self.core.update_wallet() {
             Ok(()) => Ok(()),
@@ -170,6 +410,53 @@ where
         }
     }
 
+    fn rescan_wallet(&self, from_height: u32) -> Result<u64, Error> {
+        match self.core.rescan_wallet(from_height) {
+            Ok(balance) => Ok(balance),
+            Err(e) => Err(self.format_error_msg(e)),
+        }
+    }
+
+    fn get_mnemonic(&self) -> Result<String, Error> {
+        if !self.mnemonic_export {
+            return Err(errors::mnemonic_export_disabled());
+        }
+        match self.core.get_mnemonic() {
+            Ok(mnemonic) => Ok(mnemonic),
+            Err(e) => Err(self.format_error_msg(e)),
+        }
+    }
+
+    fn import_mnemonic(&self, phrase: String, key_count: u32) -> Result<u64, Error> {
+        // keys are derived while the wallet is locked
+        if key_count > MAX_RESTORED_KEYS {
+            return Err(errors::invalid_params(
+                "key_count",
+                format!("at most {} keys could be restored", MAX_RESTORED_KEYS),
+            ));
+        }
+        match self.core.import_mnemonic(phrase, key_count) {
+            Ok(balance) => Ok(balance),
+            Err(e) => Err(self.format_error_msg(e)),
+        }
+    }
+
+    fn set_coin_selection(&self, strategy: CoinSelectionStrategySer) -> Result<(), Error> {
+        self.core.set_coin_selection(strategy.into());
+        Ok(())
+    }
+
+    fn set_wallet_fee_rate(&self, fee_rate: u64) -> Result<(), Error> {
+        if fee_rate > MAX_FEE_RATE {
+            return Err(errors::invalid_params(
+                "fee_rate",
+                format!("fee rate exceeds {} satoshis per byte", MAX_FEE_RATE),
+            ));
+        }
+        self.core.set_fee_rate(fee_rate);
+        Ok(())
+    }
+
     fn wallet_add_tx(&self, txid: H256_ser, index: u32) -> Result<(), Error> {
         let txid = txid.reversed().into();
         match self.core.wallet_add_tx(txid, index) {
@@ -192,40 +479,64 @@ where
         }
     }
 
-    fn print_coins(&self) -> Result<(), Error> {
-        self.core.print_coins();
-        Ok(())        
+    fn list_unspent(&self) -> Result<Vec<WalletCoin>, Error> {
+        Ok(self.core.list_unspent().into_iter().map(Into::into).collect())
     }
 
-    fn covet_generate_keypair(&self) -> Result<AddressHash_ser, Error> {
-        match self.core.covet_generate_keypair() {
+    fn list_wallet_coins(&self) -> Result<Vec<WalletCoin>, Error> {
+        Ok(self.core.list_coins().into_iter().map(Into::into).collect())
+    }
+
+    fn adversary_generate_keypair(&self) -> Result<AddressHash_ser, Error> {
+        match self.core.adversary_generate_keypair() {
             Ok(addr) => Ok(addr.into()),
             Err(e) => Err(self.format_error_msg(e)),
         }
     }
 
-    fn covet_get_addresshash(&self) -> Result<AddressHash_ser, Error> {
-        match self.core.covet_get_addresshash() {
+    fn adversary_get_addresshash(&self) -> Result<AddressHash_ser, Error> {
+        match self.core.adversary_get_addresshash() {
             Ok(addr) => Ok(addr.into()),
             Err(e) => Err(self.format_error_msg(e)),
         }
     }
 
-
-    fn covet_pay(&self, addrhash: AddressHash_ser, value: u64) -> Result<H256_ser, Error> {
-        let mut receipant_hash: AddressHash = addrhash.clone().into();
-        match self.core.covet_pay(receipant_hash, value) {
-            Ok(result) => Ok(result.into()),
+    fn adversary_wallet_add_tx(&self, txid: H256_ser, index: u32) -> Result<(), Error> {
+        let txid = txid.reversed().into();
+        match self.core.adversary_wallet_add_tx(txid, index) {
+            Ok(()) => Ok(()),
             Err(e) => Err(self.format_error_msg(e)),
         }
     }
 
+    fn set_adversary(&self, strategy: AdversaryStrategySer) -> Result<(), Error> {
+        self.core.set_adversary(strategy.into());
+        Ok(())
+    }
 
-    fn covet_wallet_add_tx(&self, txid: H256_ser, index: u32) -> Result<(), Error> {
-        let txid = txid.reversed().into();
-        match self.core.covet_wallet_add_tx(txid, index) {
-            Ok(()) => Ok(()),
-            Err(e) => Err(self.format_error_msg(e)),
+    fn adversary_pay(&self, addrhash: AddressHash_ser, value: u64) -> Result<AdversaryAttemptSer, Error> {
+        let receipant_hash: AddressHash = addrhash.into();
+        match self.core.adversary_pay(receipant_hash, value) {
+            Ok(attempt) => Ok(attempt.into()),
+            Err(AdversaryError::Wallet(e)) => Err(self.format_error_msg(e)),
+            Err(e) => Err(errors::execution(e)),
         }
     }
+
+    fn get_adversary_log(&self) -> Result<Vec<AdversaryAttemptSer>, Error> {
+        Ok(self.core.adversary_log().into_iter().map(Into::into).collect())
+    }
+}
+
+fn parse_public_keys(keys: Vec<Bytes>) -> Result<Vec<Public>, Error> {
+    keys.into_iter()
+        .map(|key| Public::from_slice(&key.to_vec()).map_err(|e| errors::invalid_params("keys", e)))
+        .collect()
+}
+
+fn multisig_address(address: Address, redeem_script: Script) -> MultisigAddress {
+    MultisigAddress {
+        address: address,
+        redeem_script: Bytes::new(redeem_script.to_bytes().take()),
+    }
 }
@@ -4,7 +4,7 @@ pub mod impls;
 pub mod traits;
 pub mod types;
 
-pub use self::impls::{BlockChainClient, BlockChainClientCore};
+pub use self::impls::{BlockChainClient, BlockChainClientCore, MAX_BLOCK_RANGE_COUNT};
 pub use self::impls::{MinerClient, MinerClientCore};
 pub use self::impls::{NetworkClient, NetworkClientCore};
 pub use self::impls::{RawClient, RawClientCore};
@@ -1,10 +1,15 @@
 use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
 
+use v1::types::BlockRangeBlock;
+use v1::types::{AddressIndexPage, GetAddressHistoryResponse, GetAddressUtxosResponse};
+use v1::types::GetBlockFilterResponse;
 use v1::types::GetBlockResponse;
+use v1::types::GetRawMemoryPoolResponse;
 use v1::types::GetTxOutResponse;
 use v1::types::GetTxOutSetInfoResponse;
 use v1::types::H256;
+use v1::types::{MemoryPoolEntry, MemoryPoolInfo};
 
 build_rpc_trait! {
     /// Parity-bitcoin blockchain data interface.
@@ -29,6 +34,15 @@ build_rpc_trait! {
         /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getblock", "params": ["000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
         #[rpc(name = "getblock")]
         fn block(&self, H256, Trailing<bool>) -> Result<GetBlockResponse, Error>;
+        /// Get up to given number of canonical blocks, starting from given height, with validity of their transactions.
+        /// Verbosity 1 also returns serialized transactions.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getblockrange", "params": [0, 100, 1], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getblockrange")]
+        fn block_range(&self, u32, u32, Trailing<u8>) -> Result<Vec<BlockRangeBlock>, Error>;
+        /// Get BIP158 filter of given block and its filter header. The only supported filter type is "basic".
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getblockfilter", "params": ["000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f", "basic"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getblockfilter")]
+        fn block_filter(&self, H256, Trailing<String>) -> Result<GetBlockFilterResponse, Error>;
         /// Get details about an unspent transaction output.
         /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "gettxout", "params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b", 0], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
         #[rpc(name = "gettxout")]
@@ -37,5 +51,33 @@ build_rpc_trait! {
         /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "gettxoutsetinfo", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
         #[rpc(name = "gettxoutsetinfo")]
         fn transaction_out_set_info(&self) -> Result<GetTxOutSetInfoResponse, Error>;
+        /// Get hashes (or verbose entries) of all memory pool transactions.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getrawmempool", "params": [true], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getrawmempool")]
+        fn raw_memory_pool(&self, Trailing<bool>) -> Result<GetRawMemoryPoolResponse, Error>;
+        /// Get memory pool state.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getmempoolinfo", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getmempoolinfo")]
+        fn memory_pool_info(&self) -> Result<MemoryPoolInfo, Error>;
+        /// Get memory pool entry of given transaction.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getmempoolentry", "params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getmempoolentry")]
+        fn memory_pool_entry(&self, H256) -> Result<MemoryPoolEntry, Error>;
+        /// Get hashes (or verbose entries) of all in-pool ancestors of given memory pool transaction.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getmempoolancestors", "params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b", true], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getmempoolancestors")]
+        fn memory_pool_ancestors(&self, H256, Trailing<bool>) -> Result<GetRawMemoryPoolResponse, Error>;
+        /// Get hashes (or verbose entries) of all in-pool descendants of given memory pool transaction.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getmempooldescendants", "params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b", true], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getmempooldescendants")]
+        fn memory_pool_descendants(&self, H256, Trailing<bool>) -> Result<GetRawMemoryPoolResponse, Error>;
+        /// Get confirmed unspent outputs, paying to given address. Requires address index (--addressindex).
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getaddressutxos", "params": ["1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa", {"from": 0, "count": 100}], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getaddressutxos")]
+        fn address_utxos(&self, String, Trailing<AddressIndexPage>) -> Result<GetAddressUtxosResponse, Error>;
+        /// Get confirmed balance changes of given address, in the chain order. Requires address index (--addressindex).
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getaddresshistory", "params": ["1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa", {"from": 0, "count": 100}], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getaddresshistory")]
+        fn address_history(&self, String, Trailing<AddressIndexPage>) -> Result<GetAddressHistoryResponse, Error>;
     }
 }
@@ -1,6 +1,6 @@
 use jsonrpc_core::Error;
 
-use v1::types::{BlockTemplate, BlockTemplateRequest};
+use v1::types::{BlockFault, BlockTemplate, BlockTemplateRequest, GenerateInvalidBlockResponse};
 
 use v1::types::H160;
 use v1::types::H256;
@@ -16,14 +16,11 @@ build_rpc_trait! {
         #[rpc(name = "generateblocks")]
         fn generate_blocks(&self, H160, u32) -> Result<H256, Error>;
 
+        /// Generate blocks with given faults injected. One block is generated for every list of faults.
+        /// Faulty transactions spend outputs of the given private key (WIF) and coinbases are paid to it.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "generateinvalidblocks", "params": ["KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn", [["overspend", "badsignature"], ["chaindoublespend"]]], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
         #[rpc(name = "generateinvalidblocks")]
-        fn generate_invalid_blocks(&self, H160, u32) -> Result<H256, Error>;
-
-        #[rpc(name = "logblocks")]
-        fn log_blocks(&self) -> Result<(), Error>;
-
-        #[rpc(name = "logmempool")]
-        fn log_mempool(&self) -> Result<(), Error>;
+        fn generate_invalid_blocks(&self, String, Vec<Vec<BlockFault>>) -> Result<Vec<GenerateInvalidBlockResponse>, Error>;
 
         #[rpc(name = "signalsanitize")]
         fn signal_sanitize(&self) -> Result<(), Error>;
@@ -1,8 +1,12 @@
 use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
 
+use v1::types::{DecodePsbtResponse, FinalizePsbtResponse, Psbt};
 use v1::types::GetRawTransactionResponse;
+use v1::types::GetTxValidityResponse;
+use v1::types::PreviousTransactionOutput;
 use v1::types::RawTransaction;
+use v1::types::SignRawTransactionResponse;
 use v1::types::Transaction;
 use v1::types::TransactionInput;
 use v1::types::TransactionOutputs;
@@ -27,9 +31,30 @@ build_rpc_trait! {
         /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getrawtransaction", "params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
         #[rpc(name = "getrawtransaction")]
         fn get_raw_transaction(&self, H256, Trailing<bool>) -> Result<GetRawTransactionResponse, Error>;
-
+        /// Return validity of the canon chain transaction and the reason why it has been marked invalid.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "gettxvalidity", "params": ["4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "gettxvalidity")]
+        fn get_tx_validity(&self, H256) -> Result<GetTxValidityResponse, Error>;
+        /// Sign inputs of the serialized, hex-encoded transaction with given WIF private keys. Outputs, spent by the transaction, are searched in the chain and the memory pool, unless they are passed as prevtxs.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "signrawtransaction", "params": ["01000000013ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a0000000000ffffffff0140420f00000000001976a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac00000000", ["KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn"], [{"txid":"4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b","vout":0,"scriptPubKey":"0014751e76e8199196d454941c45d1b3a323f1433bd6","amount":0.02}]], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
         #[rpc(name = "signrawtransaction")]
-        fn sign_raw_transaction(&self, Vec<TransactionInput>, TransactionOutputs, Trailing<u32>) -> Result<RawTransaction, Error>;
+        fn sign_raw_transaction(&self, RawTransaction, Vec<String>, Trailing<Vec<PreviousTransactionOutput>>) -> Result<SignRawTransactionResponse, Error>;
+        /// Create base64 encoded PSBT of the transaction, spending the given inputs and creating new outputs.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "createpsbt", "params": [[{"txid":"4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b","vout":0}],{"1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa":0.01}], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "createpsbt")]
+        fn create_psbt(&self, Vec<TransactionInput>, TransactionOutputs, Trailing<u32>) -> Result<Psbt, Error>;
+        /// Merge signatures and other data of PSBTs of the same transaction.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "combinepsbt", "params": [["cHNidP8BAFUBAAAAATuj7f16exKyescsPmd2j2F/yBvDiIpRMjqfuKpLHl5KAAAAAAD/////AUBCDwAAAAAAGXapFGLpB7FcvyfVQlOZ6/bw+1DruI8YiKwAAAAAAAAA", "cHNidP8BAFUBAAAAATuj7f16exKyescsPmd2j2F/yBvDiIpRMjqfuKpLHl5KAAAAAAD/////AUBCDwAAAAAAGXapFGLpB7FcvyfVQlOZ6/bw+1DruI8YiKwAAAAAAAAA"]], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "combinepsbt")]
+        fn combine_psbt(&self, Vec<Psbt>) -> Result<Psbt, Error>;
+        /// Finalize PSBT inputs, which have enough signatures. Signed transaction is extracted from complete PSBT, unless extract is false.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "finalizepsbt", "params": ["cHNidP8BAFUBAAAAATuj7f16exKyescsPmd2j2F/yBvDiIpRMjqfuKpLHl5KAAAAAAD/////AUBCDwAAAAAAGXapFGLpB7FcvyfVQlOZ6/bw+1DruI8YiKwAAAAAAAAA", true], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "finalizepsbt")]
+        fn finalize_psbt(&self, Psbt, Trailing<bool>) -> Result<FinalizePsbtResponse, Error>;
+        /// Return an object representing the base64 encoded PSBT.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "decodepsbt", "params": ["cHNidP8BAFUBAAAAATuj7f16exKyescsPmd2j2F/yBvDiIpRMjqfuKpLHl5KAAAAAAD/////AUBCDwAAAAAAGXapFGLpB7FcvyfVQlOZ6/bw+1DruI8YiKwAAAAAAAAA"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "decodepsbt")]
+        fn decode_psbt(&self, Psbt) -> Result<DecodePsbtResponse, Error>;
 
         
     }
@@ -2,6 +2,8 @@ use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
 use v1::types::H256 as H256_ser;
 use v1::types::H160 as AddressHash_ser;
+use v1::types::{AddressInfo, AddressType, AdversaryAttempt, AdversaryStrategy, Bytes, CoinSelectionStrategy};
+use v1::types::{MultisigAddress, Psbt, RawTransaction, SignMultisigTransactionResponse, WalletCoin, WalletProcessPsbtResponse};
 
 build_rpc_trait! {
     /// Parity-bitcoin network interface
@@ -13,6 +15,16 @@ build_rpc_trait! {
         #[rpc(name = "generatekeypair")]
         fn generate_keypair(&self) -> Result<AddressHash_ser, Error>;
 
+        /// Derive new receive address of given type: legacy, p2sh-segwit or bech32. Wallet address type is used by default.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getnewaddress", "params": ["bech32"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getnewaddress")]
+        fn get_new_address(&self, Trailing<AddressType>) -> Result<String, Error>;
+
+        /// Change type of addresses, generated by default and used for change.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "setaddresstype", "params": ["p2sh-segwit"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "setaddresstype")]
+        fn set_address_type(&self, AddressType) -> Result<(), Error>;
+
         #[rpc(name = "getbalance")]
         fn get_balance(&self) -> Result<u64, Error>;
 
@@ -20,28 +32,103 @@ build_rpc_trait! {
         #[rpc(name = "shardpay")]
         fn shard_pay(&self, AddressHash_ser, u64) -> Result<H256_ser, Error>;
 
+        /// Pay given number of satoshis to the legacy or segwit address. Returns transaction hash.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "sendtoaddress", "params": ["tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx", 1000], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "sendtoaddress")]
+        fn send_to_address(&self, String, u64) -> Result<H256_ser, Error>;
+
+        /// Create m-of-n multisig P2SH address of given public keys, without adding it to the wallet.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "createmultisig", "params": [2, ["02a6fee0a5b5a8bad5ca9ba6e1c9e1bd4a0d8d94b8a7b4e71f86fd1d84a8bca3f6", "03b2f1f0d9c58c1a0fc7a1ad3b4a2f8a18ed1f2e53f5bde89da54d8d5a27d3a6c1"]], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "createmultisig")]
+        fn create_multisig(&self, u8, Vec<Bytes>) -> Result<MultisigAddress, Error>;
+
+        /// Add m-of-n multisig P2SH address of given public keys to the wallet, so that its outputs are tracked.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "addmultisigaddress", "params": [2, ["02a6fee0a5b5a8bad5ca9ba6e1c9e1bd4a0d8d94b8a7b4e71f86fd1d84a8bca3f6", "03b2f1f0d9c58c1a0fc7a1ad3b4a2f8a18ed1f2e53f5bde89da54d8d5a27d3a6c1"]], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "addmultisigaddress")]
+        fn add_multisig_address(&self, u8, Vec<Bytes>) -> Result<MultisigAddress, Error>;
+
+        /// Add wallet signatures to multisig inputs of raw transaction, keeping signatures of other wallets.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "signmultisigtransaction", "params": ["01000000011da9283b4ddf8d89eb996988b89ead56cecdc44041ab38bf787f1206cd90b51e0000000000ffffffff01405dc600000000001976a9140dfc8bafc8419853b34d5e072ad37d1a5159f58488ac00000000"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "signmultisigtransaction")]
+        fn sign_multisig_transaction(&self, RawTransaction) -> Result<SignMultisigTransactionResponse, Error>;
+
+        /// Information about the address: is it owned by the wallet, its public key or redeem script.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getaddressinfo", "params": ["2N2JD6wb56AfK4tfmM6PwdVmoYk2dCKf4Br"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getaddressinfo")]
+        fn get_address_info(&self, String) -> Result<AddressInfo, Error>;
+
+        /// Add spent outputs and scripts, known to the node and the wallet, to PSBT and sign its inputs with the wallet keys, unless sign is false.
+        /// Inputs are signed with given sighash type (ALL by default) and inputs with other sighash type are refused.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "walletprocesspsbt", "params": ["cHNidP8BAFUBAAAAATuj7f16exKyescsPmd2j2F/yBvDiIpRMjqfuKpLHl5KAAAAAAD/////AUBCDwAAAAAAGXapFGLpB7FcvyfVQlOZ6/bw+1DruI8YiKwAAAAAAAAA", true, "ALL"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "walletprocesspsbt")]
+        fn wallet_process_psbt(&self, Psbt, bool, Trailing<String>) -> Result<WalletProcessPsbtResponse, Error>;
+
         #[rpc(name = "updatewallet")]
         fn update_wallet(&self) -> Result<(), Error>;
 
+        /// Track all wallet outputs of canonical blocks, starting from given height. Returns new balance.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "rescanwallet", "params": [0], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "rescanwallet")]
+        fn rescan_wallet(&self, u32) -> Result<u64, Error>;
+
+        /// Get BIP39 mnemonic, all wallet keys are derived from. Only available if the node is started with --wallet-mnemonic-rpc.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getmnemonic", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getmnemonic")]
+        fn get_mnemonic(&self) -> Result<String, Error>;
+
+        /// Restore wallet keys from BIP39 mnemonic: derive given number (at most 1000) of receive and change keys and rescan the chain. Returns new balance.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "importmnemonic", "params": ["abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about", 20], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "importmnemonic")]
+        fn import_mnemonic(&self, String, u32) -> Result<u64, Error>;
+
+        /// Change coin selection strategy of wallet payments.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "setcoinselection", "params": ["randomimprove"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "setcoinselection")]
+        fn set_coin_selection(&self, CoinSelectionStrategy) -> Result<(), Error>;
+
+        /// Set fee rate of wallet payments (satoshis per byte, at most 10000).
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "setwalletfeerate", "params": [5], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "setwalletfeerate")]
+        fn set_wallet_fee_rate(&self, u64) -> Result<(), Error>;
+
         #[rpc(name = "walletaddtx")]
         fn wallet_add_tx(&self, H256_ser, u32) -> Result<(), Error>;
 
         #[rpc(name = "getaddresshash")]
         fn get_addresshash(&self) -> Result<AddressHash_ser, Error>;
 
-        #[rpc(name = "printcoins")]
-        fn print_coins(&self) -> Result<(), Error>;
+        /// List spendable coins of the wallet with their confirmations and validity.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "listunspent", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "listunspent")]
+        fn list_unspent(&self) -> Result<Vec<WalletCoin>, Error>;
+
+        /// List all outpoints, tracked by the wallet, including coins which are not spendable (yet).
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "listwalletcoins", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "listwalletcoins")]
+        fn list_wallet_coins(&self) -> Result<Vec<WalletCoin>, Error>;
+
+        /// Attack the network with a payment, using selected adversary strategy.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "adversarypay", "params": ["0000000000000000000000000000000000000001", 1000], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "adversarypay")]
+        fn adversary_pay(&self, AddressHash_ser, u64) -> Result<AdversaryAttempt, Error>;
+
+        #[rpc(name = "adversarygeneratekeypair")]
+        fn adversary_generate_keypair(&self) -> Result<AddressHash_ser, Error>;
 
-        #[rpc(name = "covetpay")]
-        fn covet_pay(&self, AddressHash_ser, u64) -> Result<H256_ser, Error>;
+        #[rpc(name = "adversarygetaddresshash")]
+        fn adversary_get_addresshash(&self) -> Result<AddressHash_ser, Error>;
 
-        #[rpc(name = "covetgeneratekeypair")]
-        fn covet_generate_keypair(&self) -> Result<AddressHash_ser, Error>;
+        #[rpc(name = "adversarywalletaddtx")]
+        fn adversary_wallet_add_tx(&self, H256_ser, u32) -> Result<(), Error>;
 
-        #[rpc(name = "covetgetaddresshash")]
-        fn covet_get_addresshash(&self) -> Result<AddressHash_ser, Error>;
+        /// Select adversary strategy.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "setadversary", "params": ["conflictingfanout"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "setadversary")]
+        fn set_adversary(&self, AdversaryStrategy) -> Result<(), Error>;
 
-        #[rpc(name = "covetwalletaddtx")]
-        fn covet_wallet_add_tx(&self, H256_ser, u32) -> Result<(), Error>;
+        /// Get most recent attack attempts.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getadversarylog", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getadversarylog")]
+        fn get_adversary_log(&self) -> Result<Vec<AdversaryAttempt>, Error>;
     }
 }
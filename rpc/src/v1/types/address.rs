@@ -103,4 +103,12 @@ mod tests {
             test
         );
     }
+
+    #[test]
+    fn segwit_address_serde() {
+        let test = TestStruct::new("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4".into());
+        let json = r#"{"address":"bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"}"#;
+        assert_eq!(serde_json::to_string(&test).unwrap(), json);
+        assert_eq!(serde_json::from_str::<TestStruct>(json).unwrap(), test);
+    }
 }
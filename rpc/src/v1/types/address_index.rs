@@ -0,0 +1,97 @@
+use super::hash::H256;
+
+/// Page of getaddressutxos and getaddresshistory responses
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct AddressIndexPage {
+    /// Number of entries to skip
+    #[serde(default)]
+    pub from: usize,
+    /// Max number of entries to return
+    pub count: Option<usize>,
+}
+
+/// Confirmed unspent output, paying to the address
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct AddressUtxo {
+    /// Transaction hash
+    pub txid: H256,
+    /// Output index
+    pub vout: u32,
+    /// Output value in satoshis
+    pub satoshis: u64,
+    /// Height of the block, containing the transaction
+    pub height: u32,
+}
+
+/// Confirmed change of the address balance
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct AddressDelta {
+    /// Transaction hash
+    pub txid: H256,
+    /// Index of the funding output, or of the spending input
+    pub index: u32,
+    /// Value of the funding output, or negated value of the spent output, in satoshis
+    pub satoshis: i64,
+    /// Height of the block, containing the transaction
+    pub height: u32,
+}
+
+/// getaddressutxos response
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct GetAddressUtxosResponse {
+    /// Total number of unspent outputs, paying to the address
+    pub total: usize,
+    /// Requested page of unspent outputs
+    pub utxos: Vec<AddressUtxo>,
+}
+
+/// getaddresshistory response
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct GetAddressHistoryResponse {
+    /// Total number of the address balance changes
+    pub total: usize,
+    /// Requested page of balance changes, in the chain order
+    pub history: Vec<AddressDelta>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::hash::H256;
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn address_index_page_deserialize() {
+        assert_eq!(
+            serde_json::from_str::<AddressIndexPage>(r#"{"count":10}"#).unwrap(),
+            AddressIndexPage {
+                from: 0,
+                count: Some(10),
+            }
+        );
+        assert_eq!(
+            serde_json::from_str::<AddressIndexPage>(r#"{"from":5}"#).unwrap(),
+            AddressIndexPage {
+                from: 5,
+                count: None,
+            }
+        );
+    }
+
+    #[test]
+    fn get_address_history_response_serialize() {
+        let response = GetAddressHistoryResponse {
+            total: 2,
+            history: vec![AddressDelta {
+                txid: H256::from(1),
+                index: 0,
+                satoshis: -1000,
+                height: 10,
+            }],
+        };
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"total":2,"history":[{"txid":"0100000000000000000000000000000000000000000000000000000000000000","index":0,"satoshis":-1000,"height":10}]}"#
+        );
+    }
+}
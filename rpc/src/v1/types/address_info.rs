@@ -0,0 +1,39 @@
+use super::bytes::Bytes;
+use keys::Address;
+use v1::types;
+
+/// getaddressinfo response
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct AddressInfo {
+    /// The address
+    #[serde(with = "types::address")]
+    pub address: Address,
+    /// Is address paying to the wallet key or to the wallet multisig script?
+    pub ismine: bool,
+    /// Public key of the wallet key
+    pub pubkey: Option<Bytes>,
+    /// Redeem script of the wallet multisig address
+    #[serde(rename = "redeemScript")]
+    pub redeem_script: Option<Bytes>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::bytes::Bytes;
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn address_info_serialize() {
+        let info = AddressInfo {
+            address: "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".into(),
+            ismine: true,
+            pubkey: Some(Bytes::new(vec![2, 1])),
+            redeem_script: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&info).unwrap(),
+            r#"{"address":"1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa","ismine":true,"pubkey":"0201","redeemScript":null}"#
+        );
+    }
+}
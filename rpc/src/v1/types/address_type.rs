@@ -0,0 +1,94 @@
+use serde::de::Unexpected;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use sync::AddressType as GlobalAddressType;
+
+/// Type of wallet addresses
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AddressType {
+    Legacy,
+    P2shSegwit,
+    Bech32,
+}
+
+impl From<AddressType> for GlobalAddressType {
+    fn from(address_type: AddressType) -> Self {
+        match address_type {
+            AddressType::Legacy => GlobalAddressType::Legacy,
+            AddressType::P2shSegwit => GlobalAddressType::P2shSegwit,
+            AddressType::Bech32 => GlobalAddressType::Bech32,
+        }
+    }
+}
+
+impl From<GlobalAddressType> for AddressType {
+    fn from(address_type: GlobalAddressType) -> Self {
+        match address_type {
+            GlobalAddressType::Legacy => AddressType::Legacy,
+            GlobalAddressType::P2shSegwit => AddressType::P2shSegwit,
+            GlobalAddressType::Bech32 => AddressType::Bech32,
+        }
+    }
+}
+
+impl Serialize for AddressType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        GlobalAddressType::from(*self).name().serialize(serializer)
+    }
+}
+
+impl<'a> Deserialize<'a> for AddressType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        use serde::de::Visitor;
+
+        struct AddressTypeVisitor;
+
+        impl<'b> Visitor<'b> for AddressTypeVisitor {
+            type Value = AddressType;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("address type")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<AddressType, E>
+            where
+                E: ::serde::de::Error,
+            {
+                value
+                    .parse::<GlobalAddressType>()
+                    .map(Into::into)
+                    .map_err(|_| E::invalid_value(Unexpected::Str(value), &self))
+            }
+        }
+
+        deserializer.deserialize_identifier(AddressTypeVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AddressType;
+    use serde_json;
+
+    #[test]
+    fn address_type_serialize() {
+        assert_eq!(serde_json::to_string(&AddressType::Legacy).unwrap(), r#""legacy""#);
+        assert_eq!(serde_json::to_string(&AddressType::P2shSegwit).unwrap(), r#""p2sh-segwit""#);
+        assert_eq!(serde_json::to_string(&AddressType::Bech32).unwrap(), r#""bech32""#);
+    }
+
+    #[test]
+    fn address_type_deserialize() {
+        assert_eq!(
+            serde_json::from_str::<AddressType>(r#""p2sh-segwit""#).unwrap(),
+            AddressType::P2shSegwit
+        );
+        assert!(serde_json::from_str::<AddressType>(r#""p2tr""#).is_err());
+    }
+}
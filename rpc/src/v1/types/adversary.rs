@@ -0,0 +1,199 @@
+use super::hash::{H160, H256};
+use serde::de::Unexpected;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use sync::{
+    AdversaryAttempt as GlobalAdversaryAttempt, AdversaryStrategy as GlobalAdversaryStrategy,
+    AttemptedTransaction,
+};
+
+/// Adversary strategy
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AdversaryStrategy {
+    DoubleSpendRace,
+    ConflictingFanOut,
+    UnconfirmedChangeSpend,
+}
+
+/// Output, spent by the adversary transaction
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct AdversaryOutPoint {
+    /// Previous transaction id
+    pub txid: H256,
+    /// Previous transaction output index
+    pub vout: u32,
+}
+
+/// Transaction, created by the adversary
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct AdversaryTransaction {
+    /// Transaction id
+    pub txid: H256,
+    /// Outputs, spent by the transaction
+    pub spends: Vec<AdversaryOutPoint>,
+    /// Peers, which have received the transaction directly
+    pub peers: Vec<usize>,
+    /// Has the transaction been accepted by the local node? None if it has been sent directly to peers
+    pub accepted: Option<bool>,
+    /// Local node verification error
+    pub error: Option<String>,
+}
+
+/// Adversary attack attempt
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct AdversaryAttempt {
+    /// Strategy, used for the attack
+    pub strategy: AdversaryStrategy,
+    /// Recipient of the payment
+    pub recipient: H160,
+    /// Value of the payment
+    pub value: u64,
+    /// Transactions, created during the attack
+    pub transactions: Vec<AdversaryTransaction>,
+}
+
+impl From<AdversaryStrategy> for GlobalAdversaryStrategy {
+    fn from(strategy: AdversaryStrategy) -> Self {
+        match strategy {
+            AdversaryStrategy::DoubleSpendRace => GlobalAdversaryStrategy::DoubleSpendRace,
+            AdversaryStrategy::ConflictingFanOut => GlobalAdversaryStrategy::ConflictingFanOut,
+            AdversaryStrategy::UnconfirmedChangeSpend => {
+                GlobalAdversaryStrategy::UnconfirmedChangeSpend
+            }
+        }
+    }
+}
+
+impl From<GlobalAdversaryStrategy> for AdversaryStrategy {
+    fn from(strategy: GlobalAdversaryStrategy) -> Self {
+        match strategy {
+            GlobalAdversaryStrategy::DoubleSpendRace => AdversaryStrategy::DoubleSpendRace,
+            GlobalAdversaryStrategy::ConflictingFanOut => AdversaryStrategy::ConflictingFanOut,
+            GlobalAdversaryStrategy::UnconfirmedChangeSpend => {
+                AdversaryStrategy::UnconfirmedChangeSpend
+            }
+        }
+    }
+}
+
+impl From<AttemptedTransaction> for AdversaryTransaction {
+    fn from(transaction: AttemptedTransaction) -> Self {
+        AdversaryTransaction {
+            txid: transaction.hash.reversed().into(),
+            spends: transaction
+                .spends
+                .into_iter()
+                .map(|outpoint| AdversaryOutPoint {
+                    txid: outpoint.hash.reversed().into(),
+                    vout: outpoint.index,
+                })
+                .collect(),
+            peers: transaction.peers,
+            accepted: transaction.submit_result.as_ref().map(Result::is_ok),
+            error: transaction.submit_result.and_then(Result::err),
+        }
+    }
+}
+
+impl From<GlobalAdversaryAttempt> for AdversaryAttempt {
+    fn from(attempt: GlobalAdversaryAttempt) -> Self {
+        AdversaryAttempt {
+            strategy: attempt.strategy.into(),
+            recipient: attempt.recipient.into(),
+            value: attempt.value,
+            transactions: attempt.transactions.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl Serialize for AdversaryStrategy {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        GlobalAdversaryStrategy::from(*self).name().serialize(serializer)
+    }
+}
+
+impl<'a> Deserialize<'a> for AdversaryStrategy {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        use serde::de::Visitor;
+
+        struct AdversaryStrategyVisitor;
+
+        impl<'b> Visitor<'b> for AdversaryStrategyVisitor {
+            type Value = AdversaryStrategy;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("adversary strategy")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<AdversaryStrategy, E>
+            where
+                E: ::serde::de::Error,
+            {
+                value
+                    .parse::<GlobalAdversaryStrategy>()
+                    .map(Into::into)
+                    .map_err(|_| E::invalid_value(Unexpected::Str(value), &self))
+            }
+        }
+
+        deserializer.deserialize_identifier(AdversaryStrategyVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::hash::{H160, H256};
+    use super::{AdversaryAttempt, AdversaryOutPoint, AdversaryStrategy, AdversaryTransaction};
+    use serde_json;
+
+    #[test]
+    fn adversary_strategy_serialize() {
+        assert_eq!(
+            serde_json::to_string(&AdversaryStrategy::DoubleSpendRace).unwrap(),
+            r#""doublespendrace""#
+        );
+        assert_eq!(
+            serde_json::to_string(&AdversaryStrategy::ConflictingFanOut).unwrap(),
+            r#""conflictingfanout""#
+        );
+        assert_eq!(
+            serde_json::to_string(&AdversaryStrategy::UnconfirmedChangeSpend).unwrap(),
+            r#""unconfirmedchangespend""#
+        );
+    }
+
+    #[test]
+    fn adversary_strategy_deserialize() {
+        assert_eq!(
+            serde_json::from_str::<AdversaryStrategy>(r#""conflictingfanout""#).unwrap(),
+            AdversaryStrategy::ConflictingFanOut
+        );
+        assert!(serde_json::from_str::<AdversaryStrategy>(r#""covetous""#).is_err());
+    }
+
+    #[test]
+    fn adversary_attempt_serialize() {
+        let attempt = AdversaryAttempt {
+            strategy: AdversaryStrategy::DoubleSpendRace,
+            recipient: H160::from(0x12),
+            value: 30,
+            transactions: vec![AdversaryTransaction {
+                txid: H256::from(0x56),
+                spends: vec![AdversaryOutPoint {
+                    txid: H256::from(0x78),
+                    vout: 1,
+                }],
+                peers: vec![2],
+                accepted: None,
+                error: None,
+            }],
+        };
+        assert_eq!(serde_json::to_string(&attempt).unwrap(), r#"{"strategy":"doublespendrace","recipient":"1200000000000000000000000000000000000000","value":30,"transactions":[{"txid":"5600000000000000000000000000000000000000000000000000000000000000","spends":[{"txid":"7800000000000000000000000000000000000000000000000000000000000000","vout":1}],"peers":[2],"accepted":null,"error":null}]}"#);
+    }
+}
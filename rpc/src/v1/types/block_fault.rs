@@ -0,0 +1,184 @@
+use super::hash::H256;
+use miner::BlockFault as GlobalBlockFault;
+use serde::de::Unexpected;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Fault, injected into the block by generateinvalidblocks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockFault {
+    ChainDoubleSpend,
+    MempoolDoubleSpend,
+    BadSignature,
+    Overspend,
+    ImmatureCoinbaseSpend,
+    NonFinalLocktime,
+    OversizedScript,
+    DuplicateTxid,
+}
+
+/// Fault, which has been injected into the block
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct InjectedBlockFault {
+    /// Kind of the fault
+    pub fault: BlockFault,
+    /// Hash of the transaction, carrying the fault
+    pub txid: H256,
+}
+
+/// generateinvalidblocks response
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct GenerateInvalidBlockResponse {
+    /// Hash of the generated block
+    pub hash: H256,
+    /// Faults, injected into the block
+    pub faults: Vec<InjectedBlockFault>,
+}
+
+impl From<BlockFault> for GlobalBlockFault {
+    fn from(fault: BlockFault) -> Self {
+        match fault {
+            BlockFault::ChainDoubleSpend => GlobalBlockFault::ChainDoubleSpend,
+            BlockFault::MempoolDoubleSpend => GlobalBlockFault::MempoolDoubleSpend,
+            BlockFault::BadSignature => GlobalBlockFault::BadSignature,
+            BlockFault::Overspend => GlobalBlockFault::Overspend,
+            BlockFault::ImmatureCoinbaseSpend => GlobalBlockFault::ImmatureCoinbaseSpend,
+            BlockFault::NonFinalLocktime => GlobalBlockFault::NonFinalLocktime,
+            BlockFault::OversizedScript => GlobalBlockFault::OversizedScript,
+            BlockFault::DuplicateTxid => GlobalBlockFault::DuplicateTxid,
+        }
+    }
+}
+
+impl From<GlobalBlockFault> for BlockFault {
+    fn from(fault: GlobalBlockFault) -> Self {
+        match fault {
+            GlobalBlockFault::ChainDoubleSpend => BlockFault::ChainDoubleSpend,
+            GlobalBlockFault::MempoolDoubleSpend => BlockFault::MempoolDoubleSpend,
+            GlobalBlockFault::BadSignature => BlockFault::BadSignature,
+            GlobalBlockFault::Overspend => BlockFault::Overspend,
+            GlobalBlockFault::ImmatureCoinbaseSpend => BlockFault::ImmatureCoinbaseSpend,
+            GlobalBlockFault::NonFinalLocktime => BlockFault::NonFinalLocktime,
+            GlobalBlockFault::OversizedScript => BlockFault::OversizedScript,
+            GlobalBlockFault::DuplicateTxid => BlockFault::DuplicateTxid,
+        }
+    }
+}
+
+impl Serialize for BlockFault {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            BlockFault::ChainDoubleSpend => "chaindoublespend".serialize(serializer),
+            BlockFault::MempoolDoubleSpend => "mempooldoublespend".serialize(serializer),
+            BlockFault::BadSignature => "badsignature".serialize(serializer),
+            BlockFault::Overspend => "overspend".serialize(serializer),
+            BlockFault::ImmatureCoinbaseSpend => "immaturecoinbasespend".serialize(serializer),
+            BlockFault::NonFinalLocktime => "nonfinallocktime".serialize(serializer),
+            BlockFault::OversizedScript => "oversizedscript".serialize(serializer),
+            BlockFault::DuplicateTxid => "duplicatetxid".serialize(serializer),
+        }
+    }
+}
+
+impl<'a> Deserialize<'a> for BlockFault {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        use serde::de::Visitor;
+
+        struct BlockFaultVisitor;
+
+        impl<'b> Visitor<'b> for BlockFaultVisitor {
+            type Value = BlockFault;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("block fault")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<BlockFault, E>
+            where
+                E: ::serde::de::Error,
+            {
+                match value {
+                    "chaindoublespend" => Ok(BlockFault::ChainDoubleSpend),
+                    "mempooldoublespend" => Ok(BlockFault::MempoolDoubleSpend),
+                    "badsignature" => Ok(BlockFault::BadSignature),
+                    "overspend" => Ok(BlockFault::Overspend),
+                    "immaturecoinbasespend" => Ok(BlockFault::ImmatureCoinbaseSpend),
+                    "nonfinallocktime" => Ok(BlockFault::NonFinalLocktime),
+                    "oversizedscript" => Ok(BlockFault::OversizedScript),
+                    "duplicatetxid" => Ok(BlockFault::DuplicateTxid),
+                    _ => Err(E::invalid_value(Unexpected::Str(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_identifier(BlockFaultVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::hash::H256;
+    use super::{BlockFault, GenerateInvalidBlockResponse, InjectedBlockFault};
+    use serde_json;
+
+    #[test]
+    fn block_fault_serialize() {
+        assert_eq!(
+            serde_json::to_string(&BlockFault::ChainDoubleSpend).unwrap(),
+            r#""chaindoublespend""#
+        );
+        assert_eq!(
+            serde_json::to_string(&BlockFault::ImmatureCoinbaseSpend).unwrap(),
+            r#""immaturecoinbasespend""#
+        );
+        assert_eq!(
+            serde_json::to_string(&BlockFault::DuplicateTxid).unwrap(),
+            r#""duplicatetxid""#
+        );
+    }
+
+    #[test]
+    fn block_fault_deserialize() {
+        assert_eq!(
+            serde_json::from_str::<BlockFault>(r#""mempooldoublespend""#).unwrap(),
+            BlockFault::MempoolDoubleSpend
+        );
+        assert_eq!(
+            serde_json::from_str::<BlockFault>(r#""nonfinallocktime""#).unwrap(),
+            BlockFault::NonFinalLocktime
+        );
+        assert!(serde_json::from_str::<BlockFault>(r#""unknownfault""#).is_err());
+    }
+
+    #[test]
+    fn generate_invalid_block_response_serialize() {
+        let response = GenerateInvalidBlockResponse {
+            hash: H256::from(0x56),
+            faults: vec![InjectedBlockFault {
+                fault: BlockFault::Overspend,
+                txid: H256::from(0x78),
+            }],
+        };
+        assert_eq!(serde_json::to_string(&response).unwrap(), r#"{"hash":"5600000000000000000000000000000000000000000000000000000000000000","faults":[{"fault":"overspend","txid":"7800000000000000000000000000000000000000000000000000000000000000"}]}"#);
+    }
+
+    #[test]
+    fn generate_invalid_block_response_deserialize() {
+        let response = GenerateInvalidBlockResponse {
+            hash: H256::from(0x56),
+            faults: vec![InjectedBlockFault {
+                fault: BlockFault::BadSignature,
+                txid: H256::from(0x78),
+            }],
+        };
+        assert_eq!(
+            serde_json::from_str::<GenerateInvalidBlockResponse>(r#"{"hash":"5600000000000000000000000000000000000000000000000000000000000000","faults":[{"fault":"badsignature","txid":"7800000000000000000000000000000000000000000000000000000000000000"}]}"#).unwrap(),
+            response);
+    }
+}
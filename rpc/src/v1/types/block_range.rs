@@ -0,0 +1,87 @@
+use super::hash::H256;
+use super::transaction::RawTransaction;
+
+/// Transaction of the block, returned by getblockrange
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct BlockRangeTransaction {
+    /// Transaction id
+    pub txid: H256,
+    /// Is this a coinbase transaction?
+    pub coinbase: bool,
+    /// Is this transaction valid?
+    pub valid: bool,
+    /// Verification error, which has made this transaction invalid
+    pub error: Option<String>,
+    /// Serialized transaction. Only returned when verbosity is 1
+    pub hex: Option<RawTransaction>,
+}
+
+/// Canonical block, returned by getblockrange
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct BlockRangeBlock {
+    /// Block hash
+    pub hash: H256,
+    /// Block height
+    pub height: u32,
+    /// Block time in seconds since epoch (Jan 1 1970 GMT)
+    pub time: u32,
+    /// Hash of the previous block
+    pub previousblockhash: H256,
+    /// Transactions of the block
+    pub tx: Vec<BlockRangeTransaction>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::bytes::Bytes;
+    use super::super::hash::H256;
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn block_range_block_serialize() {
+        let block = BlockRangeBlock {
+            hash: H256::from(1),
+            height: 2,
+            time: 3,
+            previousblockhash: H256::from(4),
+            tx: vec![
+                BlockRangeTransaction {
+                    txid: H256::from(5),
+                    coinbase: true,
+                    valid: true,
+                    error: None,
+                    hex: Some(Bytes::new(vec![1, 2])),
+                },
+                BlockRangeTransaction {
+                    txid: H256::from(6),
+                    coinbase: false,
+                    valid: false,
+                    error: Some("Overspend".to_owned()),
+                    hex: None,
+                },
+            ],
+        };
+        assert_eq!(serde_json::to_string(&block).unwrap(), r#"{"hash":"0100000000000000000000000000000000000000000000000000000000000000","height":2,"time":3,"previousblockhash":"0400000000000000000000000000000000000000000000000000000000000000","tx":[{"txid":"0500000000000000000000000000000000000000000000000000000000000000","coinbase":true,"valid":true,"error":null,"hex":"0102"},{"txid":"0600000000000000000000000000000000000000000000000000000000000000","coinbase":false,"valid":false,"error":"Overspend","hex":null}]}"#);
+    }
+
+    #[test]
+    fn block_range_block_deserialize() {
+        let block = BlockRangeBlock {
+            hash: H256::from(1),
+            height: 2,
+            time: 3,
+            previousblockhash: H256::from(4),
+            tx: vec![BlockRangeTransaction {
+                txid: H256::from(5),
+                coinbase: true,
+                valid: true,
+                error: None,
+                hex: None,
+            }],
+        };
+        assert_eq!(
+			serde_json::from_str::<BlockRangeBlock>(r#"{"hash":"0100000000000000000000000000000000000000000000000000000000000000","height":2,"time":3,"previousblockhash":"0400000000000000000000000000000000000000000000000000000000000000","tx":[{"txid":"0500000000000000000000000000000000000000000000000000000000000000","coinbase":true,"valid":true,"error":null,"hex":null}]}"#).unwrap(),
+			block);
+    }
+}
@@ -0,0 +1,103 @@
+use serde::de::Unexpected;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use sync::CoinSelectionStrategy as GlobalCoinSelectionStrategy;
+
+/// Coin selection strategy of wallet payments
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoinSelectionStrategy {
+    LargestFirst,
+    BranchAndBound,
+    RandomImprove,
+}
+
+impl From<CoinSelectionStrategy> for GlobalCoinSelectionStrategy {
+    fn from(strategy: CoinSelectionStrategy) -> Self {
+        match strategy {
+            CoinSelectionStrategy::LargestFirst => GlobalCoinSelectionStrategy::LargestFirst,
+            CoinSelectionStrategy::BranchAndBound => GlobalCoinSelectionStrategy::BranchAndBound,
+            CoinSelectionStrategy::RandomImprove => GlobalCoinSelectionStrategy::RandomImprove,
+        }
+    }
+}
+
+impl From<GlobalCoinSelectionStrategy> for CoinSelectionStrategy {
+    fn from(strategy: GlobalCoinSelectionStrategy) -> Self {
+        match strategy {
+            GlobalCoinSelectionStrategy::LargestFirst => CoinSelectionStrategy::LargestFirst,
+            GlobalCoinSelectionStrategy::BranchAndBound => CoinSelectionStrategy::BranchAndBound,
+            GlobalCoinSelectionStrategy::RandomImprove => CoinSelectionStrategy::RandomImprove,
+        }
+    }
+}
+
+impl Serialize for CoinSelectionStrategy {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        GlobalCoinSelectionStrategy::from(*self).name().serialize(serializer)
+    }
+}
+
+impl<'a> Deserialize<'a> for CoinSelectionStrategy {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        use serde::de::Visitor;
+
+        struct CoinSelectionStrategyVisitor;
+
+        impl<'b> Visitor<'b> for CoinSelectionStrategyVisitor {
+            type Value = CoinSelectionStrategy;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("coin selection strategy")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<CoinSelectionStrategy, E>
+            where
+                E: ::serde::de::Error,
+            {
+                value
+                    .parse::<GlobalCoinSelectionStrategy>()
+                    .map(Into::into)
+                    .map_err(|_| E::invalid_value(Unexpected::Str(value), &self))
+            }
+        }
+
+        deserializer.deserialize_identifier(CoinSelectionStrategyVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CoinSelectionStrategy;
+    use serde_json;
+
+    #[test]
+    fn coin_selection_strategy_serialize() {
+        assert_eq!(
+            serde_json::to_string(&CoinSelectionStrategy::LargestFirst).unwrap(),
+            r#""largestfirst""#
+        );
+        assert_eq!(
+            serde_json::to_string(&CoinSelectionStrategy::BranchAndBound).unwrap(),
+            r#""branchandbound""#
+        );
+        assert_eq!(
+            serde_json::to_string(&CoinSelectionStrategy::RandomImprove).unwrap(),
+            r#""randomimprove""#
+        );
+    }
+
+    #[test]
+    fn coin_selection_strategy_deserialize() {
+        assert_eq!(
+            serde_json::from_str::<CoinSelectionStrategy>(r#""randomimprove""#).unwrap(),
+            CoinSelectionStrategy::RandomImprove
+        );
+        assert!(serde_json::from_str::<CoinSelectionStrategy>(r#""smallestfirst""#).is_err());
+    }
+}
@@ -0,0 +1,40 @@
+use super::bytes::Bytes;
+use super::hash::H256;
+
+/// getblockfilter response
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct GetBlockFilterResponse {
+    /// Serialized BIP158 filter of the block
+    pub filter: Bytes,
+    /// Filter header, committing to this filter and to filters of all previous blocks
+    pub header: H256,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::bytes::Bytes;
+    use super::super::hash::H256;
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn block_filter_response_serialize() {
+        let response = GetBlockFilterResponse {
+            filter: Bytes::new(vec![0x01, 0x9d, 0xfc, 0xa8]),
+            header: H256::from(0x21),
+        };
+        assert_eq!(serde_json::to_string(&response).unwrap(), r#"{"filter":"019dfca8","header":"2100000000000000000000000000000000000000000000000000000000000000"}"#);
+    }
+
+    #[test]
+    fn block_filter_response_deserialize() {
+        let response = GetBlockFilterResponse {
+            filter: Bytes::new(vec![0x01, 0x9d, 0xfc, 0xa8]),
+            header: H256::from(0x21),
+        };
+        assert_eq!(
+            serde_json::from_str::<GetBlockFilterResponse>(r#"{"filter":"019dfca8","header":"2100000000000000000000000000000000000000000000000000000000000000"}"#).unwrap(),
+            response
+        );
+    }
+}
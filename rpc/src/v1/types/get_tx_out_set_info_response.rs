@@ -1,2 +1,53 @@
-#[derive(Debug, Serialize, Deserialize)]
-pub struct GetTxOutSetInfoResponse {}
+use super::hash::H256;
+
+/// gettxoutsetinfo response
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct GetTxOutSetInfoResponse {
+    /// Height of the best block
+    pub height: u32,
+    /// Hash of the best block
+    pub bestblock: H256,
+    /// Number of unspent transaction outputs
+    pub txouts: u64,
+    /// Database-independent estimation of the UTXO set size
+    pub bogosize: u64,
+    /// Rolling MuHash3072 hash of the UTXO set
+    pub muhash: H256,
+    /// Total value of unspent outputs in BTC
+    pub total_amount: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::hash::H256;
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn tx_out_set_info_response_serialize() {
+        let info = GetTxOutSetInfoResponse {
+            height: 2,
+            bestblock: H256::from(0x56),
+            txouts: 3,
+            bogosize: 159,
+            muhash: H256::from(0x78),
+            total_amount: 150.5,
+        };
+        assert_eq!(serde_json::to_string(&info).unwrap(), r#"{"height":2,"bestblock":"5600000000000000000000000000000000000000000000000000000000000000","txouts":3,"bogosize":159,"muhash":"7800000000000000000000000000000000000000000000000000000000000000","total_amount":150.5}"#);
+    }
+
+    #[test]
+    fn tx_out_set_info_response_deserialize() {
+        let info = GetTxOutSetInfoResponse {
+            height: 2,
+            bestblock: H256::from(0x56),
+            txouts: 3,
+            bogosize: 159,
+            muhash: H256::from(0x78),
+            total_amount: 150.5,
+        };
+        assert_eq!(
+			serde_json::from_str::<GetTxOutSetInfoResponse>(r#"{"height":2,"bestblock":"5600000000000000000000000000000000000000000000000000000000000000","txouts":3,"bogosize":159,"muhash":"7800000000000000000000000000000000000000000000000000000000000000","total_amount":150.5}"#).unwrap(),
+			info);
+    }
+}
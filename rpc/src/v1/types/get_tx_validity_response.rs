@@ -0,0 +1,49 @@
+use super::hash::H256;
+
+/// gettxvalidity response
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct GetTxValidityResponse {
+    /// Hash of the block this transaction is included into
+    pub blockhash: H256,
+    /// Height of the block this transaction is included into
+    pub height: u32,
+    /// Is this transaction valid?
+    pub valid: bool,
+    /// Verification error, which has made this transaction invalid
+    pub error: Option<String>,
+    /// Verdict of the shard blocks sanitizer, if the block has been sanitized already
+    pub sanitized: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::hash::H256;
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn tx_validity_response_serialize() {
+        let validity = GetTxValidityResponse {
+            blockhash: H256::from(0x56),
+            height: 777,
+            valid: false,
+            error: Some("Overspend".to_owned()),
+            sanitized: None,
+        };
+        assert_eq!(serde_json::to_string(&validity).unwrap(), r#"{"blockhash":"5600000000000000000000000000000000000000000000000000000000000000","height":777,"valid":false,"error":"Overspend","sanitized":null}"#);
+    }
+
+    #[test]
+    fn tx_validity_response_deserialize() {
+        let validity = GetTxValidityResponse {
+            blockhash: H256::from(0x56),
+            height: 777,
+            valid: true,
+            error: None,
+            sanitized: Some(true),
+        };
+        assert_eq!(
+			serde_json::from_str::<GetTxValidityResponse>(r#"{"blockhash":"5600000000000000000000000000000000000000000000000000000000000000","height":777,"valid":true,"error":null,"sanitized":true}"#).unwrap(),
+			validity);
+    }
+}
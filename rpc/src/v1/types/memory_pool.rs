@@ -0,0 +1,138 @@
+use super::hash::H256;
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// getmempoolinfo response
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct MemoryPoolInfo {
+    /// Number of transactions in the memory pool
+    pub size: usize,
+    /// Sum of serialized sizes of all memory pool transactions
+    pub bytes: usize,
+    /// Total memory usage of the memory pool
+    pub usage: usize,
+}
+
+/// Memory pool transaction entry
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct MemoryPoolEntry {
+    /// Transaction size in bytes
+    pub size: usize,
+    /// Transaction fee in BTC
+    pub fee: f64,
+    /// Transaction fee, modified by virtual fee, in BTC
+    pub modifiedfee: f64,
+    /// Number of in-pool descendant transactions (including this one)
+    pub descendantcount: usize,
+    /// Size of in-pool descendants (including this one)
+    pub descendantsize: usize,
+    /// Modified fees of in-pool descendants (including this one) in satoshis
+    pub descendantfees: i64,
+    /// Number of in-pool ancestor transactions (including this one)
+    pub ancestorcount: usize,
+    /// Size of in-pool ancestors (including this one)
+    pub ancestorsize: usize,
+    /// Modified fees of in-pool ancestors (including this one) in satoshis
+    pub ancestorfees: i64,
+    /// Unconfirmed transactions, used as inputs for this transaction
+    pub depends: Vec<H256>,
+    /// Unconfirmed transactions, spending outputs of this transaction
+    pub spentby: Vec<H256>,
+}
+
+/// Response to getrawmempool, getmempoolancestors and getmempooldescendants RPC requests
+#[derive(Debug, PartialEq)]
+pub enum GetRawMemoryPoolResponse {
+    /// When asking for short response
+    Hashes(Vec<H256>),
+    /// When asking for verbose response
+    Verbose(BTreeMap<H256, MemoryPoolEntry>),
+}
+
+impl Serialize for GetRawMemoryPoolResponse {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            GetRawMemoryPoolResponse::Hashes(ref hashes) => hashes.serialize(serializer),
+            GetRawMemoryPoolResponse::Verbose(ref entries) => entries.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::hash::H256;
+    use super::*;
+    use serde_json;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn memory_pool_info_serialize() {
+        let info = MemoryPoolInfo {
+            size: 2,
+            bytes: 500,
+            usage: 1024,
+        };
+        assert_eq!(
+            serde_json::to_string(&info).unwrap(),
+            r#"{"size":2,"bytes":500,"usage":1024}"#
+        );
+    }
+
+    #[test]
+    fn memory_pool_entry_serialize() {
+        let entry = MemoryPoolEntry {
+            size: 100,
+            fee: 0.0001,
+            modifiedfee: 0.0002,
+            descendantcount: 2,
+            descendantsize: 300,
+            descendantfees: 30000,
+            ancestorcount: 1,
+            ancestorsize: 100,
+            ancestorfees: 20000,
+            depends: vec![H256::from(1)],
+            spentby: vec![H256::from(2)],
+        };
+        assert_eq!(serde_json::to_string(&entry).unwrap(), r#"{"size":100,"fee":0.0001,"modifiedfee":0.0002,"descendantcount":2,"descendantsize":300,"descendantfees":30000,"ancestorcount":1,"ancestorsize":100,"ancestorfees":20000,"depends":["0100000000000000000000000000000000000000000000000000000000000000"],"spentby":["0200000000000000000000000000000000000000000000000000000000000000"]}"#);
+    }
+
+    #[test]
+    fn memory_pool_entry_deserialize() {
+        let entry = MemoryPoolEntry {
+            size: 100,
+            fee: 0.0001,
+            modifiedfee: 0.0002,
+            descendantcount: 2,
+            descendantsize: 300,
+            descendantfees: 30000,
+            ancestorcount: 1,
+            ancestorsize: 100,
+            ancestorfees: 20000,
+            depends: vec![H256::from(1)],
+            spentby: vec![],
+        };
+        assert_eq!(
+			serde_json::from_str::<MemoryPoolEntry>(r#"{"size":100,"fee":0.0001,"modifiedfee":0.0002,"descendantcount":2,"descendantsize":300,"descendantfees":30000,"ancestorcount":1,"ancestorsize":100,"ancestorfees":20000,"depends":["0100000000000000000000000000000000000000000000000000000000000000"],"spentby":[]}"#).unwrap(),
+			entry);
+    }
+
+    #[test]
+    fn get_raw_memory_pool_response_hashes_serialize() {
+        let response = GetRawMemoryPoolResponse::Hashes(vec![H256::from(1)]);
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"["0100000000000000000000000000000000000000000000000000000000000000"]"#
+        );
+    }
+
+    #[test]
+    fn get_raw_memory_pool_response_verbose_serialize() {
+        let mut entries = BTreeMap::new();
+        entries.insert(H256::from(1), MemoryPoolEntry::default());
+        let response = GetRawMemoryPoolResponse::Verbose(entries);
+        assert_eq!(serde_json::to_string(&response).unwrap(), r#"{"0100000000000000000000000000000000000000000000000000000000000000":{"size":0,"fee":0.0,"modifiedfee":0.0,"descendantcount":0,"descendantsize":0,"descendantfees":0,"ancestorcount":0,"ancestorsize":0,"ancestorfees":0,"depends":[],"spentby":[]}}"#);
+    }
+}
@@ -1,31 +1,65 @@
 pub mod address;
+mod address_index;
+mod address_info;
+mod address_type;
+mod adversary;
 mod block;
+mod block_range;
+mod block_fault;
 mod block_template;
 mod block_template_request;
 mod bytes;
+mod coin_selection;
+mod get_block_filter_response;
 mod get_block_response;
 mod get_tx_out_response;
 mod get_tx_out_set_info_response;
+mod get_tx_validity_response;
 mod hash;
+mod memory_pool;
+mod multisig;
 mod nodes;
+mod psbt;
 mod script;
 mod transaction;
 mod uint;
+mod wallet_coin;
 
+pub use self::address_index::{
+    AddressDelta, AddressIndexPage, AddressUtxo, GetAddressHistoryResponse, GetAddressUtxosResponse,
+};
+pub use self::address_info::AddressInfo;
+pub use self::address_type::AddressType;
+pub use self::adversary::{
+    AdversaryAttempt, AdversaryOutPoint, AdversaryStrategy, AdversaryTransaction,
+};
 pub use self::block::RawBlock;
+pub use self::block_range::{BlockRangeBlock, BlockRangeTransaction};
+pub use self::block_fault::{BlockFault, GenerateInvalidBlockResponse, InjectedBlockFault};
 pub use self::block_template::{BlockTemplate, BlockTemplateTransaction};
 pub use self::block_template_request::{BlockTemplateRequest, BlockTemplateRequestMode};
 pub use self::bytes::Bytes;
+pub use self::coin_selection::CoinSelectionStrategy;
+pub use self::get_block_filter_response::GetBlockFilterResponse;
 pub use self::get_block_response::{GetBlockResponse, VerboseBlock};
 pub use self::get_tx_out_response::GetTxOutResponse;
 pub use self::get_tx_out_set_info_response::GetTxOutSetInfoResponse;
+pub use self::get_tx_validity_response::GetTxValidityResponse;
 pub use self::hash::{H160, H256};
+pub use self::memory_pool::{GetRawMemoryPoolResponse, MemoryPoolEntry, MemoryPoolInfo};
+pub use self::multisig::{MultisigAddress, SignMultisigTransactionResponse};
 pub use self::nodes::{AddNodeOperation, NodeInfo};
+pub use self::psbt::{
+    DecodePsbtResponse, DecodedPsbtInput, DecodedPsbtOutput, FinalizePsbtResponse, Psbt, PsbtKeySource,
+    PsbtWitnessUtxo, WalletProcessPsbtResponse,
+};
 pub use self::script::ScriptType;
 pub use self::transaction::{
-    GetRawTransactionResponse, RawTransaction, SignedTransactionInput, SignedTransactionOutput,
+    GetRawTransactionResponse, PreviousTransactionOutput, RawTransaction, SignRawTransactionResponse,
+    SignedTransactionInput, SignedTransactionOutput,
     Transaction, TransactionInput, TransactionInputScript, TransactionOutput,
     TransactionOutputScript, TransactionOutputWithAddress, TransactionOutputWithScriptData,
     TransactionOutputs,
 };
 pub use self::uint::U256;
+pub use self::wallet_coin::WalletCoin;
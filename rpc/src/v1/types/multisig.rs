@@ -0,0 +1,55 @@
+use super::bytes::Bytes;
+use super::transaction::RawTransaction;
+use keys::Address;
+use v1::types;
+
+/// createmultisig and addmultisigaddress response
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct MultisigAddress {
+    /// P2SH address of the multisig script
+    #[serde(with = "types::address")]
+    pub address: Address,
+    /// Multisig redeem script
+    #[serde(rename = "redeemScript")]
+    pub redeem_script: Bytes,
+}
+
+/// signmultisigtransaction response
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SignMultisigTransactionResponse {
+    /// Transaction with signatures of all wallets, which have signed it so far
+    pub hex: RawTransaction,
+    /// Do all multisig inputs of the transaction have enough signatures?
+    pub complete: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::bytes::Bytes;
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn multisig_address_serialize() {
+        let address = MultisigAddress {
+            address: "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy".into(),
+            redeem_script: Bytes::new(vec![0x51, 0x51, 0xae]),
+        };
+        assert_eq!(
+            serde_json::to_string(&address).unwrap(),
+            r#"{"address":"3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy","redeemScript":"5151ae"}"#
+        );
+    }
+
+    #[test]
+    fn sign_multisig_transaction_response_serialize() {
+        let response = SignMultisigTransactionResponse {
+            hex: Bytes::new(vec![1, 2, 3]),
+            complete: false,
+        };
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"hex":"010203","complete":false}"#
+        );
+    }
+}
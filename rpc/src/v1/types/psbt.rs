@@ -0,0 +1,263 @@
+use super::bytes::Bytes;
+use super::hash::H256;
+use super::transaction::RawTransaction;
+use base64;
+use chain::constants::SATOSHIS_IN_COIN;
+use global_script::{KeySource, Psbt as GlobalPsbt};
+use hex::ToHex;
+use keys::DerivationPath;
+use primitives::bytes::Bytes as GlobalBytes;
+use ser::{deserialize, serialize, serialize_with_flags, SERIALIZE_TRANSACTION_WITNESS};
+use serde::de::{Error, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Base64 encoded partially signed transaction
+#[derive(Debug, PartialEq, Clone)]
+pub struct Psbt(pub GlobalPsbt);
+
+impl Serialize for Psbt {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&base64::encode(&*serialize(&self.0)))
+    }
+}
+
+impl<'a> Deserialize<'a> for Psbt {
+    fn deserialize<D>(deserializer: D) -> Result<Psbt, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_identifier(PsbtVisitor)
+    }
+}
+
+struct PsbtVisitor;
+
+impl<'a> Visitor<'a> for PsbtVisitor {
+    type Value = Psbt;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a base64 encoded PSBT")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        let data = try!(base64::decode(value).map_err(|_| Error::custom("invalid base64")));
+        deserialize(&data as &[u8])
+            .map(Psbt)
+            .map_err(|e| Error::custom(format!("invalid PSBT: {:?}", e)))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        self.visit_str(value.as_ref())
+    }
+}
+
+/// walletprocesspsbt response
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct WalletProcessPsbtResponse {
+    /// PSBT with spent outputs, scripts and signatures, known to the wallet
+    pub psbt: Psbt,
+    /// Could all inputs be finalized?
+    pub complete: bool,
+}
+
+/// finalizepsbt response
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct FinalizePsbtResponse {
+    /// PSBT with finalized inputs. None if signed transaction is extracted
+    pub psbt: Option<Psbt>,
+    /// Signed transaction, extracted from complete PSBT
+    pub hex: Option<RawTransaction>,
+    /// Are all inputs finalized?
+    pub complete: bool,
+}
+
+/// Output, spent by the PSBT input
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PsbtWitnessUtxo {
+    /// Output value in BTC
+    pub amount: f64,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pubkey: Bytes,
+}
+
+/// Derivation of the PSBT public key
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PsbtKeySource {
+    pub pubkey: Bytes,
+    pub master_fingerprint: String,
+    pub path: String,
+}
+
+/// decodepsbt input
+#[derive(Debug, Serialize, PartialEq)]
+pub struct DecodedPsbtInput {
+    pub non_witness_utxo: Option<RawTransaction>,
+    pub witness_utxo: Option<PsbtWitnessUtxo>,
+    /// Signatures by hex encoded public keys
+    pub partial_signatures: BTreeMap<String, Bytes>,
+    pub sighash: Option<u32>,
+    pub redeem_script: Option<Bytes>,
+    pub witness_script: Option<Bytes>,
+    pub bip32_derivs: Vec<PsbtKeySource>,
+    #[serde(rename = "final_scriptSig")]
+    pub final_script_sig: Option<Bytes>,
+    #[serde(rename = "final_scriptwitness")]
+    pub final_script_witness: Option<Vec<Bytes>>,
+    /// Unknown values by hex encoded keys
+    pub unknown: BTreeMap<String, Bytes>,
+}
+
+/// decodepsbt output
+#[derive(Debug, Serialize, PartialEq)]
+pub struct DecodedPsbtOutput {
+    pub redeem_script: Option<Bytes>,
+    pub witness_script: Option<Bytes>,
+    pub bip32_derivs: Vec<PsbtKeySource>,
+    /// Unknown values by hex encoded keys
+    pub unknown: BTreeMap<String, Bytes>,
+}
+
+/// decodepsbt response
+#[derive(Debug, Serialize, PartialEq)]
+pub struct DecodePsbtResponse {
+    /// Unsigned transaction
+    pub tx: RawTransaction,
+    /// Unsigned transaction hash
+    pub txid: H256,
+    /// Unknown global values by hex encoded keys
+    pub unknown: BTreeMap<String, Bytes>,
+    pub inputs: Vec<DecodedPsbtInput>,
+    pub outputs: Vec<DecodedPsbtOutput>,
+    /// Transaction fee in BTC. None if some of spent outputs are unknown
+    pub fee: Option<f64>,
+}
+
+fn satoshis_to_btc(value: u64) -> f64 {
+    value as f64 / SATOSHIS_IN_COIN as f64
+}
+
+fn hex_map(map: &BTreeMap<GlobalBytes, GlobalBytes>) -> BTreeMap<String, Bytes> {
+    map.iter()
+        .map(|(key, value)| (key.to_hex::<String>(), value.clone().into()))
+        .collect()
+}
+
+fn key_sources(map: &BTreeMap<GlobalBytes, KeySource>) -> Vec<PsbtKeySource> {
+    map.iter()
+        .map(|(public, key_source)| PsbtKeySource {
+            pubkey: public.clone().into(),
+            master_fingerprint: key_source.fingerprint.to_hex::<String>(),
+            path: DerivationPath(key_source.path.clone()).to_string(),
+        })
+        .collect()
+}
+
+impl<'a> From<&'a GlobalPsbt> for DecodePsbtResponse {
+    fn from(psbt: &'a GlobalPsbt) -> Self {
+        let txid: H256 = psbt.unsigned_tx.hash().into();
+        DecodePsbtResponse {
+            tx: serialize(&psbt.unsigned_tx).into(),
+            txid: txid.reversed(),
+            unknown: hex_map(&psbt.unknown),
+            inputs: psbt.inputs
+                .iter()
+                .map(|input| DecodedPsbtInput {
+                    non_witness_utxo: input.non_witness_utxo
+                        .as_ref()
+                        .map(|tx| serialize_with_flags(tx, SERIALIZE_TRANSACTION_WITNESS).into()),
+                    witness_utxo: input.witness_utxo.as_ref().map(|output| PsbtWitnessUtxo {
+                        amount: satoshis_to_btc(output.value),
+                        script_pubkey: output.script_pubkey.clone().into(),
+                    }),
+                    partial_signatures: hex_map(&input.partial_sigs),
+                    sighash: input.sighash_type,
+                    redeem_script: input.redeem_script.clone().map(Into::into),
+                    witness_script: input.witness_script.clone().map(Into::into),
+                    bip32_derivs: key_sources(&input.bip32_derivation),
+                    final_script_sig: input.final_script_sig.clone().map(Into::into),
+                    final_script_witness: input.final_script_witness
+                        .as_ref()
+                        .map(|witness| witness.iter().cloned().map(Into::into).collect()),
+                    unknown: hex_map(&input.unknown),
+                })
+                .collect(),
+            outputs: psbt.outputs
+                .iter()
+                .map(|output| DecodedPsbtOutput {
+                    redeem_script: output.redeem_script.clone().map(Into::into),
+                    witness_script: output.witness_script.clone().map(Into::into),
+                    bip32_derivs: key_sources(&output.bip32_derivation),
+                    unknown: hex_map(&output.unknown),
+                })
+                .collect(),
+            fee: psbt.fee().map(satoshis_to_btc),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chain::{OutPoint, Transaction, TransactionInput, TransactionOutput};
+    use serde_json;
+
+    fn psbt() -> GlobalPsbt {
+        GlobalPsbt::from_unsigned_tx(Transaction {
+            version: 1,
+            inputs: vec![TransactionInput {
+                previous_output: OutPoint::default(),
+                script_sig: Default::default(),
+                sequence: 0xffffffff,
+                script_witness: vec![],
+            }],
+            outputs: vec![TransactionOutput {
+                value: 90_000,
+                script_pubkey: "51".into(),
+            }],
+            lock_time: 0,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn psbt_serialize_and_deserialize() {
+        let psbt = Psbt(psbt());
+        let serialized = serde_json::to_string(&psbt).unwrap();
+        assert!(serialized.starts_with(r#""cHNidP8"#));
+        assert_eq!(serde_json::from_str::<Psbt>(&serialized).unwrap(), psbt);
+        assert!(serde_json::from_str::<Psbt>(r#""cHNidP8=""#).is_err());
+        assert!(serde_json::from_str::<Psbt>(r#""not base64""#).is_err());
+    }
+
+    #[test]
+    fn decode_psbt_response_serialize() {
+        let mut psbt = psbt();
+        psbt.inputs[0].witness_utxo = Some(TransactionOutput {
+            value: 100_000,
+            script_pubkey: "51".into(),
+        });
+        psbt.inputs[0].partial_sigs.insert("02aa".into(), "3001".into());
+        psbt.inputs[0].bip32_derivation.insert("02aa".into(), KeySource {
+            fingerprint: "d90c6a4f".into(),
+            path: vec![0x8000002c, 1],
+        });
+
+        let decoded = DecodePsbtResponse::from(&psbt);
+        assert_eq!(decoded.fee, Some(0.0001));
+        assert_eq!(
+            serde_json::to_string(&decoded.inputs[0]).unwrap(),
+            r#"{"non_witness_utxo":null,"witness_utxo":{"amount":0.001,"scriptPubKey":"51"},"partial_signatures":{"02aa":"3001"},"sighash":null,"redeem_script":null,"witness_script":null,"bip32_derivs":[{"pubkey":"02aa","master_fingerprint":"d90c6a4f","path":"m/44'/1"}],"final_scriptSig":null,"final_scriptwitness":null,"unknown":{}}"#
+        );
+    }
+}
@@ -13,6 +13,7 @@ pub enum ScriptType {
     NullData,
     WitnessScript,
     WitnessKey,
+    WitnessTaproot,
 }
 
 impl From<GlobalScriptType> for ScriptType {
@@ -26,6 +27,7 @@ impl From<GlobalScriptType> for ScriptType {
             GlobalScriptType::NullData => ScriptType::NullData,
             GlobalScriptType::WitnessScript => ScriptType::WitnessScript,
             GlobalScriptType::WitnessKey => ScriptType::WitnessKey,
+            GlobalScriptType::WitnessTaproot => ScriptType::WitnessTaproot,
         }
     }
 }
@@ -44,6 +46,7 @@ impl Serialize for ScriptType {
             ScriptType::NullData => "nulldata".serialize(serializer),
             ScriptType::WitnessScript => "witness_v0_scripthash".serialize(serializer),
             ScriptType::WitnessKey => "witness_v0_keyhash".serialize(serializer),
+            ScriptType::WitnessTaproot => "witness_v1_taproot".serialize(serializer),
         }
     }
 }
@@ -77,6 +80,7 @@ impl<'a> Deserialize<'a> for ScriptType {
                     "nulldata" => Ok(ScriptType::NullData),
                     "witness_v0_scripthash" => Ok(ScriptType::WitnessScript),
                     "witness_v0_keyhash" => Ok(ScriptType::WitnessKey),
+                    "witness_v1_taproot" => Ok(ScriptType::WitnessTaproot),
                     _ => Err(E::invalid_value(Unexpected::Str(value), &self)),
                 }
             }
@@ -125,6 +129,10 @@ mod tests {
             serde_json::to_string(&ScriptType::WitnessKey).unwrap(),
             r#""witness_v0_keyhash""#
         );
+        assert_eq!(
+            serde_json::to_string(&ScriptType::WitnessTaproot).unwrap(),
+            r#""witness_v1_taproot""#
+        );
     }
 
     #[test]
@@ -161,5 +169,9 @@ mod tests {
             serde_json::from_str::<ScriptType>(r#""witness_v0_keyhash""#).unwrap(),
             ScriptType::WitnessKey
         );
+        assert_eq!(
+            serde_json::from_str::<ScriptType>(r#""witness_v1_taproot""#).unwrap(),
+            ScriptType::WitnessTaproot
+        );
     }
 }
@@ -120,6 +120,8 @@ pub struct Transaction {
     pub size: usize,
     /// The virtual transaction size (differs from size for witness transactions)
     pub vsize: usize,
+    /// The transaction weight (BIP141)
+    pub weight: usize,
     /// The version
     pub version: i32,
     /// The lock time
@@ -128,14 +130,50 @@ pub struct Transaction {
     pub vin: Vec<SignedTransactionInput>,
     /// Transaction outputs
     pub vout: Vec<SignedTransactionOutput>,
-    /// Hash of the block this transaction is included in
-    pub blockhash: H256,
+    /// Hash of the block this transaction is included in. None for decoded transactions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blockhash: Option<H256>,
     /// Number of confirmations of this transaction
-    pub confirmations: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmations: Option<u32>,
     /// The transaction time in seconds since epoch (Jan 1 1970 GMT)
-    pub time: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<u32>,
     /// The block time in seconds since epoch (Jan 1 1970 GMT)
-    pub blocktime: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blocktime: Option<u32>,
+    /// Is this transaction valid?
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid: Option<bool>,
+}
+
+/// Output, spent by the input of transaction to sign
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct PreviousTransactionOutput {
+    /// Previous transaction id
+    pub txid: H256,
+    /// Previous transaction output index
+    pub vout: u32,
+    /// Output script
+    #[serde(rename = "scriptPubKey")]
+    pub script_pubkey: Bytes,
+    /// Redeem script of P2SH output
+    #[serde(rename = "redeemScript")]
+    pub redeem_script: Option<Bytes>,
+    /// Witness script of P2WSH output
+    #[serde(rename = "witnessScript")]
+    pub witness_script: Option<Bytes>,
+    /// Output value in BTC. Required to sign witness inputs
+    pub amount: Option<f64>,
+}
+
+/// signrawtransaction response
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SignRawTransactionResponse {
+    /// Transaction with signed inputs
+    pub hex: RawTransaction,
+    /// Are all inputs signed?
+    pub complete: bool,
 }
 
 /// Return value of `getrawtransaction` method
@@ -458,16 +496,18 @@ mod tests {
             hash: H256::from(5),
             size: 33,
             vsize: 44,
+            weight: 176,
             version: 55,
             locktime: 66,
             vin: vec![],
             vout: vec![],
-            blockhash: H256::from(6),
-            confirmations: 77,
-            time: 88,
-            blocktime: 99,
+            blockhash: Some(H256::from(6)),
+            confirmations: Some(77),
+            time: Some(88),
+            blocktime: Some(99),
+            valid: Some(true),
         };
-        assert_eq!(serde_json::to_string(&tx).unwrap(), r#"{"hex":"deadbeef","txid":"0400000000000000000000000000000000000000000000000000000000000000","hash":"0500000000000000000000000000000000000000000000000000000000000000","size":33,"vsize":44,"version":55,"locktime":66,"vin":[],"vout":[],"blockhash":"0600000000000000000000000000000000000000000000000000000000000000","confirmations":77,"time":88,"blocktime":99}"#);
+        assert_eq!(serde_json::to_string(&tx).unwrap(), r#"{"hex":"deadbeef","txid":"0400000000000000000000000000000000000000000000000000000000000000","hash":"0500000000000000000000000000000000000000000000000000000000000000","size":33,"vsize":44,"weight":176,"version":55,"locktime":66,"vin":[],"vout":[],"blockhash":"0600000000000000000000000000000000000000000000000000000000000000","confirmations":77,"time":88,"blocktime":99,"valid":true}"#);
     }
 
     #[test]
@@ -478,17 +518,33 @@ mod tests {
             hash: H256::from(5),
             size: 33,
             vsize: 44,
+            weight: 176,
             version: 55,
             locktime: 66,
             vin: vec![],
             vout: vec![],
-            blockhash: H256::from(6),
-            confirmations: 77,
-            time: 88,
-            blocktime: 99,
+            blockhash: Some(H256::from(6)),
+            confirmations: Some(77),
+            time: Some(88),
+            blocktime: Some(99),
+            valid: Some(true),
         };
         assert_eq!(
-			serde_json::from_str::<Transaction>(r#"{"hex":"deadbeef","txid":"0400000000000000000000000000000000000000000000000000000000000000","hash":"0500000000000000000000000000000000000000000000000000000000000000","size":33,"vsize":44,"version":55,"locktime":66,"vin":[],"vout":[],"blockhash":"0600000000000000000000000000000000000000000000000000000000000000","confirmations":77,"time":88,"blocktime":99}"#).unwrap(),
+			serde_json::from_str::<Transaction>(r#"{"hex":"deadbeef","txid":"0400000000000000000000000000000000000000000000000000000000000000","hash":"0500000000000000000000000000000000000000000000000000000000000000","size":33,"vsize":44,"weight":176,"version":55,"locktime":66,"vin":[],"vout":[],"blockhash":"0600000000000000000000000000000000000000000000000000000000000000","confirmations":77,"time":88,"blocktime":99,"valid":true}"#).unwrap(),
 			tx);
     }
+
+    #[test]
+    fn previous_transaction_output_deserialize() {
+        assert_eq!(
+			serde_json::from_str::<PreviousTransactionOutput>(r#"{"txid":"0400000000000000000000000000000000000000000000000000000000000000","vout":1,"scriptPubKey":"a914","redeemScript":"0014","amount":0.5}"#).unwrap(),
+			PreviousTransactionOutput {
+				txid: H256::from(4),
+				vout: 1,
+				script_pubkey: "a914".into(),
+				redeem_script: Some("0014".into()),
+				witness_script: None,
+				amount: Some(0.5),
+			});
+    }
 }
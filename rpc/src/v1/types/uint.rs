@@ -40,7 +40,8 @@ macro_rules! impl_uint {
             where
                 S: serde::Serializer,
             {
-                let as_hex = format!("{}", self.0.to_hex());
+                // zero-padded, like bitcoind does
+                let as_hex = format!("{:0>width$}", self.0.to_hex(), width = $size * 16);
                 serializer.serialize_str(&as_hex)
             }
         }
@@ -97,7 +98,10 @@ mod tests {
     fn u256_serialize() {
         let u256 = U256::from(256);
         let serialized = serde_json::to_string(&u256).unwrap();
-        assert_eq!(serialized, r#""100""#);
+        assert_eq!(
+            serialized,
+            r#""0000000000000000000000000000000000000000000000000000000000000100""#
+        );
     }
 
     #[test]
@@ -105,5 +109,10 @@ mod tests {
         let u256 = U256::from(256);
         let deserialized = serde_json::from_str::<U256>(r#""100""#).unwrap();
         assert_eq!(deserialized, u256);
+        let deserialized = serde_json::from_str::<U256>(
+            r#""0000000000000000000000000000000000000000000000000000000000000100""#,
+        )
+        .unwrap();
+        assert_eq!(deserialized, u256);
     }
 }
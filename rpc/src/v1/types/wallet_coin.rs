@@ -0,0 +1,77 @@
+use super::hash::H256;
+use sync::CoinState;
+
+/// Coin, tracked by the wallet, returned by listunspent and listwalletcoins
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct WalletCoin {
+    /// Wallet description of the coin
+    pub id: String,
+    /// Transaction id
+    pub txid: H256,
+    /// Output index
+    pub vout: u32,
+    /// Address, the output is paying to. Null if the output is unknown to the node
+    pub address: Option<String>,
+    /// Output value in satoshis. Null if the output is unknown to the node
+    pub value: Option<u64>,
+    /// Number of canonical blocks, confirming the transaction. 0 for memory pool transactions
+    pub confirmations: u32,
+    /// Is the transaction valid? Null if the transaction is unknown to the node
+    pub valid: Option<bool>,
+    /// Could the coin be spent by the wallet?
+    pub spendable: bool,
+}
+
+impl From<CoinState> for WalletCoin {
+    fn from(state: CoinState) -> Self {
+        WalletCoin {
+            id: state.id,
+            txid: state.outpoint.hash.reversed().into(),
+            vout: state.outpoint.index,
+            address: state.address.map(|address| address.to_string()),
+            value: state.value,
+            confirmations: state.confirmations,
+            valid: state.valid,
+            spendable: state.spendable,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::hash::H256;
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn wallet_coin_serialize() {
+        let coin = WalletCoin {
+            id: "1: from network".to_owned(),
+            txid: H256::from(1),
+            vout: 2,
+            address: Some("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".to_owned()),
+            value: Some(100),
+            confirmations: 3,
+            valid: Some(true),
+            spendable: true,
+        };
+        assert_eq!(serde_json::to_string(&coin).unwrap(), r#"{"id":"1: from network","txid":"0100000000000000000000000000000000000000000000000000000000000000","vout":2,"address":"1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa","value":100,"confirmations":3,"valid":true,"spendable":true}"#);
+    }
+
+    #[test]
+    fn wallet_coin_deserialize() {
+        let coin = WalletCoin {
+            id: "2: from block".to_owned(),
+            txid: H256::from(1),
+            vout: 0,
+            address: None,
+            value: None,
+            confirmations: 0,
+            valid: None,
+            spendable: false,
+        };
+        assert_eq!(
+			serde_json::from_str::<WalletCoin>(r#"{"id":"2: from block","txid":"0100000000000000000000000000000000000000000000000000000000000000","vout":0,"address":null,"value":null,"confirmations":0,"valid":null,"spendable":false}"#).unwrap(),
+			coin);
+    }
+}
@@ -1,7 +1,8 @@
 //! Script builder
 
 use bytes::Bytes;
-use keys::AddressHash;
+use hash::H256;
+use keys::{Address, AddressHash, AddressPayload, Public, Type};
 use {Num, Opcode, Script};
 
 /// Script builder
@@ -31,6 +32,52 @@ impl Builder {
             .into_script()
     }
 
+    /// Builds p2wpkh (witness v0 key hash) script pubkey
+    pub fn build_p2wpkh(address: &AddressHash) -> Script {
+        Builder::default()
+            .push_opcode(Opcode::OP_0)
+            .push_bytes(&**address)
+            .into_script()
+    }
+
+    /// Builds p2wsh (witness v0 script hash) script pubkey
+    pub fn build_p2wsh(hash: &H256) -> Script {
+        Builder::default()
+            .push_opcode(Opcode::OP_0)
+            .push_bytes(&**hash)
+            .into_script()
+    }
+
+    /// Builds p2tr (witness v1) script pubkey
+    pub fn build_p2tr(output_key: &H256) -> Script {
+        Builder::default()
+            .push_opcode(Opcode::OP_1)
+            .push_bytes(&**output_key)
+            .into_script()
+    }
+
+    /// Builds m-of-n multisig script. Both m and n must be within [1; 16] interval
+    pub fn build_multisig(required: u8, keys: &[Public]) -> Script {
+        keys.iter()
+            .fold(Builder::default().push_small_num(required), |builder, key| builder.push_bytes(key))
+            .push_small_num(keys.len() as u8)
+            .push_opcode(Opcode::OP_CHECKMULTISIG)
+            .into_script()
+    }
+
+    /// Builds script pubkey, paying to given address.
+    /// Returns None if address type does not match its payload
+    pub fn build_address(address: &Address) -> Option<Script> {
+        match (address.kind, &address.hash) {
+            (Type::P2PKH, &AddressPayload::Hash(ref hash)) => Some(Builder::build_p2pkh(hash)),
+            (Type::P2SH, &AddressPayload::Hash(ref hash)) => Some(Builder::build_p2sh(hash)),
+            (Type::P2WPKH, &AddressPayload::Hash(ref hash)) => Some(Builder::build_p2wpkh(hash)),
+            (Type::P2WSH, &AddressPayload::WitnessProgram(ref hash)) => Some(Builder::build_p2wsh(hash)),
+            (Type::P2TR, &AddressPayload::WitnessProgram(ref output_key)) => Some(Builder::build_p2tr(output_key)),
+            _ => None,
+        }
+    }
+
     /// Builds op_return script
     pub fn build_nulldata(bytes: &[u8]) -> Script {
         Builder::default()
@@ -60,6 +107,13 @@ impl Builder {
         self.push_data(&num.to_bytes())
     }
 
+    /// Appends OP_1..OP_16 operation, pushing number within [1; 16] interval
+    fn push_small_num(self, num: u8) -> Self {
+        let opcode = Opcode::from_u8(Opcode::OP_1 as u8 + num - 1)
+            .expect("number is within [1; 16] interval; qed");
+        self.push_opcode(opcode)
+    }
+
     /// Appends bytes push operation to the end od script
     pub fn push_bytes(mut self, bytes: &[u8]) -> Self {
         let len = bytes.len();
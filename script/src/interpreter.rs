@@ -2576,6 +2576,93 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_script_with_witness_v0_signature() {
+        use chain::OutPoint;
+        use sign::UnsignedTransactionInput;
+
+        let key_pair = KeyPair::from_private(Private {
+            network: Network::Mainnet,
+            secret: 1.into(),
+            compressed: true,
+        })
+        .unwrap();
+        let key_hash = key_pair.public().address_hash();
+        let script_pubkey = Builder::build_p2wpkh(&key_hash);
+        let script_code = Builder::build_p2pkh(&key_hash);
+
+        let amount = 100000;
+        let checker = TransactionSignatureChecker {
+            input_index: 0,
+            input_amount: amount,
+            signer: TransactionInputSigner {
+                version: 1,
+                inputs: vec![UnsignedTransactionInput {
+                    previous_output: OutPoint {
+                        hash: 0u8.into(),
+                        index: 0xffffffff,
+                    },
+                    sequence: 0xffffffff,
+                }],
+                outputs: vec![],
+                lock_time: 0,
+            },
+        };
+        let flags = VerificationFlags::default()
+            .verify_p2sh(true)
+            .verify_witness(true);
+
+        // valid signature is pushed to the witness
+        {
+            let signed_input = checker.signer.signed_input(
+                &key_pair,
+                0,
+                amount,
+                &script_code,
+                SignatureVersion::WitnessV0,
+                1,
+            );
+            assert!(signed_input.script_sig.is_empty());
+            assert_eq!(signed_input.script_witness.len(), 2);
+
+            assert_eq!(
+                verify_script(
+                    &signed_input.script_sig.into(),
+                    &script_pubkey,
+                    &signed_input.script_witness,
+                    &flags,
+                    &checker,
+                    SignatureVersion::Base
+                ),
+                Ok(())
+            );
+        }
+
+        // signature with wrong amount
+        {
+            let signed_input = checker.signer.signed_input(
+                &key_pair,
+                0,
+                amount + 1,
+                &script_code,
+                SignatureVersion::WitnessV0,
+                1,
+            );
+
+            assert_eq!(
+                verify_script(
+                    &signed_input.script_sig.into(),
+                    &script_pubkey,
+                    &signed_input.script_witness,
+                    &flags,
+                    &checker,
+                    SignatureVersion::Base
+                ),
+                Err(Error::EvalFalse)
+            );
+        }
+    }
+
     fn run_witness_test(
         script_sig: Script,
         script_pubkey: Script,
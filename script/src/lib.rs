@@ -11,6 +11,7 @@ mod flags;
 mod interpreter;
 mod num;
 mod opcode;
+mod psbt;
 mod script;
 mod sign;
 mod stack;
@@ -24,8 +25,10 @@ pub use self::flags::VerificationFlags;
 pub use self::interpreter::{eval_script, verify_script};
 pub use self::num::Num;
 pub use self::opcode::Opcode;
+pub use self::psbt::{KeySource, Psbt, PsbtError, PsbtInput, PsbtOutput};
 pub use self::script::{
     is_witness_commitment_script, Script, ScriptAddress, ScriptType, ScriptWitness,
+    MAX_SCRIPT_ELEMENT_SIZE, MAX_SCRIPT_SIZE,
 };
 pub use self::sign::{SignatureVersion, TransactionInputSigner, UnsignedTransactionInput};
 pub use self::stack::Stack;
@@ -0,0 +1,743 @@
+//! Partially signed bitcoin transaction
+//!
+//! https://github.com/bitcoin/bips/blob/master/bip-0174.mediawiki
+
+use bytes::Bytes;
+use chain::{Transaction, TransactionOutput};
+use crypto::{dhash160, sha256};
+use hash::H32;
+use ser::{deserialize, serialize, serialize_list, serialize_with_flags, SERIALIZE_TRANSACTION_WITNESS};
+use ser::{Deserializable, Error as SerError, Reader, Serializable, Stream};
+use std::collections::BTreeMap;
+use std::io;
+use {Builder, Script, SignatureVersion};
+
+/// Magic bytes, PSBT starts with
+const PSBT_MAGIC: &[u8] = b"psbt\xff";
+
+/// Unsigned transaction
+const GLOBAL_UNSIGNED_TX: u8 = 0x00;
+
+/// Transaction, input is spending output of
+const IN_NON_WITNESS_UTXO: u8 = 0x00;
+/// Output, spent by the input
+const IN_WITNESS_UTXO: u8 = 0x01;
+/// Signature of the public key
+const IN_PARTIAL_SIG: u8 = 0x02;
+/// Sighash type of signatures
+const IN_SIGHASH_TYPE: u8 = 0x03;
+/// Redeem script of P2SH output
+const IN_REDEEM_SCRIPT: u8 = 0x04;
+/// Witness script of P2WSH output
+const IN_WITNESS_SCRIPT: u8 = 0x05;
+/// Derivation path of the public key
+const IN_BIP32_DERIVATION: u8 = 0x06;
+/// Finalized script_sig
+const IN_FINAL_SCRIPTSIG: u8 = 0x07;
+/// Finalized script witness
+const IN_FINAL_SCRIPTWITNESS: u8 = 0x08;
+
+/// Redeem script of P2SH output
+const OUT_REDEEM_SCRIPT: u8 = 0x00;
+/// Witness script of P2WSH output
+const OUT_WITNESS_SCRIPT: u8 = 0x01;
+/// Derivation path of the public key
+const OUT_BIP32_DERIVATION: u8 = 0x02;
+
+#[derive(Debug, PartialEq)]
+pub enum PsbtError {
+    /// Unsigned transaction has input with non-empty script_sig or witness
+    SignedTransaction,
+    /// Combined PSBTs have different unsigned transactions
+    TransactionMismatch,
+    /// Input has neither final script_sig nor final witness
+    NotFinalized,
+}
+
+/// Fingerprint of the master key and derivation path of the public key
+#[derive(Debug, PartialEq, Clone)]
+pub struct KeySource {
+    pub fingerprint: H32,
+    pub path: Vec<u32>,
+}
+
+/// Signing data of the transaction input
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct PsbtInput {
+    pub non_witness_utxo: Option<Transaction>,
+    pub witness_utxo: Option<TransactionOutput>,
+    /// Signatures with sighash type byte by serialized public keys
+    pub partial_sigs: BTreeMap<Bytes, Bytes>,
+    pub sighash_type: Option<u32>,
+    pub redeem_script: Option<Bytes>,
+    pub witness_script: Option<Bytes>,
+    /// Key sources by serialized public keys
+    pub bip32_derivation: BTreeMap<Bytes, KeySource>,
+    pub final_script_sig: Option<Bytes>,
+    pub final_script_witness: Option<Vec<Bytes>>,
+    /// Unknown key-value pairs, kept for other PSBT roles
+    pub unknown: BTreeMap<Bytes, Bytes>,
+}
+
+/// Data, describing the transaction output
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct PsbtOutput {
+    pub redeem_script: Option<Bytes>,
+    pub witness_script: Option<Bytes>,
+    /// Key sources by serialized public keys
+    pub bip32_derivation: BTreeMap<Bytes, KeySource>,
+    /// Unknown key-value pairs, kept for other PSBT roles
+    pub unknown: BTreeMap<Bytes, Bytes>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Psbt {
+    /// Transaction with empty script_sigs and witnesses
+    pub unsigned_tx: Transaction,
+    pub inputs: Vec<PsbtInput>,
+    pub outputs: Vec<PsbtOutput>,
+    /// Unknown global key-value pairs
+    pub unknown: BTreeMap<Bytes, Bytes>,
+}
+
+impl Psbt {
+    /// Create PSBT of the transaction without signatures
+    pub fn from_unsigned_tx(unsigned_tx: Transaction) -> Result<Self, PsbtError> {
+        if unsigned_tx.inputs.iter().any(|input| !input.script_sig.is_empty() || input.has_witness()) {
+            return Err(PsbtError::SignedTransaction);
+        }
+
+        Ok(Psbt {
+            inputs: vec![PsbtInput::default(); unsigned_tx.inputs.len()],
+            outputs: vec![PsbtOutput::default(); unsigned_tx.outputs.len()],
+            unsigned_tx: unsigned_tx,
+            unknown: BTreeMap::new(),
+        })
+    }
+
+    /// Merge data of other PSBT of the same transaction. Values of this PSBT are preferred
+    pub fn combine(&mut self, other: Psbt) -> Result<(), PsbtError> {
+        if self.unsigned_tx.hash() != other.unsigned_tx.hash() {
+            return Err(PsbtError::TransactionMismatch);
+        }
+
+        merge_map(&mut self.unknown, other.unknown);
+        for (input, other) in self.inputs.iter_mut().zip(other.inputs) {
+            input.combine(other);
+        }
+        for (output, other) in self.outputs.iter_mut().zip(other.outputs) {
+            output.combine(other);
+        }
+        Ok(())
+    }
+
+    /// Output, spent by the input
+    pub fn spent_output(&self, index: usize) -> Option<TransactionOutput> {
+        let input = &self.inputs[index];
+        let prevout = &self.unsigned_tx.inputs[index].previous_output;
+        input.witness_utxo.clone().or_else(|| {
+            input.non_witness_utxo
+                .as_ref()
+                .and_then(|tx| tx.outputs.get(prevout.index as usize))
+                .cloned()
+        })
+    }
+
+    /// Transaction fee. None if some of spent outputs are unknown
+    pub fn fee(&self) -> Option<u64> {
+        let spent = (0..self.inputs.len())
+            .map(|index| self.spent_output(index).map(|output| output.value))
+            .collect::<Option<Vec<_>>>()?;
+        spent.into_iter().sum::<u64>().checked_sub(self.unsigned_tx.total_spends())
+    }
+
+    /// Script code and signature version of the signatures of input. Returned script is the script_pubkey of
+    /// spent output or its redeem (witness) script. P2PKH script is returned for P2WPKH outputs (BIP143)
+    pub fn signing_script(&self, index: usize) -> Option<(Script, SignatureVersion)> {
+        let spent_output = self.spent_output(index)?;
+        let script = unwrap_script_hash(&self.inputs[index], spent_output.script_pubkey.into())?;
+        if script.is_pay_to_witness_key_hash() {
+            return Some((Builder::build_p2pkh(&script[2..22].into()), SignatureVersion::WitnessV0));
+        }
+        if script.is_pay_to_witness_script_hash() {
+            let witness_script = unwrap_witness_script_hash(&self.inputs[index], &script)?;
+            return Some((witness_script, SignatureVersion::WitnessV0));
+        }
+        Some((script, SignatureVersion::Base))
+    }
+
+    /// Build final script_sig and witness of inputs, which have enough signatures.
+    /// Returns true if all inputs are finalized
+    pub fn finalize(&mut self) -> bool {
+        for index in 0..self.inputs.len() {
+            if !self.inputs[index].is_finalized() {
+                if let Some(spent_output) = self.spent_output(index) {
+                    self.inputs[index].finalize(spent_output.script_pubkey.into());
+                }
+            }
+        }
+        self.is_finalized()
+    }
+
+    /// Are all inputs finalized?
+    pub fn is_finalized(&self) -> bool {
+        self.inputs.iter().all(PsbtInput::is_finalized)
+    }
+
+    /// Signed transaction of the finalized PSBT
+    pub fn extract_transaction(&self) -> Result<Transaction, PsbtError> {
+        let mut transaction = self.unsigned_tx.clone();
+        for (input, psbt_input) in transaction.inputs.iter_mut().zip(&self.inputs) {
+            if !psbt_input.is_finalized() {
+                return Err(PsbtError::NotFinalized);
+            }
+
+            input.script_sig = psbt_input.final_script_sig.clone().unwrap_or_default();
+            input.script_witness = psbt_input.final_script_witness.clone().unwrap_or_default();
+        }
+        Ok(transaction)
+    }
+}
+
+impl PsbtInput {
+    /// Has input final script_sig or witness?
+    pub fn is_finalized(&self) -> bool {
+        self.final_script_sig.is_some() || self.final_script_witness.is_some()
+    }
+
+    fn combine(&mut self, other: PsbtInput) {
+        self.non_witness_utxo = self.non_witness_utxo.take().or(other.non_witness_utxo);
+        self.witness_utxo = self.witness_utxo.take().or(other.witness_utxo);
+        merge_map(&mut self.partial_sigs, other.partial_sigs);
+        self.sighash_type = self.sighash_type.take().or(other.sighash_type);
+        self.redeem_script = self.redeem_script.take().or(other.redeem_script);
+        self.witness_script = self.witness_script.take().or(other.witness_script);
+        merge_map(&mut self.bip32_derivation, other.bip32_derivation);
+        self.final_script_sig = self.final_script_sig.take().or(other.final_script_sig);
+        self.final_script_witness = self.final_script_witness.take().or(other.final_script_witness);
+        merge_map(&mut self.unknown, other.unknown);
+    }
+
+    /// Build final script_sig and witness of input, spending output with given script.
+    /// Signing data is removed from finalized input
+    fn finalize(&mut self, script_pubkey: Script) -> bool {
+        let is_script_hash = script_pubkey.is_pay_to_script_hash();
+        let script = match unwrap_script_hash(self, script_pubkey) {
+            Some(script) => script,
+            None => return false,
+        };
+
+        let (stack, witness) = if script.is_pay_to_witness_key_hash() {
+            match self.key_hash_signature(&script[2..22]) {
+                Some(witness) => (vec![], Some(witness)),
+                None => return false,
+            }
+        } else if script.is_pay_to_witness_script_hash() {
+            let witness_script = match unwrap_witness_script_hash(self, &script) {
+                Some(witness_script) => witness_script,
+                None => return false,
+            };
+            match self.satisfy(&witness_script) {
+                Some(mut witness) => {
+                    witness.push(witness_script.to_bytes());
+                    (vec![], Some(witness))
+                },
+                None => return false,
+            }
+        } else {
+            match self.satisfy(&script) {
+                Some(stack) => (stack, None),
+                None => return false,
+            }
+        };
+
+        // P2SH script_sig pushes redeem script after the satisfying stack
+        let mut builder = stack.iter().fold(Builder::default(), |builder, item| builder.push_data(item));
+        if is_script_hash {
+            builder = builder.push_data(&script);
+        }
+        let script_sig = builder.into_bytes();
+
+        self.final_script_sig = if script_sig.is_empty() { None } else { Some(script_sig) };
+        self.final_script_witness = witness;
+        self.partial_sigs.clear();
+        self.sighash_type = None;
+        self.redeem_script = None;
+        self.witness_script = None;
+        self.bip32_derivation.clear();
+        self.final_script_sig.is_some() || self.final_script_witness.is_some()
+    }
+
+    /// Signature and public key of the key with given hash
+    fn key_hash_signature(&self, key_hash: &[u8]) -> Option<Vec<Bytes>> {
+        self.partial_sigs
+            .iter()
+            .find(|&(public, _)| &*dhash160(public) == key_hash)
+            .map(|(public, signature)| vec![signature.clone(), public.clone()])
+    }
+
+    /// Stack, satisfying P2PKH, P2PK or multisig script
+    fn satisfy(&self, script: &Script) -> Option<Vec<Bytes>> {
+        if script.is_pay_to_public_key_hash() {
+            return self.key_hash_signature(&script[3..23]);
+        }
+
+        if script.is_pay_to_public_key() {
+            let public: Bytes = script[1..script.len() - 1].to_vec().into();
+            return self.partial_sigs.get(&public).map(|signature| vec![signature.clone()]);
+        }
+
+        if script.is_multisig_script() {
+            let required = script.num_signatures_required() as usize;
+            let keys = script.extract_multisig_keys().ok()?;
+            // signatures must follow order of their keys; OP_CHECKMULTISIG pops one extra element
+            let signatures: Vec<Bytes> = keys.iter()
+                .filter_map(|key| self.partial_sigs.get(&key.to_vec().into()))
+                .take(required)
+                .cloned()
+                .collect();
+            if signatures.len() < required {
+                return None;
+            }
+            return Some(Some(Bytes::default()).into_iter().chain(signatures).collect());
+        }
+
+        None
+    }
+}
+
+impl PsbtOutput {
+    fn combine(&mut self, other: PsbtOutput) {
+        self.redeem_script = self.redeem_script.take().or(other.redeem_script);
+        self.witness_script = self.witness_script.take().or(other.witness_script);
+        merge_map(&mut self.bip32_derivation, other.bip32_derivation);
+        merge_map(&mut self.unknown, other.unknown);
+    }
+}
+
+/// Redeem script of P2SH output or script_pubkey of other outputs
+fn unwrap_script_hash(input: &PsbtInput, script_pubkey: Script) -> Option<Script> {
+    if !script_pubkey.is_pay_to_script_hash() {
+        return Some(script_pubkey);
+    }
+
+    match input.redeem_script {
+        Some(ref redeem_script) if &*dhash160(redeem_script) == &script_pubkey[2..22] => Some(redeem_script.clone().into()),
+        _ => None,
+    }
+}
+
+/// Witness script of P2WSH program
+fn unwrap_witness_script_hash(input: &PsbtInput, program: &Script) -> Option<Script> {
+    match input.witness_script {
+        Some(ref witness_script) if &*sha256(witness_script) == &program[2..34] => Some(witness_script.clone().into()),
+        _ => None,
+    }
+}
+
+fn merge_map<K: Ord, V>(map: &mut BTreeMap<K, V>, other: BTreeMap<K, V>) {
+    for (key, value) in other {
+        map.entry(key).or_insert(value);
+    }
+}
+
+/// Append key-value pair, key is the key type followed by the key data
+fn append_pair(stream: &mut Stream, key_type: u8, key_data: &[u8], value: &[u8]) {
+    let mut key = Vec::with_capacity(1 + key_data.len());
+    key.push(key_type);
+    key.extend_from_slice(key_data);
+    stream.append(&Bytes::from(key)).append(&Bytes::from(value.to_vec()));
+}
+
+fn append_map(stream: &mut Stream, map: &BTreeMap<Bytes, Bytes>) {
+    for (key, value) in map {
+        stream.append(key).append(value);
+    }
+}
+
+fn append_key_sources(stream: &mut Stream, key_type: u8, key_sources: &BTreeMap<Bytes, KeySource>) {
+    for (public, key_source) in key_sources {
+        let mut value = Stream::default();
+        value.append(&key_source.fingerprint);
+        for index in &key_source.path {
+            value.append(index);
+        }
+        append_pair(stream, key_type, public, &value.out());
+    }
+}
+
+/// Read key-value pairs until separator. Keys must be unique
+fn read_map<T>(reader: &mut Reader<T>) -> Result<Vec<(Bytes, Bytes)>, SerError>
+where
+    T: io::Read,
+{
+    let mut keys = Vec::new();
+    let mut pairs = Vec::new();
+    loop {
+        let key: Bytes = try!(reader.read());
+        if key.is_empty() {
+            return Ok(pairs);
+        }
+        if keys.contains(&key) {
+            return Err(SerError::MalformedData);
+        }
+
+        let value: Bytes = try!(reader.read());
+        keys.push(key.clone());
+        pairs.push((key, value));
+    }
+}
+
+/// Value of the key-value pair without key data
+fn read_value<T: Deserializable>(key: &Bytes, value: &Bytes) -> Result<T, SerError> {
+    if key.len() != 1 {
+        return Err(SerError::MalformedData);
+    }
+    deserialize(&value[..])
+}
+
+fn read_script(key: &Bytes, value: Bytes) -> Result<Bytes, SerError> {
+    if key.len() != 1 {
+        return Err(SerError::MalformedData);
+    }
+    Ok(value)
+}
+
+fn read_key_source(value: &Bytes) -> Result<KeySource, SerError> {
+    if value.len() < 4 || value.len() % 4 != 0 {
+        return Err(SerError::MalformedData);
+    }
+
+    let mut reader = Reader::new(value);
+    let fingerprint = try!(reader.read());
+    let mut path = Vec::with_capacity(value.len() / 4 - 1);
+    while !reader.is_finished() {
+        path.push(try!(reader.read()));
+    }
+    Ok(KeySource {
+        fingerprint: fingerprint,
+        path: path,
+    })
+}
+
+impl Serializable for Psbt {
+    fn serialize(&self, stream: &mut Stream) {
+        stream.append_slice(PSBT_MAGIC);
+        append_pair(stream, GLOBAL_UNSIGNED_TX, &[], &serialize(&self.unsigned_tx));
+        append_map(stream, &self.unknown);
+        stream.append(&0u8);
+
+        for input in &self.inputs {
+            if let Some(ref tx) = input.non_witness_utxo {
+                append_pair(stream, IN_NON_WITNESS_UTXO, &[], &serialize_with_flags(tx, SERIALIZE_TRANSACTION_WITNESS));
+            }
+            if let Some(ref output) = input.witness_utxo {
+                append_pair(stream, IN_WITNESS_UTXO, &[], &serialize(output));
+            }
+            for (public, signature) in &input.partial_sigs {
+                append_pair(stream, IN_PARTIAL_SIG, public, signature);
+            }
+            if let Some(sighash_type) = input.sighash_type {
+                append_pair(stream, IN_SIGHASH_TYPE, &[], &serialize(&sighash_type));
+            }
+            if let Some(ref redeem_script) = input.redeem_script {
+                append_pair(stream, IN_REDEEM_SCRIPT, &[], redeem_script);
+            }
+            if let Some(ref witness_script) = input.witness_script {
+                append_pair(stream, IN_WITNESS_SCRIPT, &[], witness_script);
+            }
+            append_key_sources(stream, IN_BIP32_DERIVATION, &input.bip32_derivation);
+            if let Some(ref script_sig) = input.final_script_sig {
+                append_pair(stream, IN_FINAL_SCRIPTSIG, &[], script_sig);
+            }
+            if let Some(ref witness) = input.final_script_witness {
+                append_pair(stream, IN_FINAL_SCRIPTWITNESS, &[], &serialize_list::<Bytes, Bytes>(witness));
+            }
+            append_map(stream, &input.unknown);
+            stream.append(&0u8);
+        }
+
+        for output in &self.outputs {
+            if let Some(ref redeem_script) = output.redeem_script {
+                append_pair(stream, OUT_REDEEM_SCRIPT, &[], redeem_script);
+            }
+            if let Some(ref witness_script) = output.witness_script {
+                append_pair(stream, OUT_WITNESS_SCRIPT, &[], witness_script);
+            }
+            append_key_sources(stream, OUT_BIP32_DERIVATION, &output.bip32_derivation);
+            append_map(stream, &output.unknown);
+            stream.append(&0u8);
+        }
+    }
+}
+
+impl Deserializable for Psbt {
+    fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, SerError>
+    where
+        Self: Sized,
+        T: io::Read,
+    {
+        let mut magic = [0u8; 5];
+        try!(reader.read_slice(&mut magic));
+        if &magic[..] != PSBT_MAGIC {
+            return Err(SerError::MalformedData);
+        }
+
+        let mut unsigned_tx = None;
+        let mut unknown = BTreeMap::new();
+        for (key, value) in try!(read_map(reader)) {
+            match key[0] {
+                GLOBAL_UNSIGNED_TX => unsigned_tx = Some(try!(read_value::<Transaction>(&key, &value))),
+                _ => {
+                    unknown.insert(key, value);
+                }
+            }
+        }
+
+        let unsigned_tx = try!(unsigned_tx.ok_or(SerError::MalformedData));
+        let mut psbt = try!(Psbt::from_unsigned_tx(unsigned_tx).map_err(|_| SerError::MalformedData));
+        psbt.unknown = unknown;
+
+        for (index, input) in psbt.inputs.iter_mut().enumerate() {
+            for (key, value) in try!(read_map(reader)) {
+                match key[0] {
+                    IN_NON_WITNESS_UTXO => {
+                        let tx: Transaction = try!(read_value(&key, &value));
+                        if tx.hash() != psbt.unsigned_tx.inputs[index].previous_output.hash {
+                            return Err(SerError::MalformedData);
+                        }
+                        input.non_witness_utxo = Some(tx);
+                    },
+                    IN_WITNESS_UTXO => input.witness_utxo = Some(try!(read_value(&key, &value))),
+                    IN_PARTIAL_SIG => {
+                        input.partial_sigs.insert(key[1..].to_vec().into(), value);
+                    },
+                    IN_SIGHASH_TYPE => input.sighash_type = Some(try!(read_value(&key, &value))),
+                    IN_REDEEM_SCRIPT => input.redeem_script = Some(try!(read_script(&key, value))),
+                    IN_WITNESS_SCRIPT => input.witness_script = Some(try!(read_script(&key, value))),
+                    IN_BIP32_DERIVATION => {
+                        input.bip32_derivation.insert(key[1..].to_vec().into(), try!(read_key_source(&value)));
+                    },
+                    IN_FINAL_SCRIPTSIG => input.final_script_sig = Some(try!(read_script(&key, value))),
+                    IN_FINAL_SCRIPTWITNESS => {
+                        if key.len() != 1 {
+                            return Err(SerError::MalformedData);
+                        }
+                        input.final_script_witness = Some(try!(Reader::new(&value).read_list()));
+                    },
+                    _ => {
+                        input.unknown.insert(key, value);
+                    },
+                }
+            }
+        }
+
+        for output in psbt.outputs.iter_mut() {
+            for (key, value) in try!(read_map(reader)) {
+                match key[0] {
+                    OUT_REDEEM_SCRIPT => output.redeem_script = Some(try!(read_script(&key, value))),
+                    OUT_WITNESS_SCRIPT => output.witness_script = Some(try!(read_script(&key, value))),
+                    OUT_BIP32_DERIVATION => {
+                        output.bip32_derivation.insert(key[1..].to_vec().into(), try!(read_key_source(&value)));
+                    },
+                    _ => {
+                        output.unknown.insert(key, value);
+                    },
+                }
+            }
+        }
+
+        Ok(psbt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeySource, Psbt, PsbtError};
+    use bytes::Bytes;
+    use chain::{OutPoint, Transaction, TransactionInput, TransactionOutput};
+    use keys::{KeyPair, Network, Private, Public};
+    use ser::{deserialize, serialize, Error as SerError};
+    use {verify_script, Builder, Script, SignatureVersion, TransactionInputSigner, TransactionSignatureChecker, VerificationFlags};
+
+    fn unsigned_tx(previous_output: OutPoint) -> Transaction {
+        Transaction {
+            version: 2,
+            inputs: vec![TransactionInput {
+                previous_output: previous_output,
+                script_sig: Bytes::default(),
+                sequence: 0xffffffff,
+                script_witness: vec![],
+            }],
+            outputs: vec![TransactionOutput {
+                value: 90_000,
+                script_pubkey: "76a914404371705fa9bd789a2fcd52d2c580b65d35549d88ac".into(),
+            }],
+            lock_time: 0,
+        }
+    }
+
+    fn keypair(secret: u8) -> KeyPair {
+        KeyPair::from_private(Private {
+            network: Network::Testnet,
+            secret: secret.into(),
+            compressed: true,
+        })
+        .unwrap()
+    }
+
+    /// Sign input of PSBT with given key, as the wallet does
+    fn sign(psbt: &mut Psbt, keypair: &KeyPair) {
+        let (script_code, sigversion) = psbt.signing_script(0).unwrap();
+        let amount = psbt.spent_output(0).unwrap().value;
+        let signer: TransactionInputSigner = psbt.unsigned_tx.clone().into();
+        let hash = signer.signature_hash(0, amount, &script_code, sigversion, 1);
+        let mut signature: Vec<u8> = keypair.private().sign(&hash).unwrap().into();
+        signature.push(1);
+        psbt.inputs[0].partial_sigs.insert(keypair.public().to_vec().into(), signature.into());
+    }
+
+    fn verify(psbt: &Psbt) {
+        let transaction = psbt.extract_transaction().unwrap();
+        let spent_output = psbt.spent_output(0).unwrap();
+        let signer: TransactionInputSigner = transaction.clone().into();
+        let checker = TransactionSignatureChecker {
+            signer: signer,
+            input_index: 0,
+            input_amount: spent_output.value,
+        };
+        let input = &transaction.inputs[0];
+        let script_pubkey: Script = spent_output.script_pubkey.into();
+        assert_eq!(
+            verify_script(
+                &input.script_sig.clone().into(),
+                &script_pubkey,
+                &input.script_witness,
+                &VerificationFlags::default().verify_p2sh(true).verify_witness(true),
+                &checker,
+                SignatureVersion::Base,
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_psbt_serialization_roundtrip() {
+        let mut psbt = Psbt::from_unsigned_tx(unsigned_tx(OutPoint::default())).unwrap();
+        psbt.inputs[0].witness_utxo = Some(TransactionOutput {
+            value: 100_000,
+            script_pubkey: "0014751e76e8199196d454941c45d1b3a323f1433bd6".into(),
+        });
+        psbt.inputs[0].partial_sigs.insert("02aa".into(), "3001".into());
+        psbt.inputs[0].sighash_type = Some(1);
+        psbt.inputs[0].bip32_derivation.insert("02aa".into(), KeySource {
+            fingerprint: "d90c6a4f".into(),
+            path: vec![0x8000002c, 0x80000001, 0x80000000, 0, 5],
+        });
+        psbt.inputs[0].unknown.insert("0f01".into(), "ff".into());
+        psbt.outputs[0].redeem_script = Some("5151ae".into());
+        psbt.unknown.insert("0f".into(), "00".into());
+
+        let serialized = serialize(&psbt);
+        assert_eq!(&serialized[..5], b"psbt\xff");
+        assert_eq!(deserialize::<_, Psbt>(&serialized[..]), Ok(psbt));
+    }
+
+    #[test]
+    fn test_psbt_deserialize_invalid() {
+        let psbt = Psbt::from_unsigned_tx(unsigned_tx(OutPoint::default())).unwrap();
+        let serialized = serialize(&psbt);
+
+        // invalid magic
+        let mut invalid = serialized.clone().take();
+        invalid[4] = 0;
+        assert_eq!(deserialize::<_, Psbt>(&invalid[..]), Err(SerError::MalformedData));
+        // missing output map
+        assert_eq!(deserialize::<_, Psbt>(&serialized[..serialized.len() - 1]), Err(SerError::UnexpectedEnd));
+        // duplicate global key
+        let mut invalid = serialized[..serialized.len() - 3].to_vec();
+        invalid.extend_from_slice(&serialized[5..serialized.len() - 3]);
+        invalid.extend_from_slice(&[0, 0, 0]);
+        assert_eq!(deserialize::<_, Psbt>(&invalid[..]), Err(SerError::MalformedData));
+    }
+
+    #[test]
+    fn test_psbt_of_signed_transaction() {
+        let mut transaction = unsigned_tx(OutPoint::default());
+        transaction.inputs[0].script_sig = "00".into();
+        assert_eq!(Psbt::from_unsigned_tx(transaction), Err(PsbtError::SignedTransaction));
+    }
+
+    #[test]
+    fn test_psbt_combine() {
+        let mut first = Psbt::from_unsigned_tx(unsigned_tx(OutPoint::default())).unwrap();
+        let mut second = first.clone();
+        first.inputs[0].partial_sigs.insert("02aa".into(), "3001".into());
+        second.inputs[0].partial_sigs.insert("02bb".into(), "3101".into());
+        second.inputs[0].redeem_script = Some("5151ae".into());
+
+        first.combine(second).unwrap();
+        assert_eq!(first.inputs[0].partial_sigs.len(), 2);
+        assert_eq!(first.inputs[0].redeem_script, Some("5151ae".into()));
+
+        let other = Psbt::from_unsigned_tx(unsigned_tx(OutPoint::null())).unwrap();
+        assert_eq!(first.combine(other), Err(PsbtError::TransactionMismatch));
+    }
+
+    #[test]
+    fn test_psbt_finalize_p2wpkh() {
+        let keypair = keypair(1);
+        let mut psbt = Psbt::from_unsigned_tx(unsigned_tx(OutPoint::default())).unwrap();
+        psbt.inputs[0].witness_utxo = Some(TransactionOutput {
+            value: 100_000,
+            script_pubkey: Builder::build_p2wpkh(&keypair.public().address_hash()).to_bytes(),
+        });
+        assert_eq!(psbt.fee(), Some(10_000));
+        assert!(!psbt.finalize());
+        assert_eq!(psbt.extract_transaction(), Err(PsbtError::NotFinalized));
+
+        sign(&mut psbt, &keypair);
+        assert!(psbt.finalize());
+        assert!(psbt.inputs[0].partial_sigs.is_empty());
+        assert_eq!(psbt.inputs[0].final_script_sig, None);
+        verify(&psbt);
+    }
+
+    #[test]
+    fn test_psbt_finalize_p2sh_multisig() {
+        let first = keypair(1);
+        let second = keypair(2);
+        let keys = [
+            Public::from_slice(&**first.public()).unwrap(),
+            Public::from_slice(&**second.public()).unwrap(),
+        ];
+        let redeem_script = Builder::build_multisig(2, &keys);
+        let previous = Transaction {
+            version: 1,
+            inputs: vec![TransactionInput::coinbase("01".into())],
+            outputs: vec![TransactionOutput {
+                value: 100_000,
+                script_pubkey: Builder::build_p2sh(&::crypto::dhash160(&redeem_script)).to_bytes(),
+            }],
+            lock_time: 0,
+        };
+
+        let mut psbt = Psbt::from_unsigned_tx(unsigned_tx(OutPoint { hash: previous.hash(), index: 0 })).unwrap();
+        psbt.inputs[0].non_witness_utxo = Some(previous);
+        // signing script is unknown without redeem script
+        assert_eq!(psbt.signing_script(0), None);
+        psbt.inputs[0].redeem_script = Some(redeem_script.to_bytes());
+
+        // signatures of both keys are collected in different PSBTs
+        let mut other = psbt.clone();
+        sign(&mut psbt, &second);
+        assert!(!psbt.clone().finalize());
+        sign(&mut other, &first);
+        let other = deserialize(&serialize(&other)[..]).unwrap();
+        psbt.combine(other).unwrap();
+
+        assert!(psbt.finalize());
+        assert_eq!(psbt.inputs[0].final_script_witness, None);
+        verify(&psbt);
+    }
+}
@@ -1,7 +1,8 @@
 //! Serialized script, used inside transaction inputs and outputs.
 
 use bytes::Bytes;
-use keys::{self, AddressHash, Public};
+use hash::H256;
+use keys::{self, AddressHash, AddressPayload, Public};
 use std::{fmt, ops};
 use {Error, Opcode};
 
@@ -28,6 +29,7 @@ pub enum ScriptType {
     NullData,
     WitnessScript,
     WitnessKey,
+    WitnessTaproot,
 }
 
 /// Address from Script
@@ -35,8 +37,8 @@ pub enum ScriptType {
 pub struct ScriptAddress {
     /// The type of the address.
     pub kind: keys::Type,
-    /// Public key hash.
-    pub hash: AddressHash,
+    /// Public key hash, script hash or witness program.
+    pub hash: AddressPayload,
 }
 
 impl ScriptAddress {
@@ -44,7 +46,7 @@ impl ScriptAddress {
     pub fn new_p2pkh(hash: AddressHash) -> Self {
         ScriptAddress {
             kind: keys::Type::P2PKH,
-            hash: hash,
+            hash: hash.into(),
         }
     }
 
@@ -52,7 +54,31 @@ impl ScriptAddress {
     pub fn new_p2sh(hash: AddressHash) -> Self {
         ScriptAddress {
             kind: keys::Type::P2SH,
-            hash: hash,
+            hash: hash.into(),
+        }
+    }
+
+    /// Creates P2WPKH-type ScriptAddress
+    pub fn new_p2wpkh(hash: AddressHash) -> Self {
+        ScriptAddress {
+            kind: keys::Type::P2WPKH,
+            hash: hash.into(),
+        }
+    }
+
+    /// Creates P2WSH-type ScriptAddress
+    pub fn new_p2wsh(hash: H256) -> Self {
+        ScriptAddress {
+            kind: keys::Type::P2WSH,
+            hash: hash.into(),
+        }
+    }
+
+    /// Creates P2TR-type ScriptAddress
+    pub fn new_p2tr(output_key: H256) -> Self {
+        ScriptAddress {
+            kind: keys::Type::P2TR,
+            hash: output_key.into(),
         }
     }
 }
@@ -168,6 +194,13 @@ impl Script {
             && self.data[1] == Opcode::OP_PUSHBYTES_32 as u8
     }
 
+    /// Extra-fast test for pay-to-taproot (witness v1) scripts.
+    pub fn is_pay_to_taproot(&self) -> bool {
+        self.data.len() == 34
+            && self.data[0] == Opcode::OP_1 as u8
+            && self.data[1] == Opcode::OP_PUSHBYTES_32 as u8
+    }
+
     /// Extra-fast test for multisig scripts.
     pub fn is_multisig_script(&self) -> bool {
         if self.data.len() < 3 {
@@ -354,6 +387,8 @@ impl Script {
             ScriptType::WitnessKey
         } else if self.is_pay_to_witness_script_hash() {
             ScriptType::WitnessScript
+        } else if self.is_pay_to_taproot() {
+            ScriptType::WitnessTaproot
         } else {
             ScriptType::NonStandard
         }
@@ -429,30 +464,37 @@ impl Script {
             }
             ScriptType::PubKeyHash => Ok(vec![ScriptAddress::new_p2pkh(self.data[3..23].into())]),
             ScriptType::ScriptHash => Ok(vec![ScriptAddress::new_p2sh(self.data[2..22].into())]),
-            ScriptType::Multisig => {
-                let mut addresses: Vec<ScriptAddress> = Vec::new();
-                let mut pc = 1;
-                while pc < self.len() - 2 {
-                    let instruction = self
-                        .get_instruction(pc)
-                        .expect("this method depends on previous check in script_type()");
-                    let data = instruction
-                        .data
-                        .expect("this method depends on previous check in script_type()");
-                    let address = Public::from_slice(data)?.address_hash();
-                    addresses.push(ScriptAddress::new_p2pkh(address));
-                    pc += instruction.step;
-                }
-                Ok(addresses)
-            }
+            ScriptType::Multisig => self.extract_multisig_keys().map(|keys| {
+                keys.iter()
+                    .map(|key| ScriptAddress::new_p2pkh(key.address_hash()))
+                    .collect()
+            }),
             ScriptType::NullData => Ok(vec![]),
-            ScriptType::WitnessScript => {
-                Ok(vec![]) // TODO
-            }
-            ScriptType::WitnessKey => {
-                Ok(vec![]) // TODO
-            }
+            ScriptType::WitnessScript => Ok(vec![ScriptAddress::new_p2wsh(self.data[2..34].into())]),
+            ScriptType::WitnessKey => Ok(vec![ScriptAddress::new_p2wpkh(self.data[2..22].into())]),
+            ScriptType::WitnessTaproot => Ok(vec![ScriptAddress::new_p2tr(self.data[2..34].into())]),
+        }
+    }
+
+    /// Public keys of multisig script. Empty if script is not a multisig script
+    pub fn extract_multisig_keys(&self) -> Result<Vec<Public>, keys::Error> {
+        if !self.is_multisig_script() {
+            return Ok(vec![]);
+        }
+
+        let mut keys = Vec::new();
+        let mut pc = 1;
+        while pc < self.len() - 2 {
+            let instruction = self
+                .get_instruction(pc)
+                .expect("this method depends on previous check in is_multisig_script()");
+            let data = instruction
+                .data
+                .expect("this method depends on previous check in is_multisig_script()");
+            keys.push(Public::from_slice(data)?);
+            pc += instruction.step;
         }
+        Ok(keys)
     }
 
     pub fn pay_to_script_hash_sigops(&self, checkdatasig_active: bool, prev_out: &Script) -> usize {
@@ -597,7 +639,8 @@ pub fn is_witness_commitment_script(script: &[u8]) -> bool {
 #[cfg(test)]
 mod tests {
     use super::{Script, ScriptAddress, ScriptType, MAX_SCRIPT_ELEMENT_SIZE};
-    use keys::{Address, Public};
+    use hash::H256;
+    use keys::{AddressHash, Public};
     use {Builder, Opcode};
 
     #[test]
@@ -782,7 +825,7 @@ OP_ADD
 
     #[test]
     fn test_extract_destinations_pub_key_hash() {
-        let address = Address::from("13NMTpfNVVJQTNH4spP4UeqBGqLdqDo27S").hash;
+        let address: AddressHash = "19fa116560ecada6849ea8cf06fd01638ba7b933".into();
         let script = Builder::build_p2pkh(&address);
         assert_eq!(script.script_type(), ScriptType::PubKeyHash);
         assert_eq!(
@@ -793,7 +836,7 @@ OP_ADD
 
     #[test]
     fn test_extract_destinations_script_hash() {
-        let address = Address::from("13NMTpfNVVJQTNH4spP4UeqBGqLdqDo27S").hash;
+        let address: AddressHash = "19fa116560ecada6849ea8cf06fd01638ba7b933".into();
         let script = Builder::build_p2sh(&address);
         assert_eq!(script.script_type(), ScriptType::ScriptHash);
         assert_eq!(
@@ -802,6 +845,42 @@ OP_ADD
         );
     }
 
+    #[test]
+    fn test_extract_destinations_witness_key_hash() {
+        let address: AddressHash = "751e76e8199196d454941c45d1b3a323f1433bd6".into();
+        let script = Builder::build_p2wpkh(&address);
+        assert_eq!(script, "0014751e76e8199196d454941c45d1b3a323f1433bd6".into());
+        assert_eq!(script.script_type(), ScriptType::WitnessKey);
+        assert_eq!(
+            script.extract_destinations(),
+            Ok(vec![ScriptAddress::new_p2wpkh(address),])
+        );
+    }
+
+    #[test]
+    fn test_extract_destinations_witness_script_hash() {
+        let hash: H256 = "1863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262".into();
+        let script = Builder::build_p2wsh(&hash);
+        assert_eq!(script, "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262".into());
+        assert_eq!(script.script_type(), ScriptType::WitnessScript);
+        assert_eq!(
+            script.extract_destinations(),
+            Ok(vec![ScriptAddress::new_p2wsh(hash),])
+        );
+    }
+
+    #[test]
+    fn test_extract_destinations_taproot() {
+        let output_key: H256 = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798".into();
+        let script = Builder::build_p2tr(&output_key);
+        assert_eq!(script, "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798".into());
+        assert_eq!(script.script_type(), ScriptType::WitnessTaproot);
+        assert_eq!(
+            script.extract_destinations(),
+            Ok(vec![ScriptAddress::new_p2tr(output_key),])
+        );
+    }
+
     #[test]
     fn test_extract_destinations_multisig() {
         let pubkey1_bytes = [0; 33];
@@ -825,6 +904,21 @@ OP_ADD
         );
     }
 
+    #[test]
+    fn test_extract_multisig_keys() {
+        let keys = vec![
+            Public::from_slice(&[2; 33]).unwrap(),
+            Public::from_slice(&[4; 65]).unwrap(),
+        ];
+        let script = Builder::build_multisig(1, &keys);
+        assert_eq!(script.script_type(), ScriptType::Multisig);
+        assert_eq!(script.num_signatures_required(), 1);
+        assert_eq!(script.extract_multisig_keys(), Ok(keys));
+
+        let not = Builder::build_p2pkh(&AddressHash::default());
+        assert_eq!(not.extract_multisig_keys(), Ok(vec![]));
+    }
+
     #[test]
     fn test_num_signatures_required() {
         let script = Builder::default()
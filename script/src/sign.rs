@@ -157,7 +157,9 @@ impl TransactionInputSigner {
     }
 
     /// input_index - index of input to sign
-    /// script_pubkey - script_pubkey of input's previous_output pubkey
+    /// script_pubkey - script_pubkey of input's previous_output pubkey (script code of witness v0 input)
+    ///
+    /// Signature and public key are pushed to the script_sig of base input and to the witness of witness v0 input
     pub fn signed_input(
         &self,
         keypair: &KeyPair,
@@ -177,17 +179,26 @@ impl TransactionInputSigner {
 
         let mut signature: Vec<u8> = keypair.private().sign(&hash).unwrap().into();
         signature.push(sighash as u8);
-        let script_sig = Builder::default()
-            .push_data(&signature)
-            .push_data(keypair.public())
-            .into_script();
+        let (script_sig, script_witness) = match sigversion {
+            SignatureVersion::WitnessV0 => (
+                Bytes::default(),
+                vec![signature.into(), keypair.public().to_vec().into()],
+            ),
+            SignatureVersion::Base | SignatureVersion::ForkId => {
+                let script_sig = Builder::default()
+                    .push_data(&signature)
+                    .push_data(keypair.public())
+                    .into_script();
+                (script_sig.to_bytes(), vec![])
+            }
+        };
 
         let unsigned_input = &self.inputs[input_index];
         TransactionInput {
             previous_output: unsigned_input.previous_output.clone(),
             sequence: unsigned_input.sequence,
-            script_sig: script_sig.to_bytes(),
-            script_witness: vec![],
+            script_sig: script_sig,
+            script_witness: script_witness,
         }
     }
 
@@ -401,7 +412,7 @@ mod tests {
         // this is irrelevant
         let kp = KeyPair::from_private(private).unwrap();
         assert_eq!(kp.address(), from);
-        assert_eq!(&current_output[3..23], &*to.hash);
+        assert_eq!(&current_output[3..23], to.hash.as_bytes());
 
         let unsigned_input = UnsignedTransactionInput {
             sequence: 0xffff_ffff,
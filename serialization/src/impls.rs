@@ -3,6 +3,7 @@ use bytes::Bytes;
 use compact::Compact;
 use compact_integer::CompactInteger;
 use hash::{H160, H256, H264, H32, H48, H512, H520, H96};
+use primitives::bigint::{Uint, U256};
 use std::io;
 use {Deserializable, Error, Reader, Serializable, Stream};
 
@@ -265,6 +266,26 @@ impl Deserializable for Bytes {
     }
 }
 
+impl Serializable for U256 {
+    fn serialize(&self, stream: &mut Stream) {
+        let mut bytes = [0u8; 32];
+        self.to_little_endian(&mut bytes);
+        stream.append_slice(&bytes);
+    }
+}
+
+impl Deserializable for U256 {
+    fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, Error>
+    where
+        T: io::Read,
+    {
+        let mut bytes = [0u8; 32];
+        try!(reader.read_slice(&mut bytes));
+        bytes.reverse();
+        Ok(U256::from(&bytes as &[u8]))
+    }
+}
+
 impl Serializable for Compact {
     fn serialize(&self, stream: &mut Stream) {
         stream.append(&u32::from(*self));
@@ -283,6 +304,7 @@ impl Deserializable for Compact {
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
+    use primitives::bigint::U256;
     use {deserialize, deserialize_iterator, serialize, Error, Reader, Stream};
 
     #[test]
@@ -366,4 +388,13 @@ mod tests {
         stream.append_slice(&slice);
         assert_eq!(stream.out(), "64000000".into());
     }
+
+    #[test]
+    fn test_u256_serialize() {
+        let value = U256::from(0x100010001u64);
+        let expected: Bytes =
+            "0100010001000000000000000000000000000000000000000000000000000000".into();
+        assert_eq!(serialize(&value), expected);
+        assert_eq!(deserialize::<_, U256>(expected.as_ref()).unwrap(), value);
+    }
 }
@@ -0,0 +1,329 @@
+//! Index of outputs, paying to the script, and inputs, spending these outputs
+
+use chain::OutPoint;
+use crypto::sha256;
+use hash::H256;
+use ser::{Deserializable, Error as ReaderError, Reader, Serializable, Stream};
+use std::cmp::Ordering;
+use std::io;
+
+/// Key of the address index: sha256 of the output script (the same key Electrum protocol uses)
+pub fn script_hash(script_pubkey: &[u8]) -> H256 {
+    sha256(script_pubkey)
+}
+
+/// Key of the address index entry. Entries of the script are stored under their own keys,
+/// ordered by the block height, so that history of the script is range-scanned.
+/// Height and output index are serialized in big-endian, so that the order of serialized keys
+/// matches the order of keys.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AddressIndexKey {
+    /// Hash of the indexed script
+    pub script_hash: H256,
+    /// Height of the block, containing the entry
+    pub height: u32,
+    /// Funding output or output, spent by the spending input
+    pub outpoint: OutPoint,
+}
+
+/// Number of the address index entries of the script
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AddressStats {
+    /// Number of outputs, paying to the script
+    pub funding: u64,
+    /// Number of inputs, spending outputs, paying to the script
+    pub spending: u64,
+}
+
+/// Canonical output, paying to the indexed script
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddressFunding {
+    /// Funding output
+    pub outpoint: OutPoint,
+    /// Value of the output
+    pub value: u64,
+    /// Height of the block, containing the output
+    pub height: u32,
+}
+
+/// Canonical input, spending output, which pays to the indexed script
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddressSpending {
+    /// Spent output
+    pub outpoint: OutPoint,
+    /// Value of the spent output
+    pub value: u64,
+    /// Spending transaction
+    pub spender: H256,
+    /// Index of the spending input
+    pub input_index: u32,
+    /// Height of the block, containing the spending transaction
+    pub height: u32,
+}
+
+impl AddressIndexKey {
+    /// The first key of the script entries
+    pub fn first(script_hash: H256) -> Self {
+        AddressIndexKey {
+            script_hash: script_hash,
+            height: 0,
+            outpoint: OutPoint {
+                hash: H256::default(),
+                index: 0,
+            },
+        }
+    }
+}
+
+impl AddressFunding {
+    /// Key of the entry in the index of given script
+    pub fn key(&self, script_hash: H256) -> AddressIndexKey {
+        AddressIndexKey {
+            script_hash: script_hash,
+            height: self.height,
+            outpoint: self.outpoint.clone(),
+        }
+    }
+}
+
+impl AddressSpending {
+    /// Key of the entry in the index of given script
+    pub fn key(&self, script_hash: H256) -> AddressIndexKey {
+        AddressIndexKey {
+            script_hash: script_hash,
+            height: self.height,
+            outpoint: self.outpoint.clone(),
+        }
+    }
+}
+
+pub trait AddressIndexProvider {
+    /// returns true if the address index is maintained by the store
+    fn has_address_index(&self) -> bool;
+
+    /// returns number of canonical outputs and inputs of the script with given hash
+    fn address_stats(&self, script_hash: &H256) -> AddressStats;
+
+    /// returns canonical outputs, paying to the script with given hash, in the chain order.
+    /// Entries are read from the store while iterating
+    fn address_funding<'a>(
+        &'a self,
+        script_hash: &H256,
+    ) -> Box<Iterator<Item = AddressFunding> + 'a>;
+
+    /// returns canonical inputs, spending outputs, paying to the script with given hash, in the chain order.
+    /// Entries are read from the store while iterating
+    fn address_spending<'a>(
+        &'a self,
+        script_hash: &H256,
+    ) -> Box<Iterator<Item = AddressSpending> + 'a>;
+}
+
+impl Ord for AddressIndexKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (
+            &*self.script_hash,
+            self.height,
+            &*self.outpoint.hash,
+            self.outpoint.index,
+        )
+            .cmp(&(
+                &*other.script_hash,
+                other.height,
+                &*other.outpoint.hash,
+                other.outpoint.index,
+            ))
+    }
+}
+
+impl PartialOrd for AddressIndexKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Serializable for AddressIndexKey {
+    fn serialize(&self, stream: &mut Stream) {
+        stream
+            .append(&self.script_hash)
+            .append_slice(&be_bytes(self.height))
+            .append(&self.outpoint.hash)
+            .append_slice(&be_bytes(self.outpoint.index));
+    }
+}
+
+impl Deserializable for AddressIndexKey {
+    fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError>
+    where
+        T: io::Read,
+    {
+        let script_hash = reader.read()?;
+        let mut height = [0u8; 4];
+        reader.read_slice(&mut height)?;
+        let hash = reader.read()?;
+        let mut index = [0u8; 4];
+        reader.read_slice(&mut index)?;
+        Ok(AddressIndexKey {
+            script_hash: script_hash,
+            height: from_be_bytes(height),
+            outpoint: OutPoint {
+                hash: hash,
+                index: from_be_bytes(index),
+            },
+        })
+    }
+}
+
+impl Serializable for AddressStats {
+    fn serialize(&self, stream: &mut Stream) {
+        stream.append(&self.funding).append(&self.spending);
+    }
+}
+
+impl Deserializable for AddressStats {
+    fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError>
+    where
+        T: io::Read,
+    {
+        Ok(AddressStats {
+            funding: reader.read()?,
+            spending: reader.read()?,
+        })
+    }
+}
+
+fn be_bytes(value: u32) -> [u8; 4] {
+    [
+        (value >> 24) as u8,
+        (value >> 16) as u8,
+        (value >> 8) as u8,
+        value as u8,
+    ]
+}
+
+fn from_be_bytes(bytes: [u8; 4]) -> u32 {
+    (bytes[0] as u32) << 24 | (bytes[1] as u32) << 16 | (bytes[2] as u32) << 8 | bytes[3] as u32
+}
+
+impl Serializable for AddressFunding {
+    fn serialize(&self, stream: &mut Stream) {
+        stream
+            .append(&self.outpoint)
+            .append(&self.value)
+            .append(&self.height);
+    }
+}
+
+impl Deserializable for AddressFunding {
+    fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError>
+    where
+        T: io::Read,
+    {
+        Ok(AddressFunding {
+            outpoint: reader.read()?,
+            value: reader.read()?,
+            height: reader.read()?,
+        })
+    }
+}
+
+impl Serializable for AddressSpending {
+    fn serialize(&self, stream: &mut Stream) {
+        stream
+            .append(&self.outpoint)
+            .append(&self.value)
+            .append(&self.spender)
+            .append(&self.input_index)
+            .append(&self.height);
+    }
+}
+
+impl Deserializable for AddressSpending {
+    fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError>
+    where
+        T: io::Read,
+    {
+        Ok(AddressSpending {
+            outpoint: reader.read()?,
+            value: reader.read()?,
+            spender: reader.read()?,
+            input_index: reader.read()?,
+            height: reader.read()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AddressFunding, AddressIndexKey, AddressSpending, AddressStats};
+    use chain::OutPoint;
+    use hash::H256;
+    use ser::{deserialize, serialize};
+
+    #[test]
+    fn test_address_index_entries_serde() {
+        let funding = AddressFunding {
+            outpoint: OutPoint {
+                hash: H256::from(1),
+                index: 2,
+            },
+            value: 100,
+            height: 10,
+        };
+        let spending = AddressSpending {
+            outpoint: funding.outpoint.clone(),
+            value: 100,
+            spender: H256::from(3),
+            input_index: 1,
+            height: 12,
+        };
+
+        assert_eq!(
+            deserialize::<_, AddressFunding>(serialize(&funding).as_ref()).unwrap(),
+            funding
+        );
+        assert_eq!(
+            deserialize::<_, AddressSpending>(serialize(&spending).as_ref()).unwrap(),
+            spending
+        );
+
+        let stats = AddressStats {
+            funding: 2,
+            spending: 1,
+        };
+        assert_eq!(
+            deserialize::<_, AddressStats>(serialize(&stats).as_ref()).unwrap(),
+            stats
+        );
+    }
+
+    #[test]
+    fn test_address_index_key_order_matches_serialized_order() {
+        let key = |script_hash: u8, height: u32, hash: u8, index: u32| AddressIndexKey {
+            script_hash: H256::from(script_hash),
+            height: height,
+            outpoint: OutPoint {
+                hash: H256::from(hash),
+                index: index,
+            },
+        };
+        let keys = vec![
+            AddressIndexKey::first(H256::from(1)),
+            key(1, 1, 2, 0x100),
+            key(1, 1, 3, 1),
+            key(1, 0x100, 1, 0),
+            key(2, 0, 0, 0),
+        ];
+
+        for pair in keys.windows(2) {
+            assert!(pair[0] < pair[1]);
+            assert!(serialize(&pair[0]).take() < serialize(&pair[1]).take());
+        }
+        for key in keys {
+            assert_eq!(
+                deserialize::<_, AddressIndexKey>(serialize(&key).as_ref()).unwrap(),
+                key
+            );
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use chain::{IndexedBlock, IndexedBlockHeader};
 use hash::H256;
-use {BlockOrigin, Error, SideChainOrigin, Store};
+use {BlockOrigin, Error, SideChainOrigin, Store, TransactionErrorCode};
 
 pub trait ForkChain {
     /// Returns forks underlaying store.
@@ -27,7 +27,13 @@ pub trait BlockChain {
     /// Checks block origin
     fn block_origin(&self, header: &IndexedBlockHeader) -> Result<BlockOrigin, Error>;
 
-    fn canonize_with_invalid(&self, block_hash: &H256 , tx_flags: &Vec<bool>) -> Result<(), Error>;
+    /// Canonizes block with given hash, marking transactions with an error as invalid.
+    /// Errors are stored along with the canonization
+    fn canonize_with_invalid(
+        &self,
+        block_hash: &H256,
+        tx_errors: &[Option<TransactionErrorCode>],
+    ) -> Result<(), Error>;
 }
 
 pub trait Forkable {
@@ -0,0 +1,285 @@
+//! BIP158 compact block filters
+
+use bytes::Bytes;
+use chain::IndexedBlock;
+use crypto::{dhash256, siphash24};
+use hash::H256;
+use ser::{
+    serialize, CompactInteger, Deserializable, Error as ReaderError, Reader, Serializable, Stream,
+};
+use std::io;
+
+/// Type of the BIP158 basic filter
+pub const BASIC_FILTER_TYPE: u8 = 0;
+/// Golomb-Rice coding parameter of the basic filter
+const BASIC_FILTER_P: u8 = 19;
+/// Inverse of the false positive rate of the basic filter
+const BASIC_FILTER_M: u64 = 784_931;
+/// OP_RETURN outputs are never added to the basic filter
+const OP_RETURN: u8 = 0x6a;
+
+/// Basic filter of the block
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockFilter {
+    /// Serialized Golomb-coded set
+    pub filter: Bytes,
+    /// Filter header, committing to this filter and to headers of all previous filters
+    pub header: H256,
+}
+
+pub trait BlockFilterProvider {
+    /// returns basic filter of the block with given hash, if it has been built
+    fn block_filter(&self, block_hash: &H256) -> Option<BlockFilter>;
+}
+
+impl BlockFilter {
+    /// Builds basic filter of the block from its output scripts and scripts of outputs, spent by the block
+    pub fn basic(block: &IndexedBlock, spent_scripts: &[Bytes], previous_header: &H256) -> Self {
+        let mut elements: Vec<&[u8]> = block
+            .transactions
+            .iter()
+            .flat_map(|tx| tx.raw.outputs.iter())
+            .map(|output| output.script_pubkey.as_ref())
+            .filter(|script| !script.is_empty() && script[0] != OP_RETURN)
+            .chain(
+                spent_scripts
+                    .iter()
+                    .map(|script| script.as_ref())
+                    .filter(|script| !script.is_empty()),
+            )
+            .collect();
+        elements.sort();
+        elements.dedup();
+
+        let filter = gcs_encode(block.hash(), &elements);
+        let header = filter_header(&dhash256(&filter), previous_header);
+        BlockFilter {
+            filter: filter.into(),
+            header: header,
+        }
+    }
+
+    /// Hash of the serialized filter, used in `cfheaders` messages
+    pub fn filter_hash(&self) -> H256 {
+        dhash256(&self.filter)
+    }
+
+    /// Returns true if any of given scripts matches the filter of the block with given hash.
+    /// False positives are possible, false negatives are not.
+    pub fn match_any(&self, block_hash: &H256, scripts: &[&[u8]]) -> bool {
+        let mut reader = Reader::new(&self.filter);
+        let count: u64 = match reader.read::<CompactInteger>() {
+            Ok(count) => count.into(),
+            Err(_) => return false,
+        };
+        if count == 0 || scripts.is_empty() {
+            return false;
+        }
+
+        let range = count * BASIC_FILTER_M;
+        let (k0, k1) = siphash_keys(block_hash);
+        let mut queries: Vec<u64> = scripts
+            .iter()
+            .map(|script| hash_to_range(k0, k1, script, range))
+            .collect();
+        queries.sort();
+
+        let offset = serialize(&CompactInteger::from(count)).len();
+        let mut bits = BitReader::new(&self.filter[offset..]);
+        let mut value = 0u64;
+        let mut queries = queries.into_iter().peekable();
+        for _ in 0..count {
+            value += match golomb_rice_decode(&mut bits) {
+                Some(delta) => delta,
+                None => return false,
+            };
+            while let Some(&query) = queries.peek() {
+                if query == value {
+                    return true;
+                }
+                if query > value {
+                    break;
+                }
+                queries.next();
+            }
+            if queries.peek().is_none() {
+                return false;
+            }
+        }
+
+        false
+    }
+}
+
+/// Header of the filter: hash of the filter hash and the previous filter header
+pub fn filter_header(filter_hash: &H256, previous_header: &H256) -> H256 {
+    let mut data = Vec::with_capacity(64);
+    data.extend_from_slice(&**filter_hash);
+    data.extend_from_slice(&**previous_header);
+    dhash256(&data)
+}
+
+/// SipHash keys are taken from the first 16 bytes of the block hash
+fn siphash_keys(block_hash: &H256) -> (u64, u64) {
+    let key = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .rev()
+            .fold(0u64, |key, byte| (key << 8) | *byte as u64)
+    };
+    (key(&block_hash[0..8]), key(&block_hash[8..16]))
+}
+
+/// Maps hash of the element into [0, range) range
+fn hash_to_range(k0: u64, k1: u64, element: &[u8], range: u64) -> u64 {
+    ((siphash24(k0, k1, element) as u128 * range as u128) >> 64) as u64
+}
+
+/// Serializes Golomb-coded set of given (unique) elements
+fn gcs_encode(block_hash: &H256, elements: &[&[u8]]) -> Vec<u8> {
+    let range = elements.len() as u64 * BASIC_FILTER_M;
+    let (k0, k1) = siphash_keys(block_hash);
+    let mut values: Vec<u64> = elements
+        .iter()
+        .map(|element| hash_to_range(k0, k1, element, range))
+        .collect();
+    values.sort();
+
+    let mut bits = BitWriter::default();
+    let mut previous = 0;
+    for value in values {
+        golomb_rice_encode(&mut bits, value - previous);
+        previous = value;
+    }
+
+    let mut result = serialize(&CompactInteger::from(elements.len())).take();
+    result.extend(bits.bytes);
+    result
+}
+
+fn golomb_rice_encode(bits: &mut BitWriter, value: u64) {
+    for _ in 0..(value >> BASIC_FILTER_P) {
+        bits.write_bit(true);
+    }
+    bits.write_bit(false);
+    bits.write_bits(value, BASIC_FILTER_P);
+}
+
+fn golomb_rice_decode(bits: &mut BitReader) -> Option<u64> {
+    let mut quotient = 0u64;
+    while bits.read_bit()? {
+        quotient += 1;
+    }
+    let remainder = bits.read_bits(BASIC_FILTER_P)?;
+    Some((quotient << BASIC_FILTER_P) + remainder)
+}
+
+/// Writes bits, starting from the most significant bit of every byte
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    used_bits: u8,
+}
+
+impl BitWriter {
+    fn write_bit(&mut self, bit: bool) {
+        if self.used_bits == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            let last = self.bytes.len() - 1;
+            self.bytes[last] |= 0x80 >> self.used_bits;
+        }
+        self.used_bits = (self.used_bits + 1) % 8;
+    }
+
+    /// Writes given number of the least significant bits of the value
+    fn write_bits(&mut self, value: u64, count: u8) {
+        for bit in (0..count).rev() {
+            self.write_bit((value >> bit) & 1 == 1);
+        }
+    }
+}
+
+/// Reads bits, written by the `BitWriter`
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        BitReader {
+            bytes: bytes,
+            position: 0,
+        }
+    }
+
+    fn read_bit(&mut self) -> Option<bool> {
+        let byte = *self.bytes.get(self.position / 8)?;
+        let bit = byte & (0x80 >> (self.position % 8)) != 0;
+        self.position += 1;
+        Some(bit)
+    }
+
+    fn read_bits(&mut self, count: u8) -> Option<u64> {
+        let mut value = 0u64;
+        for _ in 0..count {
+            value = (value << 1) | self.read_bit()? as u64;
+        }
+        Some(value)
+    }
+}
+
+impl Serializable for BlockFilter {
+    fn serialize(&self, stream: &mut Stream) {
+        stream.append(&self.filter).append(&self.header);
+    }
+}
+
+impl Deserializable for BlockFilter {
+    fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError>
+    where
+        T: io::Read,
+    {
+        Ok(BlockFilter {
+            filter: reader.read()?,
+            header: reader.read()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockFilter;
+    use bytes::Bytes;
+    use chain::IndexedBlock;
+    use hash::H256;
+    use network::Network;
+
+    #[test]
+    fn test_basic_filter_of_testnet_genesis() {
+        // https://github.com/bitcoin/bips/blob/master/bip-0158/testnet-19.json
+        let genesis: IndexedBlock = Network::Testnet.genesis_block().into();
+        let filter = BlockFilter::basic(&genesis, &[], &H256::default());
+
+        assert_eq!(filter.filter, Bytes::from("019dfca8"));
+        assert_eq!(
+            filter.header.reversed(),
+            H256::from("21584579b7eb08997773e5aeff3a7f932700042d0ed2a6129012b7d7ae81b750")
+        );
+    }
+
+    #[test]
+    fn test_basic_filter_matches_its_elements() {
+        let genesis: IndexedBlock = Network::Testnet.genesis_block().into();
+        let spent_scripts = vec![Bytes::from("76a914"), Bytes::from("0014ff")];
+        let filter = BlockFilter::basic(&genesis, &spent_scripts, &H256::default());
+
+        let output_script: &[u8] = &genesis.transactions[0].raw.outputs[0].script_pubkey;
+        assert!(filter.match_any(genesis.hash(), &[output_script]));
+        assert!(filter.match_any(genesis.hash(), &[b"unknown", &spent_scripts[1]]));
+        assert!(!filter.match_any(genesis.hash(), &[b"unknown"]));
+        assert!(!filter.match_any(genesis.hash(), &[]));
+    }
+}
@@ -3,6 +3,7 @@ use chain::{
     Block, BlockHeader, IndexedBlock, IndexedBlockHeader, IndexedTransaction, Transaction,
 };
 use hash::H256;
+use primitives::bigint::U256;
 use BlockRef;
 
 pub trait BlockHeaderProvider {
@@ -20,7 +21,11 @@ pub trait BlockProvider: BlockHeaderProvider {
     /// resolves hash by block number
     fn block_hash(&self, number: u32) -> Option<H256>;
 
-    /// resolves deserialized block body by block reference (number/hash)
+    /// resolves expected number of hashes required to produce the chain up to given block
+    fn block_chain_work(&self, hash: &H256) -> Option<U256>;
+
+    /// resolves deserialized block body by block reference (number/hash).
+    /// Returns None for pruned blocks (see `is_pruned`)
     fn block(&self, block_ref: BlockRef) -> Option<Block>;
 
     /// returns true if store contains given block
@@ -28,6 +33,9 @@ pub trait BlockProvider: BlockHeaderProvider {
         self.block_header_bytes(block_ref).is_some()
     }
 
+    /// returns true if store contains header of given block, but its body has been pruned
+    fn is_pruned(&self, block_ref: BlockRef) -> bool;
+
     /// resolves list of block transactions by block reference (number/hash)
     fn block_transaction_hashes(&self, block_ref: BlockRef) -> Vec<H256>;
 
@@ -12,6 +12,15 @@ pub enum Error {
     /// Ancient fork
     #[display(fmt = "Fork is too long to proceed")]
     AncientFork,
+    /// Block body has been pruned
+    #[display(fmt = "Block has been pruned")]
+    BlockPruned,
+    /// UTXO set snapshot could not be dumped or loaded
+    #[display(fmt = "Invalid UTXO set snapshot: {}", _0)]
+    InvalidSnapshot(String),
+    /// Address index is only built for the database, which is empty when index is enabled
+    #[display(fmt = "Address index could only be enabled on the empty database")]
+    AddressIndexUnavailable,
 }
 
 impl From<Error> for String {
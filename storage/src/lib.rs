@@ -5,14 +5,17 @@ extern crate parking_lot;
 #[macro_use]
 extern crate display_derive;
 
+extern crate bitcrypto as crypto;
 extern crate chain;
 extern crate network;
 extern crate primitives;
 extern crate serialization as ser;
 
+mod address_index;
 mod best_block;
 mod block_ancestors;
 mod block_chain;
+mod block_filter;
 mod block_impls;
 mod block_iterator;
 mod block_origin;
@@ -21,14 +24,22 @@ mod block_ref;
 mod duplex_store;
 mod error;
 mod store;
+mod transaction_error;
+mod transaction_location;
 mod transaction_meta;
 mod transaction_provider;
+mod utxo_snapshot;
+mod utxo_stats;
 
 pub use primitives::{bytes, hash};
 
+pub use address_index::{
+    script_hash, AddressFunding, AddressIndexKey, AddressIndexProvider, AddressSpending, AddressStats,
+};
 pub use best_block::BestBlock;
 pub use block_ancestors::BlockAncestors;
 pub use block_chain::{BlockChain, ForkChain, Forkable};
+pub use block_filter::{filter_header, BlockFilter, BlockFilterProvider, BASIC_FILTER_TYPE};
 pub use block_iterator::BlockIterator;
 pub use block_origin::{BlockOrigin, SideChainOrigin};
 pub use block_provider::{BlockHeaderProvider, BlockProvider, IndexedBlockProvider};
@@ -37,8 +48,13 @@ pub use duplex_store::{
     transaction_index_for_output_check, DuplexTransactionOutputProvider, NoopStore,
 };
 pub use error::Error;
-pub use store::{AsSubstore, CanonStore, ConfigStore, SharedStore, Store};
+pub use store::{AsSubstore, CanonStore, ConfigStore, SanitizationStore, SharedStore, Store};
+pub use transaction_error::TransactionErrorCode;
+pub use transaction_location::TransactionLocation;
 pub use transaction_meta::TransactionMeta;
 pub use transaction_provider::{
-    TransactionMetaProvider, TransactionOutputProvider, TransactionProvider,
+    TransactionErrorProvider, TransactionLocationProvider, TransactionMetaProvider,
+    TransactionOutputProvider, TransactionProvider,
 };
+pub use utxo_snapshot::{UtxoSnapshotInfo, UtxoSnapshotStore, UtxoSnapshotTransaction};
+pub use utxo_stats::UtxoStats;
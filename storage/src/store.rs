@@ -1,11 +1,16 @@
-use chain::BlockHeader;
+use chain::{BlockHeader, IndexedBlock};
+use hash::H256;
 use std::sync::Arc;
 use {
-    BestBlock, BlockChain, BlockHeaderProvider, BlockProvider, Error, Forkable,
-    IndexedBlockProvider, TransactionMetaProvider, TransactionOutputProvider, TransactionProvider,
+    AddressIndexProvider, BestBlock, BlockChain, BlockFilterProvider, BlockHeaderProvider,
+    BlockProvider, Error, Forkable, IndexedBlockProvider, TransactionErrorProvider,
+    TransactionLocationProvider, TransactionMetaProvider, TransactionOutputProvider,
+    TransactionProvider, UtxoSnapshotStore, UtxoStats,
 };
 
-pub trait CanonStore: Store + Forkable + ConfigStore {
+pub trait CanonStore:
+    Store + Forkable + ConfigStore + UtxoSnapshotStore + SanitizationStore
+{
     fn as_store(&self) -> &Store;
 }
 
@@ -18,6 +23,22 @@ pub trait ConfigStore {
     fn set_consensus_fork(&self, consensus_fork: &str) -> Result<(), Error>;
 }
 
+/// Storage of transactions validity, found by the lazy verification (sanitization) of canonized blocks
+pub trait SanitizationStore {
+    /// get validity of every transaction of the sanitized block (in block order)
+    fn sanitized_block(&self, block_hash: &H256) -> Option<Vec<bool>>;
+
+    /// get validity of the transaction from the sanitized block
+    fn sanitized_transaction(&self, tx_hash: &H256) -> Option<bool>;
+
+    /// store validity of every transaction of the sanitized block
+    fn insert_sanitized_block(
+        &self,
+        block: &IndexedBlock,
+        transactions_validity: &[bool],
+    ) -> Result<(), Error>;
+}
+
 /// Blockchain storage interface
 pub trait Store: AsSubstore {
     /// get best block
@@ -28,6 +49,10 @@ pub trait Store: AsSubstore {
 
     /// get blockchain difficulty
     fn difficulty(&self) -> f64;
+
+    /// get statistics of the unspent outputs set at the best block
+    /// (None if the database has been created before the statistics were maintained)
+    fn utxo_stats(&self) -> Option<UtxoStats>;
 }
 
 /// Allows casting Arc<Store> to reference to any substore type
@@ -37,6 +62,10 @@ pub trait AsSubstore:
     + TransactionProvider
     + TransactionMetaProvider
     + TransactionOutputProvider
+    + TransactionErrorProvider
+    + TransactionLocationProvider
+    + AddressIndexProvider
+    + BlockFilterProvider
 {
     fn as_block_provider(&self) -> &BlockProvider;
 
@@ -47,6 +76,14 @@ pub trait AsSubstore:
     fn as_transaction_output_provider(&self) -> &TransactionOutputProvider;
 
     fn as_transaction_meta_provider(&self) -> &TransactionMetaProvider;
+
+    fn as_transaction_error_provider(&self) -> &TransactionErrorProvider;
+
+    fn as_transaction_location_provider(&self) -> &TransactionLocationProvider;
+
+    fn as_address_index_provider(&self) -> &AddressIndexProvider;
+
+    fn as_block_filter_provider(&self) -> &BlockFilterProvider;
 }
 
 impl<T> AsSubstore for T
@@ -55,7 +92,11 @@ where
         + IndexedBlockProvider
         + TransactionProvider
         + TransactionMetaProvider
-        + TransactionOutputProvider,
+        + TransactionOutputProvider
+        + TransactionErrorProvider
+        + TransactionLocationProvider
+        + AddressIndexProvider
+        + BlockFilterProvider,
 {
     fn as_block_provider(&self) -> &BlockProvider {
         &*self
@@ -76,6 +117,22 @@ where
     fn as_transaction_meta_provider(&self) -> &TransactionMetaProvider {
         &*self
     }
+
+    fn as_transaction_error_provider(&self) -> &TransactionErrorProvider {
+        &*self
+    }
+
+    fn as_transaction_location_provider(&self) -> &TransactionLocationProvider {
+        &*self
+    }
+
+    fn as_address_index_provider(&self) -> &AddressIndexProvider {
+        &*self
+    }
+
+    fn as_block_filter_provider(&self) -> &BlockFilterProvider {
+        &*self
+    }
 }
 
 pub type SharedStore = Arc<CanonStore + Send + Sync>;
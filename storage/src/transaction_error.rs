@@ -0,0 +1,115 @@
+//! Reasons why canon chain transactions have been marked invalid
+
+use ser::{Deserializable, Error as ReaderError, Reader, Serializable, Stream};
+use std::io;
+
+/// Verification error of the canon chain transaction, as it is stored in the database.
+/// Codes are persisted, so existing variants must never be renumbered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransactionErrorCode {
+    /// Transaction has no inputs or no outputs
+    Empty = 1,
+    /// Transaction is not coinbase transaction but has null inputs
+    NullNonCoinbase = 2,
+    /// Coinbase signature is not in the range 2-100
+    CoinbaseSignatureLength = 3,
+    /// Transaction size exceeds block size limit
+    MaxSize = 4,
+    /// Transaction size is below min size limit
+    MinSize = 5,
+    /// Transaction has more sigops than it's allowed
+    MaxSigops = 6,
+    /// Transaction is a part of memory pool, but is a coinbase
+    MemoryPoolCoinbase = 7,
+    /// Not found corresponding output for transaction input
+    Input = 8,
+    /// Referenced coinbase output for the transaction input is not mature enough
+    Maturity = 9,
+    /// Signature invalid for given input
+    Signature = 10,
+    /// Unknown previous transaction referenced
+    UnknownReference = 11,
+    /// Spends more than claims
+    Overspend = 12,
+    /// Signature script can't be properly parsed
+    SignatureMallformed = 13,
+    /// Too many signature operations
+    Sigops = 14,
+    /// Too many signature operations once p2sh operations included
+    SigopsP2SH = 15,
+    /// Coinbase transaction is found at position that is not 0
+    MisplacedCoinbase = 16,
+    /// Not fully spent transaction with the same hash already exists, bip30.
+    UnspentTransactionWithTheSameHash = 17,
+    /// Using output that is surely spent
+    UsingSpentOutput = 18,
+    /// Transaction, protected using BitcoinCash OP_RETURN replay protection (REQ-6-1).
+    ReturnReplayProtection = 19,
+    /// Transaction with witness is received before SegWit is activated.
+    PrematureWitness = 20,
+}
+
+impl TransactionErrorCode {
+    fn from_u8(code: u8) -> Option<Self> {
+        let error = match code {
+            1 => TransactionErrorCode::Empty,
+            2 => TransactionErrorCode::NullNonCoinbase,
+            3 => TransactionErrorCode::CoinbaseSignatureLength,
+            4 => TransactionErrorCode::MaxSize,
+            5 => TransactionErrorCode::MinSize,
+            6 => TransactionErrorCode::MaxSigops,
+            7 => TransactionErrorCode::MemoryPoolCoinbase,
+            8 => TransactionErrorCode::Input,
+            9 => TransactionErrorCode::Maturity,
+            10 => TransactionErrorCode::Signature,
+            11 => TransactionErrorCode::UnknownReference,
+            12 => TransactionErrorCode::Overspend,
+            13 => TransactionErrorCode::SignatureMallformed,
+            14 => TransactionErrorCode::Sigops,
+            15 => TransactionErrorCode::SigopsP2SH,
+            16 => TransactionErrorCode::MisplacedCoinbase,
+            17 => TransactionErrorCode::UnspentTransactionWithTheSameHash,
+            18 => TransactionErrorCode::UsingSpentOutput,
+            19 => TransactionErrorCode::ReturnReplayProtection,
+            20 => TransactionErrorCode::PrematureWitness,
+            _ => return None,
+        };
+        Some(error)
+    }
+}
+
+impl Serializable for TransactionErrorCode {
+    fn serialize(&self, stream: &mut Stream) {
+        stream.append(&(*self as u8));
+    }
+}
+
+impl Deserializable for TransactionErrorCode {
+    fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError>
+    where
+        T: io::Read,
+    {
+        let code: u8 = reader.read()?;
+        TransactionErrorCode::from_u8(code).ok_or(ReaderError::MalformedData)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransactionErrorCode;
+    use ser::{deserialize, serialize};
+
+    #[test]
+    fn test_transaction_error_code_serde() {
+        for code in 1..21u8 {
+            let error = TransactionErrorCode::from_u8(code).unwrap();
+            assert_eq!(serialize(&error).take(), vec![code]);
+            assert_eq!(
+                deserialize::<_, TransactionErrorCode>(&[code][..]).unwrap(),
+                error
+            );
+        }
+        assert!(deserialize::<_, TransactionErrorCode>(&[0u8][..]).is_err());
+        assert!(deserialize::<_, TransactionErrorCode>(&[21u8][..]).is_err());
+    }
+}
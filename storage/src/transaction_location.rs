@@ -0,0 +1,52 @@
+//! Transaction locator index
+
+use hash::H256;
+use ser::{Deserializable, Error as ReaderError, Reader, Serializable, Stream};
+use std::io;
+
+/// Location of the canonical transaction in the chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionLocation {
+    /// Hash of the block, containing the transaction
+    pub block_hash: H256,
+    /// Position of the transaction in the block
+    pub index: u32,
+}
+
+impl Serializable for TransactionLocation {
+    fn serialize(&self, stream: &mut Stream) {
+        stream.append(&self.block_hash).append(&self.index);
+    }
+}
+
+impl Deserializable for TransactionLocation {
+    fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError>
+    where
+        T: io::Read,
+    {
+        Ok(TransactionLocation {
+            block_hash: reader.read()?,
+            index: reader.read()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransactionLocation;
+    use hash::H256;
+    use ser::{deserialize, serialize};
+
+    #[test]
+    fn test_transaction_location_serde() {
+        let location = TransactionLocation {
+            block_hash: H256::from(1),
+            index: 5,
+        };
+
+        assert_eq!(
+            deserialize::<_, TransactionLocation>(serialize(&location).as_ref()).unwrap(),
+            location
+        );
+    }
+}
@@ -19,7 +19,8 @@ impl Serializable for TransactionMeta {
     fn serialize(&self, stream: &mut Stream) {
         stream
             .append(&self.block_height)
-            .append(&Bytes::from(self.bits.to_bytes()));
+            .append(&Bytes::from(self.bits.to_bytes()))
+            .append(&self.is_valid);
     }
 }
 
@@ -75,6 +76,11 @@ impl TransactionMeta {
         self.block_height
     }
 
+    /// Returns false if transaction has been canonized as invalid (its inputs are not spent)
+    pub fn is_valid(&self) -> bool {
+        self.is_valid
+    }
+
     pub fn is_spent(&self, idx: usize) -> Option<bool> {
         self.bits.get(idx + 1)
     }
@@ -88,17 +94,31 @@ impl TransactionMeta {
 #[cfg(test)]
 mod tests {
     use super::TransactionMeta;
+    use ser::{deserialize, serialize};
 
     #[test]
     fn test_is_fully_spent() {
-        let t = TransactionMeta::new(0, 0);
+        let t = TransactionMeta::new(0, 0, true);
         assert!(t.is_fully_spent());
 
-        let mut t = TransactionMeta::new(0, 1);
+        let mut t = TransactionMeta::new(0, 1, true);
         assert!(!t.is_fully_spent());
         t.denote_used(0);
         assert!(t.is_fully_spent());
         t.denote_unused(0);
         assert!(!t.is_fully_spent());
     }
+
+    #[test]
+    fn test_serialization_keeps_validity() {
+        let mut t = TransactionMeta::new_coinbase(10, 2, false);
+        t.denote_used(1);
+
+        let t: TransactionMeta = deserialize(&*serialize(&t)).unwrap();
+        assert_eq!(t.height(), 10);
+        assert!(t.is_coinbase());
+        assert!(!t.is_valid());
+        assert_eq!(t.is_spent(0), Some(false));
+        assert_eq!(t.is_spent(1), Some(true));
+    }
 }
@@ -1,7 +1,7 @@
 use bytes::Bytes;
 use chain::{OutPoint, Transaction, TransactionOutput};
 use hash::H256;
-use TransactionMeta;
+use {TransactionErrorCode, TransactionLocation, TransactionMeta};
 
 /// Should be used to obtain all transactions from canon chain and forks.
 pub trait TransactionProvider {
@@ -36,3 +36,18 @@ pub trait TransactionMetaProvider: Send + Sync {
     /// Otherwise returns transaction meta object
     fn transaction_meta(&self, hash: &H256) -> Option<TransactionMeta>;
 }
+
+/// Transaction location provider stores which canonical block contains the transaction
+pub trait TransactionLocationProvider: Send + Sync {
+    /// Returns None if transaction with given hash is not in the canon chain
+    /// (or has been canonized before the index has been introduced)
+    /// Otherwise returns hash of the containing block and position of the transaction in this block
+    fn transaction_location(&self, hash: &H256) -> Option<TransactionLocation>;
+}
+
+/// Transaction error provider stores reasons why transactions have been marked invalid
+pub trait TransactionErrorProvider: Send + Sync {
+    /// Returns None if transaction with given hash has never been marked invalid
+    /// Otherwise returns the last verification error
+    fn transaction_error(&self, hash: &H256) -> Option<TransactionErrorCode>;
+}
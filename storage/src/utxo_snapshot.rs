@@ -0,0 +1,138 @@
+//! Snapshot of the unspent transaction outputs set
+
+use chain::Transaction;
+use hash::H256;
+use ser::{Deserializable, Error as ReaderError, Reader, Serializable, Stream};
+use std::io;
+use {Error, TransactionMeta, UtxoStats};
+
+/// Every snapshot starts with these bytes
+const SNAPSHOT_MAGIC: [u8; 8] = *b"pbtcutxo";
+/// Version of the snapshot format
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Snapshot summary, written in front of the header chain and unspent transactions.
+///
+/// Snapshot layout: summary, headers of blocks `0..=height` and then unspent transactions
+/// up to the end of the snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UtxoSnapshotInfo {
+    /// Hash of the block, at which the snapshot has been made
+    pub block_hash: H256,
+    /// Number of the block, at which the snapshot has been made
+    pub height: u32,
+    /// Statistics of the snapshotted set. Its hash commits to every unspent output of the snapshot.
+    pub utxo_stats: UtxoStats,
+}
+
+/// Transaction with at least one unspent output, along with its meta
+#[derive(Debug, Clone)]
+pub struct UtxoSnapshotTransaction {
+    pub meta: TransactionMeta,
+    pub transaction: Transaction,
+}
+
+pub trait UtxoSnapshotStore {
+    /// Writes snapshot of the UTXO set at the best block. Fails if any canonical block is pruned.
+    fn dump_utxo_snapshot(&self, output: &mut io::Write) -> Result<UtxoSnapshotInfo, Error>;
+
+    /// Initializes empty database from the snapshot. Headers of all snapshotted blocks are
+    /// imported, but their bodies are missing, as if the blocks have been pruned.
+    fn load_utxo_snapshot(&self, input: &mut io::Read) -> Result<UtxoSnapshotInfo, Error>;
+}
+
+impl UtxoSnapshotInfo {
+    /// Returns true if given set statistics match the snapshotted ones
+    pub fn matches(&self, utxo_stats: &UtxoStats) -> bool {
+        self.utxo_stats.outputs == utxo_stats.outputs
+            && self.utxo_stats.total_amount == utxo_stats.total_amount
+            && self.utxo_stats.bogosize == utxo_stats.bogosize
+            && self.utxo_stats.hash() == utxo_stats.hash()
+    }
+}
+
+impl Serializable for UtxoSnapshotInfo {
+    fn serialize(&self, stream: &mut Stream) {
+        stream
+            .append_slice(&SNAPSHOT_MAGIC)
+            .append(&SNAPSHOT_VERSION)
+            .append(&self.block_hash)
+            .append(&self.height)
+            .append(&self.utxo_stats);
+    }
+}
+
+impl Deserializable for UtxoSnapshotInfo {
+    fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError>
+    where
+        T: io::Read,
+    {
+        let mut magic = [0u8; 8];
+        reader.read_slice(&mut magic)?;
+        let version: u32 = reader.read()?;
+        if magic != SNAPSHOT_MAGIC || version != SNAPSHOT_VERSION {
+            return Err(ReaderError::MalformedData);
+        }
+
+        Ok(UtxoSnapshotInfo {
+            block_hash: reader.read()?,
+            height: reader.read()?,
+            utxo_stats: reader.read()?,
+        })
+    }
+}
+
+impl Serializable for UtxoSnapshotTransaction {
+    fn serialize(&self, stream: &mut Stream) {
+        stream.append(&self.meta).append(&self.transaction);
+    }
+}
+
+impl Deserializable for UtxoSnapshotTransaction {
+    fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError>
+    where
+        T: io::Read,
+    {
+        Ok(UtxoSnapshotTransaction {
+            meta: reader.read()?,
+            transaction: reader.read()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UtxoSnapshotInfo;
+    use chain::{OutPoint, TransactionOutput};
+    use ser::{deserialize, serialize};
+    use UtxoStats;
+
+    #[test]
+    fn test_utxo_snapshot_info_serialization() {
+        let mut utxo_stats = UtxoStats::default();
+        utxo_stats.add(
+            &OutPoint::null(),
+            5,
+            false,
+            &TransactionOutput {
+                value: 10,
+                script_pubkey: "51".into(),
+            },
+        );
+        let info = UtxoSnapshotInfo {
+            block_hash: 1.into(),
+            height: 5,
+            utxo_stats: utxo_stats.clone(),
+        };
+
+        let serialized = serialize(&info);
+        assert_eq!(&serialized[0..8], b"pbtcutxo");
+        assert_eq!(deserialize::<_, UtxoSnapshotInfo>(serialized.as_ref()).unwrap(), info);
+        assert!(info.matches(&utxo_stats));
+        assert!(!info.matches(&UtxoStats::default()));
+
+        let mut corrupted = serialized.take();
+        corrupted[8] = 2;
+        assert!(deserialize::<_, UtxoSnapshotInfo>(corrupted.as_slice()).is_err());
+    }
+}
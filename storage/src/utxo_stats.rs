@@ -0,0 +1,183 @@
+//! Statistics of the unspent transaction outputs set
+
+use bytes::Bytes;
+use chain::{OutPoint, TransactionOutput};
+use crypto::MuHash3072;
+use hash::H256;
+use ser::{Deserializable, Error as ReaderError, Reader, Serializable, Stream};
+use std::io;
+
+/// OP_RETURN opcode
+const OP_RETURN: u8 = 0x6a;
+/// Outputs with larger scripts could never be spent
+const MAX_SCRIPT_SIZE: usize = 10000;
+
+/// Statistics of the UTXO set, updated incrementally with every canonized block.
+/// Outputs of the genesis block and provably unspendable outputs are not part of the set.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UtxoStats {
+    /// Number of unspent outputs
+    pub outputs: u64,
+    /// Sum of unspent output values (in satoshis)
+    pub total_amount: u64,
+    /// Database-independent estimation of the set size
+    pub bogosize: u64,
+    /// Rolling hash of the set
+    pub muhash: MuHash3072,
+}
+
+impl UtxoStats {
+    /// Adds new unspent output to the set
+    pub fn add(
+        &mut self,
+        prevout: &OutPoint,
+        height: u32,
+        is_coinbase: bool,
+        output: &TransactionOutput,
+    ) {
+        if is_unspendable(output) {
+            return;
+        }
+
+        self.outputs += 1;
+        self.total_amount += output.value;
+        self.bogosize += bogosize(output);
+        self.muhash
+            .insert(&element(prevout, height, is_coinbase, output));
+    }
+
+    /// Removes spent output from the set
+    pub fn remove(
+        &mut self,
+        prevout: &OutPoint,
+        height: u32,
+        is_coinbase: bool,
+        output: &TransactionOutput,
+    ) {
+        if is_unspendable(output) {
+            return;
+        }
+
+        self.outputs -= 1;
+        self.total_amount -= output.value;
+        self.bogosize -= bogosize(output);
+        self.muhash
+            .remove(&element(prevout, height, is_coinbase, output));
+    }
+
+    /// Hash of the set (the same as bitcoind `gettxoutsetinfo muhash` computes)
+    pub fn hash(&self) -> H256 {
+        self.muhash.finalize()
+    }
+}
+
+/// Output is never added to the set if it is provably unspendable
+fn is_unspendable(output: &TransactionOutput) -> bool {
+    output.script_pubkey.first() == Some(&OP_RETURN)
+        || output.script_pubkey.len() > MAX_SCRIPT_SIZE
+}
+
+fn bogosize(output: &TransactionOutput) -> u64 {
+    // txid + vout + height and coinbase flag + amount + script length + script
+    (32 + 4 + 4 + 8 + 2 + output.script_pubkey.len()) as u64
+}
+
+/// Serialized set element: outpoint, (height << 1) | is_coinbase and output
+fn element(prevout: &OutPoint, height: u32, is_coinbase: bool, output: &TransactionOutput) -> Bytes {
+    let mut stream = Stream::new();
+    stream
+        .append(prevout)
+        .append(&((height << 1) | is_coinbase as u32))
+        .append(output);
+    stream.out()
+}
+
+impl Serializable for UtxoStats {
+    fn serialize(&self, stream: &mut Stream) {
+        stream
+            .append(&self.outputs)
+            .append(&self.total_amount)
+            .append(&self.bogosize)
+            .append(&Bytes::from(self.muhash.to_bytes()));
+    }
+}
+
+impl Deserializable for UtxoStats {
+    fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError>
+    where
+        T: io::Read,
+    {
+        let outputs = reader.read()?;
+        let total_amount = reader.read()?;
+        let bogosize = reader.read()?;
+        let muhash = reader.read::<Bytes>()?;
+        let muhash = MuHash3072::from_bytes(&muhash).ok_or(ReaderError::MalformedData)?;
+
+        Ok(UtxoStats {
+            outputs: outputs,
+            total_amount: total_amount,
+            bogosize: bogosize,
+            muhash: muhash,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UtxoStats;
+    use chain::{OutPoint, TransactionOutput};
+    use ser::{deserialize, serialize};
+
+    fn output(value: u64, script: &'static str) -> TransactionOutput {
+        TransactionOutput {
+            value: value,
+            script_pubkey: script.into(),
+        }
+    }
+
+    #[test]
+    fn test_utxo_stats_add_remove() {
+        let prevout = OutPoint {
+            hash: 1.into(),
+            index: 0,
+        };
+        let output = output(50, "76a914000000000000000000000000000000000000000088ac");
+
+        let mut stats = UtxoStats::default();
+        stats.add(&prevout, 1, true, &output);
+        assert_eq!(stats.outputs, 1);
+        assert_eq!(stats.total_amount, 50);
+        assert_eq!(stats.bogosize, 50 + 25);
+        assert!(stats.hash() != UtxoStats::default().hash());
+
+        // the same output at different height is a different element
+        let mut other = UtxoStats::default();
+        other.add(&prevout, 2, true, &output);
+        assert!(stats.hash() != other.hash());
+
+        stats.remove(&prevout, 1, true, &output);
+        assert_eq!(stats, UtxoStats::default());
+    }
+
+    #[test]
+    fn test_utxo_stats_skips_unspendable() {
+        let prevout = OutPoint {
+            hash: 1.into(),
+            index: 0,
+        };
+
+        let mut stats = UtxoStats::default();
+        stats.add(&prevout, 1, false, &output(10, "6a0102"));
+        assert_eq!(stats, UtxoStats::default());
+    }
+
+    #[test]
+    fn test_utxo_stats_serialization() {
+        let mut stats = UtxoStats::default();
+        stats.add(&OutPoint::null(), 5, false, &output(10, "51"));
+
+        let serialized = serialize(&stats);
+        assert_eq!(deserialize::<_, UtxoStats>(serialized.as_ref()).unwrap(), stats);
+        assert!(deserialize::<_, UtxoStats>(&serialized[..serialized.len() - 1]).is_err());
+    }
+}
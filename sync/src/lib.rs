@@ -21,6 +21,7 @@ extern crate serialization as ser;
 extern crate rand;
 extern crate network;
 extern crate keys;
+extern crate wallet;
 
 mod blocks_writer;
 mod inbound_connection;
@@ -40,19 +41,30 @@ mod types;
 mod utils;
 
 mod synchronization_wallet;
+mod synchronization_adversary;
+mod synchronization_coin_selection;
 
 pub use types::LocalNodeRef;
 pub use types::PeersRef;
+pub use types::MemoryPoolRef;
+pub use utils::MemoryPoolTransactionOutputProvider;
 
 use std::sync::Arc;
 use parking_lot::RwLock;
 use message::Services;
-use network::{Network, ConsensusParams};
+use network::{Network, ConsensusParams, TransactionValidity};
+use chain::IndexedBlock;
 use primitives::hash::H256;
 use verification::BackwardsCompatibleChainVerifier as ChainVerifier;
+use wallet::WalletDb;
 
 pub use synchronization_wallet::Wallet;
-pub use synchronization_wallet::WalletError;
+pub use synchronization_wallet::{WalletError, MAX_RESTORED_KEYS};
+pub use synchronization_wallet::AddressType;
+pub use synchronization_wallet::CoinState;
+pub use synchronization_coin_selection::{CoinSelection, CoinSelectionStrategy, DEFAULT_FEE_RATE, MAX_FEE_RATE};
+pub use synchronization_adversary::{Adversary, AdversaryAttempt, AdversaryError, AdversaryNetwork,
+	AdversaryStrategy, AttemptedTransaction, Attacker, create_adversary};
 
 /// Sync errors.
 #[derive(Debug)]
@@ -83,6 +95,14 @@ pub trait SyncListener: Send + 'static {
 	fn best_storage_block_inserted(&self, block_hash: &H256);
 }
 
+/// Canonical chain events listener
+pub trait BlockListener: Send + 'static {
+	/// Called when block is appended to the canonical chain
+	fn block_connected(&self, block: &IndexedBlock);
+	/// Called when block is removed from the canonical chain during reorganization
+	fn block_disconnected(&self, block: &IndexedBlock);
+}
+
 /// Create blocks writer.
 pub fn create_sync_blocks_writer(db: storage::SharedStore, consensus: ConsensusParams, verification_params: VerificationParameters) -> blocks_writer::BlocksWriter {
 	blocks_writer::BlocksWriter::new(db, consensus, verification_params)
@@ -95,8 +115,14 @@ pub fn create_sync_peers() -> PeersRef {
 	Arc::new(PeersImpl::default())
 }
 
-pub fn create_sync_wallet(local_node: LocalNodeRef) -> Wallet {
-	return Wallet::new(local_node)
+pub fn create_sync_wallet(local_node: LocalNodeRef, db: WalletDb) -> Result<Wallet, WalletError> {
+	Wallet::new(local_node, db)
+}
+
+/// Create adversary with its own wallet
+pub fn create_sync_attacker(local_node: LocalNodeRef, db: WalletDb, strategy: AdversaryStrategy) -> Result<Attacker, WalletError> {
+	let wallet = Wallet::new(local_node.clone(), db)?;
+	Ok(Attacker::new(local_node, wallet, strategy))
 }
 
 /// Creates local sync node for given `db`
@@ -137,9 +163,17 @@ pub fn create_local_sync_node(consensus: ConsensusParams, db: storage::SharedSto
 	let sync_client_core = SynchronizationClientCore::new(sync_client_config, sync_state.clone(), peers.clone(), sync_executor.clone(), sync_chain, chain_verifier.clone());
 	let verifier_sink = Arc::new(CoreVerificationSink::new(sync_client_core.clone()));
 	let verifier = AsyncVerifier::new(chain_verifier, db.clone(), memory_pool.clone(), verifier_sink, verification_params);
-	let sync_client = SynchronizationClient::new(sync_state.clone(), sync_client_core, verifier, true);
-
-	let mut shard_blocks_pool = ShardBlocksPool::new(sync_client.clone());
+	let is_allow_invalid = consensus.tx_validity == TransactionValidity::Lazy;
+	let sync_client = SynchronizationClient::new(sync_state.clone(), sync_client_core, verifier, is_allow_invalid);
+
+	let mut shard_blocks_pool = ShardBlocksPool::new(db.clone(), consensus.clone());
+	if is_allow_invalid {
+		// blocks, which were waiting for sanitization when the node has been stopped
+		let queued = shard_blocks_pool.queue_unsanitized_blocks();
+		if queued != 0 {
+			info!(target: "sync", "{} canon blocks are waiting for sanitization", queued);
+		}
+	}
 	let shard_blocks_pool = Arc::new(RwLock::new(shard_blocks_pool));
 	Arc::new(SyncNode::new(consensus, db, memory_pool, peers, sync_state, sync_executor, sync_client, sync_server, shard_blocks_pool))
 }
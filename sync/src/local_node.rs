@@ -1,12 +1,14 @@
-use chain::{IndexedBlock, IndexedTransaction, Transaction};
+use chain::{IndexedBlock, IndexedTransaction, OutPoint, Transaction, TransactionOutput};
 use futures::{finished, lazy};
 use message::types;
 use miner::BlockAssembler;
 use miner::BlockTemplate;
-use network::ConsensusParams;
-use parking_lot::{Condvar, Mutex};
+use network::{ConsensusParams, Network, TransactionValidity};
+use parking_lot::{Condvar, Mutex, RwLockWriteGuard};
 use primitives::hash::H256;
 use std::sync::Arc;
+use std::thread;
+use synchronization_adversary::AdversaryNetwork;
 use synchronization_client::Client;
 use synchronization_executor::{Task as SynchronizationTask, TaskExecutor};
 use synchronization_peers::{BlockAnnouncementType, TransactionAnnouncementType};
@@ -14,19 +16,22 @@ use synchronization_server::{Server, ServerTask};
 use synchronization_verifier::TransactionVerificationSink;
 use time;
 use types::{
-    ClientRef, ExecutorRef, MemoryPoolRef, PeerIndex, PeersRef, RequestId, ServerRef, StorageRef,
+    BlockListenerRef, ClientRef, ExecutorRef, MemoryPoolRef, PeerIndex, PeersRef, RequestId, ServerRef, StorageRef,
     SyncListenerRef, SynchronizationStateRef, ShardBlocksPoolRef,
 };
+use utils::MemoryPoolTransactionOutputProvider;
 use verification::median_timestamp_inclusive;
 
 use std::collections::{HashSet, HashMap};
 use keys::AddressHash;
-use synchronization_wallet::{Coin, CoinAccessor};
-use storage::{TransactionMeta, TransactionMetaProvider};
-use script::Script;
-use storage::BlockRef;
+use synchronization_wallet::{output_addresses, Coin, CoinAccessor};
 
-use miner::MemoryPoolOrderingStrategy;
+use miner::{FeeCalculator, MemoryPoolFeeCalculator};
+use ser::Serializable;
+use storage::{DuplexTransactionOutputProvider, TransactionOutputProvider, TransactionProvider};
+
+/// Max number of canon blocks, visited by sanitizer while the shard blocks pool is locked
+const SANITIZE_BATCH_SIZE: usize = 16;
 
 /// Local synchronization node
 pub struct LocalNode<T: TaskExecutor, U: Server, V: Client> {
@@ -46,7 +51,7 @@ pub struct LocalNode<T: TaskExecutor, U: Server, V: Client> {
     client: ClientRef<V>,
     /// Synchronization server
     server: ServerRef<U>,
-
+    /// Blocks, waiting for transactions sanitization
     shard_blocks_pool: ShardBlocksPoolRef,
 }
 
@@ -108,124 +113,59 @@ where
     pub fn get_spendable(&self, coins_acc: &mut HashSet<CoinAccessor>) -> HashSet<Coin> {
         let mut spendable_coins = HashSet::new();
         let mut coins_acc_to_remove: HashSet<CoinAccessor> = HashSet::new();
+        let outpoints: Vec<OutPoint> = coins_acc.iter().map(|coin_acc| coin_acc.get_new_outpoint()).collect();
+        let output_provider =
+            MemoryPoolTransactionOutputProvider::for_outpoints(self.storage.clone(), &self.memory_pool, &outpoints);
         for coin_acc in coins_acc.iter() {
             let outpoint = coin_acc.get_new_outpoint();
-            let transaction_meta = self.storage.transaction_meta(&outpoint.hash);
-            //println!("coin access {:?}", coin_acc.id);
-            //println!("transaction_meta {:?}", transaction_meta);
-
-            let output_provider = self.storage.as_transaction_output_provider();
             match output_provider.transaction_output(&outpoint, usize::max_value()) {
                 None => {println!("no transaction with id {:?}", outpoint.hash.clone());},
                 Some(tx_out) => {
-                    if output_provider.is_spent(&outpoint) {
+                    if output_provider.is_spent_in_memory_pool(&outpoint) {
+                        // the spending transaction could still be dropped => keep the coin until it is mined
+                        trace!(target: "sync", "Coin {:?} is spent by memory pool transaction", coin_acc.id);
+                    } else if output_provider.is_spent(&outpoint) {
                         println!("already spent coin {:?}", coin_acc.id);
                         coins_acc_to_remove.insert(coin_acc.clone());
                     } else {
-                        let script_pubkey: Script = tx_out.script_pubkey.clone().into();
-
-                        let recipient_addr = script_pubkey.extract_destinations().unwrap_or(vec![]);
-
-                        if !recipient_addr.is_empty() {
+                        if let Some((kind, recipient_addr)) = output_addresses(&tx_out.script_pubkey).into_iter().next() {
                             spendable_coins.insert(
-                                Coin::new( coin_acc.id.clone() , outpoint, recipient_addr[0].hash.clone(), tx_out.value)
+                                Coin::new( coin_acc.id.clone() , outpoint, kind, recipient_addr, tx_out.value)
                             );
                         }
                     }
                 }
             }
         }
-        coins_acc.difference(&mut coins_acc_to_remove);
+        coins_acc.retain(|coin_acc| !coins_acc_to_remove.contains(coin_acc));
         spendable_coins
     }
 
+    /// Sanitize blocks from the shard blocks pool in the background
     pub fn shard_sanitize_block(&self) {
-        let mut shard_blocks_pool = &mut *self.shard_blocks_pool.write();
-        shard_blocks_pool.sanitize_blocks(1000000);
-    }
-
-    pub fn log_mempool(&self) {
-        println!("**********mempool********");
-        let memory_pool = &*self.memory_pool.read();
-        let mut mempool_iter = memory_pool.iter(MemoryPoolOrderingStrategy::ByTimestamp);
-        while let Some(entry) = mempool_iter.next() {
-            println!("txid {:?}", entry.transaction.hash());
-            println!("{:#?}", entry.transaction);
-        }
-        println!("**********mempool End********");
-    }
-
-
-
-    pub fn log_blocks(&self) {
-        println!("**********PRINT blockchain********");
-        let block_provider = self.storage.as_block_provider();
-
-        //let origin_block_ref = BlockRef::Number(0);
-        //let origin_block = block_provider.block(block_ref);
-
-        let best_block = self.storage.best_block();
-        let num_blocks = best_block.number; //origin start at 0
-
-        println!("current block height \n {:?}", num_blocks);
-
-        for i in (0..num_blocks+1) {
-            let block_ref = BlockRef::Number(i);
-            let bp = self.storage.as_block_provider();
-            let block = bp.block(block_ref.clone()).unwrap();
-
-            let bhp = self.storage.as_block_header_provider();
-            let block_header = bhp.block_header(block_ref.clone()).unwrap();
-
-            //println!("block header hash {:?}\n", block_header.hash().reversed());
-            //println!("block header {:?}\n", block_header);
-
-            println!("block {} header hash {:?}", i, block.header().hash().reversed());
-
-            let transactions = block_provider.block_transactions(block_ref);
-            for transaction in transactions {
-                let indexed_tx: IndexedTransaction = transaction.clone().into();
-                if ( transaction.is_coinbase()) {
-                    println!("coinbase tx {:?}\n", indexed_tx); //no more
-
-                } else {
-                    println!("regular tx  {:?}\n", indexed_tx); //no more
+        let shard_blocks_pool = self.shard_blocks_pool.clone();
+        thread::Builder::new()
+            .name("Shard blocks sanitizer".to_string())
+            .spawn(move || {
+                let mut sanitized = 0;
+                loop {
+                    // pool is unlocked between batches, so that new blocks are not stalled
+                    let mut pool = shard_blocks_pool.write();
+                    sanitized += pool.sanitize_blocks(SANITIZE_BATCH_SIZE).len();
+                    if pool.is_sanitization_complete() {
+                        break;
+                    }
+                    RwLockWriteGuard::unlock_fair(pool);
                 }
-            }
-
-        }
-        println!("**********blockchain END********");
+                info!(target: "sync", "Sanitized {} blocks, {} blocks are waiting for sanitization",
+                    sanitized, shard_blocks_pool.read().len());
+            })
+            .expect("Error creating shard blocks sanitizer thread");
     }
 
-
-    pub fn print_transaction_output(&self) {
-        println!("**********PRINT transactions********");
-        let block_provider = self.storage.as_block_provider();
-
-        //let origin_block_ref = BlockRef::Number(0);
-        //let origin_block = block_provider.block(block_ref);
-
-        let best_block = self.storage.best_block();
-        let num_blocks = best_block.number; //origin start at 0
-
-        println!("current block height \n {:?}", num_blocks);
-
-        for i in (0..num_blocks+1) {
-            let block_ref = BlockRef::Number(i);
-            let transactions = block_provider.block_transactions(block_ref);
-            for transaction in transactions {
-                if ( transaction.is_coinbase()) {
-                    println!("txid: {:?}\n", transaction.hash()); //no more
-                    println!("{:?}\n\n\n", transaction); //no more
-
-                } else {
-                    println!("seen noncoinbase transaction");
-                }
-            }
-
-        }
-
-        println!("**********PRINT transactions END********");
+    /// Get validity of transaction from sanitized block
+    pub fn transaction_validity(&self, hash: &H256) -> Option<bool> {
+        self.shard_blocks_pool.read().transaction_validity(hash)
     }
 
     /// When new peer connects to the node
@@ -281,8 +221,9 @@ where
     /// When block is received
     pub fn on_block(&self, peer_index: PeerIndex, block: IndexedBlock) {
         trace!(target: "sync", "Got `block` message from peer#{}. Block hash: {}", peer_index, block.header.hash.to_reversed_str());
-        //let mut shard_blocks_pool = &mut *self.shard_blocks_pool.write();
-        //shard_blocks_pool.store_block(peer_index, block);
+        if self.consensus.tx_validity == TransactionValidity::Lazy {
+            self.shard_blocks_pool.write().store_block(peer_index, &block);
+        }
         self.client.on_block(peer_index, block);
     }
 
@@ -363,6 +304,42 @@ where
             .execute(ServerTask::GetBlockTxn(peer_index, message));
     }
 
+    /// When peer is requesting for compact filters of blocks
+    pub fn on_get_cfilters(&self, peer_index: PeerIndex, message: types::GetCFilters) {
+        if self.state.synchronizing() {
+            trace!(target: "sync", "Ignored `getcfilters` message from peer#{}", peer_index);
+            return;
+        }
+
+        trace!(target: "sync", "Got `getcfilters` message from peer#{}", peer_index);
+        self.server
+            .execute(ServerTask::GetCFilters(peer_index, message));
+    }
+
+    /// When peer is requesting for compact filters hashes of blocks
+    pub fn on_get_cfheaders(&self, peer_index: PeerIndex, message: types::GetCFHeaders) {
+        if self.state.synchronizing() {
+            trace!(target: "sync", "Ignored `getcfheaders` message from peer#{}", peer_index);
+            return;
+        }
+
+        trace!(target: "sync", "Got `getcfheaders` message from peer#{}", peer_index);
+        self.server
+            .execute(ServerTask::GetCFHeaders(peer_index, message));
+    }
+
+    /// When peer is requesting for compact filters headers checkpoints
+    pub fn on_get_cfcheckpt(&self, peer_index: PeerIndex, message: types::GetCFCheckpt) {
+        if self.state.synchronizing() {
+            trace!(target: "sync", "Ignored `getcfcheckpt` message from peer#{}", peer_index);
+            return;
+        }
+
+        trace!(target: "sync", "Got `getcfcheckpt` message from peer#{}", peer_index);
+        self.server
+            .execute(ServerTask::GetCFCheckpt(peer_index, message));
+    }
+
     /// When peer sets bloom filter for connection
     pub fn on_filterload(&self, peer_index: PeerIndex, message: types::FilterLoad) {
         trace!(target: "sync", "Got `filterload` message from peer#{}", peer_index);
@@ -490,6 +467,69 @@ where
     pub fn install_sync_listener(&self, listener: SyncListenerRef) {
         self.client.install_sync_listener(listener);
     }
+
+    /// Install canonical chain events listener
+    pub fn install_block_listener(&self, listener: BlockListenerRef) {
+        self.client.install_block_listener(listener);
+    }
+
+    /// Get storage
+    pub fn storage(&self) -> StorageRef {
+        self.storage.clone()
+    }
+
+    /// Get memory pool
+    pub fn memory_pool(&self) -> MemoryPoolRef {
+        self.memory_pool.clone()
+    }
+
+    /// Network node is running on
+    pub fn network(&self) -> Network {
+        self.consensus.network
+    }
+
+    /// Fee rate (satoshis per byte) of transaction, spending outputs of canonical chain and memory pool transactions
+    pub fn transaction_fee_rate(&self, transaction: &Transaction) -> u64 {
+        let memory_pool = &*self.memory_pool.read();
+        let fee = FeeCalculator(self.storage.as_transaction_output_provider()).calculate(memory_pool, transaction);
+        fee / transaction.serialized_size() as u64
+    }
+
+    /// Output of canonical chain or memory pool transaction
+    pub fn transaction_output(&self, outpoint: &OutPoint) -> Option<TransactionOutput> {
+        let memory_pool = &*self.memory_pool.read();
+        let output_provider =
+            DuplexTransactionOutputProvider::new(self.storage.as_transaction_output_provider(), memory_pool);
+        output_provider.transaction_output(outpoint, usize::max_value())
+    }
+
+    /// Canonical chain or memory pool transaction
+    pub fn transaction(&self, hash: &H256) -> Option<Transaction> {
+        self.storage
+            .as_transaction_provider()
+            .transaction(hash)
+            .or_else(|| self.memory_pool.read().transaction(hash))
+    }
+}
+
+impl<T, U, V> AdversaryNetwork for LocalNode<T, U, V>
+where
+    T: TaskExecutor,
+    U: Server,
+    V: Client,
+{
+    fn connected_peers(&self) -> Vec<PeerIndex> {
+        self.peers.enumerate()
+    }
+
+    fn send_transaction(&self, peer_index: PeerIndex, transaction: IndexedTransaction) {
+        self.executor
+            .execute(SynchronizationTask::Transaction(peer_index, transaction));
+    }
+
+    fn submit_transaction(&self, transaction: Transaction) -> Result<H256, String> {
+        self.accept_transaction(transaction)
+    }
 }
 
 impl TransactionAcceptSink {
@@ -549,6 +589,7 @@ pub mod tests {
     use synchronization_peers::PeersImpl;
     use synchronization_server::tests::DummyServer;
     use synchronization_server::ServerTask;
+    use synchronization_shard_block_pool::ShardBlocksPool;
     use synchronization_verifier::tests::DummyVerifier;
     use types::SynchronizationStateRef;
     use utils::SynchronizationState;
@@ -612,7 +653,11 @@ pub mod tests {
             None => DummyVerifier::default(),
         };
         verifier.set_sink(Arc::new(CoreVerificationSink::new(client_core.clone())));
-        let client = SynchronizationClient::new(sync_state.clone(), client_core, verifier);
+        let client = SynchronizationClient::new(sync_state.clone(), client_core, verifier, false);
+        let shard_blocks_pool = Arc::new(RwLock::new(ShardBlocksPool::new(
+            storage.clone(),
+            ConsensusParams::new(Network::Unitest, ConsensusFork::BitcoinCore),
+        )));
         let local_node = LocalNode::new(
             ConsensusParams::new(Network::Mainnet, ConsensusFork::BitcoinCore),
             storage,
@@ -622,6 +667,7 @@ pub mod tests {
             executor.clone(),
             client,
             server.clone(),
+            shard_blocks_pool,
         );
         (executor, server, local_node)
     }
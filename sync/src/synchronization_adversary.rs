@@ -0,0 +1,594 @@
+use chain::{IndexedTransaction, OutPoint, Transaction, TransactionOutput};
+use keys::{AddressHash, Type};
+use primitives::hash::H256;
+use script::Builder as ScriptBuilder;
+use std::collections::VecDeque;
+use std::str::FromStr;
+use synchronization_coin_selection::OutputSizes;
+use synchronization_wallet::{Coin, Wallet, WalletError};
+use types::{LocalNodeRef, PeerIndex};
+
+/// Max number of attack attempts, kept in the adversary log
+const MAX_LOG_SIZE: usize = 1000;
+
+/// Adversary strategy
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AdversaryStrategy {
+    /// Payment and conflicting refund to self are sent to two different peers at the same time
+    DoubleSpendRace,
+    /// Every connected peer receives its own version of the same payment
+    ConflictingFanOut,
+    /// Payment is immediately followed by transaction, spending its unconfirmed change
+    UnconfirmedChangeSpend,
+}
+
+/// Adversary error
+#[derive(Debug, PartialEq)]
+pub enum AdversaryError {
+    /// Wallet has failed to fund or sign transactions
+    Wallet(WalletError),
+    /// Strategy requires more connected peers
+    NotEnoughPeers { required: usize, connected: usize },
+}
+
+/// Network, as it is seen by the adversary
+pub trait AdversaryNetwork {
+    /// Peers, which we are currently connected to
+    fn connected_peers(&self) -> Vec<PeerIndex>;
+    /// Send transaction directly to the peer, bypassing local memory pool
+    fn send_transaction(&self, peer_index: PeerIndex, transaction: IndexedTransaction);
+    /// Verify transaction, insert it into local memory pool and relay to all peers
+    fn submit_transaction(&self, transaction: Transaction) -> Result<H256, String>;
+}
+
+/// Adversary, trying to pay with coins, which are spent elsewhere
+pub trait Adversary: Send {
+    /// Strategy of this adversary
+    fn strategy(&self) -> AdversaryStrategy;
+    /// Try to pay `value` to `recipient`, using wallet coins.
+    fn attack(
+        &self,
+        wallet: &mut Wallet,
+        network: &AdversaryNetwork,
+        recipient: &AddressHash,
+        value: u64,
+    ) -> Result<Vec<AttemptedTransaction>, AdversaryError>;
+}
+
+/// Transaction, created by the adversary
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttemptedTransaction {
+    /// Transaction hash
+    pub hash: H256,
+    /// Outputs, spent by the transaction
+    pub spends: Vec<OutPoint>,
+    /// Peers, which have received transaction directly. Empty if transaction has been submitted to the local node
+    pub peers: Vec<PeerIndex>,
+    /// Local node verification result, if transaction has been submitted to the local node
+    pub submit_result: Option<Result<(), String>>,
+}
+
+/// Single attack attempt
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdversaryAttempt {
+    /// Strategy, used for the attack
+    pub strategy: AdversaryStrategy,
+    /// Recipient of the payment
+    pub recipient: AddressHash,
+    /// Value of the payment
+    pub value: u64,
+    /// Transactions, created during the attack
+    pub transactions: Vec<AttemptedTransaction>,
+}
+
+/// Payment and conflicting refund, raced between two peers
+pub struct DoubleSpendRace;
+
+/// Conflicting versions of the payment, fanned out to all peers
+pub struct ConflictingFanOut;
+
+/// Payment and transaction, spending its unconfirmed change
+pub struct UnconfirmedChangeSpend;
+
+/// Adversary with its own wallet
+pub struct Attacker {
+    /// Local node, used to relay transactions
+    local_node: LocalNodeRef,
+    /// Wallet, which funds attacks
+    wallet: Wallet,
+    /// Selected adversary
+    adversary: Box<Adversary>,
+    /// Most recent attack attempts
+    log: VecDeque<AdversaryAttempt>,
+}
+
+impl AdversaryStrategy {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            AdversaryStrategy::DoubleSpendRace => "doublespendrace",
+            AdversaryStrategy::ConflictingFanOut => "conflictingfanout",
+            AdversaryStrategy::UnconfirmedChangeSpend => "unconfirmedchangespend",
+        }
+    }
+}
+
+impl FromStr for AdversaryStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "doublespendrace" => Ok(AdversaryStrategy::DoubleSpendRace),
+            "conflictingfanout" => Ok(AdversaryStrategy::ConflictingFanOut),
+            "unconfirmedchangespend" => Ok(AdversaryStrategy::UnconfirmedChangeSpend),
+            strategy => Err(format!("Unknown adversary strategy: {}", strategy)),
+        }
+    }
+}
+
+impl From<WalletError> for AdversaryError {
+    fn from(err: WalletError) -> Self {
+        AdversaryError::Wallet(err)
+    }
+}
+
+/// Create adversary, implementing given strategy
+pub fn create_adversary(strategy: AdversaryStrategy) -> Box<Adversary> {
+    match strategy {
+        AdversaryStrategy::DoubleSpendRace => Box::new(DoubleSpendRace),
+        AdversaryStrategy::ConflictingFanOut => Box::new(ConflictingFanOut),
+        AdversaryStrategy::UnconfirmedChangeSpend => Box::new(UnconfirmedChangeSpend),
+    }
+}
+
+impl Adversary for DoubleSpendRace {
+    fn strategy(&self) -> AdversaryStrategy {
+        AdversaryStrategy::DoubleSpendRace
+    }
+
+    fn attack(
+        &self,
+        wallet: &mut Wallet,
+        network: &AdversaryNetwork,
+        recipient: &AddressHash,
+        value: u64,
+    ) -> Result<Vec<AttemptedTransaction>, AdversaryError> {
+        let peers = required_peers(network, 2)?;
+        // adversarial transactions pay no fee
+        let coins = wallet.select_coins(value, 0, &OutputSizes::p2pkh())?.coins;
+        let total = coins_value(&coins);
+        let change_address = wallet.get_addresshash()?;
+
+        let mut payment_outputs = vec![pay_to(recipient, value)];
+        if total > value {
+            payment_outputs.push(pay_to(&change_address, total - value));
+        }
+        let payment = wallet.sign_transaction(&coins, payment_outputs)?;
+        let refund = wallet.sign_transaction(&coins, vec![pay_to(&change_address, total)])?;
+        wallet.spend_coins(&coins)?;
+
+        // we do not know which transaction wins the race => watch both
+        if total > value {
+            wallet.add_coin_candidate(OutPoint { hash: payment.hash(), index: 1 }, "race change".into())?;
+        }
+        wallet.add_coin_candidate(OutPoint { hash: refund.hash(), index: 0 }, "race refund".into())?;
+
+        Ok(vec![
+            send_to_peers(network, payment, &peers[0..1]),
+            send_to_peers(network, refund, &peers[1..2]),
+        ])
+    }
+}
+
+impl Adversary for ConflictingFanOut {
+    fn strategy(&self) -> AdversaryStrategy {
+        AdversaryStrategy::ConflictingFanOut
+    }
+
+    fn attack(
+        &self,
+        wallet: &mut Wallet,
+        network: &AdversaryNetwork,
+        recipient: &AddressHash,
+        value: u64,
+    ) -> Result<Vec<AttemptedTransaction>, AdversaryError> {
+        let peers = required_peers(network, 2)?;
+        // every version pays one satoshi less than the previous one => all versions have different hashes
+        if value < peers.len() as u64 {
+            return Err(WalletError::InsufficientMoney.into());
+        }
+
+        let coins = wallet.select_coins(value, 0, &OutputSizes::p2pkh())?.coins;
+        let total = coins_value(&coins);
+        let change_address = wallet.get_addresshash()?;
+
+        let mut attempted = Vec::with_capacity(peers.len());
+        for (index, peer_index) in peers.iter().enumerate() {
+            let mut outputs = vec![pay_to(recipient, value - index as u64)];
+            if total > value {
+                outputs.push(pay_to(&change_address, total - value));
+            }
+
+            let version = wallet.sign_transaction(&coins, outputs)?;
+            if total > value {
+                wallet.add_coin_candidate(OutPoint { hash: version.hash(), index: 1 }, "fan out change".into())?;
+            }
+            attempted.push(send_to_peers(network, version, &[*peer_index]));
+        }
+        wallet.spend_coins(&coins)?;
+
+        Ok(attempted)
+    }
+}
+
+impl Adversary for UnconfirmedChangeSpend {
+    fn strategy(&self) -> AdversaryStrategy {
+        AdversaryStrategy::UnconfirmedChangeSpend
+    }
+
+    fn attack(
+        &self,
+        wallet: &mut Wallet,
+        network: &AdversaryNetwork,
+        recipient: &AddressHash,
+        value: u64,
+    ) -> Result<Vec<AttemptedTransaction>, AdversaryError> {
+        // payment must have non-empty change
+        let value_with_change = value.checked_add(1).ok_or(WalletError::InsufficientMoney)?;
+        let coins = wallet.select_coins(value_with_change, 0, &OutputSizes::p2pkh())?.coins;
+        let total = coins_value(&coins);
+        let change_address = wallet.get_addresshash()?;
+
+        let payment = wallet.sign_transaction(
+            &coins,
+            vec![pay_to(recipient, value), pay_to(&change_address, total - value)],
+        )?;
+        let change = Coin::new(
+            "unconfirmed change".into(),
+            OutPoint { hash: payment.hash(), index: 1 },
+            Type::P2PKH,
+            change_address,
+            total - value,
+        );
+        let change_spend = wallet.sign_transaction(
+            &[change],
+            vec![pay_to(recipient, total - value)],
+        )?;
+        wallet.spend_coins(&coins)?;
+
+        Ok(vec![
+            submit(network, payment),
+            submit(network, change_spend),
+        ])
+    }
+}
+
+impl Attacker {
+    pub fn new(local_node: LocalNodeRef, wallet: Wallet, strategy: AdversaryStrategy) -> Self {
+        Attacker {
+            wallet: wallet,
+            local_node: local_node,
+            adversary: create_adversary(strategy),
+            log: VecDeque::new(),
+        }
+    }
+
+    /// Wallet, which funds attacks
+    pub fn wallet(&mut self) -> &mut Wallet {
+        &mut self.wallet
+    }
+
+    /// Strategy of the selected adversary
+    pub fn strategy(&self) -> AdversaryStrategy {
+        self.adversary.strategy()
+    }
+
+    /// Select adversary, implementing given strategy
+    pub fn set_strategy(&mut self, strategy: AdversaryStrategy) {
+        info!(target: "adversary", "strategy={}", strategy.name());
+        self.adversary = create_adversary(strategy);
+    }
+
+    /// Attack the network, using selected strategy
+    pub fn attack(
+        &mut self,
+        recipient: AddressHash,
+        value: u64,
+    ) -> Result<AdversaryAttempt, AdversaryError> {
+        self.wallet.update_wallet()?;
+
+        let strategy = self.adversary.strategy();
+        let transactions = match self.adversary.attack(&mut self.wallet, &*self.local_node, &recipient, value) {
+            Ok(transactions) => transactions,
+            Err(err) => {
+                info!(target: "adversary", "strategy={} recipient={} value={} error={:?}",
+                    strategy.name(), recipient, value, err);
+                return Err(err);
+            }
+        };
+
+        let attempt = AdversaryAttempt {
+            strategy: strategy,
+            recipient: recipient,
+            value: value,
+            transactions: transactions,
+        };
+        log_attempt(&attempt);
+
+        if self.log.len() == MAX_LOG_SIZE {
+            self.log.pop_front();
+        }
+        self.log.push_back(attempt.clone());
+        Ok(attempt)
+    }
+
+    /// Most recent attack attempts, oldest first
+    pub fn log(&self) -> Vec<AdversaryAttempt> {
+        self.log.iter().cloned().collect()
+    }
+}
+
+/// Write attack attempt to the log, one line per transaction
+fn log_attempt(attempt: &AdversaryAttempt) {
+    for transaction in &attempt.transactions {
+        let spends: Vec<String> = transaction
+            .spends
+            .iter()
+            .map(|outpoint| format!("{}:{}", outpoint.hash.to_reversed_str(), outpoint.index))
+            .collect();
+        let result = match transaction.submit_result {
+            None => "sent".to_owned(),
+            Some(Ok(())) => "accepted".to_owned(),
+            Some(Err(ref err)) => format!("rejected({})", err),
+        };
+        info!(target: "adversary", "strategy={} recipient={} value={} tx={} spends={} peers={:?} result={}",
+            attempt.strategy.name(), attempt.recipient, attempt.value, transaction.hash.to_reversed_str(),
+            spends.join(","), transaction.peers, result);
+    }
+}
+
+/// Get connected peers, if there are at least `required` of them
+fn required_peers(
+    network: &AdversaryNetwork,
+    required: usize,
+) -> Result<Vec<PeerIndex>, AdversaryError> {
+    let peers = network.connected_peers();
+    if peers.len() < required {
+        return Err(AdversaryError::NotEnoughPeers {
+            required: required,
+            connected: peers.len(),
+        });
+    }
+    Ok(peers)
+}
+
+fn coins_value(coins: &[Coin]) -> u64 {
+    coins.iter().map(Coin::get_value).sum()
+}
+
+fn pay_to(address: &AddressHash, value: u64) -> TransactionOutput {
+    TransactionOutput {
+        value: value,
+        script_pubkey: ScriptBuilder::build_p2pkh(address).to_bytes(),
+    }
+}
+
+fn spent_outputs(transaction: &Transaction) -> Vec<OutPoint> {
+    transaction
+        .inputs
+        .iter()
+        .map(|input| input.previous_output.clone())
+        .collect()
+}
+
+/// Send transaction directly to given peers
+fn send_to_peers(
+    network: &AdversaryNetwork,
+    transaction: Transaction,
+    peers: &[PeerIndex],
+) -> AttemptedTransaction {
+    let spends = spent_outputs(&transaction);
+    let transaction: IndexedTransaction = transaction.into();
+    for peer_index in peers {
+        network.send_transaction(*peer_index, transaction.clone());
+    }
+
+    AttemptedTransaction {
+        hash: transaction.hash,
+        spends: spends,
+        peers: peers.to_vec(),
+        submit_result: None,
+    }
+}
+
+/// Submit transaction to the local node
+fn submit(network: &AdversaryNetwork, transaction: Transaction) -> AttemptedTransaction {
+    let hash = transaction.hash();
+    let spends = spent_outputs(&transaction);
+    let submit_result = network.submit_transaction(transaction).map(|_| ());
+
+    AttemptedTransaction {
+        hash: hash,
+        spends: spends,
+        peers: Vec::new(),
+        submit_result: Some(submit_result),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Adversary, AdversaryError, AdversaryNetwork, AdversaryStrategy, ConflictingFanOut,
+        DoubleSpendRace, UnconfirmedChangeSpend,
+    };
+    use chain::{IndexedTransaction, OutPoint, Transaction};
+    use keys::AddressHash;
+    use parking_lot::Mutex;
+    use primitives::hash::H256;
+    use synchronization_wallet::tests::funded_wallet;
+    use synchronization_wallet::WalletError;
+    use types::PeerIndex;
+
+    struct DummyNetwork {
+        peers: Vec<PeerIndex>,
+        sent: Mutex<Vec<(PeerIndex, IndexedTransaction)>>,
+        submitted: Mutex<Vec<Transaction>>,
+    }
+
+    impl DummyNetwork {
+        fn with_peers(peers: Vec<PeerIndex>) -> Self {
+            DummyNetwork {
+                peers: peers,
+                sent: Mutex::new(Vec::new()),
+                submitted: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl AdversaryNetwork for DummyNetwork {
+        fn connected_peers(&self) -> Vec<PeerIndex> {
+            self.peers.clone()
+        }
+
+        fn send_transaction(&self, peer_index: PeerIndex, transaction: IndexedTransaction) {
+            self.sent.lock().push((peer_index, transaction));
+        }
+
+        fn submit_transaction(&self, transaction: Transaction) -> Result<H256, String> {
+            let hash = transaction.hash();
+            self.submitted.lock().push(transaction);
+            Ok(hash)
+        }
+    }
+
+    #[test]
+    fn adversary_strategy_from_str() {
+        assert_eq!(
+            "doublespendrace".parse::<AdversaryStrategy>(),
+            Ok(AdversaryStrategy::DoubleSpendRace)
+        );
+        assert_eq!(
+            "conflictingfanout".parse::<AdversaryStrategy>(),
+            Ok(AdversaryStrategy::ConflictingFanOut)
+        );
+        assert_eq!(
+            "unconfirmedchangespend".parse::<AdversaryStrategy>(),
+            Ok(AdversaryStrategy::UnconfirmedChangeSpend)
+        );
+        assert!("covetous".parse::<AdversaryStrategy>().is_err());
+    }
+
+    #[test]
+    fn double_spend_race_sends_conflicting_transactions_to_different_peers() {
+        let (mut wallet, coin, _dir) = funded_wallet(100);
+        let network = DummyNetwork::with_peers(vec![1, 2, 3]);
+
+        let attempted = DoubleSpendRace
+            .attack(&mut wallet, &network, &AddressHash::default(), 30)
+            .unwrap();
+        assert_eq!(attempted.len(), 2);
+        assert_eq!(attempted[0].peers, vec![1]);
+        assert_eq!(attempted[1].peers, vec![2]);
+        assert_eq!(attempted[0].spends, vec![coin.clone()]);
+        assert_eq!(attempted[1].spends, vec![coin]);
+        assert!(attempted[0].hash != attempted[1].hash);
+
+        let sent = network.sent.lock();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].1.raw.outputs[0].value, 30);
+        assert_eq!(sent[0].1.raw.outputs[1].value, 70);
+        assert_eq!(sent[1].1.raw.outputs[0].value, 100);
+        assert_eq!(wallet.get_balance(), 0);
+    }
+
+    #[test]
+    fn double_spend_race_without_change_tracks_refund_only() {
+        let (mut wallet, _, _dir) = funded_wallet(100);
+        let network = DummyNetwork::with_peers(vec![1, 2]);
+
+        let attempted = DoubleSpendRace
+            .attack(&mut wallet, &network, &AddressHash::default(), 100)
+            .unwrap();
+        assert_eq!(network.sent.lock()[0].1.raw.outputs.len(), 1);
+
+        let tracked: Vec<OutPoint> = wallet.list_coins().into_iter().map(|coin| coin.outpoint).collect();
+        assert!(!tracked.contains(&OutPoint {
+            hash: attempted[0].hash.clone(),
+            index: 1,
+        }));
+        assert!(tracked.contains(&OutPoint {
+            hash: attempted[1].hash.clone(),
+            index: 0,
+        }));
+    }
+
+    #[test]
+    fn double_spend_race_requires_two_peers() {
+        let (mut wallet, _, _dir) = funded_wallet(100);
+        let network = DummyNetwork::with_peers(vec![1]);
+
+        assert_eq!(
+            DoubleSpendRace.attack(&mut wallet, &network, &AddressHash::default(), 30),
+            Err(AdversaryError::NotEnoughPeers {
+                required: 2,
+                connected: 1,
+            })
+        );
+        assert_eq!(wallet.get_balance(), 100);
+    }
+
+    #[test]
+    fn conflicting_fan_out_sends_own_version_to_every_peer() {
+        let (mut wallet, coin, _dir) = funded_wallet(100);
+        let network = DummyNetwork::with_peers(vec![1, 2, 3]);
+
+        let attempted = ConflictingFanOut
+            .attack(&mut wallet, &network, &AddressHash::default(), 30)
+            .unwrap();
+        assert_eq!(attempted.len(), 3);
+        for (index, transaction) in attempted.iter().enumerate() {
+            assert_eq!(transaction.peers, vec![index + 1]);
+            assert_eq!(transaction.spends, vec![coin.clone()]);
+        }
+        assert!(attempted[0].hash != attempted[1].hash);
+        assert!(attempted[1].hash != attempted[2].hash);
+        assert!(attempted[0].hash != attempted[2].hash);
+        assert_eq!(network.sent.lock().len(), 3);
+    }
+
+    #[test]
+    fn unconfirmed_change_spend_spends_change_of_payment() {
+        let (mut wallet, coin, _dir) = funded_wallet(100);
+        let network = DummyNetwork::with_peers(vec![]);
+
+        let attempted = UnconfirmedChangeSpend
+            .attack(&mut wallet, &network, &AddressHash::default(), 30)
+            .unwrap();
+        assert_eq!(attempted.len(), 2);
+        assert_eq!(attempted[0].spends, vec![coin]);
+        assert_eq!(
+            attempted[1].spends,
+            vec![OutPoint {
+                hash: attempted[0].hash.clone(),
+                index: 1,
+            }]
+        );
+        assert_eq!(attempted[0].submit_result, Some(Ok(())));
+        assert_eq!(attempted[1].submit_result, Some(Ok(())));
+
+        let submitted = network.submitted.lock();
+        assert_eq!(submitted.len(), 2);
+        assert_eq!(submitted[1].outputs[0].value, 70);
+        assert!(network.sent.lock().is_empty());
+    }
+
+    #[test]
+    fn unconfirmed_change_spend_requires_change() {
+        let (mut wallet, _, _dir) = funded_wallet(100);
+        let network = DummyNetwork::with_peers(vec![]);
+
+        assert_eq!(
+            UnconfirmedChangeSpend.attack(&mut wallet, &network, &AddressHash::default(), 100),
+            Err(AdversaryError::Wallet(WalletError::InsufficientMoney))
+        );
+    }
+}
@@ -4,23 +4,20 @@ use chain::{
 };
 use linked_hash_map::LinkedHashMap;
 use miner::{FeeCalculator, MemoryPoolInformation, MemoryPoolOrderingStrategy};
-use network::ConsensusParams;
+use network::{ConsensusParams, TransactionValidity};
 use primitives::bytes::Bytes;
 use primitives::hash::H256;
 use std::collections::{HashSet, VecDeque};
 use std::fmt;
 use storage;
-use types::{BlockHeight, MemoryPoolRef, StorageRef};
+use types::{BlockHeight, BlockListenerRef, MemoryPoolRef, StorageRef};
 use utils::{BestHeadersChain, BestHeadersChainInformation, HashPosition, HashQueueChain};
 
-use verification::{TransactionAcceptor, ChainAcceptor, VerificationLevel};
-use verification::{Deployments, BlockDeployments};
 use storage::{DuplexTransactionOutputProvider, Store};
-
-use verification::median_timestamp_inclusive;
-use verification::CanonBlock;
-use verification::TransactionError;
-
+use verification::{
+    median_timestamp_inclusive, BlockDeployments, CanonBlock, Deployments, TransactionAcceptor,
+    TransactionError, VerificationLevel,
+};
 
 /// Index of 'verifying' queue
 const VERIFYING_QUEUE: usize = 0;
@@ -137,8 +134,12 @@ pub struct Chain {
     /// Is SegWit is possible on this chain? SegWit inventory types are used when block/tx-es are
     /// requested and this flag is true.
     is_segwit_possible: bool,
-
+    /// Consensus parameters
     consensus: ConsensusParams,
+    /// Deployments cache
+    deployments: Deployments,
+    /// Canonical chain events listeners
+    block_listeners: Vec<BlockListenerRef>,
 }
 
 impl BlockState {
@@ -186,10 +187,17 @@ impl Chain {
             memory_pool: memory_pool,
             dead_end_blocks: HashSet::new(),
             is_segwit_possible,
-            consensus: consensus.clone(),
+            consensus: consensus,
+            deployments: Deployments::new(),
+            block_listeners: Vec::new(),
         }
     }
 
+    /// Install canonical chain events listener
+    pub fn install_block_listener(&mut self, listener: BlockListenerRef) {
+        self.block_listeners.push(listener);
+    }
+
     /// Get information on current blockchain state
     pub fn information(&self) -> Information {
         Information {
@@ -394,59 +402,72 @@ impl Chain {
         self.dead_end_blocks.insert(hash.clone());
     }
 
-    fn check_transactions(&mut self, block_copy: IndexedBlock,) -> Vec<bool> {
-        let canon_block = CanonBlock::new(&block_copy);
-        let mut tx_flags: Vec<bool> = vec![];
-        let output_store = DuplexTransactionOutputProvider::new(
-            self.storage.as_transaction_output_provider(),
-            canon_block.raw(),
-        );
-        let headers = self.storage.as_block_header_provider();
-        let height = self.block_number(block_copy.hash()).unwrap();
+    /// Canonize block, marking its invalid transactions and remembering why they are invalid
+    fn canonize_with_invalid(
+        &self,
+        store: &Store,
+        block: &IndexedBlock,
+        height: BlockHeight,
+    ) -> Result<(), storage::Error> {
+        let verdicts = self.verify_transactions(store, block, height);
+        let tx_errors: Vec<_> = block
+            .transactions
+            .iter()
+            .zip(verdicts.iter())
+            .map(|(tx, verdict)| {
+                verdict.as_ref().err().map(|err| {
+                    trace!(target: "sync", "Transaction {} from block {} is invalid: {:?}",
+                        tx.hash.to_reversed_str(), block.hash().to_reversed_str(), err);
+                    err.code()
+                })
+            })
+            .collect();
+
+        store.canonize_with_invalid(block.hash(), &tx_errors)
+    }
 
+    /// Verify every transaction of the block, which is going to be canonized at given height
+    fn verify_transactions(
+        &self,
+        store: &Store,
+        block: &IndexedBlock,
+        height: BlockHeight,
+    ) -> Vec<Result<(), TransactionError>> {
+        let canon_block = CanonBlock::new(block);
+        let output_store =
+            DuplexTransactionOutputProvider::new(store.as_transaction_output_provider(), block);
         let median_time_past = median_timestamp_inclusive(
-            block_copy.header.raw.previous_header_hash.clone(),
-            self.storage.as_block_header_provider(),
+            block.header.raw.previous_header_hash.clone(),
+            store.as_block_header_provider(),
         );
-
-        let deployment = Deployments::new();
         let deployments = BlockDeployments::new(
-            &deployment,
+            &self.deployments,
             height,
-            headers,
+            store.as_block_header_provider(),
             &self.consensus,
         );
 
-        let transactions: Vec<TransactionAcceptor> =  canon_block
-                .transactions()
-                .into_iter()
-                .enumerate()
-                .map(|(tx_index, tx)| {
-                    TransactionAcceptor::new(
-                        self.storage.as_transaction_meta_provider(),
-                        output_store,
-                        &self.consensus,
-                        tx,
-                        VerificationLevel::Full,
-                        block_copy.hash(),
-                        height,
-                        block_copy.header.raw.time,
-                        median_time_past,
-                        tx_index,
-                        &deployments,
-                    )
-                })
-                .collect();
-        for tx in transactions.iter() {
-            match tx.check() {
-                Ok(()) => tx_flags.push(true),
-                Err(err) => {
-                    println!("err {:?}", err);
-                    tx_flags.push(false);
-                },
-            };
-        }
-        tx_flags
+        canon_block
+            .transactions()
+            .into_iter()
+            .enumerate()
+            .map(|(tx_index, tx)| {
+                TransactionAcceptor::new(
+                    store.as_transaction_meta_provider(),
+                    output_store,
+                    &self.consensus,
+                    tx,
+                    VerificationLevel::Full,
+                    block.hash(),
+                    height,
+                    block.header.raw.time,
+                    median_time_past,
+                    tx_index,
+                    &deployments,
+                )
+                .check()
+            })
+            .collect()
     }
 
     /// Insert new best block to storage
@@ -459,12 +480,6 @@ impl Chain {
             self.storage.block_hash(self.storage.best_block().number)
         );
 
-        // start
-        let tx_flags = self.check_transactions(block.clone());
-        println!("txs {:#?}", block.transactions);
-        println!("tx_flags {:?}", tx_flags);
-        //end
-
         let block_origin = self.storage.block_origin(&block.header)?;
         trace!(target: "sync", "insert_best_block {:?} origin: {:?}", block.hash().reversed(), block_origin);
         match block_origin {
@@ -473,17 +488,15 @@ impl Chain {
                 unreachable!();
             }
             // case 1: block has been added to the main branch
-            storage::BlockOrigin::CanonChain { .. } => {
+            storage::BlockOrigin::CanonChain { block_number } => {
                 self.storage.insert(block.clone())?;
-
-                self.storage.canonize_with_invalid(block.hash(), &tx_flags)?;
-                for tx in block.transactions.iter() {
-                    let meta_provider = self.storage.as_transaction_meta_provider();
-                    let transaction_meta = meta_provider.transaction_meta(&tx.hash);
-                    println!("transaction_meta {:#?}", transaction_meta);
+                match self.consensus.tx_validity {
+                    TransactionValidity::Strict => self.storage.canonize(block.hash())?,
+                    TransactionValidity::Lazy => {
+                        self.canonize_with_invalid(self.storage.as_store(), &block, block_number)?
+                    }
                 }
 
-
                 // remember new best block hash
                 self.best_storage_block = self.storage.as_store().best_block();
 
@@ -495,6 +508,10 @@ impl Chain {
                 // double check
                 assert_eq!(self.best_storage_block.hash, block.hash().clone());
 
+                for listener in &self.block_listeners {
+                    listener.block_connected(&block);
+                }
+
                 // all transactions from this block were accepted
                 // => delete accepted transactions from verification queue and from the memory pool
                 // + also remove transactions which spent outputs which have been spent by transactions from the block
@@ -517,7 +534,12 @@ impl Chain {
             storage::BlockOrigin::SideChainBecomingCanonChain(origin) => {
                 let fork = self.storage.fork(origin.clone())?;
                 fork.store().insert(block.clone())?;
-                fork.store().canonize(block.hash())?;
+                match self.consensus.tx_validity {
+                    TransactionValidity::Strict => fork.store().canonize(block.hash())?,
+                    TransactionValidity::Lazy => {
+                        self.canonize_with_invalid(fork.store(), &block, origin.block_number)?
+                    }
+                }
                 self.storage.switch_to_fork(fork)?;
 
                 // remember new best block hash
@@ -528,6 +550,8 @@ impl Chain {
                 self.headers_chain
                     .block_inserted_to_storage(block.hash(), &self.best_storage_block.hash);
 
+                self.notify_reorganization(&origin, &block);
+
                 // all transactions from this block were accepted
                 // + all transactions from previous blocks of this fork were accepted
                 // => delete accepted transactions from verification queue and from the memory pool
@@ -616,6 +640,34 @@ impl Chain {
         }
     }
 
+    /// Notify listeners about blocks, disconnected (newest first) and connected (oldest first) during reorganization
+    fn notify_reorganization(&self, origin: &storage::SideChainOrigin, block: &IndexedBlock) {
+        if self.block_listeners.is_empty() {
+            return;
+        }
+
+        let disconnected = origin
+            .decanonized_route
+            .iter()
+            .rev()
+            .filter_map(|hash| self.storage.indexed_block(hash.clone().into()))
+            .collect::<Vec<_>>();
+        let connected = origin
+            .canonized_route
+            .iter()
+            .filter_map(|hash| self.storage.indexed_block(hash.clone().into()))
+            .collect::<Vec<_>>();
+        for listener in &self.block_listeners {
+            for disconnected_block in &disconnected {
+                listener.block_disconnected(disconnected_block);
+            }
+            for connected_block in &connected {
+                listener.block_connected(connected_block);
+            }
+            listener.block_connected(block);
+        }
+    }
+
     /// Forget in-memory block
     pub fn forget_block(&mut self, hash: &H256) -> HashPosition {
         self.headers_chain.remove(hash);
@@ -954,14 +1006,30 @@ mod tests {
     extern crate test_data;
 
     use super::{BlockInsertionResult, BlockState, Chain, TransactionState};
-    use chain::{IndexedBlockHeader, Transaction};
+    use chain::{IndexedBlock, IndexedBlockHeader, Transaction};
     use db::BlockChainDatabase;
     use miner::MemoryPool;
     use network::{ConsensusFork, ConsensusParams, Network};
-    use parking_lot::RwLock;
+    use parking_lot::{Mutex, RwLock};
     use primitives::hash::H256;
     use std::sync::Arc;
     use utils::HashPosition;
+    use BlockListener;
+
+    /// Remembers (is_connected, block_hash) of every canonical chain event
+    struct DummyBlockListener {
+        events: Arc<Mutex<Vec<(bool, H256)>>>,
+    }
+
+    impl BlockListener for DummyBlockListener {
+        fn block_connected(&self, block: &IndexedBlock) {
+            self.events.lock().push((true, block.hash().clone()));
+        }
+
+        fn block_disconnected(&self, block: &IndexedBlock) {
+            self.events.lock().push((false, block.hash().clone()));
+        }
+    }
 
     #[test]
     fn chain_empty() {
@@ -1449,6 +1517,59 @@ mod tests {
             .any(|ref tx| &tx.hash == &tx2_hash));
     }
 
+    #[test]
+    fn block_listener_is_notified_about_connected_and_disconnected_blocks() {
+        let b0 = test_data::block_builder().header().build().build();
+        let b1 = test_data::block_builder()
+            .header()
+            .nonce(1)
+            .parent(b0.hash())
+            .build()
+            .build();
+        let b2 = test_data::block_builder()
+            .header()
+            .nonce(2)
+            .parent(b0.hash())
+            .build()
+            .build();
+        let b3 = test_data::block_builder()
+            .header()
+            .parent(b2.hash())
+            .build()
+            .build();
+
+        let db = Arc::new(BlockChainDatabase::init_test_chain(vec![b0.into()]));
+        let mut chain = Chain::new(
+            db,
+            ConsensusParams::new(Network::Unitest, ConsensusFork::BitcoinCore),
+            Arc::new(RwLock::new(MemoryPool::new())),
+        );
+        let events = Arc::new(Mutex::new(Vec::new()));
+        chain.install_block_listener(Box::new(DummyBlockListener {
+            events: events.clone(),
+        }));
+
+        // canon chain
+        chain.insert_best_block(b1.clone().into()).expect("no error");
+        assert_eq!(*events.lock(), vec![(true, b1.hash())]);
+
+        // side chain
+        chain.insert_best_block(b2.clone().into()).expect("no error");
+        assert_eq!(events.lock().len(), 1);
+
+        // reorg
+        chain.insert_best_block(b3.clone().into()).expect("no error");
+        assert_eq!(
+            *events.lock(),
+            vec![
+                (true, b1.hash()),
+                (false, b1.hash()),
+                (true, b2.hash()),
+                (true, b3.hash()),
+            ]
+        );
+    }
+
     #[test]
     fn fork_chain_block_transaction_is_removed_from_on_block_insert() {
         let genesis = test_data::genesis();
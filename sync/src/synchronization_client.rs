@@ -5,7 +5,10 @@ use std::sync::Arc;
 use synchronization_client_core::{ClientCore, SynchronizationClientCore};
 use synchronization_executor::TaskExecutor;
 use synchronization_verifier::{TransactionVerificationSink, Verifier};
-use types::{ClientCoreRef, EmptyBoxFuture, PeerIndex, SyncListenerRef, SynchronizationStateRef};
+use types::{
+    BlockListenerRef, ClientCoreRef, EmptyBoxFuture, PeerIndex, SyncListenerRef,
+    SynchronizationStateRef,
+};
 
 #[cfg_attr(feature = "cargo-clippy", allow(doc_markdown))]
 ///! TODO: update with headers-first corrections
@@ -135,6 +138,7 @@ pub trait Client: Send + Sync + 'static {
         sink: Box<TransactionVerificationSink>,
     ) -> Result<(), String>;
     fn install_sync_listener(&self, listener: SyncListenerRef);
+    fn install_block_listener(&self, listener: BlockListenerRef);
 }
 
 /// Synchronization client facade
@@ -147,7 +151,7 @@ pub struct SynchronizationClient<T: TaskExecutor, U: Verifier> {
     core: ClientCoreRef<SynchronizationClientCore<T>>,
     /// Verifier
     verifier: U,
-
+    /// Accept transactions to the memory pool without verification (lazy transaction validity)
     is_allow_invalid: bool,
 }
 
@@ -204,7 +208,6 @@ where
         // ignored, orphaned => no verification should occur
         // on-time => this transaction + all dependent orphaned should be verified
         let transactions_to_verify = self.core.lock().on_transaction(peer_index, transaction);
-        println!("on_transaction {:?}", transactions_to_verify);
         if let Some(mut transactions_to_verify) = transactions_to_verify {
             // it is not actual height of block this transaction will be included to
             // => it possibly will be invalid if included in later blocks
@@ -213,16 +216,12 @@ where
             let next_block_height = self.shared_state.best_storage_block_height() + 1;
 
             while let Some(tx) = transactions_to_verify.pop_front() {
-                if(self.is_allow_invalid) {
-                    println!("allow invalid tx");
+                if self.is_allow_invalid {
                     self.core.lock().on_transaction_verification_success(tx);
-                }
-                else {
-                    println!("not allow invalid tx");
+                } else {
                     self.verifier.verify_transaction(next_block_height, tx);
                 }
             }
-
         }
     }
 
@@ -254,6 +253,10 @@ where
     fn install_sync_listener(&self, listener: SyncListenerRef) {
         self.core.lock().install_sync_listener(listener);
     }
+
+    fn install_block_listener(&self, listener: BlockListenerRef) {
+        self.core.lock().install_block_listener(listener);
+    }
 }
 
 impl<T, U> SynchronizationClient<T, U>
@@ -273,7 +276,7 @@ where
             shared_state: shared_state,
             core: core,
             verifier: verifier,
-            is_allow_invalid: true,
+            is_allow_invalid: is_allow_invalid,
         })
     }
 }
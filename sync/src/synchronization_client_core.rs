@@ -22,7 +22,7 @@ use synchronization_verifier::{
 };
 use time::precise_time_s;
 use types::{
-    BlockHeight, ClientCoreRef, EmptyBoxFuture, PeerIndex, PeersRef, SyncListenerRef,
+    BlockHeight, BlockListenerRef, ClientCoreRef, EmptyBoxFuture, PeerIndex, PeersRef, SyncListenerRef,
     SynchronizationStateRef,
 };
 use utils::{
@@ -96,6 +96,7 @@ pub trait ClientCore {
         sink: Box<TransactionVerificationSink>,
     ) -> Result<VecDeque<IndexedTransaction>, String>;
     fn install_sync_listener(&mut self, listener: SyncListenerRef);
+    fn install_block_listener(&mut self, listener: BlockListenerRef);
     fn execute_synchronization_tasks(
         &mut self,
         forced_blocks_requests: Option<Vec<H256>>,
@@ -699,6 +700,10 @@ where
         self.listener = Some(listener);
     }
 
+    fn install_block_listener(&mut self, listener: BlockListenerRef) {
+        self.chain.install_block_listener(listener);
+    }
+
     /// Schedule new synchronization tasks, if any.
     fn execute_synchronization_tasks(
         &mut self,
@@ -950,10 +955,6 @@ where
     fn on_block_verification_error(&self, err: &str, hash: &H256) {
         self.core.lock().on_block_verification_error(err, hash)
     }
-
-    fn on_block_verification_error_but_proceed(&self, block: IndexedBlock) -> Option<Vec<VerificationTask>> {
-        self.core.lock().on_block_verification_error_but_proceed(block)
-    }
 }
 
 impl<T> TransactionVerificationSink for CoreVerificationSink<T>
@@ -1447,87 +1448,6 @@ where
         }
     }
 
-
-    pub fn on_block_verification_error_but_proceed(
-        &mut self,
-        block: IndexedBlock,
-    ) -> Option<Vec<VerificationTask>> {
-        self.block_speed_meter.checkpoint();
-
-        // remove flags
-        let needs_relay = !self.do_not_relay.remove(block.hash());
-
-        let block_hash = block.hash().clone();
-        // insert block to the storage
-        match {
-            // remove block from verification queue
-            // header is removed in `insert_best_block` call
-            // or it is removed earlier, when block was removed from the verifying queue
-            if self
-                .chain
-                .forget_block_with_state_leave_header(block.hash(), BlockState::Verifying)
-                != HashPosition::Missing
-            {
-                // block was in verification queue => insert to storage
-                self.chain.insert_best_block(block)
-            } else {
-                Ok(BlockInsertionResult::default())
-            }
-        } {
-            Ok(insert_result) => {
-                // update shared state
-                self.shared_state
-                    .update_best_storage_block_height(self.chain.best_storage_block().number);
-
-                // notify listener
-                if let Some(best_block_hash) = insert_result.canonized_blocks_hashes.last() {
-                    if let Some(ref listener) = self.listener {
-                        listener.best_storage_block_inserted(best_block_hash);
-                    }
-                }
-
-                // awake threads, waiting for this block insertion
-                self.awake_waiting_threads(&block_hash);
-
-                // continue with synchronization
-                self.execute_synchronization_tasks(None, None);
-
-                // relay block to our peers
-                if needs_relay && (self.state.is_saturated() || self.state.is_nearly_saturated()) {
-                    for block_hash in insert_result.canonized_blocks_hashes {
-                        if let Some(block) = self.chain.storage().block(block_hash.into()) {
-                            self.executor.execute(Task::RelayNewBlock(block.into()));
-                        }
-                    }
-                }
-
-                // deal with block transactions
-                let mut verification_tasks: Vec<VerificationTask> =
-                    Vec::with_capacity(insert_result.transactions_to_reverify.len());
-                let next_block_height = self.chain.best_block().number + 1;
-                for tx in insert_result.transactions_to_reverify {
-                    // do not relay resurrected transactions again
-                    if let Some(tx_orphans) = self.process_peer_transaction(None, tx.into(), false)
-                    {
-                        let tx_tasks = tx_orphans
-                            .into_iter()
-                            .map(|tx| VerificationTask::VerifyTransaction(next_block_height, tx));
-                        verification_tasks.extend(tx_tasks);
-                    };
-                }
-                Some(verification_tasks)
-            }
-            Err(e) => {
-                // process as irrecoverable failure
-                panic!(
-                    "Block {} insertion failed with error {:?}",
-                    block_hash.to_reversed_str(),
-                    e
-                );
-            }
-        }
-    }
-
     fn on_block_verification_error(&mut self, err: &str, hash: &H256) {
         warn!(target: "sync", "Block {:?} verification failed with error {:?}", hash.to_reversed_str(), err);
 
@@ -1748,7 +1668,7 @@ pub mod tests {
         verifier.set_memory_pool(memory_pool);
         verifier.set_verifier(chain_verifier);
 
-        let client = SynchronizationClient::new(sync_state, client_core.clone(), verifier);
+        let client = SynchronizationClient::new(sync_state, client_core.clone(), verifier, false);
         (executor, client_core, client)
     }
 
@@ -0,0 +1,554 @@
+use keys::Type;
+use rand::Rng;
+use script::Script;
+use std::str::FromStr;
+use synchronization_wallet::{Coin, WalletError};
+
+/// Default fee rate of wallet transactions (satoshis per byte). Equals to the minimal relay fee rate of Bitcoin Core
+pub const DEFAULT_FEE_RATE: u64 = 1;
+/// Max fee rate of wallet transactions (satoshis per byte). Protects from paying absurd fees by mistake
+pub const MAX_FEE_RATE: u64 = 10_000;
+/// Outputs of lesser value are rejected by Bitcoin Core memory pool as dust (P2PKH output at default dust relay fee)
+pub const DUST_THRESHOLD: u64 = 546;
+/// Max number of branch-and-bound search steps
+const BNB_MAX_TRIES: usize = 100_000;
+
+/// Size of transaction version, lock time and inputs/outputs counts
+const TRANSACTION_OVERHEAD_SIZE: u64 = 10;
+/// Max size of input, spending P2PKH output (signature and uncompressed public key).
+/// Inputs, spending P2WPKH and P2SH-P2WPKH outputs, are smaller, including their witness
+const P2PKH_INPUT_SIZE: u64 = 181;
+/// Size of input outpoint and sequence
+const INPUT_OVERHEAD_SIZE: u64 = 40;
+/// Max size of pushed DER signature with sighash type
+const PUSHED_SIGNATURE_SIZE: u64 = 74;
+/// Size of P2PKH output
+pub const P2PKH_OUTPUT_SIZE: u64 = 34;
+/// Size of output value
+const OUTPUT_VALUE_SIZE: u64 = 8;
+
+/// Coin selection strategy
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoinSelectionStrategy {
+    /// Largest coins are spent first, minimizing number of inputs
+    LargestFirst,
+    /// Search for coins, which are paying the value and fee without change output. Falls back to largest-first
+    BranchAndBound,
+    /// Random coins are selected, then selection is improved so that change is close to the payment value
+    RandomImprove,
+}
+
+/// Coin, along with the estimated size of input, spending it
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoinInput {
+    pub coin: Coin,
+    /// Max size of input, spending the coin (see `estimate_input_size`)
+    pub size: u64,
+}
+
+/// Sizes of payment and change outputs of the funded transaction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutputSizes {
+    pub payment: u64,
+    pub change: u64,
+}
+
+impl OutputSizes {
+    /// Sizes of outputs with given payment and change scripts
+    pub fn new(payment_script: &Script, change_script: &Script) -> Self {
+        OutputSizes {
+            payment: estimate_output_size(payment_script),
+            change: estimate_output_size(change_script),
+        }
+    }
+
+    /// Sizes of P2PKH payment and change outputs
+    pub fn p2pkh() -> Self {
+        OutputSizes {
+            payment: P2PKH_OUTPUT_SIZE,
+            change: P2PKH_OUTPUT_SIZE,
+        }
+    }
+
+    /// Sizes of transaction outputs: payment output and, if `change` is true, change output
+    fn total(&self, change: bool) -> u64 {
+        if change { self.payment + self.change } else { self.payment }
+    }
+}
+
+/// Coins, funding the payment
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoinSelection {
+    /// Coins to spend
+    pub coins: Vec<Coin>,
+    /// Transaction fee
+    pub fee: u64,
+    /// Value of change output. Zero if transaction has no change output
+    pub change: u64,
+}
+
+impl CoinSelectionStrategy {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            CoinSelectionStrategy::LargestFirst => "largestfirst",
+            CoinSelectionStrategy::BranchAndBound => "branchandbound",
+            CoinSelectionStrategy::RandomImprove => "randomimprove",
+        }
+    }
+}
+
+impl FromStr for CoinSelectionStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "largestfirst" => Ok(CoinSelectionStrategy::LargestFirst),
+            "branchandbound" => Ok(CoinSelectionStrategy::BranchAndBound),
+            "randomimprove" => Ok(CoinSelectionStrategy::RandomImprove),
+            strategy => Err(format!("Unknown coin selection strategy: {}", strategy)),
+        }
+    }
+}
+
+/// Max size of transaction, spending P2PKH outputs to outputs of given sizes
+pub fn estimate_transaction_size(inputs: usize, output_sizes: &[u64]) -> u64 {
+    TRANSACTION_OVERHEAD_SIZE + inputs as u64 * P2PKH_INPUT_SIZE + output_sizes.iter().sum::<u64>()
+}
+
+/// Size of output with given script: value, script length and script
+pub fn estimate_output_size(script_pubkey: &Script) -> u64 {
+    let script_size = script_pubkey.len() as u64;
+    let script_length_size = match script_size {
+        0...0xfc => 1,
+        0xfd...0xffff => 3,
+        _ => 5,
+    };
+    OUTPUT_VALUE_SIZE + script_length_size + script_size
+}
+
+/// Max size of input, spending output of given type. `redeem_script` is the multisig redeem script
+/// of P2SH output, which is revealed in the script_sig along with required number of signatures
+pub fn estimate_input_size(kind: Type, redeem_script: Option<&Script>) -> u64 {
+    let redeem_script = match (kind, redeem_script) {
+        (Type::P2SH, Some(redeem_script)) => redeem_script,
+        _ => return P2PKH_INPUT_SIZE,
+    };
+
+    let redeem_script_size = redeem_script.len() as u64;
+    let redeem_script_push_size = match redeem_script_size {
+        0...75 => 1,
+        76...255 => 2,
+        _ => 3,
+    } + redeem_script_size;
+    // OP_0, signatures and redeem script
+    let script_sig_size = 1
+        + redeem_script.num_signatures_required() as u64 * PUSHED_SIGNATURE_SIZE
+        + redeem_script_push_size;
+    let script_sig_length_size = if script_sig_size < 0xfd { 1 } else { 3 };
+    INPUT_OVERHEAD_SIZE + script_sig_length_size + script_sig_size
+}
+
+/// Max size of transaction, spending given coins to the payment output and, if `change` is true, change output
+fn transaction_size(inputs: &[CoinInput], outputs: &OutputSizes, change: bool) -> u64 {
+    let inputs_size: u64 = inputs.iter().map(|input| input.size).sum();
+    TRANSACTION_OVERHEAD_SIZE + inputs_size + outputs.total(change)
+}
+
+/// Select coins, paying `value` and transaction fee at `fee_rate` satoshis per byte.
+/// Change, which is less than dust threshold, is left to miners.
+pub fn select_coins<R: Rng>(
+    strategy: CoinSelectionStrategy,
+    coins: &[CoinInput],
+    value: u64,
+    fee_rate: u64,
+    outputs: &OutputSizes,
+    rng: &mut R,
+) -> Result<CoinSelection, WalletError> {
+    let selected = match strategy {
+        CoinSelectionStrategy::LargestFirst => largest_first(coins, value, fee_rate, outputs),
+        CoinSelectionStrategy::BranchAndBound => branch_and_bound(coins, value, fee_rate, outputs)
+            .or_else(|| largest_first(coins, value, fee_rate, outputs)),
+        CoinSelectionStrategy::RandomImprove => random_improve(coins, value, fee_rate, outputs, rng),
+    };
+
+    selected
+        .and_then(|coins| fund(coins, value, fee_rate, outputs))
+        .ok_or(WalletError::InsufficientMoney)
+}
+
+fn largest_first(coins: &[CoinInput], value: u64, fee_rate: u64, outputs: &OutputSizes) -> Option<Vec<CoinInput>> {
+    let mut pool = coins.to_vec();
+    pool.sort_by(|a, b| b.coin.get_value().cmp(&a.coin.get_value()));
+
+    let mut selected = Vec::new();
+    for coin in pool {
+        selected.push(coin);
+        if is_enough(&selected, value, fee_rate, outputs) {
+            return Some(selected);
+        }
+    }
+    None
+}
+
+/// Depth-first search of coins, whose value exceeds payment and fee by less than the cost of change output.
+/// Coins are compared by their effective value, i.e. value minus fee of spending the coin.
+fn branch_and_bound(coins: &[CoinInput], value: u64, fee_rate: u64, outputs: &OutputSizes) -> Option<Vec<CoinInput>> {
+    let mut pool: Vec<(u64, &CoinInput)> = coins
+        .iter()
+        .filter_map(|input| effective_value(input, fee_rate).map(|value| (value, input)))
+        .collect();
+    pool.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let target = required_value(value, fee_rate, transaction_size(&[], outputs, false))?;
+    // any greater excess is returned in change output by `fund`
+    let max_excess = required_value(DUST_THRESHOLD, fee_rate, outputs.change)?;
+
+    // selection[i] is true if i-th coin of the pool is included into current branch
+    let mut selection: Vec<bool> = Vec::new();
+    let mut current_value = 0u64;
+    let mut available_value: u64 = pool.iter().map(|&(value, _)| value).sum();
+    let mut best: Option<(u64, Vec<bool>)> = None;
+
+    for _ in 0..BNB_MAX_TRIES {
+        let backtrack = if current_value + available_value < target || current_value.saturating_sub(target) > max_excess {
+            true
+        } else if current_value >= target {
+            let excess = current_value - target;
+            if best.as_ref().map_or(true, |&(best_excess, _)| excess < best_excess) {
+                best = Some((excess, selection.clone()));
+            }
+            if excess == 0 {
+                break;
+            }
+            true
+        } else {
+            false
+        };
+
+        if backtrack {
+            // walk back to the last included coin and try branch without it
+            while selection.last() == Some(&false) {
+                selection.pop();
+                available_value += pool[selection.len()].0;
+            }
+            if selection.is_empty() {
+                break;
+            }
+
+            let last = selection.len() - 1;
+            selection[last] = false;
+            current_value -= pool[last].0;
+        } else {
+            let next = selection.len();
+            available_value -= pool[next].0;
+            // branch with previous coin of the same value excluded has already been explored
+            if next != 0 && !selection[next - 1] && pool[next - 1].0 == pool[next].0 {
+                selection.push(false);
+            } else {
+                selection.push(true);
+                current_value += pool[next].0;
+            }
+        }
+    }
+
+    best.map(|(_, selection)| {
+        selection
+            .into_iter()
+            .zip(pool.into_iter())
+            .filter(|&(included, _)| included)
+            .map(|(_, (_, coin))| coin.clone())
+            .collect()
+    })
+}
+
+/// Random coins are selected until payment and fee are covered. Then remaining coins are added
+/// (in random order) while this moves change closer to the payment value, so that the wallet
+/// keeps coins of the size of typical payments.
+fn random_improve<R: Rng>(
+    coins: &[CoinInput],
+    value: u64,
+    fee_rate: u64,
+    outputs: &OutputSizes,
+    rng: &mut R,
+) -> Option<Vec<CoinInput>> {
+    let mut pool = coins.to_vec();
+    rng.shuffle(&mut pool);
+
+    let mut selected = Vec::new();
+    while !is_enough(&selected, value, fee_rate, outputs) {
+        selected.push(pool.pop()?);
+    }
+
+    let ideal_value = value.saturating_mul(2);
+    let max_value = value.saturating_mul(3);
+    for input in pool {
+        let selected_value = coins_value(&selected);
+        let improved_value = selected_value + input.coin.get_value();
+        if effective_value(&input, fee_rate).is_some()
+            && improved_value <= max_value
+            && distance(improved_value, ideal_value) < distance(selected_value, ideal_value)
+        {
+            selected.push(input);
+        }
+    }
+
+    Some(selected)
+}
+
+/// Are coins paying the value and fee of transaction without change output?
+fn is_enough(coins: &[CoinInput], value: u64, fee_rate: u64, outputs: &OutputSizes) -> bool {
+    !coins.is_empty()
+        && required_value(value, fee_rate, transaction_size(coins, outputs, false))
+            .map_or(false, |required| coins_value(coins) >= required)
+}
+
+/// Compute fee and change of transaction, spending selected coins.
+/// Returns None if coins are not paying the value and fee
+fn fund(inputs: Vec<CoinInput>, value: u64, fee_rate: u64, outputs: &OutputSizes) -> Option<CoinSelection> {
+    let total = coins_value(&inputs);
+    let fee_with_change = fee_rate.checked_mul(transaction_size(&inputs, outputs, true))?;
+    let change = value
+        .checked_add(fee_with_change)
+        .map_or(0, |required| total.saturating_sub(required));
+    let coins = inputs.into_iter().map(|input| input.coin).collect();
+    if change >= DUST_THRESHOLD {
+        Some(CoinSelection {
+            coins: coins,
+            fee: fee_with_change,
+            change: change,
+        })
+    } else {
+        Some(CoinSelection {
+            coins: coins,
+            fee: total.checked_sub(value)?,
+            change: 0,
+        })
+    }
+}
+
+/// Value plus fee of `size` bytes at `fee_rate`. None on overflow
+fn required_value(value: u64, fee_rate: u64, size: u64) -> Option<u64> {
+    fee_rate.checked_mul(size).and_then(|fee| value.checked_add(fee))
+}
+
+/// Value of coin minus fee of spending it. None if spending the coin costs more than it pays
+fn effective_value(input: &CoinInput, fee_rate: u64) -> Option<u64> {
+    fee_rate
+        .checked_mul(input.size)
+        .and_then(|fee| input.coin.get_value().checked_sub(fee))
+        .and_then(|value| if value > 0 { Some(value) } else { None })
+}
+
+fn coins_value(coins: &[CoinInput]) -> u64 {
+    coins.iter().map(|input| input.coin.get_value()).sum()
+}
+
+fn distance(a: u64, b: u64) -> u64 {
+    if a > b { a - b } else { b - a }
+}
+
+#[cfg(test)]
+mod tests {
+    use chain::OutPoint;
+    use keys::{AddressHash, KeyPair, Network, Private, Public, Type};
+    use rand::{SeedableRng, StdRng};
+    use script::Builder;
+    use synchronization_wallet::{Coin, WalletError};
+    use super::{
+        estimate_input_size, estimate_transaction_size, select_coins, CoinInput, CoinSelection,
+        CoinSelectionStrategy, DUST_THRESHOLD,
+    };
+
+    fn coins(values: &[u64]) -> Vec<CoinInput> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(index, &value)| {
+                Coin::new(
+                    format!("{}", index),
+                    OutPoint {
+                        hash: (index as u8 + 1).into(),
+                        index: 0,
+                    },
+                    Type::P2PKH,
+                    AddressHash::default(),
+                    value,
+                )
+            })
+            .map(|coin| CoinInput {
+                coin: coin,
+                size: estimate_input_size(Type::P2PKH, None),
+            })
+            .collect()
+    }
+
+    fn select(strategy: CoinSelectionStrategy, values: &[u64], value: u64, fee_rate: u64) -> Result<CoinSelection, WalletError> {
+        select_inputs(strategy, &coins(values), value, fee_rate)
+    }
+
+    fn select_inputs(strategy: CoinSelectionStrategy, inputs: &[CoinInput], value: u64, fee_rate: u64) -> Result<CoinSelection, WalletError> {
+        let seed: &[_] = &[0, 0, 0, 0];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        select_coins(strategy, inputs, value, fee_rate, &OutputSizes::p2pkh(), &mut rng)
+    }
+
+    fn p2pkh_transaction_size(inputs: usize, outputs: usize) -> u64 {
+        estimate_transaction_size(inputs, &vec![P2PKH_OUTPUT_SIZE; outputs])
+    }
+
+    fn public_key() -> Public {
+        let private = Private {
+            network: Network::Mainnet,
+            secret: 1.into(),
+            compressed: true,
+        };
+        KeyPair::from_private(private).unwrap().public().clone()
+    }
+
+    fn selected_values(selection: &CoinSelection) -> Vec<u64> {
+        let mut values: Vec<u64> = selection.coins.iter().map(Coin::get_value).collect();
+        values.sort();
+        values
+    }
+
+    fn assert_balanced(selection: &CoinSelection, value: u64, fee_rate: u64) {
+        let outputs = if selection.change == 0 { 1 } else { 2 };
+        let total: u64 = selection.coins.iter().map(Coin::get_value).sum();
+        assert_eq!(total, value + selection.fee + selection.change);
+        assert!(selection.fee >= fee_rate * p2pkh_transaction_size(selection.coins.len(), outputs));
+        assert!(selection.change == 0 || selection.change >= DUST_THRESHOLD);
+    }
+
+    #[test]
+    fn coin_selection_strategy_parse() {
+        for strategy in &[
+            CoinSelectionStrategy::LargestFirst,
+            CoinSelectionStrategy::BranchAndBound,
+            CoinSelectionStrategy::RandomImprove,
+        ] {
+            assert_eq!(strategy.name().parse::<CoinSelectionStrategy>(), Ok(*strategy));
+        }
+        assert!("smallestfirst".parse::<CoinSelectionStrategy>().is_err());
+    }
+
+    #[test]
+    fn multisig_input_size_depends_on_redeem_script() {
+        let key = public_key();
+        let one_of_one = Builder::build_multisig(1, &[key.clone()]);
+        let two_of_three = Builder::build_multisig(2, &[key.clone(), key.clone(), key.clone()]);
+
+        // 1-of-1: OP_0, signature, redeem script of 37 bytes
+        assert_eq!(estimate_input_size(Type::P2SH, Some(&one_of_one)), 40 + 1 + 1 + 74 + 1 + 37);
+        // 2-of-3: OP_0, 2 signatures, PUSHDATA1 of redeem script of 105 bytes. Script_sig length takes 3 bytes
+        assert_eq!(estimate_input_size(Type::P2SH, Some(&two_of_three)), 40 + 3 + 1 + 2 * 74 + 2 + 105);
+        assert!(estimate_input_size(Type::P2SH, Some(&two_of_three)) > estimate_input_size(Type::P2SH, None));
+        assert_eq!(estimate_input_size(Type::P2PKH, None), p2pkh_transaction_size(1, 0) - p2pkh_transaction_size(0, 0));
+    }
+
+    #[test]
+    fn selection_pays_fee_of_multisig_inputs() {
+        let key = public_key();
+        let redeem_script = Builder::build_multisig(2, &[key.clone(), key.clone(), key]);
+        let mut inputs = coins(&[100_000]);
+        inputs[0].size = estimate_input_size(Type::P2SH, Some(&redeem_script));
+
+        let selection = select_inputs(CoinSelectionStrategy::LargestFirst, &inputs, 10_000, 1).unwrap();
+        assert_eq!(selection.fee, p2pkh_transaction_size(0, 2) + inputs[0].size);
+        assert!(selection.fee > p2pkh_transaction_size(1, 2));
+    }
+
+    #[test]
+    fn selection_pays_fee_of_segwit_outputs() {
+        let p2pkh = Builder::build_p2pkh(&AddressHash::default());
+        let p2wsh = Builder::build_p2wsh(&Default::default());
+        assert_eq!(estimate_output_size(&p2pkh), P2PKH_OUTPUT_SIZE);
+        assert_eq!(estimate_output_size(&p2wsh), 43);
+
+        let outputs = OutputSizes::new(&p2wsh, &p2wsh);
+        let seed: &[_] = &[0, 0, 0, 0];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let selection = select_coins(CoinSelectionStrategy::LargestFirst, &coins(&[100_000]), 10_000, 1, &outputs, &mut rng).unwrap();
+        assert_eq!(selection.fee, estimate_transaction_size(1, &[43, 43]));
+        assert_eq!(selection.fee, p2pkh_transaction_size(1, 2) + 2 * 9);
+    }
+
+    #[test]
+    fn largest_first_spends_largest_coins() {
+        let selection = select(CoinSelectionStrategy::LargestFirst, &[10_000, 50_000, 20_000, 40_000], 60_000, 10).unwrap();
+        assert_eq!(selected_values(&selection), vec![40_000, 50_000]);
+        assert_eq!(selection.fee, 10 * p2pkh_transaction_size(2, 2));
+        assert_balanced(&selection, 60_000, 10);
+    }
+
+    #[test]
+    fn overflowing_fee_is_insufficient_money() {
+        for strategy in &[
+            CoinSelectionStrategy::LargestFirst,
+            CoinSelectionStrategy::BranchAndBound,
+            CoinSelectionStrategy::RandomImprove,
+        ] {
+            let error = select(*strategy, &[10_000, 50_000], 1_000, u64::max_value() / 100).unwrap_err();
+            assert_eq!(error, WalletError::InsufficientMoney);
+            let error = select(*strategy, &[10_000, 50_000], u64::max_value() - 100, 1).unwrap_err();
+            assert_eq!(error, WalletError::InsufficientMoney);
+        }
+    }
+
+    #[test]
+    fn largest_first_pays_fee_of_added_inputs() {
+        // 60_000 would cover the value, but not the fee
+        let error = select(CoinSelectionStrategy::LargestFirst, &[10_000, 50_000], 60_000, 10).unwrap_err();
+        assert_eq!(error, WalletError::InsufficientMoney);
+    }
+
+    #[test]
+    fn dust_change_is_left_to_miners() {
+        let fee = p2pkh_transaction_size(1, 1);
+        let selection = select(CoinSelectionStrategy::LargestFirst, &[10_000 + fee + DUST_THRESHOLD - 1], 10_000, 1).unwrap();
+        assert_eq!(selection.change, 0);
+        assert_eq!(selection.fee, fee + DUST_THRESHOLD - 1);
+        assert_balanced(&selection, 10_000, 1);
+    }
+
+    #[test]
+    fn branch_and_bound_avoids_change() {
+        let fee_rate = 5;
+        let input_fee = fee_rate * p2pkh_transaction_size(1, 0) - fee_rate * p2pkh_transaction_size(0, 0);
+        let target = 100_000 + fee_rate * p2pkh_transaction_size(0, 1);
+        // two coins, which are exactly paying the target, hidden between larger and smaller ones
+        let values = [200_000, 70_000 + input_fee, target - 70_000 + input_fee, 1_000, 25_000];
+
+        let selection = select(CoinSelectionStrategy::BranchAndBound, &values, 100_000, fee_rate).unwrap();
+        assert_eq!(selected_values(&selection), vec![target - 70_000 + input_fee, 70_000 + input_fee]);
+        assert_eq!(selection.change, 0);
+        assert_eq!(selection.fee, fee_rate * p2pkh_transaction_size(2, 1));
+        assert_balanced(&selection, 100_000, fee_rate);
+
+        // largest-first would have spent 200_000 coin with change
+        let selection = select(CoinSelectionStrategy::LargestFirst, &values, 100_000, fee_rate).unwrap();
+        assert_eq!(selected_values(&selection), vec![200_000]);
+        assert!(selection.change != 0);
+    }
+
+    #[test]
+    fn branch_and_bound_falls_back_to_largest_first() {
+        let selection = select(CoinSelectionStrategy::BranchAndBound, &[300_000, 500_000], 100_000, 1).unwrap();
+        assert_eq!(selected_values(&selection), vec![500_000]);
+        assert_balanced(&selection, 100_000, 1);
+    }
+
+    #[test]
+    fn random_improve_keeps_change_close_to_value() {
+        let values = [10_000; 10];
+        let selection = select(CoinSelectionStrategy::RandomImprove, &values, 25_000, 1).unwrap();
+        assert_balanced(&selection, 25_000, 1);
+        // 3 coins are enough to pay, but improved selection gets closer to the double of the value
+        assert_eq!(selection.coins.len(), 5);
+    }
+
+    #[test]
+    fn random_improve_insufficient_money() {
+        let selection = select(CoinSelectionStrategy::RandomImprove, &[10_000, 10_000], 25_000, 1);
+        assert_eq!(selection, Err(WalletError::InsufficientMoney));
+    }
+}
@@ -36,6 +36,12 @@ pub enum Task {
     WitnessTransaction(PeerIndex, IndexedTransaction),
     /// Send block transactions
     BlockTxn(PeerIndex, types::BlockTxn),
+    /// Send compact filter of the block
+    CFilter(PeerIndex, types::CFilter),
+    /// Send compact filters hashes
+    CFHeaders(PeerIndex, types::CFHeaders),
+    /// Send compact filters headers checkpoints
+    CFCheckpt(PeerIndex, types::CFCheckpt),
     /// Send notfound
     NotFound(PeerIndex, types::NotFound),
     /// Send inventory
@@ -165,6 +171,27 @@ impl LocalSynchronizationTaskExecutor {
         }
     }
 
+    fn execute_cfilter(&self, peer_index: PeerIndex, cfilter: types::CFilter) {
+        if let Some(connection) = self.peers.connection(peer_index) {
+            trace!(target: "sync", "Sending cfilter for block {} to peer#{}", cfilter.block_hash.to_reversed_str(), peer_index);
+            connection.send_cfilter(&cfilter);
+        }
+    }
+
+    fn execute_cfheaders(&self, peer_index: PeerIndex, cfheaders: types::CFHeaders) {
+        if let Some(connection) = self.peers.connection(peer_index) {
+            trace!(target: "sync", "Sending cfheaders with {} hashes to peer#{}", cfheaders.filter_hashes.len(), peer_index);
+            connection.send_cfheaders(&cfheaders);
+        }
+    }
+
+    fn execute_cfcheckpt(&self, peer_index: PeerIndex, cfcheckpt: types::CFCheckpt) {
+        if let Some(connection) = self.peers.connection(peer_index) {
+            trace!(target: "sync", "Sending cfcheckpt with {} headers to peer#{}", cfcheckpt.filter_headers.len(), peer_index);
+            connection.send_cfcheckpt(&cfcheckpt);
+        }
+    }
+
     fn execute_notfound(&self, peer_index: PeerIndex, notfound: types::NotFound) {
         if let Some(connection) = self.peers.connection(peer_index) {
             trace!(target: "sync", "Sending notfound to peer#{} with {} items", peer_index, notfound.inventory.len());
@@ -259,6 +286,9 @@ impl TaskExecutor for LocalSynchronizationTaskExecutor {
                 self.execute_witness_transaction(peer_index, transaction)
             }
             Task::BlockTxn(peer_index, blocktxn) => self.execute_block_txn(peer_index, blocktxn),
+            Task::CFilter(peer_index, cfilter) => self.execute_cfilter(peer_index, cfilter),
+            Task::CFHeaders(peer_index, cfheaders) => self.execute_cfheaders(peer_index, cfheaders),
+            Task::CFCheckpt(peer_index, cfcheckpt) => self.execute_cfcheckpt(peer_index, cfcheckpt),
             Task::NotFound(peer_index, notfound) => self.execute_notfound(peer_index, notfound),
             Task::Inventory(peer_index, inventory) => self.execute_inventory(peer_index, inventory),
             Task::Headers(peer_index, headers, request_id) => {
@@ -7,6 +7,7 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
+use storage::{BlockFilter, BASIC_FILTER_TYPE};
 use synchronization_executor::{Task, TaskExecutor};
 use types::{BlockHeight, ExecutorRef, MemoryPoolRef, PeerIndex, PeersRef, RequestId, StorageRef};
 use utils::KnownHashType;
@@ -26,6 +27,12 @@ pub enum ServerTask {
     Mempool(PeerIndex),
     /// Serve 'getblocktxn' request
     GetBlockTxn(PeerIndex, types::GetBlockTxn),
+    /// Serve 'getcfilters' request
+    GetCFilters(PeerIndex, types::GetCFilters),
+    /// Serve 'getcfheaders' request
+    GetCFHeaders(PeerIndex, types::GetCFHeaders),
+    /// Serve 'getcfcheckpt' request
+    GetCFCheckpt(PeerIndex, types::GetCFCheckpt),
 }
 
 /// Synchronization server
@@ -84,7 +91,10 @@ impl ServerTask {
             | ServerTask::GetBlocks(peer_index, _)
             | ServerTask::GetHeaders(peer_index, _, _)
             | ServerTask::Mempool(peer_index)
-            | ServerTask::GetBlockTxn(peer_index, _) => peer_index,
+            | ServerTask::GetBlockTxn(peer_index, _)
+            | ServerTask::GetCFilters(peer_index, _)
+            | ServerTask::GetCFHeaders(peer_index, _)
+            | ServerTask::GetCFCheckpt(peer_index, _) => peer_index,
         }
     }
 }
@@ -264,6 +274,15 @@ where
             ServerTask::GetBlockTxn(peer_index, message) => {
                 self.serve_get_block_txn(peer_index, message)
             }
+            ServerTask::GetCFilters(peer_index, message) => {
+                self.serve_get_cfilters(peer_index, message)
+            }
+            ServerTask::GetCFHeaders(peer_index, message) => {
+                self.serve_get_cfheaders(peer_index, message)
+            }
+            ServerTask::GetCFCheckpt(peer_index, message) => {
+                self.serve_get_cfcheckpt(peer_index, message)
+            }
         }
 
         None
@@ -541,6 +560,178 @@ where
         ));
     }
 
+    fn serve_get_cfilters(&self, peer_index: PeerIndex, message: types::GetCFilters) {
+        let filters = match self.locate_block_filters(
+            peer_index,
+            "getcfilters",
+            message.filter_type,
+            message.start_height,
+            &message.stop_hash,
+            types::GETCFILTERS_MAX_RESPONSE_FILTERS,
+        ) {
+            Some(filters) => filters,
+            None => return,
+        };
+
+        trace!(target: "sync", "'getcfilters' response to peer#{} is ready with {} filters", peer_index, filters.len());
+        for (block_hash, filter) in filters {
+            self.executor.execute(Task::CFilter(
+                peer_index,
+                types::CFilter {
+                    filter_type: message.filter_type,
+                    block_hash: block_hash,
+                    filter: filter.filter,
+                },
+            ));
+        }
+    }
+
+    fn serve_get_cfheaders(&self, peer_index: PeerIndex, message: types::GetCFHeaders) {
+        let filters = match self.locate_block_filters(
+            peer_index,
+            "getcfheaders",
+            message.filter_type,
+            message.start_height,
+            &message.stop_hash,
+            types::GETCFHEADERS_MAX_RESPONSE_HASHES,
+        ) {
+            Some(filters) => filters,
+            None => return,
+        };
+
+        let previous_filter_header = if message.start_height == 0 {
+            H256::default()
+        } else {
+            match self.canonical_block_filter(message.start_height - 1) {
+                Some((_, filter)) => filter.header,
+                None => {
+                    warn!(target: "sync", "'getcfheaders' request from peer#{} is ignored as we have failed to find filter of block #{}", peer_index, message.start_height - 1);
+                    return;
+                }
+            }
+        };
+
+        trace!(target: "sync", "'getcfheaders' response to peer#{} is ready with {} hashes", peer_index, filters.len());
+        self.executor.execute(Task::CFHeaders(
+            peer_index,
+            types::CFHeaders {
+                filter_type: message.filter_type,
+                stop_hash: message.stop_hash,
+                previous_filter_header: previous_filter_header,
+                filter_hashes: filters
+                    .into_iter()
+                    .map(|(_, filter)| filter.filter_hash())
+                    .collect(),
+            },
+        ));
+    }
+
+    fn serve_get_cfcheckpt(&self, peer_index: PeerIndex, message: types::GetCFCheckpt) {
+        let stop_number = match self.locate_filters_stop_block(
+            peer_index,
+            "getcfcheckpt",
+            message.filter_type,
+            &message.stop_hash,
+        ) {
+            Some(stop_number) => stop_number,
+            None => return,
+        };
+
+        let mut filter_headers = Vec::new();
+        let mut block_number = types::CFCHECKPT_INTERVAL;
+        while block_number <= stop_number {
+            match self.canonical_block_filter(block_number) {
+                Some((_, filter)) => filter_headers.push(filter.header),
+                None => {
+                    warn!(target: "sync", "'getcfcheckpt' request from peer#{} is ignored as we have failed to find filter of block #{}", peer_index, block_number);
+                    return;
+                }
+            }
+            block_number += types::CFCHECKPT_INTERVAL;
+        }
+
+        trace!(target: "sync", "'getcfcheckpt' response to peer#{} is ready with {} headers", peer_index, filter_headers.len());
+        self.executor.execute(Task::CFCheckpt(
+            peer_index,
+            types::CFCheckpt {
+                filter_type: message.filter_type,
+                stop_hash: message.stop_hash,
+                filter_headers: filter_headers,
+            },
+        ));
+    }
+
+    /// Returns filters of canonical blocks in [start_height; stop_hash] range.
+    /// Returns None (and punishes peer, if required) if request can not be served.
+    fn locate_block_filters(
+        &self,
+        peer_index: PeerIndex,
+        command: &str,
+        filter_type: u8,
+        start_height: BlockHeight,
+        stop_hash: &H256,
+        max_filters: BlockHeight,
+    ) -> Option<Vec<(H256, BlockFilter)>> {
+        let stop_number =
+            self.locate_filters_stop_block(peer_index, command, filter_type, stop_hash)?;
+        if start_height > stop_number || stop_number - start_height >= max_filters {
+            self.peers.misbehaving(
+                peer_index,
+                &format!(
+                    "Got '{}' message for blocks range {}..{} (max {} blocks)",
+                    command, start_height, stop_number, max_filters
+                ),
+            );
+            return None;
+        }
+
+        let mut filters = Vec::with_capacity((stop_number - start_height + 1) as usize);
+        for block_number in start_height..stop_number + 1 {
+            match self.canonical_block_filter(block_number) {
+                Some(filter) => filters.push(filter),
+                None => {
+                    // filters are missing in databases, created before filters were maintained
+                    warn!(target: "sync", "'{}' request from peer#{} is ignored as we have failed to find filter of block #{}", command, peer_index, block_number);
+                    return None;
+                }
+            }
+        }
+        Some(filters)
+    }
+
+    /// Returns number of the canonical stop block of compact filters request.
+    fn locate_filters_stop_block(
+        &self,
+        peer_index: PeerIndex,
+        command: &str,
+        filter_type: u8,
+        stop_hash: &H256,
+    ) -> Option<BlockHeight> {
+        if filter_type != BASIC_FILTER_TYPE {
+            self.peers.misbehaving(
+                peer_index,
+                &format!(
+                    "Got '{}' message for unsupported filter type {}",
+                    command, filter_type
+                ),
+            );
+            return None;
+        }
+
+        // stop block could have been decanonized since request has been sent => ignore
+        let stop_number = self.storage.block_number(stop_hash);
+        if stop_number.is_none() {
+            trace!(target: "sync", "'{}' request from peer#{} is ignored as stop block {} is not canonical", command, peer_index, stop_hash.to_reversed_str());
+        }
+        stop_number
+    }
+
+    fn canonical_block_filter(&self, block_number: BlockHeight) -> Option<(H256, BlockFilter)> {
+        let block_hash = self.storage.block_hash(block_number)?;
+        let filter = self.storage.block_filter(&block_hash)?;
+        Some((block_hash, filter))
+    }
+
     fn locate_best_common_block(&self, hash_stop: &H256, locator: &[H256]) -> Option<BlockHeight> {
         for block_hash in locator.iter().chain(&[hash_stop.clone()]) {
             if let Some(block_number) = self.storage.block_number(block_hash) {
@@ -876,6 +1067,116 @@ pub mod tests {
         assert!(!peers.enumerate().contains(&0));
     }
 
+    #[test]
+    fn server_get_cfilters_responds_with_filters() {
+        let (storage, _, executor, _, server) = create_synchronization_server();
+        let genesis_hash = test_data::genesis().hash();
+        let filter = storage.block_filter(&genesis_hash).unwrap();
+
+        server.execute(ServerTask::GetCFilters(
+            0,
+            types::GetCFilters {
+                filter_type: 0,
+                start_height: 0,
+                stop_hash: genesis_hash.clone(),
+            },
+        ));
+
+        let tasks = DummyTaskExecutor::wait_tasks(executor);
+        assert_eq!(
+            tasks,
+            vec![Task::CFilter(
+                0,
+                types::CFilter {
+                    filter_type: 0,
+                    block_hash: genesis_hash,
+                    filter: filter.filter,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn server_get_cfheaders_responds_with_filter_hashes() {
+        let (storage, _, executor, _, server) = create_synchronization_server();
+        let genesis_hash = test_data::genesis().hash();
+        let filter = storage.block_filter(&genesis_hash).unwrap();
+
+        server.execute(ServerTask::GetCFHeaders(
+            0,
+            types::GetCFHeaders {
+                filter_type: 0,
+                start_height: 0,
+                stop_hash: genesis_hash.clone(),
+            },
+        ));
+
+        let tasks = DummyTaskExecutor::wait_tasks(executor);
+        assert_eq!(
+            tasks,
+            vec![Task::CFHeaders(
+                0,
+                types::CFHeaders {
+                    filter_type: 0,
+                    stop_hash: genesis_hash,
+                    previous_filter_header: H256::default(),
+                    filter_hashes: vec![filter.filter_hash()],
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn server_get_cfcheckpt_responds_with_checkpoints() {
+        let (_, _, executor, _, server) = create_synchronization_server();
+
+        server.execute(ServerTask::GetCFCheckpt(
+            0,
+            types::GetCFCheckpt {
+                filter_type: 0,
+                stop_hash: test_data::genesis().hash(),
+            },
+        ));
+
+        // there are no checkpoints before block #1000
+        let tasks = DummyTaskExecutor::wait_tasks(executor);
+        assert_eq!(
+            tasks,
+            vec![Task::CFCheckpt(
+                0,
+                types::CFCheckpt {
+                    filter_type: 0,
+                    stop_hash: test_data::genesis().hash(),
+                    filter_headers: vec![],
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn server_get_cfilters_do_not_responds_when_bad_request() {
+        let (_, _, _, peers, server) = create_synchronization_server();
+
+        peers.insert(0, Services::default(), DummyOutboundSyncConnection::new());
+        assert!(peers.enumerate().contains(&0));
+
+        // when asking for filters of unsupported type
+        server.execute(ServerTask::GetCFilters(
+            0,
+            types::GetCFilters {
+                filter_type: 1,
+                start_height: 0,
+                stop_hash: test_data::genesis().hash(),
+            },
+        ));
+
+        // server closes connection
+        use std::thread;
+        use std::time::Duration;
+        thread::park_timeout(Duration::from_millis(100)); // TODO: get rid of timeout
+        assert!(!peers.enumerate().contains(&0));
+    }
+
     #[test]
     fn server_getdata_responds_notfound_when_transaction_is_inaccessible() {
         let (_, _, executor, _, server) = create_synchronization_server();
@@ -0,0 +1,754 @@
+use chain::{IndexedBlock, IndexedTransaction, OutPoint, TransactionOutput};
+use linked_hash_map::LinkedHashMap;
+use network::ConsensusParams;
+use primitives::hash::H256;
+use std::collections::{HashMap, HashSet, VecDeque};
+use storage::{
+    DuplexTransactionOutputProvider, NoopStore, SanitizationStore, TransactionMeta,
+    TransactionMetaProvider, TransactionOutputProvider,
+};
+use types::{BlockHeight, PeerIndex, StorageRef};
+use verification::{
+    median_timestamp_inclusive, BlockDeployments, CanonBlock, Deployments, TransactionAcceptor,
+    VerificationLevel,
+};
+
+/// Depth of the canon chain reorganization, which is never exceeded (same as the number of blocks, kept by the pruned node)
+const MAX_REORG_DEPTH: BlockHeight = 288;
+/// Max number of blocks, which are requested or verified ahead of the best block
+const MAX_BLOCKS_AHEAD: BlockHeight = 512;
+
+/// Result of single block sanitization
+#[derive(Debug, Clone, PartialEq)]
+pub struct SanitizedBlock {
+    /// Hash of the block
+    pub hash: H256,
+    /// Height of the block in the canon chain
+    pub number: BlockHeight,
+    /// Validity of every block transaction (in block order)
+    pub transactions_validity: Vec<bool>,
+}
+
+/// Blocks, which were accepted to the canon chain by ordering only (i.e. their
+/// transactions were not fully verified), are waiting here until sanitization.
+/// Sanitization re-verifies every transaction of the block against the UTXO set,
+/// as it was right before the block, and stores transactions validity in the storage.
+pub struct ShardBlocksPool {
+    /// Storage reference
+    storage: StorageRef,
+    /// Consensus parameters
+    consensus: ConsensusParams,
+    /// Deployments cache
+    deployments: Deployments,
+    /// Blocks, waiting for sanitization { block hash => peer, which has provided the block (None for blocks, queued from the storage) }
+    unsanitized: LinkedHashMap<H256, Option<PeerIndex>>,
+    /// Queued blocks along with the number of the best block at the moment of queueing, in queueing order.
+    /// Used to remove side chain blocks, which could not be canonized anymore
+    queued: VecDeque<(BlockHeight, H256)>,
+    /// Position of the sanitization in the canon chain, kept between `sanitize_blocks` calls
+    cursor: Option<SanitizationCursor>,
+}
+
+/// Canon chain state right before the next block to sanitize
+struct SanitizationCursor {
+    /// Number of the next canon block to visit
+    next_number: BlockHeight,
+    /// Number of the last canon block, which spends are known to the cursor
+    scanned_number: BlockHeight,
+    /// Hash of the last canon block, which spends are known to the cursor
+    scanned_hash: H256,
+    /// Outputs, which are marked as spent in the storage, but which are spent by
+    /// transactions from blocks at `next_number` and above (or by transactions, found invalid)
+    unspent: HashSet<OutPoint>,
+    /// Outputs, which are not marked as spent in the storage, but which are spent by
+    /// transactions, found valid during sanitization
+    spent: HashSet<OutPoint>,
+}
+
+/// UTXO view of the canon chain, as it was right before the sanitized block.
+/// Assumes that there is at most one spend of every output, marked in the storage
+/// (i.e. double spends are never canonized as valid transactions).
+struct SanitizerView<'a> {
+    /// Storage reference
+    storage: &'a StorageRef,
+    /// Sanitized block
+    block: &'a IndexedBlock,
+    /// Height of sanitized block
+    height: BlockHeight,
+    /// Validity of already sanitized transactions of the sanitized block. Validity of
+    /// transactions from previously sanitized blocks is read from the storage
+    transactions_validity: &'a HashMap<H256, bool>,
+    /// Outputs, which are marked as spent in the storage, but which are spent by
+    /// transactions from blocks at `height` and above (or by transactions, found invalid)
+    unspent: &'a HashSet<OutPoint>,
+    /// Outputs, which are not marked as spent in the storage, but which are spent by
+    /// transactions, found valid during sanitization
+    spent: &'a HashSet<OutPoint>,
+}
+
+impl ShardBlocksPool {
+    /// Create new shard blocks pool
+    pub fn new(storage: StorageRef, consensus: ConsensusParams) -> Self {
+        ShardBlocksPool {
+            storage: storage,
+            consensus: consensus,
+            deployments: Deployments::new(),
+            unsanitized: LinkedHashMap::new(),
+            queued: VecDeque::new(),
+            cursor: None,
+        }
+    }
+
+    /// Get number of blocks, waiting for sanitization
+    pub fn len(&self) -> usize {
+        self.unsanitized.len()
+    }
+
+    /// Remember block for future sanitization
+    pub fn store_block(&mut self, peer_index: PeerIndex, block: &IndexedBlock) {
+        if self.storage.sanitized_block(block.hash()).is_some() {
+            return;
+        }
+
+        self.queue_block(block.hash().clone(), Some(peer_index));
+        self.remove_side_blocks();
+    }
+
+    /// Queue canon blocks above the last sanitized block, which are not sanitized yet (e.g. blocks, which
+    /// were waiting in the pool when the node has been stopped). Returns number of queued blocks
+    pub fn queue_unsanitized_blocks(&mut self) -> usize {
+        let best_number = self.storage.best_block().number;
+        let mut hashes = Vec::new();
+        // genesis block is never sanitized
+        for number in (1..best_number + 1).rev() {
+            match self.storage.block_hash(number) {
+                Some(ref hash) if self.storage.sanitized_block(hash).is_some() => break,
+                Some(hash) => hashes.push(hash),
+                None => break,
+            }
+        }
+
+        let count = hashes.len();
+        for hash in hashes.into_iter().rev() {
+            self.queue_block(hash, None);
+        }
+        count
+    }
+
+    /// Get result of sanitization of the canon block
+    pub fn sanitized_block(&self, hash: &H256) -> Option<SanitizedBlock> {
+        let transactions_validity = self.storage.sanitized_block(hash)?;
+        let number = self.storage.block_number(hash)?;
+        Some(SanitizedBlock {
+            hash: hash.clone(),
+            number: number,
+            transactions_validity: transactions_validity,
+        })
+    }
+
+    /// Get validity of transaction from sanitized block
+    pub fn transaction_validity(&self, hash: &H256) -> Option<bool> {
+        self.storage.sanitized_transaction(hash)
+    }
+
+    /// Returns true if the last `sanitize_blocks` call has visited all canon blocks, known at that moment
+    pub fn is_sanitization_complete(&self) -> bool {
+        self.cursor
+            .as_ref()
+            .map_or(true, |cursor| cursor.next_number > cursor.scanned_number)
+    }
+
+    /// Visit up to `limit` canon blocks, starting where the previous call has stopped, and
+    /// sanitize those, which are waiting in the pool. Blocks are sanitized in canon chain order.
+    /// Blocks, which are not (yet) in the canon chain, are kept in the pool.
+    pub fn sanitize_blocks(&mut self, limit: usize) -> Vec<SanitizedBlock> {
+        let mut cursor = match self.canon_cursor() {
+            Some(cursor) => cursor,
+            None => return Vec::new(),
+        };
+
+        let mut result = Vec::new();
+        let mut visited = 0;
+        while visited < limit && cursor.next_number <= cursor.scanned_number {
+            let number = cursor.next_number;
+            cursor.next_number += 1;
+            visited += 1;
+
+            let hash = match self.storage.block_hash(number) {
+                Some(hash) => hash,
+                None => break,
+            };
+            if self.unsanitized.remove(&hash).is_none() {
+                // forget spends of canonized, but not sanitized blocks
+                for tx in self.storage.indexed_block_transactions(number.into()) {
+                    if self.is_canonized_as_valid(&tx) {
+                        for input in &tx.raw.inputs {
+                            cursor.unspent.remove(&input.previous_output);
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let block = match self.storage.indexed_block(hash.clone().into()) {
+                Some(block) => block,
+                None => continue,
+            };
+
+            let transactions_validity =
+                self.sanitize_block(&block, number, &cursor.unspent, &cursor.spent);
+            for (tx, is_valid) in block.transactions.iter().zip(transactions_validity.iter()) {
+                if tx.raw.is_coinbase() {
+                    continue;
+                }
+
+                let is_canonized_as_valid = self.is_canonized_as_valid(tx);
+                for input in &tx.raw.inputs {
+                    match (is_canonized_as_valid, *is_valid) {
+                        (true, true) => {
+                            cursor.unspent.remove(&input.previous_output);
+                        }
+                        (false, true) => {
+                            cursor.spent.insert(input.previous_output.clone());
+                        }
+                        _ => (),
+                    }
+                }
+            }
+
+            trace!(target: "sync", "Sanitized block {} at height {}: {} of {} transactions are valid",
+                hash.to_reversed_str(), number,
+                transactions_validity.iter().filter(|is_valid| **is_valid).count(),
+                transactions_validity.len());
+
+            if let Err(err) = self
+                .storage
+                .insert_sanitized_block(&block, &transactions_validity)
+            {
+                warn!(target: "sync", "Failed to store sanitized block {}: {}", hash.to_reversed_str(), err);
+            }
+
+            result.push(SanitizedBlock {
+                hash: hash,
+                number: number,
+                transactions_validity: transactions_validity,
+            });
+        }
+
+        self.cursor = Some(cursor);
+        result
+    }
+
+    /// Add block to the pool, unless it is already waiting for sanitization
+    fn queue_block(&mut self, hash: H256, peer_index: Option<PeerIndex>) {
+        if self.unsanitized.contains_key(&hash) {
+            return;
+        }
+
+        self.queued
+            .push_back((self.storage.best_block().number, hash.clone()));
+        self.unsanitized.insert(hash, peer_index);
+    }
+
+    /// Remove blocks, which are still out of the canon chain, while the best block is above the best block
+    /// at the moment of their queueing by more than blocks ahead of the best block and the reorganization depth
+    fn remove_side_blocks(&mut self) {
+        let best_number = self.storage.best_block().number;
+        while let Some((queued_number, hash)) = self.queued.pop_front() {
+            if queued_number + MAX_BLOCKS_AHEAD + MAX_REORG_DEPTH >= best_number {
+                self.queued.push_front((queued_number, hash));
+                break;
+            }
+
+            // canon blocks are removed by sanitization
+            if self.storage.block_number(&hash).is_none()
+                && self.unsanitized.remove(&hash).is_some()
+            {
+                trace!(target: "sync", "Removed side chain block {} from shard blocks pool", hash.to_reversed_str());
+            }
+        }
+    }
+
+    /// Returns cursor, which knows spends of all canon blocks. The cursor is restarted from
+    /// the first canon block of the pool if the canon chain has been reorganized below its scanned block
+    fn canon_cursor(&mut self) -> Option<SanitizationCursor> {
+        let best_block = self.storage.best_block();
+        let storage = &self.storage;
+        let cursor = self.cursor.take().filter(|cursor| {
+            storage.block_hash(cursor.scanned_number).as_ref() == Some(&cursor.scanned_hash)
+        });
+
+        let (mut cursor, scan_from) = match cursor {
+            Some(cursor) => {
+                let scan_from = cursor.scanned_number + 1;
+                (cursor, scan_from)
+            }
+            None => {
+                let first_number = self
+                    .unsanitized
+                    .keys()
+                    .filter_map(|hash| self.storage.block_number(hash))
+                    .min()?;
+                let cursor = SanitizationCursor {
+                    next_number: first_number,
+                    scanned_number: best_block.number,
+                    scanned_hash: best_block.hash.clone(),
+                    unspent: HashSet::new(),
+                    spent: HashSet::new(),
+                };
+                (cursor, first_number)
+            }
+        };
+
+        // outputs, spent by blocks [next_number; best_number] are unspent at the next_number
+        for number in scan_from..best_block.number + 1 {
+            for tx in self.storage.indexed_block_transactions(number.into()) {
+                if self.is_canonized_as_valid(&tx) {
+                    cursor.unspent.extend(
+                        tx.raw
+                            .inputs
+                            .iter()
+                            .map(|input| input.previous_output.clone()),
+                    );
+                }
+            }
+        }
+        cursor.scanned_number = best_block.number;
+        cursor.scanned_hash = best_block.hash;
+
+        Some(cursor)
+    }
+
+    /// Verify every transaction of the block and return validity of transactions
+    fn sanitize_block(
+        &self,
+        block: &IndexedBlock,
+        number: BlockHeight,
+        unspent: &HashSet<OutPoint>,
+        spent: &HashSet<OutPoint>,
+    ) -> Vec<bool> {
+        let canon_block = CanonBlock::new(block);
+        let median_time_past = median_timestamp_inclusive(
+            block.header.raw.previous_header_hash.clone(),
+            self.storage.as_block_header_provider(),
+        );
+        let deployments = BlockDeployments::new(
+            &self.deployments,
+            number,
+            self.storage.as_block_header_provider(),
+            &self.consensus,
+        );
+        let noop = NoopStore;
+
+        let mut transactions_validity = HashMap::new();
+        let mut result = Vec::with_capacity(block.transactions.len());
+        for (tx_index, tx) in canon_block.transactions().into_iter().enumerate() {
+            let check_result = {
+                let view = SanitizerView {
+                    storage: &self.storage,
+                    block: block,
+                    height: number,
+                    transactions_validity: &transactions_validity,
+                    unspent: unspent,
+                    spent: spent,
+                };
+                let output_store = DuplexTransactionOutputProvider::new(&view, &noop);
+                TransactionAcceptor::new(
+                    &view,
+                    output_store,
+                    &self.consensus,
+                    tx,
+                    VerificationLevel::Full,
+                    block.hash(),
+                    number,
+                    block.header.raw.time,
+                    median_time_past,
+                    tx_index,
+                    &deployments,
+                )
+                .check()
+            };
+
+            if let Err(ref err) = check_result {
+                trace!(target: "sync", "Transaction {} is invalid: {:?}", tx.hash.to_reversed_str(), err);
+            }
+
+            let is_valid = check_result.is_ok();
+            transactions_validity.insert(tx.hash.clone(), is_valid);
+            result.push(is_valid);
+        }
+
+        result
+    }
+
+    /// Returns true if transaction inputs are marked as spent in the storage
+    fn is_canonized_as_valid(&self, tx: &IndexedTransaction) -> bool {
+        !tx.raw.is_coinbase()
+            && self
+                .storage
+                .transaction_meta(&tx.hash)
+                .map(|meta| meta.is_valid())
+                .unwrap_or(false)
+    }
+}
+
+impl<'a> SanitizerView<'a> {
+    /// Validity of the transaction, found by sanitization
+    fn sanitized_validity(&self, hash: &H256) -> Option<bool> {
+        self.transactions_validity
+            .get(hash)
+            .cloned()
+            .or_else(|| self.storage.sanitized_transaction(hash))
+    }
+
+    fn is_valid_transaction(&self, hash: &H256, meta: &TransactionMeta) -> bool {
+        self.sanitized_validity(hash)
+            .unwrap_or_else(|| meta.is_valid())
+    }
+}
+
+impl<'a> TransactionMetaProvider for SanitizerView<'a> {
+    fn transaction_meta(&self, hash: &H256) -> Option<TransactionMeta> {
+        match self.storage.transaction_meta(hash) {
+            Some(meta) => {
+                if meta.height() < self.height && self.is_valid_transaction(hash, &meta) {
+                    Some(meta)
+                } else {
+                    None
+                }
+            }
+            None => None,
+        }
+    }
+}
+
+impl<'a> TransactionOutputProvider for SanitizerView<'a> {
+    fn transaction_output(
+        &self,
+        prevout: &OutPoint,
+        transaction_index: usize,
+    ) -> Option<TransactionOutput> {
+        if self.sanitized_validity(&prevout.hash) == Some(false) {
+            return None;
+        }
+
+        // storage never returns outputs of transactions, canonized as invalid
+        match self.transaction_meta(&prevout.hash) {
+            Some(_) => self
+                .storage
+                .transaction(&prevout.hash)
+                .and_then(|tx| tx.outputs.into_iter().nth(prevout.index as usize)),
+            None => self.block.transaction_output(prevout, transaction_index),
+        }
+    }
+
+    fn is_spent(&self, prevout: &OutPoint) -> bool {
+        let is_marked_spent = self
+            .storage
+            .transaction_meta(&prevout.hash)
+            .and_then(|meta| meta.is_spent(prevout.index as usize))
+            .unwrap_or(false);
+        self.spent.contains(prevout)
+            || (is_marked_spent && !self.unspent.contains(prevout))
+            || self.block.is_spent(prevout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_data;
+
+    use super::{ShardBlocksPool, MAX_BLOCKS_AHEAD, MAX_REORG_DEPTH};
+    use chain::Block;
+    use db::kv::MemoryDatabase;
+    use db::BlockChainDatabase;
+    use network::{ConsensusFork, ConsensusParams, Network};
+    use primitives::hash::H256;
+    use std::sync::Arc;
+    use storage::TransactionErrorCode;
+
+    /// Error of the transaction, which is marked invalid by the canonization
+    const INVALID: Option<TransactionErrorCode> = Some(TransactionErrorCode::Overspend);
+
+    fn genesis() -> Block {
+        test_data::block_builder()
+            .transaction()
+            .coinbase()
+            .output()
+            .value(1)
+            .build()
+            .build()
+            .transaction()
+            .output()
+            .value(50)
+            .build()
+            .build()
+            .merkled_header()
+            .build()
+            .build()
+    }
+
+    fn spending_block(parent: H256, coinbase_value: u64, prevout: H256, value: u64) -> Block {
+        test_data::block_builder()
+            .transaction()
+            .coinbase()
+            .output()
+            .value(coinbase_value)
+            .build()
+            .build()
+            .transaction()
+            .input()
+            .hash(prevout)
+            .build()
+            .output()
+            .value(value)
+            .build()
+            .build()
+            .merkled_header()
+            .parent(parent)
+            .build()
+            .build()
+    }
+
+    fn create_pool(storage: Arc<BlockChainDatabase<MemoryDatabase>>) -> ShardBlocksPool {
+        ShardBlocksPool::new(
+            storage,
+            ConsensusParams::new(Network::Unitest, ConsensusFork::BitcoinCore),
+        )
+    }
+
+    #[test]
+    fn shard_blocks_pool_keeps_blocks_out_of_canon_chain() {
+        let genesis = genesis();
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            genesis.clone().into(),
+        ]));
+        let b1 = spending_block(genesis.hash(), 2, genesis.transactions()[1].hash(), 10);
+
+        let mut pool = create_pool(storage);
+        pool.store_block(0, &b1.clone().into());
+        assert_eq!(pool.len(), 1);
+        assert!(pool.sanitize_blocks(10).is_empty());
+        assert_eq!(pool.len(), 1);
+        assert!(pool.sanitized_block(&b1.hash()).is_none());
+    }
+
+    #[test]
+    fn shard_blocks_pool_detects_missing_inputs() {
+        let genesis = genesis();
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            genesis.clone().into(),
+        ]));
+        let b1 = test_data::block_builder()
+            .transaction()
+            .coinbase()
+            .output()
+            .value(2)
+            .build()
+            .build()
+            .transaction()
+            .input()
+            .hash(genesis.transactions()[1].hash())
+            .build()
+            .output()
+            .value(10)
+            .build()
+            .build()
+            .transaction()
+            .input()
+            .hash(H256::from(1))
+            .build()
+            .output()
+            .value(10)
+            .build()
+            .build()
+            .merkled_header()
+            .parent(genesis.hash())
+            .build()
+            .build();
+        storage.insert(b1.clone().into()).unwrap();
+        storage
+            .canonize_with_invalid(&b1.hash(), &[None, None, INVALID])
+            .unwrap();
+
+        let mut pool = create_pool(storage);
+        pool.store_block(0, &b1.clone().into());
+        let sanitized = pool.sanitize_blocks(10);
+        assert_eq!(sanitized.len(), 1);
+        assert_eq!(sanitized[0].number, 1);
+        assert_eq!(sanitized[0].transactions_validity, vec![true, true, false]);
+        assert_eq!(pool.len(), 0);
+        assert_eq!(
+            pool.transaction_validity(&b1.transactions()[1].hash()),
+            Some(true)
+        );
+        assert_eq!(
+            pool.transaction_validity(&b1.transactions()[2].hash()),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn shard_blocks_pool_detects_double_spend_in_later_block() {
+        let genesis = genesis();
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            genesis.clone().into(),
+        ]));
+        let b1 = spending_block(genesis.hash(), 2, genesis.transactions()[1].hash(), 10);
+        let b2 = spending_block(b1.hash(), 3, genesis.transactions()[1].hash(), 20);
+        storage.insert(b1.clone().into()).unwrap();
+        storage
+            .canonize_with_invalid(&b1.hash(), &[None, None])
+            .unwrap();
+        storage.insert(b2.clone().into()).unwrap();
+        storage
+            .canonize_with_invalid(&b2.hash(), &[None, INVALID])
+            .unwrap();
+
+        let mut pool = create_pool(storage);
+        // blocks are sanitized in canon chain order, not in order of arrival
+        pool.store_block(0, &b2.clone().into());
+        pool.store_block(0, &b1.clone().into());
+        let sanitized = pool.sanitize_blocks(10);
+        assert_eq!(sanitized.len(), 2);
+        assert_eq!(sanitized[0].hash, b1.hash());
+        assert_eq!(sanitized[0].transactions_validity, vec![true, true]);
+        assert_eq!(sanitized[1].hash, b2.hash());
+        assert_eq!(sanitized[1].transactions_validity, vec![true, false]);
+    }
+
+    #[test]
+    fn shard_blocks_pool_invalidates_spends_of_invalid_transactions() {
+        let genesis = genesis();
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            genesis.clone().into(),
+        ]));
+        let b1 = spending_block(genesis.hash(), 2, H256::from(1), 10);
+        let b2 = spending_block(b1.hash(), 3, b1.transactions()[1].hash(), 5);
+        storage.insert(b1.clone().into()).unwrap();
+        storage
+            .canonize_with_invalid(&b1.hash(), &[None, INVALID])
+            .unwrap();
+        storage.insert(b2.clone().into()).unwrap();
+        storage
+            .canonize_with_invalid(&b2.hash(), &[None, None])
+            .unwrap();
+
+        let mut pool = create_pool(storage);
+        pool.store_block(0, &b1.clone().into());
+        pool.store_block(0, &b2.clone().into());
+
+        let sanitized = pool.sanitize_blocks(1);
+        assert_eq!(sanitized.len(), 1);
+        assert_eq!(sanitized[0].transactions_validity, vec![true, false]);
+        assert_eq!(pool.len(), 1);
+        assert!(!pool.is_sanitization_complete());
+
+        // next batch continues from the next block
+        let sanitized = pool.sanitize_blocks(1);
+        assert_eq!(sanitized.len(), 1);
+        assert_eq!(sanitized[0].transactions_validity, vec![true, false]);
+        assert_eq!(pool.len(), 0);
+        assert!(pool.is_sanitization_complete());
+    }
+
+    #[test]
+    fn shard_blocks_pool_keeps_sanitized_blocks_in_storage() {
+        let genesis = genesis();
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            genesis.clone().into(),
+        ]));
+        let b1 = spending_block(genesis.hash(), 2, H256::from(1), 10);
+        storage.insert(b1.clone().into()).unwrap();
+        storage
+            .canonize_with_invalid(&b1.hash(), &[None, INVALID])
+            .unwrap();
+
+        let mut pool = create_pool(storage.clone());
+        pool.store_block(0, &b1.clone().into());
+        assert_eq!(pool.sanitize_blocks(10).len(), 1);
+
+        // sanitization results survive the restart
+        let mut pool = create_pool(storage);
+        assert_eq!(
+            pool.sanitized_block(&b1.hash()).map(|block| block.transactions_validity),
+            Some(vec![true, false])
+        );
+        assert_eq!(
+            pool.transaction_validity(&b1.transactions()[1].hash()),
+            Some(false)
+        );
+        pool.store_block(0, &b1.clone().into());
+        assert_eq!(pool.len(), 0);
+    }
+
+    #[test]
+    fn shard_blocks_pool_queues_unsanitized_canon_blocks() {
+        let genesis = genesis();
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            genesis.clone().into(),
+        ]));
+        let b1 = spending_block(genesis.hash(), 2, genesis.transactions()[1].hash(), 10);
+        let b2 = spending_block(b1.hash(), 3, b1.transactions()[1].hash(), 5);
+        storage.insert(b1.clone().into()).unwrap();
+        storage
+            .canonize_with_invalid(&b1.hash(), &[None, None])
+            .unwrap();
+        storage.insert(b2.clone().into()).unwrap();
+        storage
+            .canonize_with_invalid(&b2.hash(), &[None, None])
+            .unwrap();
+
+        let mut pool = create_pool(storage.clone());
+        pool.store_block(0, &b1.clone().into());
+        assert_eq!(pool.sanitize_blocks(10).len(), 1);
+
+        // b2 has been canonized, but it was not in the pool when the node has been stopped
+        let mut pool = create_pool(storage);
+        assert_eq!(pool.queue_unsanitized_blocks(), 1);
+        assert_eq!(pool.len(), 1);
+        let sanitized = pool.sanitize_blocks(10);
+        assert_eq!(sanitized.len(), 1);
+        assert_eq!(sanitized[0].hash, b2.hash());
+        assert_eq!(sanitized[0].transactions_validity, vec![true, true]);
+        assert_eq!(pool.queue_unsanitized_blocks(), 0);
+    }
+
+    #[test]
+    fn shard_blocks_pool_removes_stale_side_chain_blocks() {
+        let genesis = genesis();
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            genesis.clone().into(),
+        ]));
+        let side_block = spending_block(genesis.hash(), 1_000_000, H256::from(2), 1);
+
+        let mut pool = create_pool(storage.clone());
+        pool.store_block(0, &side_block.clone().into());
+
+        let mut parent = genesis.hash();
+        let mut last_block = genesis;
+        for number in 1..MAX_BLOCKS_AHEAD + MAX_REORG_DEPTH + 2 {
+            last_block = test_data::block_builder()
+                .transaction()
+                .coinbase()
+                .output()
+                .value(number as u64)
+                .build()
+                .build()
+                .merkled_header()
+                .parent(parent)
+                .build()
+                .build();
+            parent = last_block.hash();
+            storage.insert(last_block.clone().into()).unwrap();
+            storage.canonize_with_invalid(&parent, &[None]).unwrap();
+        }
+
+        // side chain block is removed once the best block is deep enough above it
+        pool.store_block(0, &last_block.clone().into());
+        assert_eq!(pool.len(), 1);
+        let sanitized = pool.sanitize_blocks(10);
+        assert_eq!(sanitized.len(), 1);
+        assert_eq!(sanitized[0].hash, last_block.hash());
+    }
+}
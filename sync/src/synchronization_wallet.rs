@@ -8,30 +8,78 @@ use message::{types, common};
 use primitives::hash::H256;
 use synchronization_executor::{Task, TaskExecutor};
 use types::{PeerIndex, RequestId, BlockHeight, StorageRef, ExecutorRef, MemoryPoolRef, PeersRef, LocalNodeRef};
-use utils::KnownHashType;
+use utils::{KnownHashType, MemoryPoolTransactionOutputProvider};
 use chain::{Transaction, TransactionInput, TransactionOutput, IndexedTransaction};
 
 // for wallet
-use keys::{KeyPair, Public, Private, AddressHash};
+use keys::{KeyPair, Public, Private, Address, AddressHash, DerivationPath, ExtendedPrivateKey, Mnemonic, HARDENED_INDEX};
+use keys::Error as KeysError;
 use keys::Network as Key_Network;
-use keys::generator::Random;
-use keys::generator::Generator;
+use keys::Type as Key_Type;
+use keys::AddressPayload;
+use network::Network;
 use script::Builder as ScriptBuilder;
 use chain::OutPoint;
 use script::TransactionInputSigner;
 use script::UnsignedTransactionInput;
 use script::SignatureVersion;
-use primitives::bytes::Bytes;
+use script::{Opcode, Psbt, MAX_SCRIPT_ELEMENT_SIZE};
+use bitcrypto::dhash160;
 use std::collections::HashSet;
 use std::convert::From;
+use std::mem;
+use std::str::FromStr;
+use chain::IndexedBlock;
+use primitives::bytes::Bytes;
+use script::Script;
+use wallet::{WalletDb, WalletDbError};
+use rand::thread_rng;
+use synchronization_coin_selection::{
+    estimate_input_size, select_coins, CoinInput, CoinSelection, CoinSelectionStrategy, OutputSizes, DEFAULT_FEE_RATE,
+    DUST_THRESHOLD,
+};
+use storage::TransactionOutputProvider;
+use BlockListener;
 
+/// Number of words in the mnemonic of the new wallet
+const MNEMONIC_WORDS: usize = 12;
+/// Sighash type of all wallet signatures
+const SIGHASH_ALL: u32 = 1;
+/// Max number of keys of multisig redeem script (key count is pushed with OP_1..OP_16)
+const MAX_MULTISIG_KEYS: usize = 16;
+/// Max number of receive (and change) keys, derived when the wallet is restored from the mnemonic
+pub const MAX_RESTORED_KEYS: u32 = 1000;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum WalletError {
     InsufficientMoney,
     EmptyKeySpace,
     MissingKeypairForAddressHash,
-    DuplicatePublicKey,
+    /// Payment value is below dust threshold
+    DustPayment,
+    /// Fee of signed transaction is less than required by the wallet fee rate
+    InsufficientFee,
+    /// Invalid number of required signatures or public keys of multisig address
+    InvalidMultisig,
+    Database(WalletDbError),
+    /// Failed to generate or derive wallet keys
+    Keys(KeysError),
+    /// Rescan has reached block, which body has been pruned
+    BlockPruned(u32),
+    /// Sighash type of PSBT input differs from the requested one, or the requested one does not fit in a byte
+    SighashTypeMismatch(usize),
+}
+
+impl From<WalletDbError> for WalletError {
+    fn from(err: WalletDbError) -> Self {
+        WalletError::Database(err)
+    }
+}
+
+impl From<KeysError> for WalletError {
+    fn from(err: KeysError) -> Self {
+        WalletError::Keys(err)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Default, Hash)]
@@ -60,19 +108,22 @@ impl From<Coin> for CoinAccessor {
 }
 
 
-#[derive(Debug, PartialEq, Eq, Clone, Default, Hash)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub struct Coin {
     id: String,
     outpoint: OutPoint,
-    recipient_addr: AddressHash, //recipient
+    /// Type of the output: P2PKH, P2WPKH or P2SH (P2WPKH program, nested in P2SH)
+    kind: Key_Type,
+    recipient_addr: AddressHash, //recipient: public key hash or P2SH script hash
     value: u64
 }
 
 impl Coin {
-    pub fn new(id: String, outp: OutPoint, recipient_addr: AddressHash, value: u64) -> Self {
+    pub fn new(id: String, outp: OutPoint, kind: Key_Type, recipient_addr: AddressHash, value: u64) -> Self {
         Coin {
             id,
             outpoint: outp,
+            kind,
             recipient_addr,
             value
         }
@@ -80,42 +131,370 @@ impl Coin {
 
     pub fn get_id(&self) -> String {self.id.clone()}
     pub fn get_outpoint(&self) -> OutPoint {self.outpoint.clone()}
+    pub fn get_recipient_addr(&self) -> AddressHash {self.recipient_addr.clone()}
+    pub fn get_value(&self) -> u64 {self.value}
+}
+
+/// Outpoint, tracked by the wallet, as it is seen by the local node
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoinState {
+    pub id: String,
+    pub outpoint: OutPoint,
+    /// Address, the output is paying to. None if the output is unknown to the node
+    pub address: Option<Address>,
+    /// Value of the output. None if the output is unknown to the node
+    pub value: Option<u64>,
+    /// Number of canonical blocks, confirming the transaction. 0 if it is not in the chain
+    pub confirmations: u32,
+    /// Validity of the transaction. None if the transaction is unknown to the node
+    pub valid: Option<bool>,
+    /// Is the coin in the set of spendable wallet coins?
+    pub spendable: bool,
+}
+
+/// Type of wallet receive and change addresses
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AddressType {
+    /// P2PKH address
+    Legacy,
+    /// P2WPKH program, nested in P2SH address
+    P2shSegwit,
+    /// Native P2WPKH (bech32) address
+    Bech32,
+}
+
+impl AddressType {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            AddressType::Legacy => "legacy",
+            AddressType::P2shSegwit => "p2sh-segwit",
+            AddressType::Bech32 => "bech32",
+        }
+    }
+}
+
+impl FromStr for AddressType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "legacy" => Ok(AddressType::Legacy),
+            "p2sh-segwit" => Ok(AddressType::P2shSegwit),
+            "bech32" => Ok(AddressType::Bech32),
+            address_type => Err(format!("Unknown address type: {}", address_type)),
+        }
+    }
+}
+
+/// Wallet outpoints, found in connected and disconnected blocks.
+/// Blocks are processed with synchronization chain locked => wallet is only updated on next access.
+#[derive(Default)]
+struct ChainEvents {
+    /// Addresses of the wallet keys and P2SH script hashes of their nested P2WPKH programs
+    addresses: HashSet<AddressHash>,
+    /// Outputs of connected blocks, paying to the wallet
+    received: Vec<OutPoint>,
+    /// Outputs, spent by disconnected blocks. Those, paying to the wallet, are spendable again
+    unspent: Vec<OutPoint>,
+}
+
+/// Collects wallet outpoints from the canonical chain events
+struct WalletBlockListener {
+    events: Arc<Mutex<ChainEvents>>,
+}
+
+impl BlockListener for WalletBlockListener {
+    fn block_connected(&self, block: &IndexedBlock) {
+        let mut events = self.events.lock();
+        for tx in &block.transactions {
+            for (index, output) in tx.raw.outputs.iter().enumerate() {
+                if output_addresses(&output.script_pubkey).iter().any(|&(_, ref address)| events.addresses.contains(address)) {
+                    events.received.push(OutPoint { hash: tx.hash.clone(), index: index as u32 });
+                }
+            }
+        }
+    }
+
+    fn block_disconnected(&self, block: &IndexedBlock) {
+        let mut events = self.events.lock();
+        for tx in block.transactions.iter().filter(|tx| !tx.raw.is_coinbase()) {
+            events.unspent.extend(tx.raw.inputs.iter().map(|input| input.previous_output.clone()));
+        }
+    }
+}
+
+/// Destinations of P2PK, P2PKH, multisig, P2WPKH or P2SH output with given script:
+/// public key hashes (P2PKH and P2WPKH) and script hashes (P2SH)
+pub fn output_addresses(script_pubkey: &Bytes) -> Vec<(Key_Type, AddressHash)> {
+    let script: Script = script_pubkey.clone().into();
+    script.extract_destinations()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|address| match (address.kind, address.hash) {
+            (kind @ Key_Type::P2PKH, AddressPayload::Hash(hash)) |
+            (kind @ Key_Type::P2WPKH, AddressPayload::Hash(hash)) |
+            (kind @ Key_Type::P2SH, AddressPayload::Hash(hash)) => Some((kind, hash)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// P2WPKH program of the public key hash
+fn witness_program(key_hash: &AddressHash) -> Script {
+    ScriptBuilder::build_p2wpkh(key_hash)
+}
+
+/// P2SH script hash of the P2WPKH program, nested in P2SH output
+fn nested_script_hash(key_hash: &AddressHash) -> AddressHash {
+    dhash160(&witness_program(key_hash))
+}
+
+/// Is data a SIGHASH_ALL signature of the message, made with given key?
+fn is_signature_of(key: &Public, message: &H256, data: &[u8]) -> bool {
+    match data.split_last() {
+        Some((&sighash, signature)) if sighash as u32 == SIGHASH_ALL => key.verify(message, &signature.into()).unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// P2SH script hashes of the nested P2WPKH programs of the wallet keys.
+/// Uncompressed keys are not allowed in witness programs => they have no nested scripts.
+fn nested_script_hashes(keypairs: &HashMap<AddressHash, KeyPair>) -> HashMap<AddressHash, AddressHash> {
+    keypairs.iter()
+        .filter(|&(_, kp)| match *kp.public() {
+            Public::Compressed(_) => true,
+            Public::Normal(_) => false,
+        })
+        .map(|(key_hash, _)| (nested_script_hash(key_hash), key_hash.clone()))
+        .collect()
+}
+
+/// Key of the first BIP44 account of the mnemonic seed
+fn account_key(mnemonic: &Mnemonic, network: Key_Network) -> Result<ExtendedPrivateKey, WalletError> {
+    let master = ExtendedPrivateKey::master(&*mnemonic.to_seed(""), network)?;
+    Ok(master.derive_path(&DerivationPath::bip44_account(network, 0))?)
 }
 
 pub struct Wallet {
     local_node: LocalNodeRef,
+    db: WalletDb,
+    chain_events: Arc<Mutex<ChainEvents>>,
 	coins: HashSet<Coin>,
 	keypairs: HashMap<AddressHash, KeyPair>,
+    /// Key hashes of the wallet keys by P2SH script hashes of their nested P2WPKH programs
+    nested_keys: HashMap<AddressHash, AddressHash>,
+    /// Redeem scripts of multisig P2SH addresses by their script hashes
+    redeem_scripts: HashMap<AddressHash, Script>,
     coins_candidate: HashSet<CoinAccessor>,
     num_coin: u64,
+    coin_selection: CoinSelectionStrategy,
+    fee_rate: u64,
+    /// Type of addresses, generated by default and used for change
+    address_type: AddressType,
+    /// Network of derived keys
+    network: Key_Network,
+    /// BIP44 account key, all wallet keys are derived from
+    account_key: ExtendedPrivateKey,
 }
 
 impl Wallet {
-	pub fn new(local_sync_node: LocalNodeRef) -> Self {
-        Wallet {
+	/// Create wallet, restoring keys and tracked outpoints from the wallet database.
+	/// Wallet is subscribed to canonical chain events of the local node.
+	/// New wallet database is initialized with the random mnemonic.
+	pub fn new(local_sync_node: LocalNodeRef, db: WalletDb) -> Result<Self, WalletError> {
+        let keypairs: HashMap<AddressHash, KeyPair> = db.keypairs()?
+            .into_iter()
+            .map(|kp| (kp.public().address_hash(), kp))
+            .collect();
+        let coins_candidate: HashSet<CoinAccessor> = db.outpoints()?
+            .into_iter()
+            .map(|(outpoint, id)| CoinAccessor::new(id, outpoint))
+            .collect();
+        let num_coin = db.coin_number()?;
+
+        let network = match local_sync_node.network() {
+            Network::Mainnet => Key_Network::Mainnet,
+            _ => Key_Network::Testnet,
+        };
+        let mnemonic = match db.mnemonic()? {
+            Some(mnemonic) => mnemonic,
+            None => {
+                let mnemonic = Mnemonic::generate(MNEMONIC_WORDS)?;
+                db.set_mnemonic(&mnemonic)?;
+                mnemonic
+            },
+        };
+        let account_key = account_key(&mnemonic, network)?;
+        let nested_keys = nested_script_hashes(&keypairs);
+        let redeem_scripts: HashMap<AddressHash, Script> = db.redeem_scripts()?
+            .into_iter()
+            .map(|redeem_script| (dhash160(&redeem_script), redeem_script.into()))
+            .collect();
+
+        let chain_events = Arc::new(Mutex::new(ChainEvents {
+            addresses: keypairs.keys().chain(nested_keys.keys()).chain(redeem_scripts.keys()).cloned().collect(),
+            ..Default::default()
+        }));
+        local_sync_node.install_block_listener(Box::new(WalletBlockListener {
+            events: chain_events.clone(),
+        }));
+
+        let mut wallet = Wallet {
             local_node: local_sync_node,
+            db: db,
+            chain_events: chain_events,
             coins: HashSet::new(),
-            keypairs: HashMap::new(),
-            coins_candidate: HashSet::new(),
-            num_coin: 0,
-        }
+            keypairs: keypairs,
+            nested_keys: nested_keys,
+            redeem_scripts: redeem_scripts,
+            num_coin: num_coin,
+            coins_candidate: coins_candidate,
+            coin_selection: CoinSelectionStrategy::BranchAndBound,
+            fee_rate: DEFAULT_FEE_RATE,
+            address_type: AddressType::Legacy,
+            network: network,
+            account_key: account_key,
+        };
+        wallet.update_wallet()?;
+        Ok(wallet)
 	}
 
+	/// Derive next key of the wallet receive chain
 	pub fn generate_keypair(&mut self) -> Result<AddressHash, WalletError> {
-		let kp_generator = Random::new(Key_Network::Testnet);
-		let kp = kp_generator.generate().unwrap();
+		self.derive_keypair(false)
+	}
 
-		let pub_key_hash = kp.public().address_hash();
+	/// Derive next key of the receive (or change) chain: m/44'/coin_type'/0'/change/index
+	fn derive_keypair(&mut self, change: bool) -> Result<AddressHash, WalletError> {
+		let (index, kp) = self.next_keypair(change)?;
+		self.store_keypair(change, index, kp)
+	}
 
-		if self.keypairs.contains_key(&pub_key_hash) {
-			println!("pubkey exists, no key generated, retry");
-            return Err(WalletError::DuplicatePublicKey);
-		} else {
+	/// Next key of the receive (or change) chain and its index. The key is not stored until `store_keypair`
+	fn next_keypair(&self, change: bool) -> Result<(u32, KeyPair), WalletError> {
+		let index = self.db.next_key_index(change)?;
+		// hardened children could not be derived from the public chain key
+		if index >= HARDENED_INDEX {
+			return Err(WalletError::EmptyKeySpace);
+		}
+		let kp = self.account_key
+			.derive_child(change as u32)
+			.and_then(|chain_key| chain_key.derive_child(index))
+			.and_then(|key| key.key_pair())?;
+		Ok((index, kp))
+	}
+
+	/// Store derived key and move the chain to the next index
+	fn store_keypair(&mut self, change: bool, index: u32, kp: KeyPair) -> Result<AddressHash, WalletError> {
+		let next_index = index.checked_add(1).ok_or(WalletError::EmptyKeySpace)?;
+		self.db.set_next_key_index(change, next_index)?;
+
+		let pub_key_hash = kp.public().address_hash();
+		if !self.keypairs.contains_key(&pub_key_hash) {
+			self.db.insert_keypair(&kp)?;
+			// derived keys are compressed => they could be used in witness programs
+			let script_hash = nested_script_hash(&pub_key_hash);
+			{
+				let mut events = self.chain_events.lock();
+				events.addresses.insert(pub_key_hash.clone());
+				events.addresses.insert(script_hash.clone());
+			}
+			self.nested_keys.insert(script_hash, pub_key_hash.clone());
 			self.keypairs.insert(pub_key_hash.clone(), kp);
 		}
         Ok(pub_key_hash)
 	}
 
+	/// Derive next key of the wallet receive chain and return its address of given type
+	pub fn generate_address(&mut self, address_type: AddressType) -> Result<Address, WalletError> {
+		let key_hash = self.derive_keypair(false)?;
+		Ok(self.key_address(address_type, &key_hash))
+	}
+
+	/// Address of given type, paying to the wallet key
+	fn key_address(&self, address_type: AddressType, key_hash: &AddressHash) -> Address {
+		let (kind, hash) = match address_type {
+			AddressType::Legacy => (Key_Type::P2PKH, key_hash.clone()),
+			AddressType::P2shSegwit => (Key_Type::P2SH, nested_script_hash(key_hash)),
+			AddressType::Bech32 => (Key_Type::P2WPKH, key_hash.clone()),
+		};
+		Address {
+			kind: kind,
+			network: self.network,
+			hash: hash.into(),
+		}
+	}
+
+    /// m-of-n multisig P2SH address and its redeem script
+    pub fn multisig_address(&self, required: u8, keys: &[Public]) -> Result<(Address, Script), WalletError> {
+        if required == 0 || required as usize > keys.len() || keys.len() > MAX_MULTISIG_KEYS {
+            return Err(WalletError::InvalidMultisig);
+        }
+
+        // redeem script is pushed to the script_sig of spending input
+        let redeem_script = ScriptBuilder::build_multisig(required, keys);
+        if redeem_script.len() > MAX_SCRIPT_ELEMENT_SIZE {
+            return Err(WalletError::InvalidMultisig);
+        }
+
+        let address = Address {
+            kind: Key_Type::P2SH,
+            network: self.network,
+            hash: dhash160(&redeem_script).into(),
+        };
+        Ok((address, redeem_script))
+    }
+
+    /// Add m-of-n multisig P2SH address to the wallet. Outputs, paying to the address, are tracked by the wallet
+    /// and are spendable if the wallet holds enough keys. Otherwise they are signed with `sign_multisig_transaction`
+    pub fn add_multisig_address(&mut self, required: u8, keys: &[Public]) -> Result<(Address, Script), WalletError> {
+        let (address, redeem_script) = self.multisig_address(required, keys)?;
+        let script_hash = dhash160(&redeem_script);
+        if !self.redeem_scripts.contains_key(&script_hash) {
+            self.db.insert_redeem_script(&redeem_script)?;
+            self.chain_events.lock().addresses.insert(script_hash.clone());
+            self.redeem_scripts.insert(script_hash, redeem_script.clone());
+        }
+        Ok((address, redeem_script))
+    }
+
+    /// Public key of the wallet key, spending outputs of P2PKH, P2SH-P2WPKH or P2WPKH address
+    pub fn address_public_key(&self, address: &Address) -> Option<&Public> {
+        match address.hash {
+            AddressPayload::Hash(ref hash) => self.spending_key_hash(address.kind, hash)
+                .and_then(|key_hash| self.keypairs.get(&key_hash))
+                .map(KeyPair::public),
+            AddressPayload::WitnessProgram(_) => None,
+        }
+    }
+
+    /// Redeem script of the wallet multisig address
+    pub fn address_redeem_script(&self, address: &Address) -> Option<&Script> {
+        match (address.kind, &address.hash) {
+            (Key_Type::P2SH, &AddressPayload::Hash(ref hash)) => self.redeem_scripts.get(hash),
+            _ => None,
+        }
+    }
+
+    /// Mnemonic, wallet keys are derived from
+    pub fn mnemonic(&self) -> Result<Mnemonic, WalletError> {
+        self.db.mnemonic()?.ok_or(WalletError::Database(WalletDbError::CorruptedRecord))
+    }
+
+    /// Switch wallet to keys of the given mnemonic: derive first `key_count` receive and change keys
+    /// and rescan canonical chain for their coins. Previously generated keys are kept.
+    pub fn restore(&mut self, mnemonic: Mnemonic, key_count: u32) -> Result<(), WalletError> {
+        self.account_key = account_key(&mnemonic, self.network)?;
+        self.db.set_mnemonic(&mnemonic)?;
+        self.db.set_next_key_index(false, 0)?;
+        self.db.set_next_key_index(true, 0)?;
+        for _ in 0..key_count {
+            self.derive_keypair(false)?;
+            self.derive_keypair(true)?;
+        }
+        self.rescan(0)
+    }
+
     pub fn get_pubkey(&self) -> Result<&Public, WalletError> {
         if let Some(keypair) = self.keypairs.values().next() {
             return Ok(keypair.public());
@@ -132,6 +511,36 @@ impl Wallet {
     }
 
 
+    /// Coin selection strategy of wallet payments
+    pub fn coin_selection(&self) -> CoinSelectionStrategy {
+        self.coin_selection
+    }
+
+    /// Change coin selection strategy of wallet payments
+    pub fn set_coin_selection(&mut self, strategy: CoinSelectionStrategy) {
+        self.coin_selection = strategy;
+    }
+
+    /// Type of addresses, generated by default and used for change
+    pub fn address_type(&self) -> AddressType {
+        self.address_type
+    }
+
+    /// Change type of addresses, generated by default and used for change
+    pub fn set_address_type(&mut self, address_type: AddressType) {
+        self.address_type = address_type;
+    }
+
+    /// Fee rate of wallet payments (satoshis per byte)
+    pub fn fee_rate(&self) -> u64 {
+        self.fee_rate
+    }
+
+    /// Set fee rate of wallet payments (satoshis per byte)
+    pub fn set_fee_rate(&mut self, fee_rate: u64) {
+        self.fee_rate = fee_rate;
+    }
+
     pub fn get_balance(&self) -> u64 {
         let balance =   self.coins
                         .iter()
@@ -143,72 +552,90 @@ impl Wallet {
         balance
     }
 
-    fn delete_coin(&mut self, coin: &Coin) {
+    fn delete_coin(&mut self, coin: &Coin) -> Result<(), WalletError> {
+        self.db.delete_outpoint(&coin.outpoint)?;
         self.coins.remove(coin);
         self.coins_candidate.remove(&CoinAccessor {
             id: coin.id.clone(),
             outpoint: coin.outpoint.clone(),
         });
+        Ok(())
     }
 
-    fn add_coin_candidate(&mut self, outpoint: OutPoint, desc: String) {
+    /// Is outpoint in the set of coins, which are checked by `update_wallet`?
+    fn is_tracked(&self, outpoint: &OutPoint) -> bool {
+        self.coins_candidate.iter().any(|coin| &coin.outpoint == outpoint)
+    }
+
+    /// Hash of the wallet key, which is able to spend output of given type, paying to given hash
+    fn spending_key_hash(&self, kind: Key_Type, hash: &AddressHash) -> Option<AddressHash> {
+        match kind {
+            Key_Type::P2PKH | Key_Type::P2WPKH if self.keypairs.contains_key(hash) => Some(hash.clone()),
+            Key_Type::P2SH => self.nested_keys.get(hash).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Redeem script of the wallet multisig address, output of given type and hash is paying to
+    fn multisig_redeem_script(&self, kind: Key_Type, hash: &AddressHash) -> Option<&Script> {
+        match kind {
+            Key_Type::P2SH => self.redeem_scripts.get(hash),
+            _ => None,
+        }
+    }
+
+    /// Does output with given script pay to one of the wallet keys or multisig addresses?
+    fn is_mine(&self, script_pubkey: &Bytes) -> bool {
+        output_addresses(script_pubkey).into_iter().any(|(kind, hash)| {
+            self.spending_key_hash(kind, &hash).is_some() || self.multisig_redeem_script(kind, &hash).is_some()
+        })
+    }
+
+    /// Does wallet hold enough keys to sign input, spending the coin? Multisig coins may require signatures of other wallets
+    fn is_solvable(&self, coin: &Coin) -> bool {
+        match self.multisig_redeem_script(coin.kind, &coin.recipient_addr) {
+            Some(redeem_script) => {
+                let required = redeem_script.num_signatures_required() as usize;
+                redeem_script.extract_multisig_keys()
+                    .map(|keys| keys.iter().filter(|key| self.keypairs.contains_key(&key.address_hash())).count() >= required)
+                    .unwrap_or(false)
+            },
+            None => true,
+        }
+    }
+
+    /// Add outpoint to the set of coins, which are checked by `update_wallet`
+    pub fn add_coin_candidate(&mut self, outpoint: OutPoint, desc: String) -> Result<(), WalletError> {
         self.num_coin += 1;
         let msg = format!("{}: {}", self.num_coin, desc);
+        self.db.insert_outpoint(&outpoint, self.num_coin, &msg)?;
         self.coins_candidate.insert(
             CoinAccessor {
                 id: msg,
                 outpoint,
             }
         );
+        Ok(())
     }
 
-    //Transaction
-    fn create_transaction(&mut self, recipient: AddressHash, value: u64) -> Result<Transaction, WalletError> {
-        let mut coins_to_use: Vec<Coin> = vec![];
-        let mut value_sum = 0u64;
-
-        // iterate thru our wallet
-        for coin in self.coins.iter() {
-            value_sum += coin.value;
-            coins_to_use.push(coin.clone()); // coins that will be used for this transaction
-            if value_sum >= value {
-                // if we already have enough money, break
-                break;
-            }
-        }
-        if value_sum < value {
-            // we don't have enough money in wallet
-            println!("WalletError::InsufficientMoney {} < {}", value_sum, value);
-            return Err(WalletError::InsufficientMoney);
-        }
-
-        let script = ScriptBuilder::build_p2pkh(&recipient);
-
-        // if we have enough money in our wallet, create tx
-
-        //tx output currently, single only
-        let mut transaction_outputs = vec![
-            TransactionOutput {
-                value: value,
-                script_pubkey: script.to_bytes(),
-        }];
-
-        if value_sum > value {
-            // transfer the remaining value back to self
-            let self_recipient = self.get_addresshash()?;
-            let pay_self_script = ScriptBuilder::build_p2pkh(&self_recipient);
-            transaction_outputs.push(
-                TransactionOutput {
-                    value: value_sum - value,
-                    script_pubkey: pay_self_script.to_bytes(),
-                }
-            );
-        };
+    /// Select coins, paying given value and fee at `fee_rate` of transaction with outputs of given sizes,
+    /// using the wallet coin selection strategy
+    pub fn select_coins(&self, value: u64, fee_rate: u64, outputs: &OutputSizes) -> Result<CoinSelection, WalletError> {
+        let coins: Vec<CoinInput> = self.coins.iter()
+            .map(|coin| CoinInput {
+                coin: coin.clone(),
+                size: estimate_input_size(coin.kind, self.multisig_redeem_script(coin.kind, &coin.recipient_addr)),
+            })
+            .collect();
+        select_coins(self.coin_selection, &coins, value, fee_rate, outputs, &mut thread_rng())
+    }
 
+    /// Create transaction, spending given coins, and sign all its inputs
+    pub fn sign_transaction(&self, coins_to_use: &[Coin], transaction_outputs: Vec<TransactionOutput>) -> Result<Transaction, WalletError> {
         // create unsigned transaction inputs
         let mut unsigned_inputs: Vec<UnsignedTransactionInput> = vec![];
-        for coin in &coins_to_use {
-            println!("use coin {:?}\n", coin.id);
+        for coin in coins_to_use {
+            trace!(target: "wallet", "Using coin {:?}", coin.id);
             unsigned_inputs.push(UnsignedTransactionInput {
                     previous_output: coin.outpoint.clone(),
                     sequence: 0x00,
@@ -226,48 +653,293 @@ impl Wallet {
         let mut signed_inputs: Vec<TransactionInput> = vec![];
 
         for (i, coin) in coins_to_use.iter().enumerate() {
-            let keypair = match self.keypairs.get(&coin.recipient_addr) {
-                None => {
-                    //println!("MissingKeypairForAddressHash");
-                    //println!("coin.recipient_addr {:#?}", coin.recipient_addr);
-                    //for kp in self.keypairs.values() {
-                    //    println!("keypair {:#?}", kp.public().address_hash());
-                    //}
+            if let Some(redeem_script) = self.multisig_redeem_script(coin.kind, &coin.recipient_addr) {
+                let (script_sig, complete) = self.multisig_script_sig(&unsigned_transactions, i, redeem_script, &Bytes::default())?;
+                if !complete {
                     return Err(WalletError::MissingKeypairForAddressHash);
+                }
+                signed_inputs.push(TransactionInput {
+                    previous_output: coin.outpoint.clone(),
+                    sequence: unsigned_transactions.inputs[i].sequence,
+                    script_sig: script_sig,
+                    script_witness: vec![],
+                });
+                continue;
+            }
+
+            let key_hash = match self.spending_key_hash(coin.kind, &coin.recipient_addr) {
+                None => return Err(WalletError::MissingKeypairForAddressHash),
+                Some(key_hash) => key_hash,
+            };
+            let keypair = &self.keypairs[&key_hash];
+            // script code of P2WPKH input is the P2PKH script of its key (BIP143)
+            let to_me_pubkey_script = ScriptBuilder::build_p2pkh(&key_hash);
+            let signed_input = match coin.kind {
+                Key_Type::P2PKH => unsigned_transactions.signed_input(keypair, i, coin.value,
+                            &to_me_pubkey_script, SignatureVersion::Base, SIGHASH_ALL),
+                Key_Type::P2WPKH => unsigned_transactions.signed_input(keypair, i, coin.value,
+                            &to_me_pubkey_script, SignatureVersion::WitnessV0, SIGHASH_ALL),
+                _ => {
+                    // P2SH input reveals nested witness program in its script_sig
+                    let mut input = unsigned_transactions.signed_input(keypair, i, coin.value,
+                            &to_me_pubkey_script, SignatureVersion::WitnessV0, SIGHASH_ALL);
+                    input.script_sig = ScriptBuilder::default()
+                        .push_data(&witness_program(&key_hash))
+                        .into_bytes();
+                    input
                 },
-                Some(kp) => kp,
             };
-            //println!("use keypair {:#?}", keypair);
-            //println!("addresshash {:#?}\n", keypair.public().address_hash());
-            let to_me_pubkey_script = ScriptBuilder::build_p2pkh(&coin.recipient_addr);
-            signed_inputs.push(
-                unsigned_transactions.signed_input(keypair, i, coin.value,
-                            &to_me_pubkey_script, SignatureVersion::Base, 0x40)
-            );
-        }
-
-        // remove used coin from wallet
-        for c in &coins_to_use {
-            println!("delete coin id {:?}, value {}", c.get_id(), c.value);
-            self.delete_coin(c);
+            signed_inputs.push(signed_input);
         }
 
-        let transaction = Transaction {
+        Ok(Transaction {
             version: 1,
             inputs: signed_inputs,
             outputs: transaction_outputs,
             lock_time: 0,
+        })
+    }
+
+    /// Script_sig of input, spending multisig P2SH output. Signatures, found in the given script_sig, are merged
+    /// with signatures of the wallet keys. Returns script_sig and whether it has enough signatures
+    fn multisig_script_sig(
+        &self,
+        signer: &TransactionInputSigner,
+        input_index: usize,
+        redeem_script: &Script,
+        script_sig: &Bytes,
+    ) -> Result<(Bytes, bool), WalletError> {
+        let keys = redeem_script.extract_multisig_keys()?;
+        let required = redeem_script.num_signatures_required() as usize;
+        let hash = signer.signature_hash(input_index, 0, redeem_script, SignatureVersion::Base, SIGHASH_ALL);
+
+        let script_sig: Script = script_sig.clone().into();
+        let pushed: Vec<&[u8]> = script_sig.iter()
+            .filter_map(|instruction| instruction.ok().and_then(|instruction| instruction.data))
+            .collect();
+
+        // signatures must follow order of their keys in the redeem script
+        let mut signatures: Vec<Vec<u8>> = Vec::new();
+        for key in &keys {
+            if signatures.len() == required {
+                break;
+            }
+
+            if let Some(signature) = pushed.iter().find(|data| is_signature_of(key, &hash, data)) {
+                signatures.push(signature.to_vec());
+            } else if let Some(keypair) = self.keypairs.get(&key.address_hash()) {
+                let mut signature: Vec<u8> = keypair.private().sign(&hash)?.into();
+                signature.push(SIGHASH_ALL as u8);
+                signatures.push(signature);
+            }
+        }
+
+        // OP_CHECKMULTISIG pops one extra element
+        let complete = signatures.len() == required;
+        let script_sig = signatures.iter()
+            .fold(ScriptBuilder::default().push_opcode(Opcode::OP_0), |builder, signature| builder.push_data(signature))
+            .push_data(redeem_script)
+            .into_bytes();
+        Ok((script_sig, complete))
+    }
+
+    /// Add wallet signatures to inputs, spending outputs of the wallet multisig addresses. Signatures of other
+    /// wallets are kept, so that transaction could be passed between wallets until it is complete.
+    /// Returns transaction and whether all multisig inputs have enough signatures
+    pub fn sign_multisig_transaction(&self, mut transaction: Transaction) -> Result<(Transaction, bool), WalletError> {
+        let signer: TransactionInputSigner = transaction.clone().into();
+        let mut complete = true;
+        for (index, input) in transaction.inputs.iter_mut().enumerate() {
+            let output = match self.local_node.transaction_output(&input.previous_output) {
+                Some(output) => output,
+                None => continue,
+            };
+            let redeem_script = match output_addresses(&output.script_pubkey)
+                .into_iter()
+                .filter_map(|(kind, hash)| self.multisig_redeem_script(kind, &hash))
+                .next() {
+                Some(redeem_script) => redeem_script,
+                None => continue,
+            };
+
+            let (script_sig, is_complete) = self.multisig_script_sig(&signer, index, redeem_script, &input.script_sig)?;
+            input.script_sig = script_sig;
+            complete = complete && is_complete;
+        }
+        Ok((transaction, complete))
+    }
+
+    /// Fill spent outputs and redeem scripts of PSBT inputs and outputs, known to the node and the wallet.
+    /// If `sign` is true, inputs, spendable by the wallet keys, are signed with given sighash type, which must match
+    /// sighash type of every signed input, if it is set. Inputs are not finalized, so that PSBT could be passed to other signers
+    pub fn process_psbt(&self, psbt: &mut Psbt, sign: bool, sighash_type: u32) -> Result<(), WalletError> {
+        let signer: TransactionInputSigner = psbt.unsigned_tx.clone().into();
+        for index in 0..psbt.inputs.len() {
+            if psbt.inputs[index].is_finalized() {
+                continue;
+            }
+
+            self.update_psbt_input(psbt, index);
+            let (script_code, sigversion) = match psbt.signing_script(index) {
+                Some(signing_script) if sign => signing_script,
+                _ => continue,
+            };
+
+            // signature has single byte of sighash type, while the hash commits to all four
+            if sighash_type > 0xff || psbt.inputs[index].sighash_type.map_or(false, |input_type| input_type != sighash_type) {
+                return Err(WalletError::SighashTypeMismatch(index));
+            }
+
+            let amount = psbt.spent_output(index).map_or(0, |output| output.value);
+            let hash = signer.signature_hash(index, amount, &script_code, sigversion, sighash_type);
+            // P2PKH script code and multisig script have destinations of their keys
+            for destination in script_code.extract_destinations().unwrap_or_default() {
+                let keypair = match destination.hash {
+                    AddressPayload::Hash(ref key_hash) => self.keypairs.get(key_hash),
+                    AddressPayload::WitnessProgram(_) => None,
+                };
+                if let Some(keypair) = keypair {
+                    let mut signature: Vec<u8> = keypair.private().sign(&hash)?.into();
+                    signature.push(sighash_type as u8);
+                    psbt.inputs[index].partial_sigs
+                        .entry(keypair.public().to_vec().into())
+                        .or_insert(signature.into());
+                }
+            }
+        }
+
+        for (output, transaction_output) in psbt.outputs.iter_mut().zip(&psbt.unsigned_tx.outputs) {
+            if output.redeem_script.is_none() {
+                output.redeem_script = self.wallet_redeem_script(&transaction_output.script_pubkey);
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill spent output and redeem script of PSBT input. Full previous transaction is added to
+    /// inputs, spending non-witness outputs, and spent output is added to other inputs
+    fn update_psbt_input(&self, psbt: &mut Psbt, index: usize) {
+        let previous_output = psbt.unsigned_tx.inputs[index].previous_output.clone();
+        let input = &mut psbt.inputs[index];
+        let output = match input.witness_utxo.clone() {
+            Some(output) => Some(output),
+            None => self.local_node.transaction_output(&previous_output),
+        };
+        let output = match output {
+            Some(output) => output,
+            None => return,
+        };
+
+        if input.redeem_script.is_none() {
+            input.redeem_script = self.wallet_redeem_script(&output.script_pubkey);
+        }
+        if input.non_witness_utxo.is_some() || input.witness_utxo.is_some() {
+            return;
+        }
+
+        let script: Script = match input.redeem_script {
+            Some(ref redeem_script) => redeem_script.clone().into(),
+            None => output.script_pubkey.clone().into(),
+        };
+        input.non_witness_utxo = match script.parse_witness_program() {
+            Some(_) => None,
+            None => self.local_node.transaction(&previous_output.hash),
         };
+        if input.non_witness_utxo.is_none() {
+            input.witness_utxo = Some(output);
+        }
+    }
+
+    /// Redeem script of P2SH output, paying to the nested witness program of the wallet key or wallet multisig address
+    fn wallet_redeem_script(&self, script_pubkey: &Bytes) -> Option<Bytes> {
+        output_addresses(script_pubkey)
+            .into_iter()
+            .filter(|&(kind, _)| kind == Key_Type::P2SH)
+            .filter_map(|(_, hash)| match self.nested_keys.get(&hash) {
+                Some(key_hash) => Some(witness_program(key_hash).to_bytes()),
+                None => self.redeem_scripts.get(&hash).map(Script::to_bytes),
+            })
+            .next()
+    }
+
+    /// Remove coins, spent by our transaction, from wallet
+    pub fn spend_coins(&mut self, coins: &[Coin]) -> Result<(), WalletError> {
+        for c in coins {
+            trace!(target: "wallet", "Deleting coin {:?}, value {}", c.get_id(), c.value);
+            self.delete_coin(c)?;
+        }
+        Ok(())
+    }
+
+    /// Create transaction, paying `value` to the output with `recipient` script at the wallet fee rate.
+    /// Change is sent to the fresh wallet address of the wallet address type.
+    fn create_transaction(&mut self, recipient: Script, value: u64) -> Result<Transaction, WalletError> {
+        if value < DUST_THRESHOLD {
+            return Err(WalletError::DustPayment);
+        }
 
-        let return_outpoint = OutPoint { hash: transaction.hash(), index: 1};
-        self.add_coin_candidate(return_outpoint, "pay to self".to_string());
+        // change script has the same size for any key of the wallet address type
+        let change_script = ScriptBuilder::build_address(&self.key_address(self.address_type, &AddressHash::default()))
+            .expect("wallet address type matches its payload; qed");
+        let selection = self.select_coins(value, self.fee_rate, &OutputSizes::new(&recipient, &change_script))?;
+
+        let mut transaction_outputs = vec![
+            TransactionOutput {
+                value: value,
+                script_pubkey: recipient.to_bytes(),
+        }];
+
+        // change key is only stored once the transaction is accepted by the wallet
+        let change_keypair = if selection.change != 0 {
+            Some(self.next_keypair(true)?)
+        } else {
+            None
+        };
+        if let Some((_, ref change_keypair)) = change_keypair {
+            // transfer the remaining value back to self
+            let change_address = self.key_address(self.address_type, &change_keypair.public().address_hash());
+            let pay_self_script = ScriptBuilder::build_address(&change_address)
+                .expect("wallet address type matches its payload; qed");
+            transaction_outputs.push(
+                TransactionOutput {
+                    value: selection.change,
+                    script_pubkey: pay_self_script.to_bytes(),
+                }
+            );
+        }
+
+        let transaction = self.sign_transaction(&selection.coins, transaction_outputs)?;
+        if self.local_node.transaction_fee_rate(&transaction) < self.fee_rate {
+            return Err(WalletError::InsufficientFee);
+        }
+
+        // remove used coin from wallet
+        self.spend_coins(&selection.coins)?;
+
+        if let Some((index, change_keypair)) = change_keypair {
+            self.store_keypair(true, index, change_keypair)?;
+            let return_outpoint = OutPoint { hash: transaction.hash(), index: 1};
+            self.add_coin_candidate(return_outpoint, "pay to self".to_string())?;
+        }
 
         Ok(transaction)
     }
 
+    /// Pay to P2PKH output of given public key hash
     pub fn pay(&mut self, recipient: AddressHash, value: u64) -> Result<H256, WalletError> {
-        let tx = self.create_transaction(recipient, value)?;
+        let tx = self.create_transaction(ScriptBuilder::build_p2pkh(&recipient), value)?;
+        Ok(self.send_transaction(tx))
+    }
 
+    /// Pay to the address of any supported type
+    pub fn pay_to_address(&mut self, recipient: &Address, value: u64) -> Result<H256, WalletError> {
+        let script = ScriptBuilder::build_address(recipient).ok_or(KeysError::InvalidAddress)?;
+        let tx = self.create_transaction(script, value)?;
+        Ok(self.send_transaction(tx))
+    }
+
+    /// Send wallet transaction to local node memory pool and to the network
+    fn send_transaction(&self, tx: Transaction) -> H256 {
         let indexed_transaction = IndexedTransaction::from(tx);
         let peer_index = 1000;
 
@@ -276,81 +948,638 @@ impl Wallet {
         // send to network
         self.local_node.unsolicited_transaction(peer_index.clone(), indexed_transaction.clone());
 
-        Ok(indexed_transaction.hash) //.reversed()
+        indexed_transaction.hash //.reversed()
+    }
+
+    /// Track outpoints, found in connected and disconnected blocks
+    fn process_chain_events(&mut self) -> Result<(), WalletError> {
+        let (received, unspent) = {
+            let mut events = self.chain_events.lock();
+            (mem::replace(&mut events.received, Vec::new()), mem::replace(&mut events.unspent, Vec::new()))
+        };
+
+        for outpoint in received {
+            if !self.is_tracked(&outpoint) {
+                self.add_coin_candidate(outpoint, "from block".to_string())?;
+            }
+        }
+
+        let storage = self.local_node.storage();
+        let output_provider = storage.as_transaction_output_provider();
+        for outpoint in unspent {
+            let is_mine = output_provider.transaction_output(&outpoint, usize::max_value())
+                .map_or(false, |output| self.is_mine(&output.script_pubkey));
+            if is_mine && !self.is_tracked(&outpoint) {
+                self.add_coin_candidate(outpoint, "unspent by reorganization".to_string())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Refresh set of spendable coins. Coins, spent in canonical chain, are no longer tracked
+    pub fn update_wallet(&mut self) -> Result<(), WalletError> {
+        self.process_chain_events()?;
+
+        let tracked = self.coins_candidate.clone();
+        let coins: HashSet<Coin> = self.local_node.get_spendable(&mut self.coins_candidate)
+            .into_iter()
+            .filter(|coin| self.is_solvable(coin))
+            .collect();
+        self.coins = coins;
+        for spent in tracked.difference(&self.coins_candidate) {
+            self.db.delete_outpoint(&spent.outpoint)?;
+        }
+        Ok(())
     }
 
-    pub fn update_wallet(&mut self) {
-        self.coins = self.local_node.get_spendable(&mut self.coins_candidate);
+    /// Track all outputs of canonical blocks, starting from given height, which are paying to the wallet
+    pub fn rescan(&mut self, from_height: u32) -> Result<(), WalletError> {
+        let storage = self.local_node.storage();
+        let best_height = storage.best_block().number;
+        for height in from_height..best_height + 1 {
+            let block = match storage.indexed_block(height.into()) {
+                Some(block) => block,
+                None if storage.is_pruned(height.into()) => return Err(WalletError::BlockPruned(height)),
+                None => break,
+            };
+
+            for tx in &block.transactions {
+                for (index, output) in tx.raw.outputs.iter().enumerate() {
+                    let outpoint = OutPoint { hash: tx.hash.clone(), index: index as u32 };
+                    if self.is_mine(&output.script_pubkey) && !self.is_tracked(&outpoint) {
+                        self.add_coin_candidate(outpoint, "from rescan".to_string())?;
+                    }
+                }
+            }
+        }
+
+        self.update_wallet()
     }
 
-    pub fn wallet_add_tx(&mut self,  hash: H256, index: u32) {
+    pub fn wallet_add_tx(&mut self,  hash: H256, index: u32) -> Result<(), WalletError> {
         //println!("add tx {:?} out {} to wallet candidate pool", hash, index);
-        let id = self.num_coin.clone().to_string();
         let outpoint = chain::OutPoint {
                             hash: hash.reversed(),
                             index
                         };
         let desc = "from network".to_string();
-        self.add_coin_candidate(outpoint ,desc);
+        self.add_coin_candidate(outpoint ,desc)
     }
 
-    pub fn print_coins(&self) {
-        println!("\n**********spendable coin");
-        for coin in self.coins.iter() {
-            println!("coin {}; value {}", coin.id, coin.value);
-        }
+    /// Spendable coins of the wallet, ordered by their ids
+    pub fn list_unspent(&self) -> Vec<CoinState> {
+        let mut coins: Vec<CoinState> = self.coins
+            .iter()
+            .map(|coin| self.coin_state(&coin.id, &coin.outpoint))
+            .collect();
+        coins.sort_by(|a, b| a.id.cmp(&b.id));
+        coins
+    }
+
+    /// All outpoints, tracked by the wallet, ordered by their ids. Includes coins, which are not spendable (yet)
+    pub fn list_coins(&self) -> Vec<CoinState> {
+        let mut coins: Vec<CoinState> = self.coins_candidate
+            .iter()
+            .map(|coin| self.coin_state(&coin.id, &coin.outpoint))
+            .collect();
+        coins.sort_by(|a, b| a.id.cmp(&b.id));
+        coins
+    }
+
+    fn coin_state(&self, id: &str, outpoint: &OutPoint) -> CoinState {
+        let storage = self.local_node.storage();
+        let output_provider = MemoryPoolTransactionOutputProvider::for_outpoints(
+            storage.clone(), &self.local_node.memory_pool(), &[outpoint.clone()]);
+        let output = output_provider.transaction_output(outpoint, usize::max_value());
+        let (confirmations, valid) = match storage.transaction_meta(&outpoint.hash) {
+            Some(meta) => (storage.best_block().number - meta.height() + 1, Some(meta.is_valid())),
+            // memory pool transactions are verified before they are accepted
+            None => (0, output.as_ref().map(|_| true)),
+        };
 
-        println!("**********coin Accessor");
-        for coin in self.coins_candidate.iter() {
-            println!("coin acc {}", coin.id);
+        CoinState {
+            id: id.to_owned(),
+            outpoint: outpoint.clone(),
+            address: output.as_ref()
+                .and_then(|output| output_addresses(&output.script_pubkey).into_iter().next())
+                .map(|(kind, hash)| Address {
+                    kind: kind,
+                    network: self.network,
+                    hash: hash.into(),
+                }),
+            value: output.map(|output| output.value),
+            confirmations: confirmations,
+            valid: valid,
+            spendable: self.coins.iter().any(|coin| &coin.outpoint == outpoint),
         }
-        println!("********************");
     }
+}
 
-    pub fn covet_pay(&self, recipient: AddressHash, value: u64) -> Result<H256, WalletError> {
-        let tx = self.create_covet_transaction(recipient, value)?;
+#[cfg(test)]
+pub mod tests {
+    extern crate tempdir;
+    extern crate test_data;
 
-        let indexed_transaction = IndexedTransaction::from(tx);
-        let peer_index = 1000;
+    use self::tempdir::TempDir;
+    use chain::{IndexedBlock, OutPoint, Transaction, TransactionInput, TransactionOutput};
+    use db::BlockChainDatabase;
+    use keys::{AddressHash, Public, HARDENED_INDEX};
+    use keys::Type as Key_Type;
+    use network::{ConsensusFork, ConsensusParams, Network};
+    use primitives::hash::H256;
+    use script::Builder as ScriptBuilder;
+    use script::{verify_script, Psbt, Script, SignatureVersion, TransactionSignatureChecker, VerificationFlags};
+    use std::sync::Arc;
+    use storage::SharedStore;
+    use verification::VerificationLevel;
+    use wallet::WalletDb;
+    use synchronization_coin_selection::{
+        estimate_transaction_size, CoinSelectionStrategy, OutputSizes, DEFAULT_FEE_RATE, DUST_THRESHOLD, P2PKH_OUTPUT_SIZE,
+    };
+    use {create_local_sync_node, create_sync_peers, BlockListener, VerificationParameters};
+    use super::{output_addresses, AddressType, Wallet, WalletBlockListener, WalletError, SIGHASH_ALL};
 
-        // send to local node mempool
-        self.local_node.on_transaction(peer_index.clone(), indexed_transaction.clone());
-        // send to network
-        self.local_node.unsolicited_transaction(peer_index.clone(), indexed_transaction.clone());
+    /// Wallet with single key, stored in the returned directory
+    fn empty_wallet() -> (Wallet, AddressHash, TempDir) {
+        let storage: SharedStore = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let local_node = create_local_sync_node(
+            ConsensusParams::new(Network::Unitest, ConsensusFork::BitcoinCore),
+            storage,
+            create_sync_peers(),
+            VerificationParameters {
+                verification_level: VerificationLevel::NoVerification,
+                verification_edge: H256::default(),
+            },
+        );
 
-        Ok(indexed_transaction.hash)
+        let dir = TempDir::new("wallet").unwrap();
+        let mut wallet = Wallet::new(local_node, WalletDb::open(dir.path(), "passphrase").unwrap()).unwrap();
+        let address = wallet.generate_keypair().unwrap();
+        (wallet, address, dir)
     }
 
-    pub fn create_covet_transaction(&self, recipient: AddressHash, value: u64) -> Result<Transaction, WalletError> {
-        let script = ScriptBuilder::build_p2pkh(&recipient);
-        //tx output currently, single only
-        let mut transaction_outputs = vec![
-            TransactionOutput {
-                value: value,
-                script_pubkey: script.to_bytes(),
-        }];
+    /// Copy of the public key of the wallet key
+    fn public_key(wallet: &Wallet, address: &AddressHash) -> Public {
+        Public::from_slice(wallet.keypairs[address].public()).unwrap()
+    }
 
-        //void Coin
-        let void_coin = OutPoint::null();
+    /// Verify P2SH input, spending the first output of the block coinbase
+    fn verify_p2sh_input(transaction: &Transaction, index: usize, funding_block: &IndexedBlock) {
+        let funding_output = &funding_block.transactions[0].raw.outputs[0];
+        let checker = TransactionSignatureChecker {
+            signer: transaction.clone().into(),
+            input_index: index,
+            input_amount: funding_output.value,
+        };
+        assert_eq!(verify_script(
+            &transaction.inputs[index].script_sig.clone().into(),
+            &funding_output.script_pubkey.clone().into(),
+            &transaction.inputs[index].script_witness,
+            &VerificationFlags::default().verify_p2sh(true),
+            &checker,
+            SignatureVersion::Base,
+        ), Ok(()));
+    }
 
-        let mut unsigned_inputs: Vec<TransactionInput> = vec![];
+    /// Append canonical block with coinbase, paying given value to the address
+    fn insert_block_paying_to(wallet: &Wallet, address: &AddressHash, value: u64) -> IndexedBlock {
+        insert_block_paying_to_script(wallet, ScriptBuilder::build_p2pkh(address), value)
+    }
 
-        println!("use void coin");
-        unsigned_inputs.push(TransactionInput {
-                previous_output: void_coin,
-                script_sig: Bytes::new(),
-                sequence: 0x00,
-                script_witness: vec![],
-            }
+    /// Append canonical block with coinbase, paying given value to the output with given script
+    fn insert_block_paying_to_script(wallet: &Wallet, script_pubkey: Script, value: u64) -> IndexedBlock {
+        let storage = wallet.local_node.storage();
+        let block: IndexedBlock = test_data::block_builder()
+            .transaction()
+            .coinbase()
+            .output()
+            .value(value)
+            .script_pubkey_bytes(script_pubkey.to_bytes())
+            .build()
+            .build()
+            .merkled_header()
+            .parent(storage.best_block().hash)
+            .build()
+            .build()
+            .into();
+        storage.insert(block.clone()).unwrap();
+        storage.canonize(block.hash()).unwrap();
+        block
+    }
+
+    /// Wallet with single coin of given value, stored in the returned directory
+    pub fn funded_wallet(value: u64) -> (Wallet, OutPoint, TempDir) {
+        let (mut wallet, address, dir) = empty_wallet();
+        let block = insert_block_paying_to(&wallet, &address, value);
+
+        let coin = OutPoint {
+            hash: block.transactions[0].hash.clone(),
+            index: 0,
+        };
+        wallet.wallet_add_tx(coin.hash.reversed(), 0).unwrap();
+        wallet.update_wallet().unwrap();
+        assert_eq!(wallet.get_balance(), value);
+        (wallet, coin, dir)
+    }
+
+    #[test]
+    fn wallet_restores_keys_and_coins_after_restart() {
+        let (wallet, coin, dir) = funded_wallet(100);
+        let local_node = wallet.local_node.clone();
+        let address = wallet.get_addresshash().unwrap();
+        drop(wallet);
+
+        let wallet = Wallet::new(local_node, WalletDb::open(dir.path(), "passphrase").unwrap()).unwrap();
+        assert_eq!(wallet.get_addresshash(), Ok(address.clone()));
+        assert_eq!(wallet.get_balance(), 100);
+
+        // restored keys are able to spend restored coins
+        let coins = wallet.select_coins(100, 0, &OutputSizes::p2pkh()).unwrap().coins;
+        assert_eq!(coins.len(), 1);
+        assert_eq!(coins[0].get_outpoint(), coin);
+        assert!(wallet.sign_transaction(&coins, vec![]).is_ok());
+    }
+
+    #[test]
+    fn wallet_forgets_spent_coins_after_restart() {
+        let (mut wallet, _, dir) = funded_wallet(100);
+        let local_node = wallet.local_node.clone();
+        let coins = wallet.select_coins(100, 0, &OutputSizes::p2pkh()).unwrap().coins;
+        wallet.spend_coins(&coins).unwrap();
+        drop(wallet);
+
+        let mut wallet = Wallet::new(local_node, WalletDb::open(dir.path(), "passphrase").unwrap()).unwrap();
+        assert_eq!(wallet.get_balance(), 0);
+
+        // number of the spent coin is not reused
+        wallet.wallet_add_tx(H256::from(1), 0).unwrap();
+        let coins = wallet.list_coins();
+        assert_eq!(coins.len(), 1);
+        assert_eq!(coins[0].id, "2: from network");
+    }
+
+    #[test]
+    fn wallet_lists_spendable_and_tracked_coins() {
+        let (mut wallet, coin, _dir) = funded_wallet(100);
+        let address = wallet.get_addresshash().unwrap();
+        let unknown = OutPoint {
+            hash: H256::from(1),
+            index: 0,
+        };
+        wallet.wallet_add_tx(unknown.hash.reversed(), 0).unwrap();
+        wallet.update_wallet().unwrap();
+
+        let unspent = wallet.list_unspent();
+        assert_eq!(unspent.len(), 1);
+        assert_eq!(unspent[0].outpoint, coin);
+        assert_eq!(unspent[0].address.as_ref().map(|address| address.hash.clone()), Some(address.into()));
+        assert_eq!(unspent[0].value, Some(100));
+        assert_eq!(unspent[0].confirmations, 1);
+        assert_eq!(unspent[0].valid, Some(true));
+        assert!(unspent[0].spendable);
+
+        // unknown outpoint is still tracked, but it is not spendable
+        let coins = wallet.list_coins();
+        assert_eq!(coins.len(), 2);
+        let unknown_state = coins.iter().find(|state| state.outpoint == unknown).unwrap();
+        assert_eq!(unknown_state.address, None);
+        assert_eq!(unknown_state.value, None);
+        assert_eq!(unknown_state.confirmations, 0);
+        assert_eq!(unknown_state.valid, None);
+        assert!(!unknown_state.spendable);
+    }
+
+    #[test]
+    fn wallet_tracks_coins_of_connected_and_disconnected_blocks() {
+        let (mut wallet, address, _dir) = empty_wallet();
+        let listener = WalletBlockListener {
+            events: wallet.chain_events.clone(),
+        };
+
+        // output, paying to the wallet, is found in connected block
+        let funding_block = insert_block_paying_to(&wallet, &address, 100);
+        listener.block_connected(&funding_block);
+        wallet.update_wallet().unwrap();
+        assert_eq!(wallet.get_balance(), 100);
+
+        // coin is spent in the canonical chain
+        let storage = wallet.local_node.storage();
+        let spending_block: IndexedBlock = test_data::block_builder()
+            .transaction()
+            .coinbase()
+            .output()
+            .value(1)
+            .build()
+            .build()
+            .transaction()
+            .input()
+            .hash(funding_block.transactions[0].hash.clone())
+            .index(0)
+            .build()
+            .output()
+            .value(100)
+            .build()
+            .build()
+            .merkled_header()
+            .parent(funding_block.hash().clone())
+            .build()
+            .build()
+            .into();
+        storage.insert(spending_block.clone()).unwrap();
+        storage.canonize(spending_block.hash()).unwrap();
+        listener.block_connected(&spending_block);
+        wallet.update_wallet().unwrap();
+        assert_eq!(wallet.get_balance(), 0);
+
+        // spending block is disconnected => coin is spendable again
+        storage.decanonize().unwrap();
+        listener.block_disconnected(&spending_block);
+        wallet.update_wallet().unwrap();
+        assert_eq!(wallet.get_balance(), 100);
+
+        // funding block is disconnected => coin no longer exists
+        storage.decanonize().unwrap();
+        listener.block_disconnected(&funding_block);
+        wallet.update_wallet().unwrap();
+        assert_eq!(wallet.get_balance(), 0);
+    }
+
+    #[test]
+    fn wallet_rescan_finds_coins_of_canonical_blocks() {
+        let (mut wallet, address, _dir) = empty_wallet();
+        insert_block_paying_to(&wallet, &address, 100);
+        insert_block_paying_to(&wallet, &AddressHash::default(), 50);
+        insert_block_paying_to(&wallet, &address, 30);
+        wallet.update_wallet().unwrap();
+        assert_eq!(wallet.get_balance(), 0);
+
+        wallet.rescan(2).unwrap();
+        assert_eq!(wallet.get_balance(), 30);
+
+        wallet.rescan(0).unwrap();
+        assert_eq!(wallet.get_balance(), 130);
+    }
+
+    #[test]
+    fn wallet_pays_fee_and_sends_change_to_fresh_address() {
+        let (mut wallet, _, _dir) = funded_wallet(100_000);
+        wallet.set_fee_rate(10);
+        let transaction = wallet.create_transaction(ScriptBuilder::build_p2pkh(&AddressHash::default()), 10_000).unwrap();
+
+        assert_eq!(transaction.outputs.len(), 2);
+        assert_eq!(transaction.outputs[0].value, 10_000);
+        assert_eq!(100_000 - 10_000 - transaction.outputs[1].value, 10 * estimate_transaction_size(1, &[P2PKH_OUTPUT_SIZE; 2]));
+        assert!(wallet.local_node.transaction_fee_rate(&transaction) >= 10);
+
+        // change is paid to the new wallet key
+        let change_addresses = output_addresses(&transaction.outputs[1].script_pubkey);
+        assert_eq!(change_addresses.len(), 1);
+        assert_eq!(change_addresses[0].0, Key_Type::P2PKH);
+        assert!(wallet.keypairs.contains_key(&change_addresses[0].1));
+        assert_eq!(wallet.keypairs.len(), 2);
+    }
+
+    #[test]
+    fn wallet_leaves_dust_change_to_miners() {
+        let (mut wallet, _, _dir) = funded_wallet(100_000);
+        let value = 100_000 - DEFAULT_FEE_RATE * estimate_transaction_size(1, &[P2PKH_OUTPUT_SIZE]) - 100;
+        let transaction = wallet.create_transaction(ScriptBuilder::build_p2pkh(&AddressHash::default()), value).unwrap();
+
+        assert_eq!(transaction.outputs.len(), 1);
+        assert_eq!(wallet.keypairs.len(), 1);
+        assert_eq!(wallet.get_balance(), 0);
+    }
+
+    #[test]
+    fn wallet_rejects_dust_payment() {
+        let (mut wallet, _, _dir) = funded_wallet(100_000);
+        assert_eq!(
+            wallet.create_transaction(ScriptBuilder::build_p2pkh(&AddressHash::default()), DUST_THRESHOLD - 1).err(),
+            Some(WalletError::DustPayment)
         );
+    }
+
+    #[test]
+    fn wallet_keys_are_derived_from_mnemonic() {
+        let (mut wallet, address, _dir) = empty_wallet();
+        let second_address = wallet.generate_keypair().unwrap();
+        insert_block_paying_to(&wallet, &second_address, 100);
+        let mnemonic = wallet.mnemonic().unwrap();
+
+        let dir = TempDir::new("wallet").unwrap();
+        let mut restored = Wallet::new(wallet.local_node.clone(), WalletDb::open(dir.path(), "passphrase").unwrap()).unwrap();
+        assert_ne!(restored.mnemonic().unwrap(), mnemonic);
+        restored.restore(mnemonic.clone(), 2).unwrap();
+        assert_eq!(restored.mnemonic().unwrap(), mnemonic);
+        assert!(restored.keypairs.contains_key(&address));
+        assert!(restored.keypairs.contains_key(&second_address));
+        assert_eq!(restored.get_balance(), 100);
+
+        // both wallets continue with the same receive key
+        assert_eq!(restored.generate_keypair().unwrap(), wallet.generate_keypair().unwrap());
+    }
+
+    #[test]
+    fn wallet_never_derives_hardened_keys() {
+        let (mut wallet, _, _dir) = empty_wallet();
+        wallet.db.set_next_key_index(false, HARDENED_INDEX - 1).unwrap();
+        wallet.generate_keypair().unwrap();
+        assert_eq!(wallet.generate_keypair(), Err(WalletError::EmptyKeySpace));
+    }
+
+    #[test]
+    fn wallet_signs_native_and_nested_segwit_inputs() {
+        let (mut wallet, _, _dir) = empty_wallet();
+        let native = wallet.generate_address(AddressType::Bech32).unwrap();
+        let nested = wallet.generate_address(AddressType::P2shSegwit).unwrap();
+        assert_eq!(native.kind, Key_Type::P2WPKH);
+        assert_eq!(nested.kind, Key_Type::P2SH);
+
+        let native_block = insert_block_paying_to_script(&wallet, ScriptBuilder::build_address(&native).unwrap(), 60_000);
+        let nested_block = insert_block_paying_to_script(&wallet, ScriptBuilder::build_address(&nested).unwrap(), 50_000);
+        wallet.rescan(0).unwrap();
+        assert_eq!(wallet.get_balance(), 110_000);
+
+        wallet.set_coin_selection(CoinSelectionStrategy::LargestFirst);
+        let transaction = wallet.create_transaction(ScriptBuilder::build_p2pkh(&AddressHash::default()), 100_000).unwrap();
+        assert_eq!(transaction.inputs.len(), 2);
+
+        // BIP143 signatures commit to the value of spent outputs
+        let flags = VerificationFlags::default().verify_p2sh(true).verify_witness(true);
+        for (index, input) in transaction.inputs.iter().enumerate() {
+            let is_native = input.previous_output.hash == native_block.transactions[0].hash;
+            let funding_block = if is_native { &native_block } else { &nested_block };
+            let funding_output = &funding_block.transactions[0].raw.outputs[0];
+            let checker = TransactionSignatureChecker {
+                signer: transaction.clone().into(),
+                input_index: index,
+                input_amount: funding_output.value,
+            };
+
+            assert_eq!(input.script_witness.len(), 2);
+            assert_eq!(input.script_sig.is_empty(), is_native);
+            assert_eq!(verify_script(
+                &input.script_sig.clone().into(),
+                &funding_output.script_pubkey.clone().into(),
+                &input.script_witness,
+                &flags,
+                &checker,
+                SignatureVersion::Base,
+            ), Ok(()));
+        }
+    }
+
+    #[test]
+    fn wallet_sends_change_to_address_of_wallet_address_type() {
+        let (mut wallet, _, _dir) = funded_wallet(100_000);
+        wallet.set_address_type(AddressType::Bech32);
+        let transaction = wallet.create_transaction(ScriptBuilder::build_p2pkh(&AddressHash::default()), 10_000).unwrap();
+
+        let change_addresses = output_addresses(&transaction.outputs[1].script_pubkey);
+        assert_eq!(change_addresses.len(), 1);
+        assert_eq!(change_addresses[0].0, Key_Type::P2WPKH);
+        assert!(wallet.keypairs.contains_key(&change_addresses[0].1));
+    }
+
+    #[test]
+    fn wallet_multisig_transaction_is_signed_by_several_wallets() {
+        let (mut first, first_key, _first_dir) = empty_wallet();
+        let dir = TempDir::new("wallet").unwrap();
+        let mut second = Wallet::new(first.local_node.clone(), WalletDb::open(dir.path(), "passphrase").unwrap()).unwrap();
+        let second_key = second.generate_keypair().unwrap();
+
+        let keys = [public_key(&first, &first_key), public_key(&second, &second_key)];
+        let (address, redeem_script) = first.add_multisig_address(2, &keys).unwrap();
+        assert_eq!(second.add_multisig_address(2, &keys).unwrap(), (address.clone(), redeem_script.clone()));
+        assert_eq!(first.address_redeem_script(&address), Some(&redeem_script));
+        assert_eq!(first.multisig_address(3, &keys).err(), Some(WalletError::InvalidMultisig));
+
+        // multisig coin is tracked, but is not spendable by a single wallet
+        let funding_block = insert_block_paying_to_script(&first, ScriptBuilder::build_address(&address).unwrap(), 100_000);
+        first.rescan(0).unwrap();
+        second.rescan(0).unwrap();
+        assert_eq!(first.get_balance(), 0);
+        assert_eq!(first.coins_candidate.len(), 1);
 
         let transaction = Transaction {
             version: 1,
-            inputs: unsigned_inputs,
-            outputs: transaction_outputs,
+            inputs: vec![TransactionInput {
+                previous_output: OutPoint { hash: funding_block.transactions[0].hash.clone(), index: 0 },
+                script_sig: Default::default(),
+                sequence: 0xffffffff,
+                script_witness: vec![],
+            }],
+            outputs: vec![TransactionOutput {
+                value: 90_000,
+                script_pubkey: ScriptBuilder::build_p2pkh(&AddressHash::default()).to_bytes(),
+            }],
             lock_time: 0,
         };
-        Ok(transaction)
+
+        // second signature is collected first, but is pushed after the signature of the first key
+        let (transaction, complete) = second.sign_multisig_transaction(transaction).unwrap();
+        assert!(!complete);
+        let (transaction, complete) = first.sign_multisig_transaction(transaction).unwrap();
+        assert!(complete);
+        verify_p2sh_input(&transaction, 0, &funding_block);
+
+        // complete transaction is not changed
+        assert_eq!(second.sign_multisig_transaction(transaction.clone()).unwrap(), (transaction, true));
+    }
+
+    #[test]
+    fn wallet_spends_multisig_coins_with_own_keys() {
+        let (mut wallet, first_key, _dir) = empty_wallet();
+        let second_key = wallet.generate_keypair().unwrap();
+        let keys = [public_key(&wallet, &first_key), public_key(&wallet, &second_key)];
+        let (address, _) = wallet.add_multisig_address(2, &keys).unwrap();
+
+        let funding_block = insert_block_paying_to_script(&wallet, ScriptBuilder::build_address(&address).unwrap(), 100_000);
+        wallet.rescan(0).unwrap();
+        assert_eq!(wallet.get_balance(), 100_000);
+
+        let transaction = wallet.create_transaction(ScriptBuilder::build_p2pkh(&AddressHash::default()), 10_000).unwrap();
+        verify_p2sh_input(&transaction, 0, &funding_block);
     }
 
+    #[test]
+    fn wallet_pays_fee_of_segwit_outputs() {
+        let (mut wallet, first_key, _dir) = empty_wallet();
+        let second_key = wallet.generate_keypair().unwrap();
+        let keys = [public_key(&wallet, &first_key), public_key(&wallet, &second_key)];
+        let (address, _) = wallet.add_multisig_address(2, &keys).unwrap();
+        insert_block_paying_to_script(&wallet, ScriptBuilder::build_address(&address).unwrap(), 100_000);
+        wallet.rescan(0).unwrap();
+        wallet.set_fee_rate(10);
+
+        // multisig input is estimated tightly, so larger P2WSH payment output must be paid for
+        let transaction = wallet.create_transaction(ScriptBuilder::build_p2wsh(&Default::default()), 10_000).unwrap();
+        assert_eq!(transaction.outputs.len(), 2);
+        assert!(wallet.local_node.transaction_fee_rate(&transaction) >= 10);
+    }
+
+    #[test]
+    fn wallet_fills_and_signs_psbt_inputs() {
+        let (mut wallet, address, _dir) = empty_wallet();
+        let nested_address = wallet.generate_address(AddressType::P2shSegwit).unwrap();
+        let legacy_block = insert_block_paying_to(&wallet, &address, 100_000);
+        let nested_block = insert_block_paying_to_script(&wallet, ScriptBuilder::build_address(&nested_address).unwrap(), 50_000);
+
+        let unsigned_tx = Transaction {
+            version: 1,
+            inputs: [&legacy_block, &nested_block].iter().map(|block| TransactionInput {
+                previous_output: OutPoint { hash: block.transactions[0].hash.clone(), index: 0 },
+                script_sig: Default::default(),
+                sequence: 0xffffffff,
+                script_witness: vec![],
+            }).collect(),
+            outputs: vec![TransactionOutput {
+                value: 140_000,
+                script_pubkey: ScriptBuilder::build_p2pkh(&address).to_bytes(),
+            }],
+            lock_time: 0,
+        };
+        let mut psbt = Psbt::from_unsigned_tx(unsigned_tx).unwrap();
+
+        // legacy input gets previous transaction, nested segwit input gets spent output and redeem script
+        wallet.process_psbt(&mut psbt, false, SIGHASH_ALL).unwrap();
+        assert_eq!(psbt.inputs[0].non_witness_utxo, Some(legacy_block.transactions[0].raw.clone()));
+        assert_eq!(psbt.inputs[1].witness_utxo, Some(nested_block.transactions[0].raw.outputs[0].clone()));
+        assert!(psbt.inputs[1].redeem_script.is_some());
+        assert!(psbt.inputs.iter().all(|input| input.partial_sigs.is_empty()));
+        assert_eq!(psbt.fee(), Some(10_000));
+        assert!(!psbt.clone().finalize());
+
+        // sighash type of the input must match the requested one
+        let mut none_psbt = psbt.clone();
+        none_psbt.inputs[1].sighash_type = Some(0x82);
+        assert_eq!(wallet.process_psbt(&mut none_psbt.clone(), true, SIGHASH_ALL), Err(WalletError::SighashTypeMismatch(1)));
+        assert_eq!(wallet.process_psbt(&mut none_psbt, true, 0x102), Err(WalletError::SighashTypeMismatch(0)));
+
+        psbt.inputs[0].sighash_type = Some(SIGHASH_ALL);
+        wallet.process_psbt(&mut psbt, true, SIGHASH_ALL).unwrap();
+        assert!(psbt.inputs.iter().all(|input| input.partial_sigs.len() == 1));
+        assert!(psbt.finalize());
+
+        let transaction = psbt.extract_transaction().unwrap();
+        for (index, block) in [&legacy_block, &nested_block].iter().enumerate() {
+            let funding_output = &block.transactions[0].raw.outputs[0];
+            let checker = TransactionSignatureChecker {
+                signer: transaction.clone().into(),
+                input_index: index,
+                input_amount: funding_output.value,
+            };
+            assert_eq!(verify_script(
+                &transaction.inputs[index].script_sig.clone().into(),
+                &funding_output.script_pubkey.clone().into(),
+                &transaction.inputs[index].script_witness,
+                &VerificationFlags::default().verify_p2sh(true).verify_witness(true),
+                &checker,
+                SignatureVersion::Base,
+            ), Ok(()));
+        }
+    }
 }
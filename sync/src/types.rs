@@ -1,4 +1,4 @@
-use super::SyncListener;
+use super::{BlockListener, SyncListener};
 use futures::Future;
 use local_node::LocalNode;
 use miner::MemoryPool;
@@ -57,7 +57,11 @@ pub type LocalNodeRef = Arc<
     >,
 >;
 
-pub type ShardBlocksPoolRef = Arc<RwLock<ShardBlocksPool<SynchronizationClient<LocalSynchronizationTaskExecutor, AsyncVerifier>>>>;
+/// Reference to shard blocks pool
+pub type ShardBlocksPoolRef = Arc<RwLock<ShardBlocksPool>>;
 
 /// Synchronization events listener reference
 pub type SyncListenerRef = Box<SyncListener>;
+
+/// Canonical chain events listener reference
+pub type BlockListenerRef = Box<BlockListener>;
@@ -1,7 +1,7 @@
 use super::super::types::{MemoryPoolRef, StorageRef};
 use chain::{OutPoint, Transaction, TransactionOutput};
 use miner::{DoubleSpendCheckResult, HashedOutPoint, NonFinalDoubleSpendSet};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use storage::TransactionOutputProvider;
 use verification::TransactionError;
 
@@ -12,6 +12,8 @@ pub struct MemoryPoolTransactionOutputProvider {
     storage_provider: StorageRef,
     /// Transaction inputs from memory pool transactions
     mempool_inputs: HashMap<HashedOutPoint, Option<TransactionOutput>>,
+    /// Previous outputs, which are spent by memory pool transactions
+    mempool_spends: HashSet<HashedOutPoint>,
     /// Previous outputs, for which we should return 'Not spent' value.
     /// These are used when new version of transaction is received.
     nonfinal_spends: Option<NonFinalDoubleSpendSet>,
@@ -46,6 +48,7 @@ impl MemoryPoolTransactionOutputProvider {
                         )
                     })
                     .collect(),
+                mempool_spends: HashSet::new(),
                 nonfinal_spends: None,
             }),
             // there are non-final transactions, which are spending same inputs in memory pool
@@ -63,11 +66,45 @@ impl MemoryPoolTransactionOutputProvider {
                             )
                         })
                         .collect(),
+                    mempool_spends: HashSet::new(),
                     nonfinal_spends: Some(nonfinal_spends),
                 })
             }
         }
     }
+
+    /// Create new provider for reading given outputs.
+    /// Outputs of memory pool transactions are visible, outputs spent by memory pool transactions are spent.
+    pub fn for_outpoints(
+        storage: StorageRef,
+        memory_pool: &MemoryPoolRef,
+        outpoints: &[OutPoint],
+    ) -> Self {
+        let memory_pool = memory_pool.read();
+        MemoryPoolTransactionOutputProvider {
+            storage_provider: storage,
+            mempool_inputs: outpoints
+                .iter()
+                .map(|outpoint| {
+                    (
+                        outpoint.clone().into(),
+                        memory_pool.transaction_output(outpoint, usize::max_value()),
+                    )
+                })
+                .collect(),
+            mempool_spends: outpoints
+                .iter()
+                .filter(|outpoint| memory_pool.is_spent(outpoint))
+                .map(|outpoint| outpoint.clone().into())
+                .collect(),
+            nonfinal_spends: None,
+        }
+    }
+
+    /// Is output spent by memory pool transaction (only known for providers, created with `for_outpoints`)
+    pub fn is_spent_in_memory_pool(&self, prevout: &OutPoint) -> bool {
+        self.mempool_spends.contains(&prevout.clone().into())
+    }
 }
 
 impl TransactionOutputProvider for MemoryPoolTransactionOutputProvider {
@@ -111,9 +148,9 @@ impl TransactionOutputProvider for MemoryPoolTransactionOutputProvider {
             }
         }
 
-        // we can omit memory_pool check here, because it has been completed in `for_transaction` method
+        // for verified transactions, memory_pool check has been completed in `for_transaction` method
         // => just check spending in storage
-        self.storage_provider.is_spent(prevout)
+        self.is_spent_in_memory_pool(prevout) || self.storage_provider.is_spent(prevout)
     }
 }
 
@@ -122,7 +159,7 @@ mod tests {
     extern crate test_data;
 
     use super::MemoryPoolTransactionOutputProvider;
-    use chain::OutPoint;
+    use chain::{OutPoint, Transaction};
     use db::BlockChainDatabase;
     use miner::{MemoryPool, NonZeroFeeCalculator};
     use parking_lot::RwLock;
@@ -232,4 +269,63 @@ mod tests {
             None
         );
     }
+
+    #[test]
+    fn when_outputs_are_read_with_memory_pool() {
+        let genesis = test_data::genesis();
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![genesis
+            .clone()
+            .into()]));
+        let memory_pool = Arc::new(RwLock::new(MemoryPool::new()));
+
+        // t0 spends genesis coinbase and is spent by t1
+        let t0: Transaction = test_data::TransactionBuilder::with_output(10)
+            .set_input(&genesis.transactions[0], 0)
+            .into();
+        let t1: Transaction = test_data::TransactionBuilder::with_output(20)
+            .set_input(&t0, 0)
+            .into();
+        memory_pool
+            .write()
+            .insert_verified(t0.clone().into(), &NonZeroFeeCalculator);
+        memory_pool
+            .write()
+            .insert_verified(t1.clone().into(), &NonZeroFeeCalculator);
+
+        let genesis_output = OutPoint {
+            hash: genesis.transactions[0].hash(),
+            index: 0,
+        };
+        let t0_output = OutPoint {
+            hash: t0.hash(),
+            index: 0,
+        };
+        let t1_output = OutPoint {
+            hash: t1.hash(),
+            index: 0,
+        };
+        let provider = MemoryPoolTransactionOutputProvider::for_outpoints(
+            storage,
+            &memory_pool,
+            &[genesis_output.clone(), t0_output.clone(), t1_output.clone()],
+        );
+
+        assert_eq!(
+            provider.transaction_output(&genesis_output, usize::max_value()),
+            Some(genesis.transactions[0].outputs[0].clone())
+        );
+        assert!(provider.is_spent(&genesis_output));
+        assert!(provider.is_spent_in_memory_pool(&genesis_output));
+        assert_eq!(
+            provider.transaction_output(&t0_output, usize::max_value()),
+            Some(t0.outputs[0].clone())
+        );
+        assert!(provider.is_spent(&t0_output));
+        assert_eq!(
+            provider.transaction_output(&t1_output, usize::max_value()),
+            Some(t1.outputs[0].clone())
+        );
+        assert!(!provider.is_spent(&t1_output));
+        assert!(!provider.is_spent_in_memory_pool(&t1_output));
+    }
 }
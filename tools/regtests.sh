@@ -1,6 +1,6 @@
 #!/bin/bash
 
-./target/$CARGO_TARGET/release/pbtc --btc --regtest --db-cache=192 &
+PBTC_WALLET_PASSPHRASE=${PBTC_WALLET_PASSPHRASE:-regtest} ./target/$CARGO_TARGET/release/pbtc --btc --regtest --db-cache=192 &
 ! java -jar ./tools/compare-tool/pull-tests-be0eef7.jar /tmp/regtest-db 2>&1 | tee regtests-full.log | grep -E --color=auto 'org.bitcoinj.store.BlockStoreException\:|BitcoindComparisonTool.main\: ERROR|bitcoind sent us a block it already had, make sure bitcoind has no blocks!|java.lang.NullPointerException'
 GREP_COLOR="01;32" grep 'BitcoindComparisonTool.main: Block "b1001" completed processing' regtests-full.log
 result=$?
@@ -2,7 +2,7 @@ use canon::CanonBlock;
 use crypto::dhash256;
 use deployments::BlockDeployments;
 use error::{Error, TransactionError};
-use network::{ConsensusFork, ConsensusParams, TransactionOrdering};
+use network::{ConsensusFork, ConsensusParams, TransactionOrdering, TransactionValidity};
 use script;
 use ser::Stream;
 use sigops::{transaction_sigops, transaction_sigops_cost};
@@ -67,6 +67,13 @@ impl<'a> BlockAcceptor<'a> {
         self.ordering.check()?;
         Ok(())
     }
+
+    /// Checks only coinbase rules (used when transactions validity is decided after ordering)
+    pub fn check_coinbase(&self) -> Result<(), Error> {
+        self.coinbase_claim.check()?;
+        self.coinbase_script.check()?;
+        Ok(())
+    }
 }
 
 pub struct BlockFinality<'a> {
@@ -248,6 +255,7 @@ pub struct BlockCoinbaseClaim<'a> {
     store: &'a TransactionOutputProvider,
     height: u32,
     transaction_ordering: TransactionOrdering,
+    tx_validity: TransactionValidity,
 }
 
 impl<'a> BlockCoinbaseClaim<'a> {
@@ -263,6 +271,7 @@ impl<'a> BlockCoinbaseClaim<'a> {
             store: store,
             height: height,
             transaction_ordering: consensus_params.fork.transaction_ordering(median_time_past),
+            tx_validity: consensus_params.tx_validity,
         }
     }
 
@@ -274,10 +283,12 @@ impl<'a> BlockCoinbaseClaim<'a> {
         for (tx_idx, tx) in self.block.transactions.iter().enumerate().skip(1) {
             // (1) Total sum of all referenced outputs
             let mut incoming: u64 = 0;
+            let mut has_missing_inputs = false;
             for input in tx.raw.inputs.iter() {
                 let prevout_tx_idx =
                     transaction_index_for_output_check(self.transaction_ordering, tx_idx);
                 let prevout = store.transaction_output(&input.previous_output, prevout_tx_idx);
+                has_missing_inputs |= prevout.is_none();
                 let (sum, overflow) =
                     incoming.overflowing_add(prevout.map(|o| o.value).unwrap_or(0));
                 if overflow {
@@ -289,6 +300,13 @@ impl<'a> BlockCoinbaseClaim<'a> {
             // (2) Total sum of all outputs
             let spends = tx.raw.total_spends();
 
+            // in lazy mode, transactions, which are obviously invalid, are not paying fees
+            if self.tx_validity == TransactionValidity::Lazy
+                && (has_missing_inputs || incoming < spends)
+            {
+                continue;
+            }
+
             // Difference between (1) and (2)
             let (difference, overflow) = incoming.overflowing_sub(spends);
             if overflow {
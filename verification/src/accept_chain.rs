@@ -4,7 +4,7 @@ use accept_transaction::TransactionAcceptor;
 use canon::CanonBlock;
 use deployments::BlockDeployments;
 use error::Error;
-use network::ConsensusParams;
+use network::{ConsensusParams, TransactionValidity};
 use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use storage::{DuplexTransactionOutputProvider, Store};
 use VerificationLevel;
@@ -13,6 +13,7 @@ pub struct ChainAcceptor<'a> {
     pub block: BlockAcceptor<'a>,
     pub header: HeaderAcceptor<'a>,
     pub transactions: Vec<TransactionAcceptor<'a>>,
+    tx_validity: TransactionValidity,
 }
 
 impl<'a> ChainAcceptor<'a> {
@@ -31,6 +32,11 @@ impl<'a> ChainAcceptor<'a> {
             block.raw(),
         );
         let headers = store.as_block_header_provider();
+        // in lazy mode, transactions are verified after the block is ordered
+        let transactions = match consensus.tx_validity {
+            TransactionValidity::Strict => block.transactions(),
+            TransactionValidity::Lazy => Vec::new(),
+        };
 
         ChainAcceptor {
             block: BlockAcceptor::new(
@@ -43,8 +49,7 @@ impl<'a> ChainAcceptor<'a> {
                 headers,
             ),
             header: HeaderAcceptor::new(headers, consensus, block.header(), height, deployments),
-            transactions: block
-                .transactions()
+            transactions: transactions
                 .into_iter()
                 .enumerate()
                 .map(|(tx_index, tx)| {
@@ -63,11 +68,15 @@ impl<'a> ChainAcceptor<'a> {
                     )
                 })
                 .collect(),
+            tx_validity: consensus.tx_validity,
         }
     }
 
     pub fn check(&self) -> Result<(), Error> {
-        try!(self.block.check());
+        match self.tx_validity {
+            TransactionValidity::Strict => try!(self.block.check()),
+            TransactionValidity::Lazy => try!(self.block.check_coinbase()),
+        }
         try!(self.header.check());
         try!(self.check_transactions());
         Ok(())
@@ -44,7 +44,12 @@ impl BackwardsCompatibleChainVerifier {
 
         let current_time = ::time::get_time().sec as u32;
         // first run pre-verification
-        let chain_verifier = ChainVerifier::new(block, self.consensus.network, current_time);
+        let chain_verifier = ChainVerifier::new(
+            block,
+            self.consensus.network,
+            self.consensus.tx_validity,
+            current_time,
+        );
         chain_verifier.check()?;
 
         assert_eq!(
@@ -219,7 +224,10 @@ mod tests {
     use chain::{Block, IndexedBlock, Transaction};
     use constants::DOUBLE_SPACING_SECONDS;
     use db::BlockChainDatabase;
-    use network::{BitcoinCashConsensusParams, ConsensusFork, ConsensusParams, Network};
+    use hash::H256;
+    use network::{
+        BitcoinCashConsensusParams, ConsensusFork, ConsensusParams, Network, TransactionValidity,
+    };
     use script;
     use std::sync::Arc;
     use storage::Error as DBError;
@@ -745,4 +753,98 @@ mod tests {
             verifier.verify(VerificationLevel::Full, &block.into())
         );
     }
+
+    #[test]
+    fn lazy_tx_validity_accepts_invalid_transactions() {
+        let genesis = test_data::block_builder()
+            .transaction()
+            .coinbase()
+            .build()
+            .merkled_header()
+            .build()
+            .build();
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            genesis.clone().into(),
+        ]));
+
+        let block: IndexedBlock = test_data::block_builder()
+            .transaction()
+            .coinbase()
+            .output()
+            .value(5000000000)
+            .build()
+            .build()
+            .transaction()
+            .input()
+            .hash(H256::from(1))
+            .build()
+            .output()
+            .value(10)
+            .build()
+            .build()
+            .merkled_header()
+            .parent(genesis.hash())
+            .build()
+            .build()
+            .into();
+
+        let strict_verifier = ChainVerifier::new(
+            storage.clone(),
+            ConsensusParams::new(Network::Unitest, ConsensusFork::BitcoinCore),
+        );
+        assert_eq!(
+            Err(Error::Transaction(1, TransactionError::Overspend)),
+            strict_verifier.verify(VerificationLevel::Full, &block)
+        );
+
+        let mut consensus = ConsensusParams::new(Network::Unitest, ConsensusFork::BitcoinCore);
+        consensus.tx_validity = TransactionValidity::Lazy;
+        let lazy_verifier = ChainVerifier::new(storage, consensus);
+        assert_eq!(Ok(()), lazy_verifier.verify(VerificationLevel::Full, &block));
+    }
+
+    #[test]
+    fn lazy_tx_validity_rejects_coinbase_overspend() {
+        let genesis = test_data::block_builder()
+            .transaction()
+            .coinbase()
+            .build()
+            .merkled_header()
+            .build()
+            .build();
+        let storage = BlockChainDatabase::init_test_chain(vec![genesis.clone().into()]);
+
+        // invalid transaction fee could not be claimed
+        let block: IndexedBlock = test_data::block_builder()
+            .transaction()
+            .coinbase()
+            .output()
+            .value(5000000001)
+            .build()
+            .build()
+            .transaction()
+            .input()
+            .hash(H256::from(1))
+            .build()
+            .build()
+            .merkled_header()
+            .parent(genesis.hash())
+            .build()
+            .build()
+            .into();
+
+        let mut consensus = ConsensusParams::new(Network::Unitest, ConsensusFork::BitcoinCore);
+        consensus.tx_validity = TransactionValidity::Lazy;
+        let verifier = ChainVerifier::new(Arc::new(storage), consensus);
+
+        let expected = Err(Error::CoinbaseOverspend {
+            expected_max: 5000000000,
+            actual: 5000000001,
+        });
+
+        assert_eq!(
+            expected,
+            verifier.verify(VerificationLevel::Full, &block.into())
+        );
+    }
 }
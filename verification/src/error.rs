@@ -2,6 +2,7 @@ use compact::Compact;
 use hash::H256;
 use script::Error as SignatureError;
 use storage::Error as DBError;
+use storage::TransactionErrorCode;
 
 #[derive(Debug, PartialEq)]
 /// All possible verification errors
@@ -113,3 +114,37 @@ pub enum TransactionError {
     /// Transaction with witness is received before SegWit is activated.
     PrematureWitness,
 }
+
+impl TransactionError {
+    /// Code of the error, which is stored for invalid canon chain transactions
+    pub fn code(&self) -> TransactionErrorCode {
+        match *self {
+            TransactionError::Empty => TransactionErrorCode::Empty,
+            TransactionError::NullNonCoinbase => TransactionErrorCode::NullNonCoinbase,
+            TransactionError::CoinbaseSignatureLength(_) => {
+                TransactionErrorCode::CoinbaseSignatureLength
+            }
+            TransactionError::MaxSize => TransactionErrorCode::MaxSize,
+            TransactionError::MinSize => TransactionErrorCode::MinSize,
+            TransactionError::MaxSigops => TransactionErrorCode::MaxSigops,
+            TransactionError::MemoryPoolCoinbase => TransactionErrorCode::MemoryPoolCoinbase,
+            TransactionError::Input(_) => TransactionErrorCode::Input,
+            TransactionError::Maturity => TransactionErrorCode::Maturity,
+            TransactionError::Signature(_, _) => TransactionErrorCode::Signature,
+            TransactionError::UnknownReference(_) => TransactionErrorCode::UnknownReference,
+            TransactionError::Overspend => TransactionErrorCode::Overspend,
+            TransactionError::SignatureMallformed(_) => TransactionErrorCode::SignatureMallformed,
+            TransactionError::Sigops(_) => TransactionErrorCode::Sigops,
+            TransactionError::SigopsP2SH(_) => TransactionErrorCode::SigopsP2SH,
+            TransactionError::MisplacedCoinbase => TransactionErrorCode::MisplacedCoinbase,
+            TransactionError::UnspentTransactionWithTheSameHash => {
+                TransactionErrorCode::UnspentTransactionWithTheSameHash
+            }
+            TransactionError::UsingSpentOutput(_, _) => TransactionErrorCode::UsingSpentOutput,
+            TransactionError::ReturnReplayProtection => {
+                TransactionErrorCode::ReturnReplayProtection
+            }
+            TransactionError::PrematureWitness => TransactionErrorCode::PrematureWitness,
+        }
+    }
+}
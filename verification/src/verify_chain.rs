@@ -1,6 +1,6 @@
 use chain::IndexedBlock;
 use error::Error;
-use network::Network;
+use network::{Network, TransactionValidity};
 use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use verify_block::BlockVerifier;
 use verify_header::HeaderVerifier;
@@ -10,10 +10,16 @@ pub struct ChainVerifier<'a> {
     pub block: BlockVerifier<'a>,
     pub header: HeaderVerifier<'a>,
     pub transactions: Vec<TransactionVerifier<'a>>,
+    tx_validity: TransactionValidity,
 }
 
 impl<'a> ChainVerifier<'a> {
-    pub fn new(block: &'a IndexedBlock, network: Network, current_time: u32) -> Self {
+    pub fn new(
+        block: &'a IndexedBlock,
+        network: Network,
+        tx_validity: TransactionValidity,
+        current_time: u32,
+    ) -> Self {
         trace!(target: "verification", "Block pre-verification {}", block.hash().to_reversed_str());
         ChainVerifier {
             block: BlockVerifier::new(block),
@@ -23,13 +29,17 @@ impl<'a> ChainVerifier<'a> {
                 .iter()
                 .map(TransactionVerifier::new)
                 .collect(),
+            tx_validity: tx_validity,
         }
     }
 
     pub fn check(&self) -> Result<(), Error> {
         try!(self.block.check());
         try!(self.header.check());
-        try!(self.check_transactions());
+        // in lazy mode, invalid transactions do not make block invalid
+        if self.tx_validity == TransactionValidity::Strict {
+            try!(self.check_transactions());
+        }
         Ok(())
     }
 
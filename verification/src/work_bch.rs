@@ -1,6 +1,6 @@
 use chain::{BlockHeader, IndexedBlockHeader};
 use network::{BitcoinCashConsensusParams, ConsensusParams, Network};
-use primitives::bigint::{Uint, U256};
+use primitives::bigint::U256;
 use primitives::compact::Compact;
 use primitives::hash::H256;
 use storage::BlockHeaderProvider;
@@ -100,12 +100,7 @@ fn work_required_bitcoin_cash_adjusted(
 
     /// Get block proof.
     fn block_proof(header: &BlockHeader) -> U256 {
-        let proof: U256 = header.bits.into();
-        // We need to compute 2**256 / (bnTarget+1), but we can't represent 2**256
-        // as it's too large for a arith_uint256. However, as 2**256 is at least as
-        // large as bnTarget+1, it is equal to ((2**256 - bnTarget - 1) /
-        // (bnTarget+1)) + 1, or ~bnTarget / (nTarget+1) + 1.
-        (!proof / (proof + U256::one())) + U256::one()
+        header.bits.to_work()
     }
 
     /// Compute chain work between two blocks. Last block work is included. First block work is excluded.
@@ -1,4 +1,4 @@
-//! Bitcoin keys.
+//! Wallet storage.
 
 extern crate rand;
 extern crate rustc_hex as hex;
@@ -7,10 +7,17 @@ extern crate lazy_static;
 extern crate base58;
 extern crate secp256k1;
 extern crate bitcrypto as crypto;
+extern crate crypto as rcrypto;
 extern crate primitives;
 extern crate keys;
-extern crate miner;
+extern crate chain;
+extern crate db;
+extern crate serialization as ser;
+
+mod wallet_db;
+
 pub use primitives::{hash, bytes};
+pub use wallet_db::{WalletDb, WalletDbError};
 
 use hash::{H160, H256};
 
@@ -1,54 +1,379 @@
-use std::fs::OpenOptions;
-use std::io::prelude::*;
+//! Persistent storage of the wallet keys and tracked outpoints.
+//!
+//! Private keys are encrypted at rest with AES-256-CTR and authenticated with HMAC-SHA256.
+//! Encryption and authentication keys are derived from the wallet passphrase with PBKDF2-HMAC-SHA256
+//! and the random salt, stored along with the keys.
+
+use std::fs;
 use std::path::Path;
-use std::fs::File;
-use std::collections::HashMap;
-use std::io::{BufRead, BufReader, Result};
-use std::sync::{Arc, Mutex};
-use keys::{KeyPair, Public, Private, AddressHash};
-use miner::memory_pool::MemoryPool;
-use Arc<RwLock<MemoryPool>>
+use rand::{OsRng, Rng};
+use rcrypto::aes::{ctr, KeySize};
+use rcrypto::hmac::Hmac;
+use rcrypto::mac::{Mac, MacResult};
+use rcrypto::pbkdf2::pbkdf2;
+use rcrypto::sha2::Sha256;
+use bytes::Bytes;
+use chain::OutPoint;
+use crypto::dhash160;
+use db::kv::{DatabaseConfig, DiskDatabase, Location, RawKey, RawTransaction};
+use keys::{DisplayLayout, KeyPair, Mnemonic, Private};
+use ser::{deserialize, serialize};
+
+/// Salt, passphrase check record, mnemonic, key derivation indexes, coin number and redeem scripts
+const COL_META: u32 = 0;
+/// Address hash => encrypted private key
+const COL_KEYS: u32 = 1;
+/// Tracked outpoint => description
+const COL_OUTPOINTS: u32 = 2;
+const COL_COUNT: u32 = 3;
 
+const KEY_SALT: &'static [u8] = b"salt";
+const KEY_CHECK: &'static [u8] = b"check";
+const KEY_MNEMONIC: &'static [u8] = b"mnemonic";
+const KEY_RECEIVE_INDEX: &'static [u8] = b"receive_index";
+const KEY_CHANGE_INDEX: &'static [u8] = b"change_index";
+/// Number of the last tracked outpoint. Numbers of untracked outpoints are not reused
+const KEY_COIN_NUMBER: &'static [u8] = b"coin_number";
+/// Prefix of script hash => redeem script records. Stored in meta column, so that
+/// databases of previous versions are opened without migration
+const KEY_REDEEM_SCRIPT_PREFIX: &'static [u8] = b"redeem_script";
+/// Encrypted with the wallet keys to detect wrong passphrase on open
+const CHECK_PLAINTEXT: &'static [u8] = b"pbtc wallet";
 
-//pub struct WalletDb {
-//	key_db_path: String,
-//	coin_db_path: String,
-//	keypairs: HashMap<AddressHash, KeyPair>,
-//	mempool: Arc<RwLock<MemoryPool>>,
+const SALT_LEN: usize = 16;
+const IV_LEN: usize = 16;
+const MAC_LEN: usize = 32;
+const PBKDF2_ROUNDS: u32 = 10240;
 
-//}
+#[derive(Debug, PartialEq)]
+pub enum WalletDbError {
+	/// Underlying database error
+	Database(String),
+	/// Passphrase differs from the one, which has been used to create the wallet
+	InvalidPassphrase,
+	/// Stored record can not be decoded
+	CorruptedRecord,
+}
+
+/// Wallet database
+pub struct WalletDb {
+	db: DiskDatabase,
+	keys: Keys,
+}
+
+/// Keys, derived from the wallet passphrase
+struct Keys {
+	/// AES-256 key
+	encryption: [u8; 32],
+	/// HMAC-SHA256 key
+	authentication: [u8; 32],
+}
 
 impl WalletDb {
-	fn new(file_name: &str)
-	{
-
-	}
-
-	//fn new(file_name: &str) {
-	//	self.db_path = file_name.to_string();
-	//	self.pub_pri_map = HashMap::new();
-	//	self.pri_pub_map = HashMap::new();
-	//}
-
-	//fn open_db(&self) {
-	//	if Path::new(&self.db_path).exists() {
-	//		let file = File::open(self.db_path).unwrap();
-
-	//		for line in BufReader::new(file).lines() {
-	//			let mut token: Vec<&str> = line.unwrap().split_whitespace().collect();
-
-	//			let pub_key = token[0].to_string();
-	//			let pri_key = token[1].to_string();
-	//			self.pub_pri_map.insert(pub_key.clone(), pri_key.clone());
-	//			self.pri_pub_map.insert(pri_key, pub_key);
-	//		}
-	//	} else {
-	//		OpenOptions::new()
-    //        .create(true)
-    //        .write(true)
-    //        .append(true)
-    //        .open(self.db_path)
-    //        .unwrap();
-	//	}
-	//}
+	/// Open wallet database at given path, creating it if required
+	pub fn open<P: AsRef<Path>>(path: P, passphrase: &str) -> Result<Self, WalletDbError> {
+		fs::create_dir_all(path.as_ref()).map_err(|err| WalletDbError::Database(err.to_string()))?;
+		let db = DiskDatabase::open(DatabaseConfig::with_columns(Some(COL_COUNT)), path)
+			.map_err(WalletDbError::Database)?;
+
+		let salt = db.get(&RawKey::new(Location::Column(COL_META), KEY_SALT))
+			.map_err(WalletDbError::Database)?;
+		match salt {
+			Some(salt) => {
+				let wallet_db = WalletDb {
+					keys: derive_keys(passphrase, &salt),
+					db: db,
+				};
+				let check = wallet_db.db.get(&RawKey::new(Location::Column(COL_META), KEY_CHECK))
+					.map_err(WalletDbError::Database)?
+					.ok_or(WalletDbError::CorruptedRecord)?;
+				// check record is authenticated with the key of the given passphrase only
+				match wallet_db.decrypt(&check) {
+					Ok(ref plaintext) if &**plaintext == CHECK_PLAINTEXT => (),
+					Ok(_) | Err(WalletDbError::CorruptedRecord) => return Err(WalletDbError::InvalidPassphrase),
+					Err(err) => return Err(err),
+				}
+
+				Ok(wallet_db)
+			},
+			None => {
+				let salt = random_bytes(SALT_LEN)?;
+				let wallet_db = WalletDb {
+					keys: derive_keys(passphrase, &salt),
+					db: db,
+				};
+				let mut transaction = RawTransaction::default();
+				transaction.insert_raw(Location::Column(COL_META), KEY_SALT, &salt);
+				transaction.insert_raw(Location::Column(COL_META), KEY_CHECK, &wallet_db.encrypt(CHECK_PLAINTEXT)?);
+				wallet_db.db.write(transaction).map_err(WalletDbError::Database)?;
+
+				Ok(wallet_db)
+			},
+		}
+	}
+
+	/// All stored keypairs
+	pub fn keypairs(&self) -> Result<Vec<KeyPair>, WalletDbError> {
+		self.db.iter(Location::Column(COL_KEYS))
+			.map(|(_, value)| -> Result<KeyPair, WalletDbError> {
+				let layout = self.decrypt(&value)?;
+				let private = Private::from_layout(&layout).map_err(|_| WalletDbError::CorruptedRecord)?;
+				KeyPair::from_private(private).map_err(|_| WalletDbError::CorruptedRecord)
+			})
+			.collect()
+	}
+
+	/// Store keypair
+	pub fn insert_keypair(&self, keypair: &KeyPair) -> Result<(), WalletDbError> {
+		let address_hash = keypair.public().address_hash();
+		let mut transaction = RawTransaction::default();
+		transaction.insert_raw(Location::Column(COL_KEYS), &*address_hash, &self.encrypt(&keypair.private().layout())?);
+		self.db.write(transaction).map_err(WalletDbError::Database)
+	}
+
+	/// Mnemonic of the wallet keys, if any
+	pub fn mnemonic(&self) -> Result<Option<Mnemonic>, WalletDbError> {
+		match self.db.get(&RawKey::new(Location::Column(COL_META), KEY_MNEMONIC)).map_err(WalletDbError::Database)? {
+			Some(value) => {
+				let phrase = String::from_utf8(self.decrypt(&value)?).map_err(|_| WalletDbError::CorruptedRecord)?;
+				phrase.parse::<Mnemonic>().map(Some).map_err(|_| WalletDbError::CorruptedRecord)
+			},
+			None => Ok(None),
+		}
+	}
+
+	/// Store mnemonic of the wallet keys
+	pub fn set_mnemonic(&self, mnemonic: &Mnemonic) -> Result<(), WalletDbError> {
+		let mut transaction = RawTransaction::default();
+		transaction.insert_raw(Location::Column(COL_META), KEY_MNEMONIC, &self.encrypt(mnemonic.to_string().as_bytes())?);
+		self.db.write(transaction).map_err(WalletDbError::Database)
+	}
+
+	/// Index of the next key to derive in receive (or change) chain
+	pub fn next_key_index(&self, change: bool) -> Result<u32, WalletDbError> {
+		match self.db.get(&RawKey::new(Location::Column(COL_META), key_index_key(change))).map_err(WalletDbError::Database)? {
+			Some(value) => deserialize(&*value).map_err(|_| WalletDbError::CorruptedRecord),
+			None => Ok(0),
+		}
+	}
+
+	/// Store index of the next key to derive in receive (or change) chain
+	pub fn set_next_key_index(&self, change: bool, index: u32) -> Result<(), WalletDbError> {
+		let mut transaction = RawTransaction::default();
+		transaction.insert_raw(Location::Column(COL_META), key_index_key(change), &serialize(&index));
+		self.db.write(transaction).map_err(WalletDbError::Database)
+	}
+
+	/// All stored redeem scripts of P2SH addresses
+	pub fn redeem_scripts(&self) -> Result<Vec<Bytes>, WalletDbError> {
+		Ok(self.db.iter(Location::Column(COL_META))
+			.filter(|&(ref key, _)| key.starts_with(KEY_REDEEM_SCRIPT_PREFIX))
+			.map(|(_, value)| value.to_vec().into())
+			.collect())
+	}
+
+	/// Store redeem script of P2SH address
+	pub fn insert_redeem_script(&self, redeem_script: &[u8]) -> Result<(), WalletDbError> {
+		let mut key = KEY_REDEEM_SCRIPT_PREFIX.to_vec();
+		key.extend_from_slice(&*dhash160(redeem_script));
+		let mut transaction = RawTransaction::default();
+		transaction.insert_raw(Location::Column(COL_META), &key, redeem_script);
+		self.db.write(transaction).map_err(WalletDbError::Database)
+	}
+
+	/// All tracked outpoints along with their descriptions
+	pub fn outpoints(&self) -> Result<Vec<(OutPoint, String)>, WalletDbError> {
+		self.db.iter(Location::Column(COL_OUTPOINTS))
+			.map(|(key, value)| -> Result<(OutPoint, String), WalletDbError> {
+				let outpoint = deserialize(&*key).map_err(|_| WalletDbError::CorruptedRecord)?;
+				let desc = deserialize(&*value).map_err(|_| WalletDbError::CorruptedRecord)?;
+				Ok((outpoint, desc))
+			})
+			.collect()
+	}
+
+	/// Number of the last tracked outpoint
+	pub fn coin_number(&self) -> Result<u64, WalletDbError> {
+		match self.db.get(&RawKey::new(Location::Column(COL_META), KEY_COIN_NUMBER)).map_err(WalletDbError::Database)? {
+			Some(value) => deserialize(&*value).map_err(|_| WalletDbError::CorruptedRecord),
+			None => Ok(0),
+		}
+	}
+
+	/// Start tracking outpoint, which is given the number `coin_number`
+	pub fn insert_outpoint(&self, outpoint: &OutPoint, coin_number: u64, desc: &str) -> Result<(), WalletDbError> {
+		let mut transaction = RawTransaction::default();
+		transaction.insert_raw(Location::Column(COL_OUTPOINTS), &serialize(outpoint), &serialize(&desc.to_owned()));
+		transaction.insert_raw(Location::Column(COL_META), KEY_COIN_NUMBER, &serialize(&coin_number));
+		self.db.write(transaction).map_err(WalletDbError::Database)
+	}
+
+	/// Stop tracking outpoint
+	pub fn delete_outpoint(&self, outpoint: &OutPoint) -> Result<(), WalletDbError> {
+		let mut transaction = RawTransaction::default();
+		transaction.delete_raw(Location::Column(COL_OUTPOINTS), &serialize(outpoint));
+		self.db.write(transaction).map_err(WalletDbError::Database)
+	}
+
+	/// Encrypt data with the wallet key. Random IV is prepended and MAC of IV and ciphertext
+	/// is appended to the result.
+	fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>, WalletDbError> {
+		let mut result = random_bytes(IV_LEN)?;
+		let mut encrypted = vec![0u8; data.len()];
+		ctr(KeySize::KeySize256, &self.keys.encryption, &result).process(data, &mut encrypted);
+		result.extend(encrypted);
+		let mac = self.mac(&result);
+		result.extend_from_slice(mac.code());
+		Ok(result)
+	}
+
+	/// Authenticate and decrypt data, previously encrypted with `encrypt`
+	fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, WalletDbError> {
+		if data.len() < IV_LEN + MAC_LEN {
+			return Err(WalletDbError::CorruptedRecord);
+		}
+
+		let (authenticated, mac) = data.split_at(data.len() - MAC_LEN);
+		// MacResult comparison is constant-time
+		if self.mac(authenticated) != MacResult::new(mac) {
+			return Err(WalletDbError::CorruptedRecord);
+		}
+
+		let (iv, encrypted) = authenticated.split_at(IV_LEN);
+		let mut result = vec![0u8; encrypted.len()];
+		ctr(KeySize::KeySize256, &self.keys.encryption, iv).process(encrypted, &mut result);
+		Ok(result)
+	}
+
+	fn mac(&self, data: &[u8]) -> MacResult {
+		let mut hmac = Hmac::new(Sha256::new(), &self.keys.authentication);
+		hmac.input(data);
+		hmac.result()
+	}
+}
+
+fn key_index_key(change: bool) -> &'static [u8] {
+	if change { KEY_CHANGE_INDEX } else { KEY_RECEIVE_INDEX }
+}
+
+fn derive_keys(passphrase: &str, salt: &[u8]) -> Keys {
+	let mut mac = Hmac::new(Sha256::new(), passphrase.as_bytes());
+	let mut derived = [0u8; 64];
+	pbkdf2(&mut mac, salt, PBKDF2_ROUNDS, &mut derived);
+	let mut keys = Keys {
+		encryption: [0u8; 32],
+		authentication: [0u8; 32],
+	};
+	keys.encryption.copy_from_slice(&derived[..32]);
+	keys.authentication.copy_from_slice(&derived[32..]);
+	keys
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, WalletDbError> {
+	let mut rng = OsRng::new().map_err(|err| WalletDbError::Database(err.to_string()))?;
+	let mut result = vec![0u8; len];
+	rng.fill_bytes(&mut result);
+	Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate tempdir;
+
+	use self::tempdir::TempDir;
+	use bytes::Bytes;
+	use chain::OutPoint;
+	use db::kv::{Location, RawKey, RawTransaction};
+	use keys::generator::{Generator, Random};
+	use keys::{DisplayLayout, Mnemonic, Network};
+	use super::{WalletDb, WalletDbError, COL_KEYS, COL_META, IV_LEN, KEY_MNEMONIC};
+
+	#[test]
+	fn wallet_db_reopen_round_trip() {
+		let tempdir = TempDir::new("wallet").unwrap();
+		let keypair = Random::new(Network::Testnet).generate().unwrap();
+		let outpoint1 = OutPoint { hash: 1.into(), index: 0 };
+		let outpoint2 = OutPoint { hash: 2.into(), index: 1 };
+
+		{
+			let wallet_db = WalletDb::open(tempdir.path(), "passphrase").unwrap();
+			wallet_db.insert_keypair(&keypair).unwrap();
+			wallet_db.insert_outpoint(&outpoint1, 1, "1: from network").unwrap();
+			wallet_db.insert_outpoint(&outpoint2, 2, "2: pay to self").unwrap();
+			wallet_db.delete_outpoint(&outpoint1).unwrap();
+			wallet_db.insert_redeem_script(&[0x51, 0x51, 0xae]).unwrap();
+		}
+
+		let wallet_db = WalletDb::open(tempdir.path(), "passphrase").unwrap();
+		let keypairs = wallet_db.keypairs().unwrap();
+		assert_eq!(keypairs.len(), 1);
+		assert_eq!(keypairs[0].private(), keypair.private());
+		assert_eq!(keypairs[0].public(), keypair.public());
+		assert_eq!(wallet_db.outpoints().unwrap(), vec![(outpoint2, "2: pay to self".to_owned())]);
+		assert_eq!(wallet_db.coin_number(), Ok(2));
+		assert_eq!(wallet_db.redeem_scripts().unwrap(), vec![Bytes::from(vec![0x51, 0x51, 0xae])]);
+	}
+
+	#[test]
+	fn wallet_db_keeps_mnemonic_and_key_indexes() {
+		let tempdir = TempDir::new("wallet").unwrap();
+		let mnemonic: Mnemonic = "legal winner thank year wave sausage worth useful legal winner thank yellow".parse().unwrap();
+
+		{
+			let wallet_db = WalletDb::open(tempdir.path(), "passphrase").unwrap();
+			assert_eq!(wallet_db.mnemonic(), Ok(None));
+			assert_eq!(wallet_db.next_key_index(false), Ok(0));
+			wallet_db.set_mnemonic(&mnemonic).unwrap();
+			wallet_db.set_next_key_index(false, 3).unwrap();
+			wallet_db.set_next_key_index(true, 7).unwrap();
+		}
+
+		let wallet_db = WalletDb::open(tempdir.path(), "passphrase").unwrap();
+		assert_eq!(wallet_db.mnemonic(), Ok(Some(mnemonic.clone())));
+		assert_eq!(wallet_db.next_key_index(false), Ok(3));
+		assert_eq!(wallet_db.next_key_index(true), Ok(7));
+		assert_eq!(wallet_db.redeem_scripts(), Ok(vec![]));
+
+		let stored = wallet_db.db.get(&RawKey::new(Location::Column(COL_META), KEY_MNEMONIC)).unwrap().unwrap();
+		assert!(!stored.windows(5).any(|window| window == b"legal"));
+	}
+
+	#[test]
+	fn wallet_db_rejects_wrong_passphrase() {
+		let tempdir = TempDir::new("wallet").unwrap();
+		drop(WalletDb::open(tempdir.path(), "passphrase").unwrap());
+
+		assert_eq!(WalletDb::open(tempdir.path(), "wrong passphrase").err(), Some(WalletDbError::InvalidPassphrase));
+		assert!(WalletDb::open(tempdir.path(), "passphrase").is_ok());
+	}
+
+	#[test]
+	fn wallet_db_detects_tampered_records() {
+		let tempdir = TempDir::new("wallet").unwrap();
+		let keypair = Random::new(Network::Testnet).generate().unwrap();
+		let wallet_db = WalletDb::open(tempdir.path(), "passphrase").unwrap();
+		wallet_db.insert_keypair(&keypair).unwrap();
+
+		let (key, value) = wallet_db.db.iter(Location::Column(COL_KEYS)).next().unwrap();
+		let mut value = value.to_vec();
+		value[IV_LEN] ^= 1;
+		let mut transaction = RawTransaction::default();
+		transaction.insert_raw(Location::Column(COL_KEYS), &key, &value);
+		wallet_db.db.write(transaction).unwrap();
+
+		assert_eq!(wallet_db.keypairs().err(), Some(WalletDbError::CorruptedRecord));
+	}
+
+	#[test]
+	fn wallet_db_keeps_private_keys_encrypted() {
+		let tempdir = TempDir::new("wallet").unwrap();
+		let keypair = Random::new(Network::Testnet).generate().unwrap();
+		let wallet_db = WalletDb::open(tempdir.path(), "passphrase").unwrap();
+		wallet_db.insert_keypair(&keypair).unwrap();
+
+		let layout = keypair.private().layout();
+		let stored: Vec<_> = wallet_db.db.iter(Location::Column(COL_KEYS)).collect();
+		assert_eq!(stored.len(), 1);
+		assert!(!stored[0].1.windows(layout.len()).any(|window| window == &layout[..]));
+	}
 }